@@ -0,0 +1,177 @@
+//! A product configurator demo server.
+//!
+//! This example loads a Decision-DNNF and serves a small HTTP JSON API on top of it.
+//! Each query may restrict the configurations to the ones consistent with some choices of the user, given as DIMACS literals in the `assume` parameter (e.g. `assume=1,-3`).
+//!
+//! - `GET /count` returns the number of configurations;
+//! - `GET /implied` returns the literals that are true in every configuration;
+//! - `GET /valid-values` returns, for each variable, the values it can still take;
+//! - `GET /sample` returns a configuration drawn uniformly at random.
+//!
+//! Run it with:
+//!
+//! ```text
+//! cargo run --example configurator_server --features configurator-server -- instance.nnf 127.0.0.1:8080
+//! curl "http://127.0.0.1:8080/valid-values?assume=1,-3"
+//! ```
+//!
+//! The server is single-threaded and has no dependency; it is meant as a living documentation of the library, not as a production server.
+
+use anyhow::{anyhow, Context, Result};
+use decdnnf_rs::{
+    Assumptions, D4Reader, DecisionDNNF, Literal, ModelCounter, ModelSampler, NodeIndex,
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+};
+
+fn main() -> Result<()> {
+    let args = std::env::args().collect::<Vec<_>>();
+    let (input, address) = match args.as_slice() {
+        [_, input] => (input.as_str(), "127.0.0.1:8080"),
+        [_, input, address] => (input.as_str(), address.as_str()),
+        _ => return Err(anyhow!("usage: {} <instance.nnf> [address]", args[0])),
+    };
+    let file = File::open(input).with_context(|| format!(r#"while opening "{input}""#))?;
+    let ddnnf = D4Reader::read(BufReader::new(file)).context("while reading the formula")?;
+    let counter = ModelCounter::new(&ddnnf);
+    let listener =
+        TcpListener::bind(address).with_context(|| format!("while binding {address}"))?;
+    eprintln!("serving {input} on http://{address}");
+    for (i, stream) in listener.incoming().enumerate() {
+        let seed = u64::try_from(i).unwrap();
+        if let Err(e) = handle_connection(&ddnnf, &counter, seed, stream?) {
+            eprintln!("error: {e:#}");
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(
+    ddnnf: &DecisionDNNF,
+    counter: &ModelCounter,
+    seed: u64,
+    stream: TcpStream,
+) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let target = match request_line
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["GET", target, _] => (*target).to_string(),
+        _ => {
+            return respond(
+                stream,
+                "405 Method Not Allowed",
+                &json_error("only GET is supported"),
+            )
+        }
+    };
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let assumptions = match parse_assumptions(query, ddnnf.n_vars()) {
+        Ok(a) => a,
+        Err(e) => return respond(stream, "400 Bad Request", &json_error(&format!("{e:#}"))),
+    };
+    let count =
+        |literals: &[Literal]| counter.count_from_under_assumptions(NodeIndex::from(0), literals);
+    let body = match path {
+        "/count" => format!(r#"{{"count":"{}"}}"#, count(&assumptions)),
+        "/implied" => {
+            let implied = valid_values(ddnnf.n_vars(), &assumptions, count)
+                .into_iter()
+                .filter_map(|values| match values.as_slice() {
+                    [l] => Some(isize::from(*l).to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            format!(r#"{{"implied":[{}]}}"#, implied.join(","))
+        }
+        "/valid-values" => {
+            let values = valid_values(ddnnf.n_vars(), &assumptions, count)
+                .into_iter()
+                .enumerate()
+                .map(|(i, values)| {
+                    let polarities = values
+                        .iter()
+                        .map(|l| l.polarity().to_string())
+                        .collect::<Vec<_>>();
+                    format!(r#"{{"var":{},"values":[{}]}}"#, i + 1, polarities.join(","))
+                })
+                .collect::<Vec<_>>();
+            format!(r#"{{"variables":[{}]}}"#, values.join(","))
+        }
+        "/sample" => {
+            let conditioned = ddnnf.condition(&assumptions);
+            let mut sampler = ModelSampler::new(&conditioned);
+            sampler.set_seed(seed);
+            match sampler.sample_one() {
+                Some(model) => {
+                    let literals = model.iter().map(ToString::to_string).collect::<Vec<_>>();
+                    format!(r#"{{"model":[{}]}}"#, literals.join(","))
+                }
+                None => r#"{"model":null}"#.to_string(),
+            }
+        }
+        _ => return respond(stream, "404 Not Found", &json_error("no such endpoint")),
+    };
+    respond(stream, "200 OK", &body)
+}
+
+/// Parses the `assume` parameter of a query string, which contains comma separated DIMACS literals.
+fn parse_assumptions(query: &str, n_vars: usize) -> Result<Vec<Literal>> {
+    let mut literals = vec![];
+    for parameter in query.split('&').filter(|p| !p.is_empty()) {
+        match parameter.split_once('=') {
+            Some(("assume", value)) => {
+                let cube = value.replace(',', " ");
+                let assumptions = Assumptions::from_reader(cube.as_bytes(), n_vars)?;
+                literals.extend_from_slice(assumptions.literals());
+            }
+            _ => return Err(anyhow!(r#"unexpected parameter "{parameter}""#)),
+        }
+    }
+    Ok(literals)
+}
+
+/// Returns, for each variable, the literals on this variable that are consistent with the assumptions.
+fn valid_values<F>(n_vars: usize, assumptions: &[Literal], count: F) -> Vec<Vec<Literal>>
+where
+    F: Fn(&[Literal]) -> rug::Integer,
+{
+    let mut extended = assumptions.to_vec();
+    (0..n_vars)
+        .map(|i| {
+            let positive = Literal::from(isize::try_from(i + 1).unwrap());
+            [positive.flip(), positive]
+                .into_iter()
+                .filter(|l| {
+                    extended.push(*l);
+                    let consistent = count(&extended) != 0;
+                    extended.pop();
+                    consistent
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn json_error(message: &str) -> String {
+    format!(
+        r#"{{"error":"{}"}}"#,
+        message.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn respond(mut stream: TcpStream, status: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
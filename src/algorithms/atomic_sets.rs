@@ -0,0 +1,177 @@
+use crate::{DecisionDNNF, FreeVariables, Literal, ModelCounter, ModelFinder};
+use rug::Integer;
+use rustc_hash::FxHashMap;
+
+/// The atomic sets of a [`DecisionDNNF`], that is the groups of variables which values are equal, possibly up to a negation, in all the models.
+///
+/// Each atomic set is given as a list of literals sorted by variable index.
+/// The first literal of a set is positive, and each other literal is true in a model if and only if the first one is;
+/// thus, a negative literal denotes a variable which always takes the opposite value of the first variable.
+/// Only the sets of at least two variables are given; the variables that are not involved in the formula never belong to an atomic set.
+/// A formula with no model has no atomic set.
+///
+/// Two variables may only be in the same atomic set if their literals have the same numbers of models, as given by [`ModelCounter::marginals`];
+/// the candidates that share these numbers are then checked using a [`ModelFinder`].
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{AtomicSets, D4Reader, Literal};
+///
+/// // (1 and -2 and 3) or (-1 and 2 and -3)
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 1 -2 3 0\n1 2 -1 2 -3 0\n".as_bytes()).unwrap();
+/// let atomic_sets = AtomicSets::compute(&ddnnf);
+/// assert_eq!(
+///     &[vec![Literal::from(1), Literal::from(-2), Literal::from(3)]],
+///     atomic_sets.sets()
+/// );
+/// ```
+#[derive(Debug)]
+pub struct AtomicSets {
+    sets: Vec<Vec<Literal>>,
+}
+
+impl AtomicSets {
+    /// Computes the atomic sets of a [`DecisionDNNF`].
+    ///
+    /// The computation requires a model search for each pair of candidate variables in the worst case.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn compute(ddnnf: &DecisionDNNF) -> Self {
+        let counter = ModelCounter::new(ddnnf);
+        if *counter.count() == 0 {
+            return Self { sets: vec![] };
+        }
+        let marginals = counter.marginals();
+        let finder = ModelFinder::new(ddnnf);
+        let involved = involved_vars(ddnnf);
+        let mut candidates: FxHashMap<(&Integer, &Integer), Vec<usize>> = FxHashMap::default();
+        for (var_index, (n_pos, n_neg)) in marginals.iter().enumerate() {
+            if involved[var_index] {
+                candidates
+                    .entry((n_pos.min(n_neg), n_pos.max(n_neg)))
+                    .or_default()
+                    .push(var_index);
+            }
+        }
+        let mut sets = Vec::new();
+        for vars in candidates.into_values().filter(|v| v.len() > 1) {
+            let mut group_sets: Vec<Vec<Literal>> = Vec::new();
+            for var_index in vars {
+                let positive = Literal::from(isize::try_from(var_index + 1).unwrap());
+                let set = group_sets.iter_mut().find_map(|set| {
+                    let representative = set[0];
+                    let representative_marginals = &marginals[representative.var_index()];
+                    [positive, positive.flip()]
+                        .into_iter()
+                        .filter(|l| {
+                            let (n_pos, n_neg) = &marginals[var_index];
+                            if l.polarity() {
+                                (n_pos, n_neg)
+                                    == (&representative_marginals.0, &representative_marginals.1)
+                            } else {
+                                (n_neg, n_pos)
+                                    == (&representative_marginals.0, &representative_marginals.1)
+                            }
+                        })
+                        .find(|l| {
+                            finder
+                                .find_model_under_assumptions(&[representative, l.flip()])
+                                .is_none()
+                                && finder
+                                    .find_model_under_assumptions(&[representative.flip(), *l])
+                                    .is_none()
+                        })
+                        .map(|l| (set, l))
+                });
+                match set {
+                    Some((set, l)) => set.push(l),
+                    None => group_sets.push(vec![positive]),
+                }
+            }
+            sets.extend(group_sets.into_iter().filter(|s| s.len() > 1));
+        }
+        sets.sort_unstable_by_key(|s| s[0].var_index());
+        Self { sets }
+    }
+
+    /// Returns the atomic sets, sorted by the index of their first variable.
+    #[must_use]
+    pub fn sets(&self) -> &[Vec<Literal>] {
+        &self.sets
+    }
+}
+
+/// Returns, for each variable, whether it is involved in the formula.
+fn involved_vars(ddnnf: &DecisionDNNF) -> Vec<bool> {
+    let mut involved = vec![true; ddnnf.n_vars()];
+    for var_index in FreeVariables::compute(ddnnf).root_free_vars() {
+        involved[*var_index] = false;
+    }
+    involved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    fn atomic_sets(instance: &str, n_vars: Option<usize>) -> Vec<Vec<isize>> {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        AtomicSets::compute(&ddnnf)
+            .sets()
+            .iter()
+            .map(|s| s.iter().map(|l| isize::from(*l)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_equal_and_opposite() {
+        // (1 and -2 and 3) or (-1 and 2 and -3)
+        assert_eq!(
+            vec![vec![1, -2, 3]],
+            atomic_sets("o 1 0\nt 2 0\n1 2 1 -2 3 0\n1 2 -1 2 -3 0\n", None)
+        );
+    }
+
+    #[test]
+    fn test_same_marginals_not_equivalent() {
+        // (1 and 2) or (-1 and -2) or (1 and -2 and 3) or (-1 and 2 and -3): 1 and 2 have the same marginals, but are not equivalent
+        assert_eq!(
+            Vec::<Vec<isize>>::new(),
+            atomic_sets(
+                "o 1 0\no 2 0\nt 3 0\n1 3 1 2 0\n1 3 -1 -2 0\n1 2 0\n2 3 1 -2 3 0\n2 3 -1 2 -3 0\n",
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_several_sets() {
+        // ((1 and 2) or (-1 and -2)) and ((3 and -4) or (-3 and 4)), on 5 variables
+        assert_eq!(
+            vec![vec![1, 2], vec![3, -4]],
+            atomic_sets(
+                "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 1 2 0\n2 4 -1 -2 0\n3 4 3 -4 0\n3 4 -3 4 0\n",
+                Some(5)
+            )
+        );
+    }
+
+    #[test]
+    fn test_core_vars() {
+        // 1 and 2 and (-3 or 3)
+        assert_eq!(
+            vec![vec![1, 2]],
+            atomic_sets("o 1 0\nt 2 0\n1 2 1 2 -3 0\n1 2 1 2 3 0\n", None)
+        );
+    }
+
+    #[test]
+    fn test_no_model() {
+        assert_eq!(Vec::<Vec<isize>>::new(), atomic_sets("f 1 0\n", Some(3)));
+    }
+}
@@ -1,7 +1,75 @@
 use crate::{
-    core::{BottomUpVisitor, InvolvedVars, NodeIndex},
-    DecisionDNNF, Literal,
+    core::{BottomUpVisitor, InvolvedVars, Node, NodeIndex},
+    DecisionDNNF, Literal, ModelFinder,
 };
+use anyhow::anyhow;
+use std::{fmt::Display, str::FromStr};
+
+/// The amount of checks to apply when a Decision-DNNF is loaded.
+///
+/// The levels are ordered: each level involves the checks of the previous ones.
+/// Lower levels decrease the loading time, at the cost of safety: algorithms applied on formulas that do not pass the checks may return wrong results or panic.
+///
+/// The levels are the following:
+/// - [`None`](Self::None): no check is made except the ones that are required to build the formula;
+/// - [`Syntactic`](Self::Syntactic): the input data follows the format;
+/// - [`Connectivity`](Self::Connectivity): the formula has a single root, no cycles, and each node is reachable from the root;
+/// - [`Decomposability`](Self::Decomposability): the conjunction nodes are decomposable (see [`CheckingVisitor`]);
+/// - [`Strict`](Self::Strict): in addition, any disjunction node that may not be deterministic is considered as an error.
+///
+/// At the strict level, the determinism warnings of the [`CheckingVisitor`] first go through the lightweight refutation step of [`CheckingVisitorData::refute_warnings`]:
+/// only the warnings it does not refute are errors.
+/// The [`D4Reader`](crate::D4Reader) and the command line tool apply the same rule, so that they accept the same formulas.
+/// Deciding the warnings exactly with [`CheckingVisitorData::decide_warnings`] (option `--exact-determinism` of the command line tool) refutes more warnings, at a higher cost.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::CheckLevel;
+///
+/// let level: CheckLevel = "connectivity".parse().unwrap();
+/// assert_eq!(CheckLevel::Connectivity, level);
+/// assert!(level < CheckLevel::Decomposability);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckLevel {
+    /// No check is made, except the ones required to build the formula.
+    None,
+    /// The input data must follow the format.
+    Syntactic,
+    /// The formula must have a single root, no cycles, and each node must be reachable from the root.
+    Connectivity,
+    /// The conjunction nodes must be decomposable.
+    Decomposability,
+    /// The disjunction nodes must be recognized as deterministic by the [`CheckingVisitor`], possibly after the refutation of its warnings.
+    Strict,
+}
+
+impl CheckLevel {
+    /// The names of the check levels, from the lowest to the highest one.
+    pub const NAMES: [&'static str; 5] = [
+        "none",
+        "syntactic",
+        "connectivity",
+        "decomposability",
+        "strict",
+    ];
+}
+
+impl FromStr for CheckLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(CheckLevel::None),
+            "syntactic" => Ok(CheckLevel::Syntactic),
+            "connectivity" => Ok(CheckLevel::Connectivity),
+            "decomposability" => Ok(CheckLevel::Decomposability),
+            "strict" => Ok(CheckLevel::Strict),
+            _ => Err(anyhow!("unknown check level {s}")),
+        }
+    }
+}
 
 /// A bottom-up algorithm used for an algorithm that checks if a Decision-DNNF is correct.
 ///
@@ -11,8 +79,9 @@ use crate::{
 /// For this reason, potential faults on determinism simply triggers warnings.
 /// Thus, even if the checking process does not returns an error, a check of the list of the warnings emitted during the search should be done.
 ///
-/// The detection of an error stops the checking process.
-/// This is not the case when a warning is raised.
+/// By default, the detection of an error stops the checking process;
+/// use [`set_report_all_errors`](Self::set_report_all_errors) to get all the errors of the formula at once.
+/// The detection of a warning never stops the checking process.
 ///
 /// This object relies on the [`BottomUpVisitor`] trait.
 /// See its documentation for more information.
@@ -39,32 +108,105 @@ use crate::{
 /// # check_decision_dnnf(&decdnnf_rs::D4Reader::read("t 1 0".as_bytes()).unwrap())
 /// ```
 #[derive(Clone, Default)]
-pub struct CheckingVisitor;
+pub struct CheckingVisitor {
+    report_all_errors: bool,
+}
+
+impl CheckingVisitor {
+    /// Sets whether the checking process must go on after an error is detected.
+    ///
+    /// When this option is set, the errors of all the nodes are reported by [`CheckingVisitorData::get_errors`],
+    /// which allows to fix all the issues of a formula at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{BottomUpTraversal, CheckingVisitor, D4Reader};
+    ///
+    /// // two conjunction nodes which children share variable 1
+    /// let ddnnf = D4Reader::read("o 1 0\na 2 0\na 3 0\nt 4 0\n1 2 -2 0\n1 3 2 0\n2 4 1 0\n2 4 -1 0\n3 4 1 0\n3 4 1 0\n".as_bytes()).unwrap();
+    /// let mut visitor = CheckingVisitor::default();
+    /// visitor.set_report_all_errors(true);
+    /// let checking_data = BottomUpTraversal::new(Box::new(visitor)).traverse(&ddnnf);
+    /// assert_eq!(2, checking_data.get_errors().len());
+    /// ```
+    pub fn set_report_all_errors(&mut self, report_all_errors: bool) {
+        self.report_all_errors = report_all_errors;
+    }
+}
 
 /// The data returned by the [`CheckingVisitor`] algorithm.
 ///
 /// See its documentation for more information.
 #[derive(Clone)]
 pub struct CheckingVisitorData {
-    error: Option<String>,
-    warnings: Vec<String>,
+    errors: Vec<CheckerIssue>,
+    warnings: Vec<CheckerIssue>,
     is_false_node: bool,
     involved_vars: InvolvedVars,
 }
 
-impl CheckingVisitorData {
-    fn new_error(message: String) -> Self {
-        Self {
-            error: Some(message),
-            warnings: vec![],
-            is_false_node: false,
-            involved_vars: InvolvedVars::empty(),
+/// An issue detected by the [`CheckingVisitor`].
+///
+/// Issues give the indices of the nodes and of the children involved, so that they can be processed programmatically;
+/// their [`Display`] implementation gives a human-readable message.
+/// Children are given by their index among the children of their parent node.
+#[derive(Clone, Debug)]
+pub enum CheckerIssue {
+    /// Two children of a conjunction node share variables, which is an error.
+    NonDecomposableAnd {
+        /// The conjunction node.
+        node: NodeIndex,
+        /// The indices of the two children.
+        children: (usize, usize),
+        /// The indices of the variables the children share.
+        shared_vars: Vec<usize>,
+    },
+    /// The literals propagated to two children of a disjunction node are not contradictory, so they may share models, which is a warning.
+    PossiblyNonDeterministicOr {
+        /// The disjunction node.
+        node: NodeIndex,
+        /// The indices of the two children.
+        children: (usize, usize),
+    },
+}
+
+impl CheckerIssue {
+    /// Returns the index of the node and the indices of the children the issue is about.
+    fn location(&self) -> (usize, (usize, usize)) {
+        match self {
+            CheckerIssue::NonDecomposableAnd { node, children, .. }
+            | CheckerIssue::PossiblyNonDeterministicOr { node, children } => {
+                (usize::from(*node), *children)
+            }
         }
     }
+}
 
+impl Display for CheckerIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckerIssue::NonDecomposableAnd { node, .. } => write!(
+                f,
+                "AND children share variables (AND node index is {})",
+                usize::from(*node)
+            ),
+            CheckerIssue::PossiblyNonDeterministicOr {
+                node,
+                children: (i, j),
+            } => write!(
+                f,
+                "OR children at indices {i} and {j} may not be contradictory (OR node index is {})",
+                usize::from(*node)
+            ),
+        }
+    }
+}
+
+impl CheckingVisitorData {
     fn new_involved_vars(involved_vars: InvolvedVars) -> Self {
         Self {
-            error: None,
+            errors: vec![],
             warnings: vec![],
             is_false_node: false,
             involved_vars,
@@ -73,7 +215,7 @@ impl CheckingVisitorData {
 
     fn new_for_leaf(n_vars: usize, is_false_node: bool) -> Self {
         Self {
-            error: None,
+            errors: vec![],
             warnings: vec![],
             is_false_node,
             involved_vars: InvolvedVars::new(n_vars),
@@ -81,17 +223,211 @@ impl CheckingVisitorData {
     }
 
     /// Return an option containing an error, if one was discovered during the traversal.
+    ///
+    /// If several errors were discovered, the first one returned by [`get_errors`](Self::get_errors) is given.
+    #[must_use]
+    pub fn get_error(&self) -> Option<&CheckerIssue> {
+        self.errors.first()
+    }
+
+    /// Returns the list of errors discovered during the traversal, sorted by node index.
+    ///
+    /// Unless the checker was set to report all the errors (see [`CheckingVisitor::set_report_all_errors`]), the list contains at most one error.
     #[must_use]
-    pub fn get_error(&self) -> Option<&str> {
-        self.error.as_deref()
+    pub fn get_errors(&self) -> &[CheckerIssue] {
+        &self.errors
     }
 
-    /// Returns the list of warnings produced by the checker.
+    /// Returns the list of warnings produced by the checker, sorted by node index.
     /// The list is empty if none.
     #[must_use]
-    pub fn get_warnings(&self) -> &[String] {
+    pub fn get_warnings(&self) -> &[CheckerIssue] {
         &self.warnings
     }
+
+    /// Tries to confirm or discharge the determinism warnings, returning a verdict for each of them.
+    ///
+    /// For each pair of disjunction children that may not be contradictory, the conjunction of the literals propagated to both children is considered.
+    /// If one of the children is inconsistent with this conjunction, the pair is contradictory and the warning is refuted.
+    /// If a model of one of the children can be extended into a model of the other one, the warning is confirmed.
+    /// Otherwise, the verdict is unknown.
+    ///
+    /// The verdicts are given in the order of the warnings returned by [`get_warnings`](Self::get_warnings).
+    /// The formula must be the one this data was computed on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{BottomUpTraversal, CheckingVisitor, D4Reader, DeterminismVerdict};
+    ///
+    /// // the second child propagates 1, which is inconsistent with its subformula
+    /// let ddnnf = D4Reader::read(r"
+    /// o 1 0
+    /// o 2 0
+    /// t 3 0
+    /// 1 3 -2 0
+    /// 1 2 1 0
+    /// 2 3 -1 0
+    /// ".as_bytes()).unwrap();
+    /// let checking_data = BottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(&ddnnf);
+    /// assert_eq!(1, checking_data.get_warnings().len());
+    /// assert_eq!(vec![DeterminismVerdict::Refuted], checking_data.refute_warnings(&ddnnf));
+    /// ```
+    #[must_use]
+    pub fn refute_warnings(&self, ddnnf: &DecisionDNNF) -> Vec<DeterminismVerdict> {
+        let finder = ModelFinder::new(ddnnf);
+        self.undetermined_pairs()
+            .map(|(or_node, i, j)| determinism_verdict(ddnnf, &finder, or_node, i, j))
+            .collect()
+    }
+
+    /// Decides the determinism warnings exactly, returning a verdict for each of them.
+    ///
+    /// Contrary to [`refute_warnings`](Self::refute_warnings), the verdicts are never [`DeterminismVerdict::Unknown`].
+    /// For each pair of disjunction children that may not be contradictory, the search looks for a common model of both children.
+    /// It computes a partial model of each child under the literals propagated to both of them;
+    /// if these partial models are consistent, their union is a common model, and the warning is confirmed.
+    /// Otherwise, the search branches on a variable the partial models disagree on, and tries both values.
+    /// If no branch leads to a common model, the warning is refuted.
+    ///
+    /// The verdicts are given in the order of the warnings returned by [`get_warnings`](Self::get_warnings).
+    /// The formula must be the one this data was computed on.
+    /// Since deciding whether two children share a model is a hard problem, this function may take a time exponential in the number of variables.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{BottomUpTraversal, CheckingVisitor, D4Reader, DeterminismVerdict};
+    ///
+    /// // ((-1 and -2) or (1 and 2)) or ((1 and -2) or (-1 and 2) or (1 and 2)): both children have the model {1, 2}
+    /// let ddnnf = D4Reader::read(r"
+    /// o 1 0
+    /// o 2 0
+    /// o 3 0
+    /// t 4 0
+    /// 1 2 0
+    /// 1 3 0
+    /// 2 4 -1 -2 0
+    /// 2 4 1 2 0
+    /// 3 4 1 -2 0
+    /// 3 4 -1 2 0
+    /// 3 4 1 2 0
+    /// ".as_bytes()).unwrap();
+    /// let checking_data = BottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(&ddnnf);
+    /// assert_eq!(vec![DeterminismVerdict::Unknown], checking_data.refute_warnings(&ddnnf));
+    /// assert_eq!(vec![DeterminismVerdict::Confirmed], checking_data.decide_warnings(&ddnnf));
+    /// ```
+    #[must_use]
+    pub fn decide_warnings(&self, ddnnf: &DecisionDNNF) -> Vec<DeterminismVerdict> {
+        let finder = ModelFinder::new(ddnnf);
+        self.undetermined_pairs()
+            .map(|(or_node, i, j)| exact_determinism_verdict(ddnnf, &finder, or_node, i, j))
+            .collect()
+    }
+
+    fn undetermined_pairs(&self) -> impl Iterator<Item = (NodeIndex, usize, usize)> + '_ {
+        self.warnings.iter().filter_map(|w| match w {
+            CheckerIssue::PossiblyNonDeterministicOr {
+                node,
+                children: (i, j),
+            } => Some((*node, *i, *j)),
+            CheckerIssue::NonDecomposableAnd { .. } => None,
+        })
+    }
+}
+
+/// The verdict of the refutation step applied on a determinism warning.
+///
+/// See [`CheckingVisitorData::refute_warnings`] for more information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeterminismVerdict {
+    /// The children share a model: the disjunction node is not deterministic.
+    Confirmed,
+    /// The children are contradictory: the warning is a false alarm.
+    Refuted,
+    /// The refutation step was not able to conclude.
+    Unknown,
+}
+
+impl Display for DeterminismVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeterminismVerdict::Confirmed => write!(f, "confirmed"),
+            DeterminismVerdict::Refuted => write!(f, "refuted"),
+            DeterminismVerdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+fn determinism_verdict(
+    ddnnf: &DecisionDNNF,
+    finder: &ModelFinder,
+    or_node: NodeIndex,
+    i: usize,
+    j: usize,
+) -> DeterminismVerdict {
+    let Node::Or(edge_indices) = ddnnf.node(or_node) else {
+        unreachable!()
+    };
+    let edges = [ddnnf.edge(edge_indices[i]), ddnnf.edge(edge_indices[j])];
+    let mut propagated = edges[0].propagated().to_vec();
+    propagated.extend_from_slice(edges[1].propagated());
+    let mut models = Vec::with_capacity(2);
+    for edge in edges {
+        match finder.find_partial_model_from_node(edge.target(), &propagated) {
+            Some(m) => models.push(m),
+            None => return DeterminismVerdict::Refuted,
+        }
+    }
+    for (model, other_edge) in models.iter().zip(edges.iter().rev()) {
+        let mut assumptions = propagated.clone();
+        assumptions.extend_from_slice(model);
+        if finder
+            .find_partial_model_from_node(other_edge.target(), &assumptions)
+            .is_some()
+        {
+            return DeterminismVerdict::Confirmed;
+        }
+    }
+    DeterminismVerdict::Unknown
+}
+
+fn exact_determinism_verdict(
+    ddnnf: &DecisionDNNF,
+    finder: &ModelFinder,
+    or_node: NodeIndex,
+    i: usize,
+    j: usize,
+) -> DeterminismVerdict {
+    let Node::Or(edge_indices) = ddnnf.node(or_node) else {
+        unreachable!()
+    };
+    let edges = [ddnnf.edge(edge_indices[i]), ddnnf.edge(edge_indices[j])];
+    let mut propagated = edges[0].propagated().to_vec();
+    propagated.extend_from_slice(edges[1].propagated());
+    let mut to_explore = vec![propagated];
+    while let Some(assumptions) = to_explore.pop() {
+        let Some(model) = finder.find_partial_model_from_node(edges[0].target(), &assumptions)
+        else {
+            continue;
+        };
+        let Some(other_model) =
+            finder.find_partial_model_from_node(edges[1].target(), &assumptions)
+        else {
+            continue;
+        };
+        match model.iter().find(|l| other_model.contains(&l.flip())) {
+            None => return DeterminismVerdict::Confirmed,
+            Some(l) => {
+                for branch in [*l, l.flip()] {
+                    let mut branch_assumptions = assumptions.clone();
+                    branch_assumptions.push(branch);
+                    to_explore.push(branch_assumptions);
+                }
+            }
+        }
+    }
+    DeterminismVerdict::Refuted
 }
 
 impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
@@ -101,8 +437,10 @@ impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
         path: &[NodeIndex],
         children: Vec<(&[Literal], CheckingVisitorData)>,
     ) -> CheckingVisitorData {
-        if let Some(error) = get_error(&children) {
-            return error;
+        if !self.report_all_errors {
+            if let Some(error) = get_error(&children) {
+                return error;
+            }
         }
         let involved_in_children = children
             .iter()
@@ -112,19 +450,30 @@ impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
                 bv
             })
             .collect::<Vec<_>>();
-        for i in 0..involved_in_children.len() - 1 {
+        let mut errors = Vec::new();
+        'pairs: for i in 0..involved_in_children.len() - 1 {
             for j in i + 1..involved_in_children.len() {
                 let mut intersection = involved_in_children[i].clone();
                 intersection.and_assign(&involved_in_children[j]);
                 if intersection.any() {
-                    return CheckingVisitorData::new_error(format!(
-                        "AND children share variables (AND node index is {})",
-                        usize::from(*path.last().unwrap())
-                    ));
+                    errors.push(CheckerIssue::NonDecomposableAnd {
+                        node: *path.last().unwrap(),
+                        children: (i, j),
+                        shared_vars: intersection
+                            .iter_pos_literals()
+                            .map(|l| l.var_index())
+                            .collect(),
+                    });
+                    if !self.report_all_errors {
+                        break 'pairs;
+                    }
                 }
             }
         }
-        CheckingVisitorData::new_involved_vars(InvolvedVars::union(involved_in_children))
+        let mut result =
+            CheckingVisitorData::new_involved_vars(InvolvedVars::union(involved_in_children));
+        merge_issues(&mut result, errors, vec![], &children);
+        result
     }
 
     fn merge_for_or(
@@ -133,8 +482,10 @@ impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
         path: &[NodeIndex],
         children: Vec<(&[Literal], CheckingVisitorData)>,
     ) -> CheckingVisitorData {
-        if let Some(error) = get_error(&children) {
-            return error;
+        if !self.report_all_errors {
+            if let Some(error) = get_error(&children) {
+                return error;
+            }
         }
         let mut warnings = Vec::new();
         for i in 0..children.len() - 1 {
@@ -144,8 +495,10 @@ impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
             for j in i + 1..children.len() {
                 if !children[j].1.is_false_node && !are_contradictory(children[i].0, children[j].0)
                 {
-                    warnings.push(format!("OR children at indices {i} and {j} may not be contradictory (OR node index is {})", usize::from(*path.last()
-                .unwrap())));
+                    warnings.push(CheckerIssue::PossiblyNonDeterministicOr {
+                        node: *path.last().unwrap(),
+                        children: (i, j),
+                    });
                 }
             }
         }
@@ -158,7 +511,7 @@ impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
             },
         );
         let mut result = CheckingVisitorData::new_involved_vars(involved_vars);
-        result.warnings = warnings;
+        merge_issues(&mut result, vec![], warnings, &children);
         result
     }
 
@@ -169,15 +522,44 @@ impl BottomUpVisitor<CheckingVisitorData> for CheckingVisitor {
     fn new_for_false(&self, ddnnf: &DecisionDNNF, _path: &[NodeIndex]) -> CheckingVisitorData {
         CheckingVisitorData::new_for_leaf(ddnnf.n_vars(), true)
     }
+
+    fn is_cacheable(&self, _ddnnf: &DecisionDNNF, _path: &[NodeIndex]) -> bool {
+        true
+    }
 }
 
 fn get_error(children: &[(&[Literal], CheckingVisitorData)]) -> Option<CheckingVisitorData> {
     children
         .iter()
-        .position(|(_, child)| child.error.is_some())
+        .position(|(_, child)| !child.errors.is_empty())
         .map(|p| children[p].1.clone())
 }
 
+/// Sets the issues of a node, given the ones detected at this node and the ones of its children.
+///
+/// Since a node is reached once for each path from the root, the issues of the nodes reached by several paths are only kept once.
+fn merge_issues(
+    result: &mut CheckingVisitorData,
+    errors: Vec<CheckerIssue>,
+    warnings: Vec<CheckerIssue>,
+    children: &[(&[Literal], CheckingVisitorData)],
+) {
+    let merge =
+        |mut issues: Vec<CheckerIssue>,
+         children_issues: &dyn Fn(&CheckingVisitorData) -> &[CheckerIssue]| {
+            issues.extend(
+                children
+                    .iter()
+                    .flat_map(|(_, c)| children_issues(c).iter().cloned()),
+            );
+            issues.sort_by_key(CheckerIssue::location);
+            issues.dedup_by_key(|i| i.location());
+            issues
+        };
+    result.errors = merge(errors, &|c| &c.errors);
+    result.warnings = merge(warnings, &|c| &c.warnings);
+}
+
 fn are_contradictory(p0: &[Literal], p1: &[Literal]) -> bool {
     p0.iter().any(|l| p1.contains(&l.flip()))
 }
@@ -193,9 +575,22 @@ mod tests {
         let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
         let traversal = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
         let result = traversal.traverse(&ddnnf);
+        let error = result.get_error().unwrap().clone();
         assert_eq!(
             "AND children share variables (AND node index is 0)",
-            result.error.unwrap()
+            error.to_string()
+        );
+        let CheckerIssue::NonDecomposableAnd {
+            node,
+            children,
+            shared_vars,
+        } = error
+        else {
+            panic!()
+        };
+        assert_eq!(
+            (0, (0, 1), vec![0]),
+            (usize::from(node), children, shared_vars)
         );
     }
 
@@ -205,13 +600,139 @@ mod tests {
         let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
         let traversal = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
         let result = traversal.traverse(&ddnnf);
-        assert!(result.error.is_none());
+        assert!(result.get_error().is_none());
         assert_eq!(
             vec!["OR children at indices 0 and 1 may not be contradictory (OR node index is 0)"],
-            result.warnings
+            result
+                .warnings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            result.warnings[0],
+            CheckerIssue::PossiblyNonDeterministicOr {
+                children: (0, 1),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_report_all_errors() {
+        // the root and both its children are not decomposable
+        let str_ddnnf =
+            "a 1 0\na 2 0\na 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 1 0\n2 4 -1 0\n3 4 1 0\n3 4 1 0\n";
+        let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        let result = BottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(&ddnnf);
+        assert_eq!(1, result.get_errors().len());
+        let mut visitor = CheckingVisitor::default();
+        visitor.set_report_all_errors(true);
+        let result = BottomUpTraversal::new(Box::new(visitor)).traverse(&ddnnf);
+        assert_eq!(
+            vec![0, 1, 2],
+            result
+                .get_errors()
+                .iter()
+                .map(|e| e.location().0)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_inner_warnings_reported_once() {
+        // both children of the root lead to a non-deterministic disjunction node
+        let str_ddnnf = "o 1 0\no 2 0\nt 3 0\n1 2 -1 0\n1 2 1 0\n2 3 2 0\n2 3 2 0\n";
+        let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        let result = BottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(&ddnnf);
+        assert!(result.get_error().is_none());
+        assert_eq!(
+            vec!["OR children at indices 0 and 1 may not be contradictory (OR node index is 1)"],
+            result
+                .get_warnings()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn verdicts(str_ddnnf: &str) -> Vec<DeterminismVerdict> {
+        let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        let traversal = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
+        traversal.traverse(&ddnnf).refute_warnings(&ddnnf)
+    }
+
+    fn decisions(str_ddnnf: &str) -> Vec<DeterminismVerdict> {
+        let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        let traversal = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
+        traversal.traverse(&ddnnf).decide_warnings(&ddnnf)
+    }
+
+    #[test]
+    fn test_refute_warnings_confirmed() {
+        assert_eq!(
+            vec![DeterminismVerdict::Confirmed],
+            verdicts("o 1 0\nt 2 0\n1 2 1 0\n1 2 1 0")
+        );
+    }
+
+    #[test]
+    fn test_refute_warnings_refuted() {
+        assert_eq!(
+            vec![DeterminismVerdict::Refuted],
+            verdicts("o 1 0\no 2 0\nt 3 0\n1 3 -2 0\n1 2 1 0\n2 3 -1 0\n")
         );
     }
 
+    #[test]
+    fn test_refute_warnings_unknown() {
+        let str_ddnnf = r"
+        o 1 0
+        o 2 0
+        o 3 0
+        t 4 0
+        1 2 0
+        1 3 0
+        2 4 -1 -2 0
+        2 4 1 2 0
+        3 4 1 -2 0
+        3 4 -1 2 0
+        3 4 1 2 0
+        ";
+        assert_eq!(vec![DeterminismVerdict::Unknown], verdicts(str_ddnnf));
+    }
+
+    #[test]
+    fn test_decide_warnings() {
+        assert_eq!(
+            vec![DeterminismVerdict::Confirmed],
+            decisions("o 1 0\nt 2 0\n1 2 1 0\n1 2 1 0")
+        );
+        assert_eq!(
+            vec![DeterminismVerdict::Refuted],
+            decisions("o 1 0\no 2 0\nt 3 0\n1 3 -2 0\n1 2 1 0\n2 3 -1 0\n")
+        );
+    }
+
+    #[test]
+    fn test_decide_warnings_branching() {
+        // (1 and 2) or (-1 and -2), against (1 and -2) or (-1 and 2): the first partial models disagree, and no common model exists
+        let str_ddnnf = r"
+        o 1 0
+        o 2 0
+        o 3 0
+        t 4 0
+        1 2 0
+        1 3 0
+        2 4 1 2 0
+        2 4 -1 -2 0
+        3 4 1 -2 0
+        3 4 -1 2 0
+        ";
+        assert_eq!(vec![DeterminismVerdict::Unknown], verdicts(str_ddnnf));
+        assert_eq!(vec![DeterminismVerdict::Refuted], decisions(str_ddnnf));
+    }
+
     #[test]
     fn test_ok() {
         let str_ddnnf =
@@ -219,7 +740,7 @@ mod tests {
         let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
         let traversal = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
         let result = traversal.traverse(&ddnnf);
-        assert!(result.error.is_none());
+        assert!(result.get_error().is_none());
     }
 
     #[test]
@@ -228,6 +749,6 @@ mod tests {
         let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
         let traversal = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
         let result = traversal.traverse(&ddnnf);
-        assert!(result.error.is_none());
+        assert!(result.get_error().is_none());
     }
 }
@@ -0,0 +1,127 @@
+use crate::{Cnf, DecisionDNNF, ModelCounter, QueryEngine};
+use anyhow::{anyhow, Result};
+use rug::Integer;
+
+/// A structure used to check a [`DecisionDNNF`] against the CNF formula it was compiled from.
+///
+/// A correct compilation produces a formula equivalent to the CNF one.
+/// This validator checks that each clause of the CNF formula is entailed by the Decision-DNNF, which means that the Decision-DNNF has no model that falsifies the CNF formula.
+/// Together with the equality of the model counts, this ensures that both formulas are equivalent.
+/// The model count of the CNF formula is not computed here; it can be compared to a count given by an external tool using [`count_matches`](Self::count_matches).
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{Cnf, CnfValidator, D4Reader};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let cnf = Cnf::from_reader("p cnf 2 1\n1 -2 0\n".as_bytes()).unwrap();
+/// let validator = CnfValidator::new(&ddnnf, &cnf).unwrap();
+/// assert!(validator.non_entailed_clauses().is_empty());
+/// assert!(validator.count_matches(&3.into()));
+/// ```
+pub struct CnfValidator<'a> {
+    cnf: &'a Cnf,
+    query_engine: QueryEngine<'a>,
+    model_counter: ModelCounter<'a>,
+}
+
+impl<'a> CnfValidator<'a> {
+    /// Builds a new validator given a [`DecisionDNNF`] and the CNF formula it should be equivalent to.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the formulas do not have the same number of variables.
+    pub fn new(ddnnf: &'a DecisionDNNF, cnf: &'a Cnf) -> Result<Self> {
+        if ddnnf.n_vars() != cnf.n_vars() {
+            return Err(anyhow!(
+                "the Decision-DNNF has {} variables while the CNF formula has {}",
+                ddnnf.n_vars(),
+                cnf.n_vars()
+            ));
+        }
+        Ok(Self {
+            cnf,
+            query_engine: QueryEngine::new(ddnnf),
+            model_counter: ModelCounter::new(ddnnf),
+        })
+    }
+
+    /// Returns the indices of the clauses of the CNF formula that are not entailed by the Decision-DNNF, in increasing order.
+    ///
+    /// Each clause is checked in a time linear in the size of the Decision-DNNF.
+    #[must_use]
+    pub fn non_entailed_clauses(&self) -> Vec<usize> {
+        self.cnf
+            .clauses()
+            .iter()
+            .enumerate()
+            .filter(|(_, clause)| !self.query_engine.entails_clause(clause))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns `true` if and only if the model count of the Decision-DNNF is the given one.
+    #[must_use]
+    pub fn count_matches(&self, expected: &Integer) -> bool {
+        self.model_counter.count() == expected
+    }
+
+    /// Returns the model count of the Decision-DNNF.
+    #[must_use]
+    pub fn count(&self) -> &Integer {
+        self.model_counter.count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    // (-1 and -2) or (1 and (-3 or 3)), i.e. 1 or -2
+    const INSTANCE: &str = "o 1 0\no 2 0\nt 3 0\n1 3 -1 -2 0\n1 2 1 0\n2 3 -3 0\n2 3 3 0\n";
+
+    fn non_entailed(cnf: &str) -> Vec<usize> {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let cnf = Cnf::from_reader(cnf.as_bytes()).unwrap();
+        CnfValidator::new(&ddnnf, &cnf)
+            .unwrap()
+            .non_entailed_clauses()
+    }
+
+    #[test]
+    fn test_equivalent() {
+        assert!(non_entailed("p cnf 3 1\n1 -2 0\n").is_empty());
+    }
+
+    #[test]
+    fn test_non_entailed_clauses() {
+        assert_eq!(
+            vec![1, 2],
+            non_entailed("p cnf 3 4\n1 -2 0\n3 0\n-1 2 0\n1 -2 3 0\n")
+        );
+    }
+
+    #[test]
+    fn test_empty_clause() {
+        assert_eq!(vec![0], non_entailed("p cnf 3 1\n0\n"));
+    }
+
+    #[test]
+    fn test_count() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let cnf = Cnf::from_reader("p cnf 3 1\n1 -2 0\n".as_bytes()).unwrap();
+        let validator = CnfValidator::new(&ddnnf, &cnf).unwrap();
+        assert!(validator.count_matches(&Integer::from(6)));
+        assert!(!validator.count_matches(&Integer::from(5)));
+    }
+
+    #[test]
+    fn test_var_mismatch() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let cnf = Cnf::from_reader("p cnf 4 0\n".as_bytes()).unwrap();
+        assert!(CnfValidator::new(&ddnnf, &cnf).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    core::{assumed_polarities, EdgeIndex, InvolvedVars, LitMap, Node, VarMap},
+    DecisionDNNF, Literal,
+};
+use anyhow::{anyhow, Result};
+use rug::Integer;
+use std::collections::BTreeMap;
+
+/// A structure used to compute the distribution of the costs of the models of a [`DecisionDNNF`].
+///
+/// A cost is attached to each literal; the cost of a model is the sum of the costs of its literals.
+/// For each cost, the distribution gives the number of models having this cost, from which the range of the costs can be read.
+/// Models can be restricted by assumptions, e.g. to get the range of the prices of the configurations that remain after some choices.
+///
+/// The distributions are computed by a bottom-up dynamic program, in which conjunction nodes convolve the distributions of their children and disjunction nodes sum them.
+/// The time and memory it takes are polynomial in the size of the formula and the number of distinct costs, which is low when the costs are small integers.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{CostDistributionComputer, D4Reader, LitMap, Literal};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let mut costs = LitMap::new(2, 0);
+/// costs[Literal::from(1)] = 10;
+/// costs[Literal::from(2)] = 5;
+/// let computer = CostDistributionComputer::new(&ddnnf, costs);
+/// let distribution = computer.distribution();
+/// assert_eq!(Some(0), distribution.min_cost());
+/// assert_eq!(Some(15), distribution.max_cost());
+/// let under_assumptions = computer.distribution_under_assumptions(&[Literal::from(1)]);
+/// assert_eq!(Some(10), under_assumptions.min_cost());
+/// assert_eq!(2, under_assumptions.n_models().to_usize_wrapping());
+/// ```
+pub struct CostDistributionComputer<'a> {
+    ddnnf: &'a DecisionDNNF,
+    costs: LitMap<i64>,
+}
+
+impl<'a> CostDistributionComputer<'a> {
+    /// Builds a new computer given a [`DecisionDNNF`] and the costs of the literals.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of variables of the costs does not match the one of the formula.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF, costs: LitMap<i64>) -> Self {
+        Self::try_new(ddnnf, costs).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Builds a new computer, as [`new`](Self::new) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the number of variables of the costs does not match the one of the formula.
+    pub fn try_new(ddnnf: &'a DecisionDNNF, costs: LitMap<i64>) -> Result<Self> {
+        if ddnnf.n_vars() != costs.n_vars() {
+            return Err(anyhow!(
+                "the costs must be given for the variables of the formula"
+            ));
+        }
+        Ok(Self { ddnnf, costs })
+    }
+
+    /// Returns the distribution of the costs of the models of the formula.
+    #[must_use]
+    pub fn distribution(&self) -> CostDistribution {
+        self.distribution_under_assumptions(&[])
+    }
+
+    /// Returns the distribution of the costs of the models of the formula that are consistent with some assumptions.
+    ///
+    /// Assumptions are given as literals.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an assumption refers to a variable that does not exist.
+    #[must_use]
+    pub fn distribution_under_assumptions(&self, assumptions: &[Literal]) -> CostDistribution {
+        self.try_distribution_under_assumptions(assumptions)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the distribution of the costs of the models of the formula that are consistent with some assumptions,
+    /// as [`distribution_under_assumptions`](Self::distribution_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn try_distribution_under_assumptions(
+        &self,
+        assumptions: &[Literal],
+    ) -> Result<CostDistribution> {
+        let Some(assumed) = assumed_polarities(assumptions, self.ddnnf.n_vars())? else {
+            return Ok(CostDistribution::default());
+        };
+        let n_vars = self.ddnnf.n_vars();
+        let mut node_data: Vec<Option<(Costs, InvolvedVars)>> = vec![None; self.ddnnf.n_nodes()];
+        for node in self.ddnnf.topological_order().into_iter().rev() {
+            let data = match self.ddnnf.node(node) {
+                Node::And(edges) => {
+                    let mut costs = Costs::from([(0, Integer::from(1))]);
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for edge_index in edges {
+                        let (child_costs, child_vars) =
+                            self.edge_data(*edge_index, &assumed, &node_data);
+                        costs = convolve(&costs, &child_costs);
+                        involved_vars.or_assign(&child_vars);
+                    }
+                    (costs, involved_vars)
+                }
+                Node::Or(edges) => {
+                    let children = edges
+                        .iter()
+                        .map(|e| self.edge_data(*e, &assumed, &node_data))
+                        .collect::<Vec<_>>();
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for (_, child_vars) in &children {
+                        involved_vars.or_assign(child_vars);
+                    }
+                    let mut costs = Costs::new();
+                    for (child_costs, mut child_vars) in children {
+                        child_vars.xor_assign(&involved_vars);
+                        let child_costs = self.add_free_vars(
+                            child_costs,
+                            child_vars.iter_pos_literals(),
+                            &assumed,
+                        );
+                        for (c, n) in child_costs {
+                            *costs.entry(c).or_default() += n;
+                        }
+                    }
+                    (costs, involved_vars)
+                }
+                Node::True => (
+                    Costs::from([(0, Integer::from(1))]),
+                    InvolvedVars::new(n_vars),
+                ),
+                Node::False => (Costs::new(), InvolvedVars::new(n_vars)),
+            };
+            node_data[usize::from(node)] = Some(data);
+        }
+        let (costs, involved_vars) = node_data.swap_remove(0).unwrap();
+        let costs = self.add_free_vars(costs, involved_vars.iter_missing_literals(), &assumed);
+        Ok(CostDistribution { costs })
+    }
+
+    fn edge_data(
+        &self,
+        edge_index: EdgeIndex,
+        assumed: &VarMap<Option<bool>>,
+        node_data: &[Option<(Costs, InvolvedVars)>],
+    ) -> (Costs, InvolvedVars) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (target_costs, target_vars) = node_data[usize::from(edge.target())].as_ref().unwrap();
+        let mut involved_vars = target_vars.clone();
+        involved_vars.set_literals(edge.propagated());
+        if edge
+            .propagated()
+            .iter()
+            .any(|l| assumed[*l].is_some_and(|p| p != l.polarity()))
+        {
+            return (Costs::new(), involved_vars);
+        }
+        let shift = edge
+            .propagated()
+            .iter()
+            .map(|l| self.costs[*l])
+            .sum::<i64>();
+        let costs = target_costs
+            .iter()
+            .map(|(c, n)| (c + shift, n.clone()))
+            .collect();
+        (costs, involved_vars)
+    }
+
+    /// Convolves a distribution with the ones of free variables, given by their positive literals.
+    fn add_free_vars<I>(
+        &self,
+        mut costs: Costs,
+        free_vars: I,
+        assumed: &VarMap<Option<bool>>,
+    ) -> Costs
+    where
+        I: Iterator<Item = Literal>,
+    {
+        for l in free_vars {
+            let var_costs = match assumed[l] {
+                Some(true) => Costs::from([(self.costs[l], Integer::from(1))]),
+                Some(false) => Costs::from([(self.costs[l.flip()], Integer::from(1))]),
+                None => {
+                    let mut var_costs = Costs::from([(self.costs[l], Integer::from(1))]);
+                    *var_costs.entry(self.costs[l.flip()]).or_default() += 1;
+                    var_costs
+                }
+            };
+            costs = convolve(&costs, &var_costs);
+        }
+        costs
+    }
+}
+
+type Costs = BTreeMap<i64, Integer>;
+
+fn convolve(costs: &Costs, other_costs: &Costs) -> Costs {
+    let mut result = Costs::new();
+    for (c0, n0) in costs {
+        for (c1, n1) in other_costs {
+            let mut n = n0.clone();
+            n *= n1;
+            *result.entry(c0 + c1).or_default() += n;
+        }
+    }
+    result
+}
+
+/// The distribution of the costs of some models, computed by a [`CostDistributionComputer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CostDistribution {
+    costs: Costs,
+}
+
+impl CostDistribution {
+    /// Returns the minimal cost of a model, or [`None`] if there is no model.
+    #[must_use]
+    pub fn min_cost(&self) -> Option<i64> {
+        self.costs.keys().next().copied()
+    }
+
+    /// Returns the maximal cost of a model, or [`None`] if there is no model.
+    #[must_use]
+    pub fn max_cost(&self) -> Option<i64> {
+        self.costs.keys().next_back().copied()
+    }
+
+    /// Returns the number of models.
+    #[must_use]
+    pub fn n_models(&self) -> Integer {
+        self.costs.values().sum()
+    }
+
+    /// Returns the number of models having the given cost.
+    #[must_use]
+    pub fn n_models_with_cost(&self, cost: i64) -> Integer {
+        self.costs.get(&cost).cloned().unwrap_or_default()
+    }
+
+    /// Iterates over the costs of the models, in increasing order, together with the number of models having them.
+    ///
+    /// The costs no model has are not given.
+    pub fn iter(&self) -> impl Iterator<Item = (i64, &Integer)> + '_ {
+        self.costs.iter().map(|(c, n)| (*c, n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    // (-1 and -2) or (1 and (-3 or 3))
+    const INSTANCE: &str = "o 1 0\no 2 0\nt 3 0\n1 3 -1 -2 0\n1 2 1 0\n2 3 -3 0\n2 3 3 0\n";
+
+    fn histogram(
+        instance: &str,
+        costs: &[(isize, i64)],
+        assumptions: &[isize],
+    ) -> Vec<(i64, usize)> {
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let mut cost_map = LitMap::new(ddnnf.n_vars(), 0);
+        for (l, c) in costs {
+            cost_map[Literal::from(*l)] = *c;
+        }
+        let assumptions = assumptions
+            .iter()
+            .map(|l| Literal::from(*l))
+            .collect::<Vec<_>>();
+        CostDistributionComputer::new(&ddnnf, cost_map)
+            .distribution_under_assumptions(&assumptions)
+            .iter()
+            .map(|(c, n)| (c, n.to_usize_wrapping()))
+            .collect()
+    }
+
+    #[test]
+    fn test_distribution() {
+        // the models are -1 -2 -3, -1 -2 3, 1 ±2 ±3
+        assert_eq!(
+            vec![(0, 1), (1, 1), (4, 1), (5, 1), (6, 1), (7, 1)],
+            histogram(INSTANCE, &[(1, 4), (2, 2), (3, 1)], &[])
+        );
+        assert_eq!(
+            vec![(0, 1), (1, 2), (2, 2), (3, 1)],
+            histogram(INSTANCE, &[(-1, 1), (-2, 1), (-3, 1)], &[])
+        );
+    }
+
+    #[test]
+    fn test_under_assumptions() {
+        assert_eq!(
+            vec![(4, 1), (5, 1), (6, 1), (7, 1)],
+            histogram(INSTANCE, &[(1, 4), (2, 2), (3, 1)], &[1])
+        );
+        assert_eq!(
+            vec![(1, 1), (5, 1)],
+            histogram(INSTANCE, &[(1, 4), (2, 2), (3, 1)], &[-2, 3])
+        );
+        assert!(histogram(INSTANCE, &[], &[-1, 2]).is_empty());
+        assert!(histogram(INSTANCE, &[], &[1, -1]).is_empty());
+    }
+
+    #[test]
+    fn test_free_vars() {
+        let mut ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let mut costs = LitMap::new(2, 1);
+        costs[Literal::from(-2)] = 3;
+        let distribution = CostDistributionComputer::new(&ddnnf, costs).distribution();
+        assert_eq!(Some(2), distribution.min_cost());
+        assert_eq!(Some(4), distribution.max_cost());
+        assert_eq!(2, distribution.n_models_with_cost(2).to_usize_wrapping());
+        assert_eq!(2, distribution.n_models_with_cost(4).to_usize_wrapping());
+        assert_eq!(4, distribution.n_models().to_usize_wrapping());
+    }
+
+    #[test]
+    fn test_false() {
+        let ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        let distribution = CostDistributionComputer::new(&ddnnf, LitMap::new(0, 0)).distribution();
+        assert_eq!(None, distribution.min_cost());
+        assert_eq!(0, distribution.n_models().to_usize_wrapping());
+    }
+
+    #[test]
+    fn test_unknown_assumption() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let computer = CostDistributionComputer::new(&ddnnf, LitMap::new(3, 0));
+        assert!(computer
+            .try_distribution_under_assumptions(&[Literal::from(4)])
+            .is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use crate::{
+    core::{InvolvedVars, Node, NodeIndex},
+    DecisionDNNF, ModelCounter,
+};
+use anyhow::{anyhow, Context, Result};
+use rug::Integer;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    str::FromStr,
+};
+
+impl ModelCounter<'_> {
+    /// Writes a certificate of the model count of the formula, which can be checked by a [`CountCertificateVerifier`].
+    ///
+    /// See [`CountCertificateVerifier`] for a description of the certificate format.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the certificate cannot be written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{CountCertificateVerifier, D4Reader, ModelCounter};
+    ///
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let mut certificate = Vec::new();
+    /// ModelCounter::new(&ddnnf).write_certificate(&mut certificate).unwrap();
+    /// let verified = CountCertificateVerifier::new(&ddnnf).verify(certificate.as_slice()).unwrap();
+    /// assert_eq!(2, verified);
+    /// ```
+    pub fn write_certificate<W>(&self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let ddnnf = self.ddnnf();
+        let hashes = structure_hashes(ddnnf);
+        writeln!(writer, "c model count certificate")?;
+        writeln!(
+            writer,
+            "p mcc {} {} {}",
+            ddnnf.n_vars(),
+            ddnnf.n_nodes(),
+            ddnnf.n_edges()
+        )?;
+        for (i, hash) in hashes.iter().enumerate() {
+            let (n, n_involved_vars) = self.involved_vars_count_from(NodeIndex::from(i));
+            writeln!(
+                writer,
+                "n {} {} {hash:016x} {n_involved_vars} {n}",
+                i + 1,
+                node_kind(ddnnf.node(NodeIndex::from(i))),
+            )?;
+        }
+        writeln!(writer, "m {}", self.count())
+    }
+}
+
+/// A structure used to check the certificates written by [`ModelCounter::write_certificate`].
+///
+/// A certificate gives the model count of each node of the formula, so that the model count of the formula can be checked by verifying each node against its children.
+/// The verification does not need to trust the code that produced the counts; it can be made by an independent tool.
+///
+/// The certificate is a text file.
+/// Lines beginning with `c` are comments.
+/// The first other line is `p mcc <n_vars> <n_nodes> <n_edges>`.
+/// It is followed by a line `n <index> <kind> <hash> <n_involved> <count>` for each node, where:
+/// - `index` is the index of the node, beginning at 1 as in the d4 format;
+/// - `kind` is `a`, `o`, `t` or `f` for conjunction, disjunction, true and false nodes;
+/// - `hash` is a 64 bits hash of the subformula rooted at the node, in hexadecimal;
+/// - `n_involved` is the number of variables involved in the subformula (i.e. appearing in the literals propagated by the edges below the node);
+/// - `count` is the number of models of the subformula, restricted to these variables.
+///
+/// The last line is `m <count>`, giving the number of models of the formula.
+///
+/// The hash of a node is the FNV-1a hash of the following sequence of bytes: the character of its kind, and for each of its edges, in order,
+/// the hash of the target node, the number of propagated literals and the propagated literals, each of them written as a 64 bits little-endian integer.
+///
+/// The verifier checks that the hashes, kinds and numbers of involved variables match the formula, that the count of each node is consistent with the counts of its children, and that the count of the formula is consistent with the count of the root.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{CountCertificateVerifier, D4Reader};
+///
+/// let ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+/// let certificate = "p mcc 0 1 0\nn 1 t cbf29ce484222320 0 2\nm 2\n";
+/// assert!(CountCertificateVerifier::new(&ddnnf).verify(certificate.as_bytes()).is_err());
+/// ```
+pub struct CountCertificateVerifier<'a> {
+    ddnnf: &'a DecisionDNNF,
+}
+
+impl<'a> CountCertificateVerifier<'a> {
+    /// Builds a new verifier for the certificates of a [`DecisionDNNF`].
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        Self { ddnnf }
+    }
+
+    /// Verifies a certificate, and returns the certified number of models.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the certificate cannot be read, does not follow the format, or is not consistent with the formula.
+    pub fn verify<R>(&self, reader: R) -> Result<Integer>
+    where
+        R: Read,
+    {
+        let context = "while verifying a model count certificate";
+        let certificate = self.read_certificate(reader).context(context)?;
+        self.check_certificate(&certificate).context(context)?;
+        Ok(certificate.n_models)
+    }
+
+    fn read_certificate<R>(&self, reader: R) -> Result<Certificate>
+    where
+        R: Read,
+    {
+        let n_nodes = self.ddnnf.n_nodes();
+        let mut nodes = vec![None; n_nodes];
+        let mut header_read = false;
+        let mut n_models = None;
+        for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let mut read_line = || -> Result<()> {
+                match words.as_slice() {
+                    [] | ["c", ..] => {}
+                    ["p", "mcc", header @ ..] if !header_read && header.len() == 3 => {
+                        let expected = [self.ddnnf.n_vars(), n_nodes, self.ddnnf.n_edges()];
+                        for (w, e) in header.iter().zip(expected) {
+                            if usize::from_str(w)? != e {
+                                return Err(anyhow!("the header does not match the formula"));
+                            }
+                        }
+                    }
+                    _ if !header_read => return Err(anyhow!("missing header")),
+                    ["n", index, kind, hash, n_involved, count] if n_models.is_none() => {
+                        let index = usize::from_str(index)?;
+                        if index == 0 || index > n_nodes {
+                            return Err(anyhow!("no node with index {index}"));
+                        }
+                        if nodes[index - 1].is_some() {
+                            return Err(anyhow!("node with index {index} is given twice"));
+                        }
+                        let node = CertifiedNode {
+                            kind: (*kind).to_string(),
+                            hash: u64::from_str_radix(hash, 16)?,
+                            n_involved_vars: usize::from_str(n_involved)?,
+                            n_models: Integer::from_str(count)?,
+                        };
+                        if node.n_models < 0 {
+                            return Err(anyhow!("negative model count"));
+                        }
+                        nodes[index - 1] = Some(node);
+                    }
+                    ["m", count] if n_models.is_none() => {
+                        n_models = Some(Integer::from_str(count)?);
+                    }
+                    _ => return Err(anyhow!("unexpected line")),
+                }
+                Ok(())
+            };
+            read_line().with_context(|| format!("while parsing line at index {line_index}"))?;
+            if !words.is_empty() && words[0] != "c" {
+                header_read = true;
+            }
+        }
+        let nodes = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(i, opt_node)| opt_node.ok_or_else(|| anyhow!("missing node {}", i + 1)))
+            .collect::<Result<Vec<_>>>()?;
+        let n_models = n_models.ok_or_else(|| anyhow!("missing model count"))?;
+        Ok(Certificate { nodes, n_models })
+    }
+
+    fn check_certificate(&self, certificate: &Certificate) -> Result<()> {
+        let ddnnf = self.ddnnf;
+        let hashes = structure_hashes(ddnnf);
+        let mut involved_vars: Vec<InvolvedVars> = vec![InvolvedVars::empty(); ddnnf.n_nodes()];
+        for node_index in post_order(ddnnf) {
+            let i = usize::from(node_index);
+            let certified = &certificate.nodes[i];
+            let node_error = |message: &str| Err(anyhow!("{message} for node {}", i + 1));
+            let node = ddnnf.node(node_index);
+            if certified.kind != node_kind(node).to_string() {
+                return node_error("wrong kind");
+            }
+            if certified.hash != hashes[i] {
+                return node_error("wrong hash");
+            }
+            let edges = match node {
+                Node::And(edges) | Node::Or(edges) => edges.as_slice(),
+                Node::True | Node::False => &[],
+            };
+            let children = edges
+                .iter()
+                .map(|e| {
+                    let edge = ddnnf.edge(*e);
+                    let mut vars = involved_vars[usize::from(edge.target())].clone();
+                    vars.set_literals(edge.propagated());
+                    (
+                        &certificate.nodes[usize::from(edge.target())].n_models,
+                        vars,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let mut node_vars = InvolvedVars::new(ddnnf.n_vars());
+            for (_, vars) in &children {
+                node_vars.or_assign(vars);
+            }
+            let n_involved = node_vars.count_ones();
+            if certified.n_involved_vars != n_involved {
+                return node_error("wrong number of involved variables");
+            }
+            let expected: Integer = match node {
+                Node::And(_) => children.into_iter().map(|(n, _)| n).product(),
+                Node::Or(_) => children
+                    .into_iter()
+                    .map(|(n, vars)| Integer::from(n << (n_involved - vars.count_ones())))
+                    .sum(),
+                Node::True => Integer::from(1),
+                Node::False => Integer::from(0),
+            };
+            if certified.n_models != expected {
+                return node_error("inconsistent model count");
+            }
+            involved_vars[i] = node_vars;
+        }
+        let root = &certificate.nodes[0];
+        if certificate.n_models
+            != Integer::from(&root.n_models << (ddnnf.n_vars() - root.n_involved_vars))
+        {
+            return Err(anyhow!(
+                "the model count of the formula is inconsistent with the one of the root"
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct Certificate {
+    nodes: Vec<CertifiedNode>,
+    n_models: Integer,
+}
+
+#[derive(Clone)]
+struct CertifiedNode {
+    kind: String,
+    hash: u64,
+    n_involved_vars: usize,
+    n_models: Integer,
+}
+
+fn node_kind(node: &Node) -> char {
+    match node {
+        Node::And(_) => 'a',
+        Node::Or(_) => 'o',
+        Node::True => 't',
+        Node::False => 'f',
+    }
+}
+
+/// Returns the nodes reachable from the root, each node being placed after its children.
+fn post_order(ddnnf: &DecisionDNNF) -> Vec<NodeIndex> {
+    let mut order = Vec::with_capacity(ddnnf.n_nodes());
+    let mut seen = vec![false; ddnnf.n_nodes()];
+    let mut stack = vec![(NodeIndex::from(0), 0)];
+    seen[0] = true;
+    while let Some((node, next_child)) = stack.pop() {
+        let edges = match ddnnf.node(node) {
+            Node::And(edges) | Node::Or(edges) => edges.as_slice(),
+            Node::True | Node::False => &[],
+        };
+        if let Some(edge_index) = edges.get(next_child) {
+            stack.push((node, next_child + 1));
+            let target = ddnnf.edge(*edge_index).target();
+            if !seen[usize::from(target)] {
+                seen[usize::from(target)] = true;
+                stack.push((target, 0));
+            }
+        } else {
+            order.push(node);
+        }
+    }
+    order
+}
+
+fn structure_hashes(ddnnf: &DecisionDNNF) -> Vec<u64> {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+    let fnv = |hash: u64, bytes: &[u8]| {
+        bytes
+            .iter()
+            .fold(hash, |h, b| (h ^ u64::from(*b)).wrapping_mul(FNV_PRIME))
+    };
+    let mut hashes = vec![0_u64; ddnnf.n_nodes()];
+    for node_index in post_order(ddnnf) {
+        let node = ddnnf.node(node_index);
+        let mut hash = fnv(FNV_OFFSET_BASIS, &[node_kind(node) as u8]);
+        if let Node::And(edges) | Node::Or(edges) = node {
+            for e in edges {
+                let edge = ddnnf.edge(*e);
+                hash = fnv(hash, &hashes[usize::from(edge.target())].to_le_bytes());
+                hash = fnv(hash, &(edge.propagated().len() as u64).to_le_bytes());
+                for l in edge.propagated() {
+                    hash = fnv(hash, &(isize::from(*l) as i64).to_le_bytes());
+                }
+            }
+        }
+        hashes[usize::from(node_index)] = hash;
+    }
+    hashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    const INSTANCE: &str =
+        "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+
+    fn certificate(ddnnf: &DecisionDNNF) -> String {
+        let mut certificate = Vec::new();
+        ModelCounter::new(ddnnf)
+            .write_certificate(&mut certificate)
+            .unwrap();
+        String::from_utf8(certificate).unwrap()
+    }
+
+    fn verify(ddnnf: &DecisionDNNF, certificate: &str) -> Result<Integer> {
+        CountCertificateVerifier::new(ddnnf).verify(certificate.as_bytes())
+    }
+
+    #[test]
+    fn test_verify_written_certificate() {
+        let mut ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        ddnnf.update_n_vars(4);
+        let certificate = certificate(&ddnnf);
+        assert_eq!(16, verify(&ddnnf, &certificate).unwrap());
+    }
+
+    #[test]
+    fn test_wrong_node_count() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let certificate = certificate(&ddnnf);
+        let node_line = certificate.lines().find(|l| l.starts_with("n 2 ")).unwrap();
+        let wrong_line = format!("{}3", &node_line[..node_line.len() - 1]);
+        let wrong = certificate.replace(node_line, &wrong_line);
+        let error = verify(&ddnnf, &wrong).unwrap_err();
+        assert_eq!(
+            "inconsistent model count for node 2",
+            error.root_cause().to_string()
+        );
+    }
+
+    #[test]
+    fn test_wrong_total_count() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let wrong = certificate(&ddnnf).replace("m 4", "m 5");
+        assert!(verify(&ddnnf, &wrong).is_err());
+    }
+
+    #[test]
+    fn test_other_formula() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let other = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 3 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let error = verify(&other, &certificate(&ddnnf)).unwrap_err();
+        assert_eq!(
+            "the header does not match the formula",
+            error.root_cause().to_string()
+        );
+    }
+
+    #[test]
+    fn test_missing_node() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let certificate = certificate(&ddnnf);
+        let truncated = certificate
+            .lines()
+            .filter(|l| !l.starts_with("n 4 "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let error = verify(&ddnnf, &truncated).unwrap_err();
+        assert_eq!("missing node 4", error.root_cause().to_string());
+    }
+}
@@ -0,0 +1,400 @@
+use crate::{core::VarMap, DecisionDNNF, Literal, ModelCounter, NodeIndex};
+use anyhow::{anyhow, Result};
+use rug::Integer;
+
+/// A set of derived variables, defined as Boolean functions of the variables of a formula.
+///
+/// Derived variables allow to query the models of a [`DecisionDNNF`] on features that are not variables of the formula,
+/// without having to compile the formula again with additional variables.
+/// A derived variable is defined by a string like `premium := x3 AND NOT x9`.
+/// Expressions are built from the variables of the formula, written `x` followed by their DIMACS index,
+/// from the derived variables defined before, from the operators `NOT`, `AND` and `OR` (in decreasing priority order, case insensitive) and from parentheses.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, DerivedVariables, ModelCounter};
+///
+/// // a tautology on 3 variables
+/// let mut ddnnf = D4Reader::read("t 1 0".as_bytes()).unwrap();
+/// ddnnf.update_n_vars(3);
+/// let mut derived = DerivedVariables::new(&ddnnf);
+/// derived.define("premium := x1 AND NOT x3").unwrap();
+/// let expression = derived.parse("premium OR x2").unwrap();
+/// assert_eq!(5, expression.count(&ModelCounter::new(&ddnnf)));
+/// ```
+pub struct DerivedVariables {
+    n_vars: usize,
+    definitions: Vec<(String, BooleanExpression)>,
+}
+
+impl DerivedVariables {
+    /// Builds an empty set of derived variables for the given formula.
+    #[must_use]
+    pub fn new(ddnnf: &DecisionDNNF) -> Self {
+        Self {
+            n_vars: ddnnf.n_vars(),
+            definitions: vec![],
+        }
+    }
+
+    /// Defines a new derived variable, given a string of the form `name := expression`.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the definition is syntactically incorrect, if it refers to an unknown variable, or if the name is already used.
+    pub fn define(&mut self, definition: &str) -> Result<()> {
+        let (name, str_expression) = definition
+            .split_once(":=")
+            .ok_or_else(|| anyhow!(r#"expected "name := expression", got "{definition}""#))?;
+        let name = name.trim();
+        let mut chars = name.chars();
+        if !chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(anyhow!(r#"invalid name for a derived variable: "{name}""#));
+        }
+        if is_keyword(name) || base_var(name).is_some() {
+            return Err(anyhow!(r#"reserved name: "{name}""#));
+        }
+        if self.definitions.iter().any(|(n, _)| n == name) {
+            return Err(anyhow!(r#"derived variable "{name}" is already defined"#));
+        }
+        let expression = self.parse(str_expression)?;
+        self.definitions.push((name.to_string(), expression));
+        Ok(())
+    }
+
+    /// Parses an expression involving the variables of the formula and the derived variables defined so far.
+    ///
+    /// The derived variables are replaced by their definitions.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the expression is syntactically incorrect or if it refers to an unknown variable.
+    pub fn parse(&self, expression: &str) -> Result<BooleanExpression> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            derived: self,
+            tokens: &tokens,
+            next: 0,
+        };
+        let result = parser.parse_or()?;
+        if let Some(t) = tokens.get(parser.next) {
+            return Err(anyhow!(r#"unexpected token "{t}" in "{expression}""#));
+        }
+        Ok(result)
+    }
+}
+
+/// A Boolean expression over the variables of a formula, as built by [`DerivedVariables::parse`].
+#[derive(Clone, Debug)]
+pub struct BooleanExpression(Expr);
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Var(usize),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl BooleanExpression {
+    /// Returns the indices of the variables involved in this expression, in increasing order.
+    #[must_use]
+    pub fn vars(&self) -> Vec<usize> {
+        let mut vars = vec![];
+        let mut stack = vec![&self.0];
+        while let Some(e) = stack.pop() {
+            match e {
+                Expr::Var(v) => vars.push(*v),
+                Expr::Not(e) => stack.push(e),
+                Expr::And(v) | Expr::Or(v) => stack.extend(v),
+            }
+        }
+        vars.sort_unstable();
+        vars.dedup();
+        vars
+    }
+
+    /// Returns a set of pairwise disjoint cubes which union is the set of assignments satisfying this expression.
+    ///
+    /// The cubes are computed by a Shannon expansion on the variables of the expression, stopped as soon as the expression is decided.
+    /// In the worst case, the number of cubes is exponential in the number of variables involved in the expression.
+    #[must_use]
+    pub fn disjoint_cubes(&self) -> Vec<Vec<Literal>> {
+        let vars = self.vars();
+        let mut assignment = VarMap::new(vars.last().map_or(0, |v| v + 1), None);
+        let mut cubes = vec![];
+        self.expand(&vars, &mut assignment, &mut vec![], &mut cubes);
+        cubes
+    }
+
+    fn expand(
+        &self,
+        vars: &[usize],
+        assignment: &mut VarMap<Option<bool>>,
+        cube: &mut Vec<Literal>,
+        cubes: &mut Vec<Vec<Literal>>,
+    ) {
+        match self.0.eval(assignment) {
+            Some(true) => cubes.push(cube.clone()),
+            Some(false) => {}
+            None => {
+                let var = vars[0];
+                let positive = Literal::from(isize::try_from(var + 1).unwrap());
+                for literal in [positive.flip(), positive] {
+                    assignment[var] = Some(literal.polarity());
+                    cube.push(literal);
+                    self.expand(&vars[1..], assignment, cube, cubes);
+                    cube.pop();
+                }
+                assignment[var] = None;
+            }
+        }
+    }
+
+    /// Counts the models of the formula of a [`ModelCounter`] that satisfy this expression.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the expression refers to a variable that does not exist in the formula.
+    #[must_use]
+    pub fn count(&self, counter: &ModelCounter) -> Integer {
+        self.disjoint_cubes()
+            .iter()
+            .map(|cube| counter.count_from_under_assumptions(NodeIndex::from(0), cube))
+            .sum()
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression under a partial assignment, returning [`None`] if it is not decided yet.
+    fn eval(&self, assignment: &VarMap<Option<bool>>) -> Option<bool> {
+        match self {
+            Expr::Var(v) => assignment[*v],
+            Expr::Not(e) => e.eval(assignment).map(|b| !b),
+            Expr::And(operands) | Expr::Or(operands) => {
+                let absorbing = matches!(self, Expr::Or(_));
+                let mut decided = true;
+                for e in operands {
+                    match e.eval(assignment) {
+                        Some(b) if b == absorbing => return Some(absorbing),
+                        Some(_) => {}
+                        None => decided = false,
+                    }
+                }
+                if decided {
+                    Some(!absorbing)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    ["and", "or", "not"]
+        .iter()
+        .any(|k| word.eq_ignore_ascii_case(k))
+}
+
+/// Returns the index of the variable denoted by a word like `x3`, if it has this form.
+fn base_var(word: &str) -> Option<usize> {
+    word.strip_prefix('x')
+        .filter(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+        .and_then(|s| str::parse::<usize>(s).ok())
+}
+
+fn tokenize(expression: &str) -> Result<Vec<String>> {
+    let mut tokens = vec![];
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if c == '(' || c == ')' {
+            tokens.push(c.to_string());
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let mut word = c.to_string();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                word.push(c);
+            }
+            tokens.push(word);
+        } else {
+            return Err(anyhow!(r#"unexpected character '{c}' in "{expression}""#));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    derived: &'a DerivedVariables,
+    tokens: &'a [String],
+    next: usize,
+}
+
+impl Parser<'_> {
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        self.tokens
+            .get(self.next)
+            .is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<BooleanExpression> {
+        let mut operands = vec![self.parse_and()?.0];
+        while self.next_is_keyword("or") {
+            self.next += 1;
+            operands.push(self.parse_and()?.0);
+        }
+        Ok(BooleanExpression(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Expr::Or(operands)
+        }))
+    }
+
+    fn parse_and(&mut self) -> Result<BooleanExpression> {
+        let mut operands = vec![self.parse_unary()?.0];
+        while self.next_is_keyword("and") {
+            self.next += 1;
+            operands.push(self.parse_unary()?.0);
+        }
+        Ok(BooleanExpression(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Expr::And(operands)
+        }))
+    }
+
+    fn parse_unary(&mut self) -> Result<BooleanExpression> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .ok_or_else(|| anyhow!("unexpected end of expression"))?;
+        self.next += 1;
+        if token.eq_ignore_ascii_case("not") {
+            return Ok(BooleanExpression(Expr::Not(Box::new(
+                self.parse_unary()?.0,
+            ))));
+        }
+        if token == "(" {
+            let inner = self.parse_or()?;
+            if self.tokens.get(self.next).map(String::as_str) != Some(")") {
+                return Err(anyhow!("missing closing parenthesis"));
+            }
+            self.next += 1;
+            return Ok(inner);
+        }
+        if let Some(v) = base_var(token) {
+            let n_vars = self.derived.n_vars;
+            if v == 0 || v > n_vars {
+                return Err(anyhow!(
+                    "no such variable: {token} (the formula has {n_vars} variables)"
+                ));
+            }
+            return Ok(BooleanExpression(Expr::Var(v - 1)));
+        }
+        self.derived
+            .definitions
+            .iter()
+            .find(|(n, _)| n == token)
+            .map(|(_, e)| e.clone())
+            .ok_or_else(|| anyhow!(r#"unexpected token "{token}""#))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    fn tautology(n_vars: usize) -> DecisionDNNF {
+        let mut ddnnf = D4Reader::read("t 1 0".as_bytes()).unwrap();
+        ddnnf.update_n_vars(n_vars);
+        ddnnf
+    }
+
+    fn count(ddnnf: &DecisionDNNF, definitions: &[&str], expression: &str) -> usize {
+        let mut derived = DerivedVariables::new(ddnnf);
+        for d in definitions {
+            derived.define(d).unwrap();
+        }
+        derived
+            .parse(expression)
+            .unwrap()
+            .count(&ModelCounter::new(ddnnf))
+            .to_usize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_base_vars() {
+        let ddnnf = tautology(3);
+        assert_eq!(4, count(&ddnnf, &[], "x1"));
+        assert_eq!(4, count(&ddnnf, &[], "NOT x1"));
+        assert_eq!(2, count(&ddnnf, &[], "x1 and x2"));
+        assert_eq!(6, count(&ddnnf, &[], "x1 or x2"));
+    }
+
+    #[test]
+    fn test_priorities() {
+        let ddnnf = tautology(3);
+        assert_eq!(5, count(&ddnnf, &[], "x1 OR x2 AND x3"));
+        assert_eq!(3, count(&ddnnf, &[], "(x1 OR x2) AND x3"));
+        assert_eq!(3, count(&ddnnf, &[], "NOT x1 AND x2 OR x1 AND x2 AND x3"));
+    }
+
+    #[test]
+    fn test_derived() {
+        let ddnnf = tautology(3);
+        let definitions = ["a := x1 AND NOT x3", "b := a OR x2"];
+        assert_eq!(5, count(&ddnnf, &definitions, "b"));
+        assert_eq!(3, count(&ddnnf, &definitions, "NOT b"));
+    }
+
+    #[test]
+    fn test_on_formula() {
+        // (-1 and (-2 or 2)) or (1 and 2)
+        let ddnnf = D4Reader::read(
+            "o 1 0\no 2 0\nt 3 0\n1 2 -1 0\n1 3 1 2 0\n2 3 -2 0\n2 3 2 0\n".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(2, count(&ddnnf, &[], "x2"));
+        assert_eq!(1, count(&ddnnf, &["d := x1 OR NOT x2"], "d AND x2"));
+        assert_eq!(0, count(&ddnnf, &[], "x1 AND NOT x2"));
+    }
+
+    #[test]
+    fn test_disjoint_cubes() {
+        let ddnnf = tautology(3);
+        let derived = DerivedVariables::new(&ddnnf);
+        let cubes = derived.parse("x1 OR x3").unwrap().disjoint_cubes();
+        assert_eq!(
+            vec![
+                vec![Literal::from(-1), Literal::from(3)],
+                vec![Literal::from(1)]
+            ],
+            cubes
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let ddnnf = tautology(3);
+        let mut derived = DerivedVariables::new(&ddnnf);
+        assert!(derived.define("a = x1").is_err());
+        assert!(derived.define("x2 := x1").is_err());
+        assert!(derived.define("and := x1").is_err());
+        assert!(derived.define("a := x4").is_err());
+        assert!(derived.define("a := x1 AND").is_err());
+        assert!(derived.define("a := (x1").is_err());
+        assert!(derived.define("a := x1 x2").is_err());
+        assert!(derived.define("a := b").is_err());
+        derived.define("a := x1").unwrap();
+        assert!(derived.define("a := x2").is_err());
+    }
+}
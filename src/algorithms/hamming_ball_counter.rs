@@ -0,0 +1,293 @@
+use crate::{
+    core::{full_assignment, EdgeIndex, InvolvedVars, Node, NodeIndex},
+    DecisionDNNF, Literal,
+};
+use anyhow::Result;
+use rug::Integer;
+
+/// A structure used to count the models of a [`DecisionDNNF`] that are close to a given assignment.
+///
+/// The distance between two full assignments is the Hamming distance, that is the number of variables on which they differ.
+/// Given a full assignment (the center) and a maximal distance, this algorithm counts the models which distance to the center does not exceed the maximal one.
+///
+/// The algorithm computes for each node a vector of counts indexed by the distance to the center, truncated at the maximal distance.
+/// It takes a time polynomial in the size of the Decision-DNNF and the maximal distance.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, HammingBallCounter, Literal};
+///
+/// // a tautology on 3 variables
+/// let mut ddnnf = D4Reader::read("t 1 0".as_bytes()).unwrap();
+/// ddnnf.update_n_vars(3);
+/// let counter = HammingBallCounter::new(&ddnnf);
+/// let center = [1, 2, 3].into_iter().map(Literal::from).collect::<Vec<_>>();
+/// // one model at distance 0, three at distance 1
+/// assert_eq!(4, counter.count(&center, 1));
+/// assert_eq!(vec![1, 3, 3, 1], counter.count_by_distance(&center, 3));
+/// ```
+pub struct HammingBallCounter<'a> {
+    ddnnf: &'a DecisionDNNF,
+}
+
+impl<'a> HammingBallCounter<'a> {
+    /// Builds a new counter given a [`DecisionDNNF`].
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        Self { ddnnf }
+    }
+
+    /// Counts the models which distance to the center is at most `max_distance`.
+    ///
+    /// # Panics
+    ///
+    /// The center must be a full assignment, i.e. it must contain exactly one literal for each variable of the formula.
+    /// This function panics if this is not the case.
+    #[must_use]
+    pub fn count(&self, center: &[Literal], max_distance: usize) -> Integer {
+        self.try_count(center, max_distance)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Counts the models which distance to the center is at most `max_distance`, as [`count`](Self::count) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the center is not a full assignment.
+    pub fn try_count(&self, center: &[Literal], max_distance: usize) -> Result<Integer> {
+        Ok(self
+            .try_count_by_distance(center, max_distance)?
+            .into_iter()
+            .sum())
+    }
+
+    /// Returns a vector which `i`-th element is the number of models at distance `i` of the center, for `i` from 0 to `max_distance`.
+    ///
+    /// # Panics
+    ///
+    /// The center must be a full assignment, i.e. it must contain exactly one literal for each variable of the formula.
+    /// This function panics if this is not the case.
+    #[must_use]
+    pub fn count_by_distance(&self, center: &[Literal], max_distance: usize) -> Vec<Integer> {
+        self.try_count_by_distance(center, max_distance)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the numbers of models at each distance of the center, as [`count_by_distance`](Self::count_by_distance) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the center is not a full assignment.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn try_count_by_distance(
+        &self,
+        center: &[Literal],
+        max_distance: usize,
+    ) -> Result<Vec<Integer>> {
+        let mut data = CountingData {
+            center_polarities: full_assignment(center, self.ddnnf.n_vars())?,
+            max_distance,
+            profiles: vec![None; self.ddnnf.nodes().as_slice().len()],
+        };
+        self.compute_profile(NodeIndex::from(0), &mut data);
+        let (root_profile, root_vars) = data.profiles[0].take().unwrap();
+        Ok(smooth(root_profile, root_vars.count_zeros(), max_distance))
+    }
+
+    fn compute_profile(&self, from: NodeIndex, data: &mut CountingData) {
+        if data.profiles[usize::from(from)].is_some() {
+            return;
+        }
+        let n_vars = self.ddnnf.n_vars();
+        let max_distance = data.max_distance;
+        let mut unit = vec![Integer::from(0); max_distance + 1];
+        unit[0] = Integer::from(1);
+        let result = match &self.ddnnf.nodes()[from] {
+            Node::And(edges) => edges.iter().fold(
+                (unit, InvolvedVars::new(n_vars)),
+                |(acc_profile, mut acc_vars), e| {
+                    let (child_profile, child_vars) = self.compute_edge_profile(*e, data);
+                    acc_vars.or_assign(&child_vars);
+                    (
+                        convolve(&acc_profile, &child_profile, max_distance),
+                        acc_vars,
+                    )
+                },
+            ),
+            Node::Or(edges) => {
+                let children = edges
+                    .iter()
+                    .map(|e| self.compute_edge_profile(*e, data))
+                    .collect::<Vec<_>>();
+                let mut or_vars = InvolvedVars::new(n_vars);
+                for (_, child_vars) in &children {
+                    or_vars.or_assign(child_vars);
+                }
+                let mut or_profile = vec![Integer::from(0); max_distance + 1];
+                for (child_profile, mut child_vars) in children {
+                    child_vars.xor_assign(&or_vars);
+                    let smoothed = smooth(child_profile, child_vars.count_ones(), max_distance);
+                    or_profile
+                        .iter_mut()
+                        .zip(smoothed)
+                        .for_each(|(acc, n)| *acc += n);
+                }
+                (or_profile, or_vars)
+            }
+            Node::True => (unit, InvolvedVars::new(n_vars)),
+            Node::False => (
+                vec![Integer::from(0); max_distance + 1],
+                InvolvedVars::new(n_vars),
+            ),
+        };
+        data.profiles[usize::from(from)] = Some(result);
+    }
+
+    fn compute_edge_profile(
+        &self,
+        edge_index: EdgeIndex,
+        data: &mut CountingData,
+    ) -> (Vec<Integer>, InvolvedVars) {
+        let edge = &self.ddnnf.edges()[edge_index];
+        self.compute_profile(edge.target(), data);
+        let (target_profile, target_vars) =
+            data.profiles[usize::from(edge.target())].as_ref().unwrap();
+        let mut edge_vars = target_vars.clone();
+        edge_vars.set_literals(edge.propagated());
+        let shift = edge
+            .propagated()
+            .iter()
+            .filter(|l| data.center_polarities[l.var_index()] != l.polarity())
+            .count();
+        let mut edge_profile = vec![Integer::from(0); data.max_distance + 1];
+        edge_profile
+            .iter_mut()
+            .skip(shift)
+            .zip(target_profile.iter())
+            .for_each(|(n, target_n)| *n += target_n);
+        (edge_profile, edge_vars)
+    }
+}
+
+struct CountingData {
+    center_polarities: Vec<bool>,
+    max_distance: usize,
+    profiles: Vec<Option<(Vec<Integer>, InvolvedVars)>>,
+}
+
+fn convolve(p0: &[Integer], p1: &[Integer], max_distance: usize) -> Vec<Integer> {
+    let mut result = vec![Integer::from(0); max_distance + 1];
+    for (i, n0) in p0.iter().enumerate() {
+        for (j, n1) in p1.iter().enumerate().take(max_distance + 1 - i) {
+            result[i + j] += n0 * n1;
+        }
+    }
+    result
+}
+
+fn smooth(profile: Vec<Integer>, n_free_vars: usize, max_distance: usize) -> Vec<Integer> {
+    if n_free_vars == 0 {
+        return profile;
+    }
+    let mut binomials = Vec::with_capacity(max_distance + 1);
+    binomials.push(Integer::from(1));
+    for j in 0..usize::min(n_free_vars, max_distance) {
+        let next = Integer::from(&binomials[j] * (n_free_vars - j)) / (j + 1);
+        binomials.push(next);
+    }
+    convolve(&profile, &binomials, max_distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    fn count_by_distance(
+        instance: &str,
+        n_vars: Option<usize>,
+        center: &[isize],
+        max_distance: usize,
+    ) -> Vec<usize> {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let center = center.iter().map(|i| Literal::from(*i)).collect::<Vec<_>>();
+        HammingBallCounter::new(&ddnnf)
+            .count_by_distance(&center, max_distance)
+            .into_iter()
+            .map(|n| n.to_usize_wrapping())
+            .collect()
+    }
+
+    #[test]
+    fn test_false() {
+        assert_eq!(vec![0, 0], count_by_distance("f 1 0", Some(1), &[1], 1));
+    }
+
+    #[test]
+    fn test_true() {
+        assert_eq!(
+            vec![1, 2, 1],
+            count_by_distance("t 1 0", Some(2), &[1, -2], 2)
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(
+            vec![1, 3],
+            count_by_distance("t 1 0", Some(3), &[1, 2, 3], 1)
+        );
+    }
+
+    #[test]
+    fn test_and_or() {
+        let instance =
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+        assert_eq!(vec![1, 2, 1], count_by_distance(instance, None, &[1, 2], 2));
+    }
+
+    #[test]
+    fn test_clause() {
+        let instance = "o 1 0\no 2 0\nt 3 0\n2 3 -1 -2 0\n2 3 1 0\n1 2 0";
+        assert_eq!(
+            vec![0, 2, 1],
+            count_by_distance(instance, None, &[-1, 2], 2)
+        );
+        assert_eq!(
+            vec![1, 2, 0],
+            count_by_distance(instance, None, &[1, -2], 2)
+        );
+    }
+
+    #[test]
+    fn test_or_free_vars() {
+        let instance = "o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0";
+        assert_eq!(vec![1, 1, 1], count_by_distance(instance, None, &[1, 2], 2));
+        assert_eq!(
+            vec![1, 1, 1],
+            count_by_distance(instance, None, &[-1, -2], 2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "missing literal for variable 2")]
+    fn test_not_full_assignment() {
+        let _ = count_by_distance("t 1 0", Some(2), &[1], 1);
+    }
+
+    #[test]
+    fn test_try_count_multiple_literals() {
+        let mut ddnnf = D4Reader::read("t 1 0".as_bytes()).unwrap();
+        ddnnf.update_n_vars(1);
+        let counter = HammingBallCounter::new(&ddnnf);
+        let center = [Literal::from(1), Literal::from(-1)];
+        assert_eq!(
+            "multiple literals for variable 1",
+            counter.try_count(&center, 1).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use super::model_counter;
+use crate::{
+    core::{EdgeIndex, InvolvedVars, Node, NodeIndex},
+    DecisionDNNF,
+};
+use rug::Integer;
+use std::cell::OnceCell;
+
+/// A structure used to count the models of a [`DecisionDNNF`] using machine integers where it is possible.
+///
+/// The counting is made in two passes.
+/// The first one computes the counts of the nodes using 64 bits integers, marking the nodes for which an overflow occurred.
+/// The second one computes the counts of the marked nodes using big integers, reusing the machine integer counts of their descendants that did not overflow.
+/// The big integer counts are merged by the same functions as the ones of the [`ModelCounter`](crate::ModelCounter).
+/// Since the counts only overflow near the root in most formulas, most of the big integer operations are avoided.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, HybridModelCounter};
+///
+/// let mut ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// ddnnf.update_n_vars(100);
+/// let counter = HybridModelCounter::new(&ddnnf);
+/// assert_eq!(rug::Integer::from(1) << 100, counter.count());
+/// ```
+pub struct HybridModelCounter<'a> {
+    ddnnf: &'a DecisionDNNF,
+    small_counts: Vec<OnceCell<(Option<u64>, InvolvedVars)>>,
+    big_counts: Vec<OnceCell<Integer>>,
+}
+
+impl<'a> HybridModelCounter<'a> {
+    /// Builds a new hybrid model counter given a [`DecisionDNNF`].
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        let n_nodes = ddnnf.n_nodes();
+        Self {
+            ddnnf,
+            small_counts: (0..n_nodes).map(|_| OnceCell::new()).collect(),
+            big_counts: (0..n_nodes).map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    /// Returns the number of models of the formula.
+    #[must_use]
+    pub fn count(&self) -> Integer {
+        let root = NodeIndex::from(0);
+        let (opt_n, involved_vars) = self.small_count(root);
+        let n_free_vars = involved_vars.count_zeros();
+        match opt_n.and_then(|n| checked_shl(n, n_free_vars)) {
+            Some(n) => Integer::from(n),
+            None => self.node_count(root) << n_free_vars,
+        }
+    }
+
+    /// Returns the number of nodes which count does not fit into a machine integer.
+    #[must_use]
+    pub fn n_overflowing_nodes(&self) -> usize {
+        let _ = self.small_count(NodeIndex::from(0));
+        self.small_counts
+            .iter()
+            .filter(|c| matches!(c.get(), Some((None, _))))
+            .count()
+    }
+
+    fn small_count(&self, node: NodeIndex) -> &(Option<u64>, InvolvedVars) {
+        self.small_counts[usize::from(node)].get_or_init(|| {
+            let n_vars = self.ddnnf.n_vars();
+            match self.ddnnf.node(node) {
+                Node::And(edges) => {
+                    let mut n = Some(1_u64);
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for edge_index in edges {
+                        let (child_n, child_vars) = self.small_edge_count(*edge_index);
+                        n = n.zip(child_n).and_then(|(a, b)| a.checked_mul(b));
+                        involved_vars.or_assign(&child_vars);
+                    }
+                    (n, involved_vars)
+                }
+                Node::Or(edges) => {
+                    let children = edges
+                        .iter()
+                        .map(|e| self.small_edge_count(*e))
+                        .collect::<Vec<_>>();
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for (_, child_vars) in &children {
+                        involved_vars.or_assign(child_vars);
+                    }
+                    let n_involved = involved_vars.count_ones();
+                    let n = children
+                        .into_iter()
+                        .try_fold(0_u64, |acc, (child_n, child_vars)| {
+                            let shifted =
+                                checked_shl(child_n?, n_involved - child_vars.count_ones())?;
+                            acc.checked_add(shifted)
+                        });
+                    (n, involved_vars)
+                }
+                Node::True => (Some(1), InvolvedVars::new(n_vars)),
+                Node::False => (Some(0), InvolvedVars::new(n_vars)),
+            }
+        })
+    }
+
+    fn small_edge_count(&self, edge_index: EdgeIndex) -> (Option<u64>, InvolvedVars) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (n, target_vars) = self.small_count(edge.target());
+        let mut involved_vars = target_vars.clone();
+        involved_vars.set_literals(edge.propagated());
+        (*n, involved_vars)
+    }
+
+    fn node_count(&self, node: NodeIndex) -> Integer {
+        match self.small_count(node).0 {
+            Some(n) => Integer::from(n),
+            None => self.big_count(node).clone(),
+        }
+    }
+
+    fn big_count(&self, node: NodeIndex) -> &Integer {
+        self.big_counts[usize::from(node)].get_or_init(|| {
+            let n_vars = self.ddnnf.n_vars();
+            let edge_counts = |edges: &[EdgeIndex]| {
+                edges
+                    .iter()
+                    .map(|e| {
+                        let (_, involved_vars) = self.small_edge_count(*e);
+                        (self.node_count(self.ddnnf.edge(*e).target()), involved_vars)
+                    })
+                    .collect()
+            };
+            match self.ddnnf.node(node) {
+                Node::And(edges) => model_counter::and_count(n_vars, edge_counts(edges)).0,
+                Node::Or(edges) => model_counter::or_count(n_vars, edge_counts(edges), None).0,
+                Node::True | Node::False => unreachable!(),
+            }
+        })
+    }
+}
+
+fn checked_shl(n: u64, shift: usize) -> Option<u64> {
+    if n == 0 {
+        Some(0)
+    } else if shift < 64 && n.leading_zeros() as usize >= shift {
+        Some(n << shift)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{D4Reader, ModelCounter};
+
+    fn assert_same_count(instance: &str, n_vars: Option<usize>, n_overflowing_nodes: usize) {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let counter = HybridModelCounter::new(&ddnnf);
+        assert_eq!(*ModelCounter::new(&ddnnf).count(), counter.count());
+        assert_eq!(n_overflowing_nodes, counter.n_overflowing_nodes());
+    }
+
+    #[test]
+    fn test_no_overflow() {
+        let instance =
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+        assert_same_count(instance, None, 0);
+        assert_same_count(instance, Some(10), 0);
+        assert_same_count("f 1 0\n", Some(100), 0);
+    }
+
+    #[test]
+    fn test_overflow_at_root_free_vars() {
+        assert_same_count("t 1 0\n", Some(64), 0);
+        assert_same_count("t 1 0\n", Some(100), 0);
+    }
+
+    #[test]
+    fn test_overflow_in_or_node() {
+        let long_edge = (1..=70)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let instance = format!("a 1 0\no 2 0\nt 3 0\n1 2 0\n2 3 -1 0\n2 3 {long_edge} 0\n");
+        assert_same_count(&instance, Some(72), 2);
+    }
+
+    #[test]
+    fn test_checked_shl() {
+        assert_eq!(Some(0), checked_shl(0, 100));
+        assert_eq!(Some(1 << 63), checked_shl(1, 63));
+        assert_eq!(None, checked_shl(1, 64));
+        assert_eq!(None, checked_shl(3, 63));
+    }
+}
@@ -0,0 +1,230 @@
+use super::model_counter;
+use crate::{
+    core::{check_literals, InvolvedVars, Node, NodeIndex, VarMap},
+    DecisionDNNF, Literal, ModelCounter,
+};
+use anyhow::Result;
+use rug::Integer;
+
+/// A structure used to count the models of a [`DecisionDNNF`] under a stack of assumptions.
+///
+/// This counter is designed for workloads issuing many counting queries under sets of assumptions that differ by a few literals.
+/// Assumptions are added with [`push`](Self::push) and removed in the reverse order with [`pop`](Self::pop).
+/// The counts of the nodes under the current assumptions are stored; when an assumption is added or removed, only the counts of the nodes that have an edge propagating the assumed variable and the counts of their ancestors are discarded.
+/// They are computed again lazily, at the next call to [`count`](Self::count).
+///
+/// Contradictory assumptions are allowed; the count is then 0 until one of them is removed.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, IncrementalModelCounter, Literal};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let mut counter = IncrementalModelCounter::new(&ddnnf);
+/// assert_eq!(3, counter.count());
+/// counter.push(Literal::from(-1));
+/// assert_eq!(1, counter.count());
+/// counter.push(Literal::from(1));
+/// assert_eq!(0, counter.count());
+/// counter.pop();
+/// counter.pop();
+/// counter.push(Literal::from(2));
+/// assert_eq!(1, counter.count());
+/// ```
+pub struct IncrementalModelCounter<'a> {
+    model_counter: ModelCounter<'a>,
+    parents: Vec<Vec<NodeIndex>>,
+    nodes_by_var: Vec<Vec<NodeIndex>>,
+    assumed: VarMap<Option<bool>>,
+    assumptions: Vec<(Literal, bool)>,
+    n_contradictions: usize,
+    cache: Vec<Option<(Integer, InvolvedVars)>>,
+}
+
+impl<'a> IncrementalModelCounter<'a> {
+    /// Builds a new incremental model counter given a [`DecisionDNNF`], with no assumptions.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        let n_nodes = ddnnf.n_nodes();
+        let mut parents = vec![vec![]; n_nodes];
+        let mut nodes_by_var = vec![vec![]; ddnnf.n_vars()];
+        for i in 0..n_nodes {
+            let node_index = NodeIndex::from(i);
+            let edges = match ddnnf.node(node_index) {
+                Node::And(v) | Node::Or(v) => v.as_slice(),
+                Node::True | Node::False => &[],
+            };
+            for edge_index in edges {
+                let edge = ddnnf.edge(*edge_index);
+                parents[usize::from(edge.target())].push(node_index);
+                for l in edge.propagated() {
+                    let var_nodes: &mut Vec<NodeIndex> = &mut nodes_by_var[l.var_index()];
+                    if var_nodes.last().map(|n| usize::from(*n)) != Some(i) {
+                        var_nodes.push(node_index);
+                    }
+                }
+            }
+        }
+        Self {
+            model_counter: ModelCounter::new(ddnnf),
+            parents,
+            nodes_by_var,
+            assumed: VarMap::new(ddnnf.n_vars(), None),
+            assumptions: vec![],
+            n_contradictions: 0,
+            cache: vec![None; n_nodes],
+        }
+    }
+
+    /// Adds an assumption.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the literal refers to a variable that does not exist.
+    pub fn push(&mut self, assumption: Literal) {
+        self.try_push(assumption).unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds an assumption, as [`push`](Self::push) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the literal refers to a variable that does not exist; in this case, the assumption is not added.
+    pub fn try_push(&mut self, assumption: Literal) -> Result<()> {
+        check_literals(&[assumption], self.assumed.n_vars())?;
+        match self.assumed[assumption] {
+            None => {
+                self.assumed[assumption] = Some(assumption.polarity());
+                self.assumptions.push((assumption, true));
+                self.invalidate(assumption);
+            }
+            Some(p) => {
+                if p != assumption.polarity() {
+                    self.n_contradictions += 1;
+                }
+                self.assumptions.push((assumption, false));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the last assumption that was added, and returns it.
+    ///
+    /// Returns [`None`] if there is no assumption.
+    pub fn pop(&mut self) -> Option<Literal> {
+        let (assumption, is_effective) = self.assumptions.pop()?;
+        if is_effective {
+            self.assumed[assumption] = None;
+            self.invalidate(assumption);
+        } else if self.assumed[assumption] != Some(assumption.polarity()) {
+            self.n_contradictions -= 1;
+        }
+        Some(assumption)
+    }
+
+    /// Returns the current assumptions, in the order they were added.
+    #[must_use]
+    pub fn assumptions(&self) -> Vec<Literal> {
+        self.assumptions.iter().map(|(l, _)| *l).collect()
+    }
+
+    /// Returns the number of models of the formula that are consistent with the current assumptions.
+    pub fn count(&mut self) -> Integer {
+        if self.n_contradictions > 0 {
+            return Integer::from(0);
+        }
+        let (n, involved_vars) = self.model_counter.node_count_under_assumptions(
+            NodeIndex::from(0),
+            &self.assumed,
+            &mut self.cache,
+        );
+        n << model_counter::n_unassumed_missing_vars(&involved_vars, &self.assumed)
+    }
+
+    fn invalidate(&mut self, assumption: Literal) {
+        let mut stack = self.nodes_by_var[assumption.var_index()].clone();
+        while let Some(node) = stack.pop() {
+            if self.cache[usize::from(node)].take().is_some() {
+                stack.extend_from_slice(&self.parents[usize::from(node)]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    const INSTANCE: &str =
+        "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+
+    #[test]
+    fn test_push_pop() {
+        let mut ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        ddnnf.update_n_vars(3);
+        let model_counter = ModelCounter::new(&ddnnf);
+        let mut counter = IncrementalModelCounter::new(&ddnnf);
+        assert_eq!(8, counter.count());
+        let literals = [1, -2, 3, -1].map(Literal::from);
+        for i in 0..literals.len() {
+            counter.push(literals[i]);
+            assert_eq!(
+                model_counter.count_from_under_assumptions(NodeIndex::from(0), &literals[..=i]),
+                counter.count()
+            );
+        }
+        assert_eq!(literals.to_vec(), counter.assumptions());
+        for i in (0..literals.len()).rev() {
+            assert_eq!(Some(literals[i]), counter.pop());
+            assert_eq!(
+                model_counter.count_from_under_assumptions(NodeIndex::from(0), &literals[..i]),
+                counter.count()
+            );
+        }
+        assert_eq!(None, counter.pop());
+    }
+
+    #[test]
+    fn test_contradiction() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let mut counter = IncrementalModelCounter::new(&ddnnf);
+        counter.push(Literal::from(1));
+        counter.push(Literal::from(1));
+        assert_eq!(2, counter.count());
+        counter.push(Literal::from(-1));
+        assert_eq!(0, counter.count());
+        counter.pop();
+        assert_eq!(2, counter.count());
+        counter.pop();
+        assert_eq!(2, counter.count());
+        counter.pop();
+        assert_eq!(4, counter.count());
+    }
+
+    #[test]
+    fn test_false() {
+        let ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        let mut counter = IncrementalModelCounter::new(&ddnnf);
+        assert_eq!(0, counter.count());
+    }
+
+    #[test]
+    #[should_panic(expected = "no such literal: 3 (the formula has 2 variables)")]
+    fn test_unknown_var() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let mut counter = IncrementalModelCounter::new(&ddnnf);
+        counter.push(Literal::from(3));
+    }
+
+    #[test]
+    fn test_try_push_unknown_var() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let mut counter = IncrementalModelCounter::new(&ddnnf);
+        assert!(counter.try_push(Literal::from(-3)).is_err());
+        assert!(counter.assumptions().is_empty());
+        assert!(counter.try_push(Literal::from(-2)).is_ok());
+    }
+}
@@ -1,13 +1,80 @@
+mod atomic_sets;
+pub use atomic_sets::AtomicSets;
+
 mod checker;
+pub use checker::CheckLevel;
+pub use checker::CheckerIssue;
 pub use checker::CheckingVisitor;
 pub use checker::CheckingVisitorData;
+pub use checker::DeterminismVerdict;
+
+mod cnf_validator;
+pub use cnf_validator::CnfValidator;
+
+mod cost_distribution;
+pub use cost_distribution::CostDistribution;
+pub use cost_distribution::CostDistributionComputer;
+
+mod count_certificate;
+pub use count_certificate::CountCertificateVerifier;
+
+mod derived_variables;
+pub use derived_variables::BooleanExpression;
+pub use derived_variables::DerivedVariables;
+
+mod hamming_ball_counter;
+pub use hamming_ball_counter::HammingBallCounter;
+
+mod hybrid_model_counter;
+pub use hybrid_model_counter::HybridModelCounter;
+
+mod incremental_model_counter;
+pub use incremental_model_counter::IncrementalModelCounter;
+
+mod model_count_bounds;
+pub use model_count_bounds::ModelCountBounds;
 
 mod model_counter;
+pub use model_counter::ModelCounter;
 pub use model_counter::ModelCountingVisitor;
 pub use model_counter::ModelCountingVisitorData;
 
 mod model_enumerator;
 pub use model_enumerator::ModelEnumerator;
+pub use model_enumerator::ModelIter;
+pub use model_enumerator::OwnedModelIter;
 
 mod model_finder;
 pub use model_finder::ModelFinder;
+
+mod model_sampler;
+pub use model_sampler::ModelSampler;
+
+mod negated_view;
+pub use negated_view::NegatedView;
+pub use negated_view::NonModelIter;
+
+mod optimal_model_finder;
+pub use optimal_model_finder::OptimalModelFinder;
+
+#[cfg(any(test, feature = "naive-compiler"))]
+mod naive_compilation;
+
+mod projected_model_counter;
+pub use projected_model_counter::ProjectedModelCounter;
+
+mod query_engine;
+pub use query_engine::QueryEngine;
+
+mod quota_model_enumerator;
+pub use quota_model_enumerator::QuotaModelEnumerator;
+
+mod reconfiguration;
+pub use reconfiguration::ReconfigurationPathFinder;
+
+mod similarity;
+pub use similarity::Similarity;
+pub use similarity::SimilarityComputer;
+
+mod weighted_model_counter;
+pub use weighted_model_counter::WeightedModelCounter;
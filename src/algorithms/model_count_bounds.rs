@@ -0,0 +1,201 @@
+use crate::{
+    core::{InvolvedVars, Node},
+    DecisionDNNF, Literal,
+};
+use rug::Integer;
+
+/// A structure used to bound the number of models of a formula whose disjunction nodes may not be deterministic.
+///
+/// The exact model counters sum the counts of the children of the disjunction nodes, which is only correct if these children have no common model.
+/// When the determinism of some disjunction nodes cannot be established (see [`CheckingVisitorData::refute_warnings`](crate::CheckingVisitorData::refute_warnings)), this structure computes bounds that remain valid for any decomposable formula.
+///
+/// The upper bound sums the counts of the children of the disjunction nodes, without exceeding the number of assignments of the variables they involve.
+/// The lower bound only sums the counts of children that are known to be disjoint:
+/// the children are considered by decreasing lower bounds, and a child is selected if, for each child already selected, the literals propagated to them are contradictory.
+/// For formulas produced by a Decision-DNNF compiler, the children of the disjunction nodes are decided on a common variable, and both bounds are equal to the exact count.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, ModelCountBounds};
+///
+/// // 1 or 2, with overlapping children
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 1 0\n1 2 2 0\n".as_bytes()).unwrap();
+/// let bounds = ModelCountBounds::new(&ddnnf);
+/// assert_eq!(2, bounds.lower_bound().to_usize_wrapping());
+/// assert_eq!(4, bounds.upper_bound().to_usize_wrapping());
+/// assert!(!bounds.is_exact());
+/// ```
+#[derive(Debug)]
+pub struct ModelCountBounds {
+    lower_bound: Integer,
+    upper_bound: Integer,
+}
+
+impl ModelCountBounds {
+    /// Computes the bounds of the model count of a formula.
+    ///
+    /// The conjunction nodes of the formula must be decomposable.
+    /// The computation takes a time polynomial in the size of the formula.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn new(ddnnf: &DecisionDNNF) -> Self {
+        let n_vars = ddnnf.n_vars();
+        let mut bounds: Vec<Option<(Integer, Integer, InvolvedVars)>> = vec![None; ddnnf.n_nodes()];
+        for node in ddnnf.topological_order().into_iter().rev() {
+            let node_bounds = match ddnnf.node(node) {
+                Node::And(edges) => {
+                    let mut lower = Integer::from(1);
+                    let mut upper = Integer::from(1);
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for edge_index in edges {
+                        let edge = ddnnf.edge(*edge_index);
+                        let (child_lower, child_upper, child_vars) =
+                            bounds[usize::from(edge.target())].as_ref().unwrap();
+                        lower *= child_lower;
+                        upper *= child_upper;
+                        involved_vars.or_assign(child_vars);
+                        involved_vars.set_literals(edge.propagated());
+                    }
+                    (lower, upper, involved_vars)
+                }
+                Node::Or(edges) => {
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for edge_index in edges {
+                        let edge = ddnnf.edge(*edge_index);
+                        involved_vars
+                            .or_assign(&bounds[usize::from(edge.target())].as_ref().unwrap().2);
+                        involved_vars.set_literals(edge.propagated());
+                    }
+                    let n_involved = involved_vars.count_ones();
+                    let mut children = edges
+                        .iter()
+                        .map(|edge_index| {
+                            let edge = ddnnf.edge(*edge_index);
+                            let (child_lower, child_upper, child_vars) =
+                                bounds[usize::from(edge.target())].as_ref().unwrap();
+                            let mut edge_vars = child_vars.clone();
+                            edge_vars.set_literals(edge.propagated());
+                            let shift = n_involved - edge_vars.count_ones();
+                            (
+                                edge.propagated(),
+                                Integer::from(child_lower << shift),
+                                Integer::from(child_upper << shift),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    children.sort_by(|c0, c1| c1.1.cmp(&c0.1));
+                    let mut selected: Vec<&[Literal]> = Vec::new();
+                    let mut lower = Integer::from(0);
+                    let mut upper = Integer::from(0);
+                    for (propagated, child_lower, child_upper) in children {
+                        if selected.iter().all(|s| are_contradictory(s, propagated)) {
+                            selected.push(propagated);
+                            lower += child_lower;
+                        }
+                        upper += child_upper;
+                    }
+                    let n_assignments = Integer::from(1) << n_involved;
+                    if upper > n_assignments {
+                        upper = n_assignments;
+                    }
+                    (lower, upper, involved_vars)
+                }
+                Node::True => (
+                    Integer::from(1),
+                    Integer::from(1),
+                    InvolvedVars::new(n_vars),
+                ),
+                Node::False => (
+                    Integer::from(0),
+                    Integer::from(0),
+                    InvolvedVars::new(n_vars),
+                ),
+            };
+            bounds[usize::from(node)] = Some(node_bounds);
+        }
+        let (lower, upper, involved_vars) = bounds.swap_remove(0).unwrap();
+        let n_free_vars = involved_vars.count_zeros();
+        Self {
+            lower_bound: lower << n_free_vars,
+            upper_bound: upper << n_free_vars,
+        }
+    }
+
+    /// Returns the lower bound of the model count.
+    #[must_use]
+    pub fn lower_bound(&self) -> &Integer {
+        &self.lower_bound
+    }
+
+    /// Returns the upper bound of the model count.
+    #[must_use]
+    pub fn upper_bound(&self) -> &Integer {
+        &self.upper_bound
+    }
+
+    /// Returns `true` if and only if both bounds are equal, in which case they give the exact model count.
+    #[must_use]
+    pub fn is_exact(&self) -> bool {
+        self.lower_bound == self.upper_bound
+    }
+}
+
+fn are_contradictory(literals: &[Literal], other_literals: &[Literal]) -> bool {
+    literals.iter().any(|l| other_literals.contains(&l.flip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{D4Reader, ModelCounter};
+
+    fn bounds(instance: &str) -> (usize, usize) {
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let bounds = ModelCountBounds::new(&ddnnf);
+        (
+            bounds.lower_bound().to_usize_wrapping(),
+            bounds.upper_bound().to_usize_wrapping(),
+        )
+    }
+
+    #[test]
+    fn test_deterministic() {
+        // (-1 and -2) or (1 and (-3 or 3))
+        let instance = "o 1 0\no 2 0\nt 3 0\n1 3 -1 -2 0\n1 2 1 0\n2 3 -3 0\n2 3 3 0\n";
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let bounds = ModelCountBounds::new(&ddnnf);
+        assert!(bounds.is_exact());
+        assert_eq!(ModelCounter::new(&ddnnf).count(), bounds.lower_bound());
+    }
+
+    #[test]
+    fn test_overlapping_children() {
+        // 1 or 2 or -3, which has 7 models
+        assert_eq!((4, 8), bounds("o 1 0\nt 2 0\n1 2 1 0\n1 2 2 0\n1 2 -3 0\n"));
+    }
+
+    #[test]
+    fn test_greedy_selection() {
+        // (1 and 2) or -1 or 3, which has 7 models: 3 is not selected since it may overlap -1
+        assert_eq!(
+            (6, 8),
+            bounds("o 1 0\nt 2 0\n1 2 1 2 0\n1 2 -1 0\n1 2 3 0\n")
+        );
+    }
+
+    #[test]
+    fn test_false_and_true() {
+        assert_eq!((0, 0), bounds("f 1 0\n"));
+        assert_eq!((1, 1), bounds("t 1 0\n"));
+    }
+
+    #[test]
+    fn test_free_vars() {
+        let mut ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 1 0\n1 2 2 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(3);
+        let bounds = ModelCountBounds::new(&ddnnf);
+        assert_eq!(4, bounds.lower_bound().to_usize_wrapping());
+        assert_eq!(8, bounds.upper_bound().to_usize_wrapping());
+    }
+}
@@ -1,12 +1,16 @@
 use crate::{
-    core::{BottomUpVisitor, InvolvedVars, NodeIndex},
+    core::{assumed_polarities, BottomUpVisitor, EdgeIndex, InvolvedVars, Node, NodeIndex, VarMap},
     DecisionDNNF, Literal,
 };
+use anyhow::Result;
 use rug::Integer;
+use std::cell::OnceCell;
 
-/// A structure used to count the models of a [`DecisionDNNF`].
+/// The visitor form of the [`ModelCounter`], used to count the models of a [`DecisionDNNF`] with a [`BottomUpTraversal`](crate::BottomUpTraversal).
 ///
 /// The algorithm takes a time polynomial in the size of the Decision-DNNF.
+/// This visitor is a thin adapter over the functions the [`ModelCounter`] uses to merge the counts of the nodes, so that both always give the same number of models.
+/// It is meant to be combined with other visitors, e.g. with a [`BiBottomUpVisitor`](crate::BiBottomUpVisitor); use the [`ModelCounter`] for everything else.
 ///
 /// # Example
 ///
@@ -26,6 +30,7 @@ pub struct ModelCountingVisitor;
 /// The data returned by the [`ModelCountingVisitor`] algorithm.
 ///
 /// See its documentation for more information.
+#[derive(Clone)]
 pub struct ModelCountingVisitorData {
     n_models: Integer,
     involved_vars: InvolvedVars,
@@ -49,33 +54,32 @@ impl ModelCountingVisitorData {
 impl BottomUpVisitor<ModelCountingVisitorData> for ModelCountingVisitor {
     fn merge_for_and(
         &self,
-        _ddnnf: &DecisionDNNF,
+        ddnnf: &DecisionDNNF,
         path: &[NodeIndex],
         children: Vec<(&[Literal], ModelCountingVisitorData)>,
     ) -> ModelCountingVisitorData {
+        let (n_models, involved_vars) = and_count(ddnnf.n_vars(), edge_counts(children));
         adapt_for_root(
-            merge_children(children, &|v0, v1| {
-                v0.n_models.clone() * v1.n_models.clone()
-            }),
+            ModelCountingVisitorData {
+                n_models,
+                involved_vars,
+            },
             path,
         )
     }
 
     fn merge_for_or(
         &self,
-        _ddnnf: &DecisionDNNF,
+        ddnnf: &DecisionDNNF,
         path: &[NodeIndex],
         children: Vec<(&[Literal], ModelCountingVisitorData)>,
     ) -> ModelCountingVisitorData {
+        let (n_models, involved_vars) = or_count(ddnnf.n_vars(), edge_counts(children), None);
         adapt_for_root(
-            merge_children(children, &|v0, v1| {
-                let mut intersection = v0.involved_vars.clone();
-                intersection.and_assign(&v1.involved_vars);
-                let intersection_ones = intersection.count_ones();
-                v0.n_models.clone() * (1 << (v1.involved_vars.count_ones() - intersection_ones))
-                    + v1.n_models.clone()
-                        * (1 << (v0.involved_vars.count_ones() - intersection_ones))
-            }),
+            ModelCountingVisitorData {
+                n_models,
+                involved_vars,
+            },
             path,
         )
     }
@@ -93,27 +97,23 @@ impl BottomUpVisitor<ModelCountingVisitorData> for ModelCountingVisitor {
             path,
         )
     }
+
+    fn is_cacheable(&self, _ddnnf: &DecisionDNNF, path: &[NodeIndex]) -> bool {
+        path.len() > 1
+    }
 }
 
-fn merge_children(
+/// Returns the counts of the children given to the visitor, in which the variables involved by the children include the literals propagated by their edges.
+fn edge_counts(
     children: Vec<(&[Literal], ModelCountingVisitorData)>,
-    n_models_fn: &dyn Fn(&ModelCountingVisitorData, &ModelCountingVisitorData) -> Integer,
-) -> ModelCountingVisitorData {
-    let new_children = children
+) -> Vec<(Integer, InvolvedVars)> {
+    children
         .into_iter()
         .map(|(propagated, mut child)| {
             child.involved_vars.set_literals(propagated);
-            child
+            (child.n_models, child.involved_vars)
         })
-        .collect::<Vec<_>>();
-    new_children
-        .into_iter()
-        .reduce(|mut acc, to_merge| {
-            acc.n_models = n_models_fn(&acc, &to_merge);
-            acc.involved_vars.or_assign(&to_merge.involved_vars);
-            acc
-        })
-        .expect("cannot merge an empty set of children")
+        .collect()
 }
 
 fn adapt_for_root(
@@ -126,10 +126,624 @@ fn adapt_for_root(
     data
 }
 
+/// A structure used to count the models of the subformulas of a [`DecisionDNNF`].
+///
+/// Contrary to its visitor form, the [`ModelCountingVisitor`], this counter stores the counts computed for each node, so that the subformula rooted at any node can be queried at no additional cost.
+/// It also supports assumptions, partial models and parallel counting.
+/// The subformula rooted at a node is considered on the same variables than the whole formula; the variables it does not involve are free.
+///
+/// Nodes are identified by their [`NodeIndex`]; the root has index 0.
+/// The structure of the formula can be explored using [`DecisionDNNF::node`] and [`DecisionDNNF::edge`].
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, Literal, ModelCounter, Node, NodeIndex};
+///
+/// // (-1 and (-2 or 2)) or 1
+/// let ddnnf = D4Reader::read(r"
+/// o 1 0
+/// o 2 0
+/// t 3 0
+/// 1 2 -1 0
+/// 1 3 1 0
+/// 2 3 -2 0
+/// 2 3 2 0
+/// ".as_bytes()).unwrap();
+/// let counter = ModelCounter::new(&ddnnf);
+/// assert_eq!(4, *counter.count_from(NodeIndex::from(0)));
+/// // the first child of the root
+/// let Node::Or(root_edges) = ddnnf.node(NodeIndex::from(0)) else { unreachable!() };
+/// let child = ddnnf.edge(root_edges[0]).target();
+/// assert_eq!(4, *counter.count_from(child));
+/// assert_eq!(2, counter.count_from_under_assumptions(child, &[Literal::from(2)]));
+/// ```
+pub struct ModelCounter<'a> {
+    ddnnf: &'a DecisionDNNF,
+    counts: Vec<OnceCell<(Integer, InvolvedVars)>>,
+    smoothed_counts: Vec<OnceCell<Integer>>,
+}
+
+impl<'a> ModelCounter<'a> {
+    /// Builds a new model counter given a [`DecisionDNNF`].
+    ///
+    /// The counts are computed lazily, when they are queried.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        let n_nodes = ddnnf.n_nodes();
+        Self {
+            ddnnf,
+            counts: (0..n_nodes).map(|_| OnceCell::new()).collect(),
+            smoothed_counts: (0..n_nodes).map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    /// Builds a new model counter given a [`DecisionDNNF`], and computes the counts of all its nodes using several threads.
+    ///
+    /// The nodes are processed by increasing height, so that the nodes of a same height can be counted at the same time;
+    /// the nodes of each height are split among the threads, unless they are too few to make it worth it.
+    /// This is worth it for formulas with many nodes, provided they are not too deep.
+    /// Contrary to the lazy computation of [`new`](Self::new), this computation is not recursive, and thus does not depend on the size of the stack.
+    ///
+    /// Once built, the counter behaves as a counter built by [`new`](Self::new) for which all the counts were queried.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of threads is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, ModelCounter};
+    ///
+    /// // (-1 and -2) or 1
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let counter = ModelCounter::new_parallel(&ddnnf, 4);
+    /// assert_eq!(3, *counter.count());
+    /// ```
+    #[must_use]
+    pub fn new_parallel(ddnnf: &'a DecisionDNNF, n_threads: usize) -> Self {
+        assert!(n_threads > 0, "the number of threads must be positive");
+        let mut computed: Vec<Option<(Integer, InvolvedVars)>> = vec![None; ddnnf.n_nodes()];
+        for level in nodes_by_height(ddnnf) {
+            let chunk_size = ((level.len() + n_threads - 1) / n_threads).max(MIN_NODES_PER_THREAD);
+            let level_counts = if chunk_size >= level.len() {
+                count_nodes(ddnnf, &level, &computed)
+            } else {
+                std::thread::scope(|scope| {
+                    let computed = &computed;
+                    let handles = level
+                        .chunks(chunk_size)
+                        .map(|chunk| scope.spawn(move || count_nodes(ddnnf, chunk, computed)))
+                        .collect::<Vec<_>>();
+                    handles
+                        .into_iter()
+                        .flat_map(|h| h.join().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            };
+            for (node, count) in level.into_iter().zip(level_counts) {
+                computed[usize::from(node)] = Some(count);
+            }
+        }
+        let counter = Self::new(ddnnf);
+        for (cell, count) in counter.counts.iter().zip(computed) {
+            let _ = cell.set(count.unwrap());
+        }
+        counter
+    }
+
+    /// Counts the models of a [`DecisionDNNF`] by counting the models of its independent components using several threads.
+    ///
+    /// The formula is split by [`DecisionDNNF::components`], and the components are shared among the threads;
+    /// each thread counts the models of its components without recursion, as [`new_parallel`](Self::new_parallel) does.
+    /// Since the components do not share any variable, the number of models of the formula is the product of their numbers of models, restricted to the variables they involve.
+    /// This is worth it for formulas which root is a conjunction of large subformulas.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of threads is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, ModelCounter};
+    ///
+    /// // ((-1 and -2) or 1) and (-3 or 3)
+    /// let ddnnf = D4Reader::read(r"
+    /// a 1 0
+    /// o 2 0
+    /// o 3 0
+    /// t 4 0
+    /// 1 2 0
+    /// 1 3 0
+    /// 2 4 -1 -2 0
+    /// 2 4 1 0
+    /// 3 4 -3 0
+    /// 3 4 3 0
+    /// ".as_bytes()).unwrap();
+    /// assert_eq!(6, ModelCounter::count_by_components(&ddnnf, 2));
+    /// ```
+    #[must_use]
+    pub fn count_by_components(ddnnf: &DecisionDNNF, n_threads: usize) -> Integer {
+        assert!(n_threads > 0, "the number of threads must be positive");
+        let components = ddnnf.components();
+        let chunk_size = ((components.len() + n_threads - 1) / n_threads).max(1);
+        let component_counts = std::thread::scope(|scope| {
+            let handles = components
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|c| {
+                                ModelCounter::new_parallel(c, 1)
+                                    .node_count(0.into())
+                                    .clone()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        let mut n_involved_vars = 0;
+        let mut n_models = Integer::from(1);
+        for (n, involved_vars) in component_counts {
+            n_models *= n;
+            n_involved_vars += involved_vars.count_ones();
+        }
+        n_models << (ddnnf.n_vars() - n_involved_vars)
+    }
+
+    /// Returns the number of models of the whole formula.
+    #[must_use]
+    pub fn count(&self) -> &Integer {
+        self.count_from(NodeIndex::from(0))
+    }
+
+    /// Returns the number of models of the subformula rooted at the given node.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn count_from(&self, node: NodeIndex) -> &Integer {
+        self.smoothed_counts[usize::from(node)].get_or_init(|| {
+            let (n, involved_vars) = self.node_count(node);
+            Integer::from(n << involved_vars.count_zeros())
+        })
+    }
+
+    /// Returns the number of models of the subformula rooted at the given node that are consistent with some assumptions.
+    ///
+    /// Assumptions are given as literals.
+    /// The counts computed by this function are not stored.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds, or if an assumption refers to a variable that does not exist.
+    #[must_use]
+    pub fn count_from_under_assumptions(
+        &self,
+        node: NodeIndex,
+        assumptions: &[Literal],
+    ) -> Integer {
+        self.try_count_from_under_assumptions(node, assumptions)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the number of models of the subformula rooted at the given node that are consistent with some assumptions,
+    /// as [`count_from_under_assumptions`](Self::count_from_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    pub fn try_count_from_under_assumptions(
+        &self,
+        node: NodeIndex,
+        assumptions: &[Literal],
+    ) -> Result<Integer> {
+        let Some(assumed) = assumed_polarities(assumptions, self.ddnnf.n_vars())? else {
+            return Ok(Integer::from(0));
+        };
+        let mut cache = vec![None; self.ddnnf.n_nodes()];
+        let (n, involved_vars) = self.node_count_under_assumptions(node, &assumed, &mut cache);
+        Ok(n << n_unassumed_missing_vars(&involved_vars, &assumed))
+    }
+
+    /// Returns, for each variable, the number of models of the formula in which it is true and the number of models in which it is false.
+    ///
+    /// The vector is indexed by the variable indices, and the first count of each pair refers to the positive literal.
+    /// All the counts are computed in a bottom-up pass followed by a top-down pass on the formula, instead of counting the models under each literal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, ModelCounter};
+    ///
+    /// // (-1 and -2) or 1
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let marginals = ModelCounter::new(&ddnnf).marginals();
+    /// assert_eq!((2, 1), (marginals[0].0.to_u32().unwrap(), marginals[0].1.to_u32().unwrap()));
+    /// assert_eq!((1, 2), (marginals[1].0.to_u32().unwrap(), marginals[1].1.to_u32().unwrap()));
+    /// ```
+    #[must_use]
+    pub fn marginals(&self) -> Vec<(Integer, Integer)> {
+        let n_vars = self.ddnnf.n_vars();
+        let order = self.ddnnf.topological_order();
+        for node in order.iter().rev() {
+            let _ = self.node_count(*node);
+        }
+        let mut marginals = vec![(Integer::from(0), Integer::from(0)); n_vars];
+        let (root_count, root_vars) = self.node_count(NodeIndex::from(0));
+        let n_root_free_vars = root_vars.count_zeros();
+        if n_root_free_vars > 0 {
+            let half = Integer::from(root_count << (n_root_free_vars - 1));
+            for l in root_vars.iter_missing_literals() {
+                marginals[l.var_index()] = (half.clone(), half.clone());
+            }
+        }
+        // the number of ways to complete a model of the subformula rooted at each node into a model of the whole formula
+        let mut completions = vec![Integer::from(0); self.ddnnf.n_nodes()];
+        completions[0] = Integer::from(1) << n_root_free_vars;
+        for node in order {
+            let node_completions = std::mem::take(&mut completions[usize::from(node)]);
+            if node_completions == 0 {
+                continue;
+            }
+            match self.ddnnf.node(node) {
+                Node::And(edges) => {
+                    let edge_counts = edges
+                        .iter()
+                        .map(|e| &self.node_count(self.ddnnf.edge(*e).target()).0)
+                        .collect::<Vec<_>>();
+                    for (i, edge_index) in edges.iter().enumerate() {
+                        let mut edge_completions = node_completions.clone();
+                        for (j, n) in edge_counts.iter().enumerate() {
+                            if i != j {
+                                edge_completions *= *n;
+                            }
+                        }
+                        self.add_edge_marginals(
+                            *edge_index,
+                            edge_completions,
+                            None,
+                            &mut completions,
+                            &mut marginals,
+                        );
+                    }
+                }
+                Node::Or(edges) => {
+                    let node_vars = &self.node_count(node).1;
+                    for edge_index in edges {
+                        self.add_edge_marginals(
+                            *edge_index,
+                            node_completions.clone(),
+                            Some(node_vars),
+                            &mut completions,
+                            &mut marginals,
+                        );
+                    }
+                }
+                Node::True | Node::False => {}
+            }
+        }
+        marginals
+    }
+
+    /// Updates the marginals with the models going through an edge, given the number of completions of the models of its target.
+    ///
+    /// If the source of the edge is a disjunction node, the variables it involves must be given, so that the variables that are free in the edge are taken into account.
+    fn add_edge_marginals(
+        &self,
+        edge_index: EdgeIndex,
+        mut edge_completions: Integer,
+        or_vars: Option<&InvolvedVars>,
+        completions: &mut [Integer],
+        marginals: &mut [(Integer, Integer)],
+    ) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (target_count, target_vars) = self.node_count(edge.target());
+        let mut edge_vars = target_vars.clone();
+        edge_vars.set_literals(edge.propagated());
+        if let Some(or_vars) = or_vars {
+            edge_vars.xor_assign(or_vars);
+            edge_completions <<= edge_vars.count_ones();
+        }
+        let n_models = Integer::from(&edge_completions * target_count);
+        if n_models == 0 {
+            return;
+        }
+        if or_vars.is_some() {
+            let half = Integer::from(&n_models >> 1);
+            for l in edge_vars.iter_pos_literals() {
+                marginals[l.var_index()].0 += &half;
+                marginals[l.var_index()].1 += &half;
+            }
+        }
+        for l in edge.propagated() {
+            if l.polarity() {
+                marginals[l.var_index()].0 += &n_models;
+            } else {
+                marginals[l.var_index()].1 += &n_models;
+            }
+        }
+        completions[usize::from(edge.target())] += edge_completions;
+    }
+
+    /// Returns the number of partial models of the formula that are consistent with some assumptions.
+    ///
+    /// The partial models are the ones given by a [`ModelEnumerator`](crate::ModelEnumerator) that eludes the free variables;
+    /// there is one for each path from the root to a true node that goes through all the children of the conjunction nodes and through a single child of the disjunction nodes.
+    /// A partial model is consistent with the assumptions if none of the literals of its path contradicts them.
+    /// Contrary to the number of models, the number of partial models depends on the structure of the formula.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an assumption refers to a variable that does not exist.
+    #[must_use]
+    pub fn count_partial_models_under_assumptions(&self, assumptions: &[Literal]) -> Integer {
+        self.try_count_partial_models_under_assumptions(assumptions)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the number of partial models of the formula that are consistent with some assumptions,
+    /// as [`count_partial_models_under_assumptions`](Self::count_partial_models_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    pub fn try_count_partial_models_under_assumptions(
+        &self,
+        assumptions: &[Literal],
+    ) -> Result<Integer> {
+        let Some(assumed) = assumed_polarities(assumptions, self.ddnnf.n_vars())? else {
+            return Ok(Integer::from(0));
+        };
+        let mut counts = vec![Integer::from(0); self.ddnnf.n_nodes()];
+        for node in self.ddnnf.topological_order().into_iter().rev() {
+            let edge_count = |edge_index: &EdgeIndex| {
+                let edge = self.ddnnf.edge(*edge_index);
+                if edge
+                    .propagated()
+                    .iter()
+                    .any(|l| assumed[*l].is_some_and(|p| p != l.polarity()))
+                {
+                    Integer::from(0)
+                } else {
+                    counts[usize::from(edge.target())].clone()
+                }
+            };
+            let n = match self.ddnnf.node(node) {
+                Node::And(edges) => edges.iter().map(edge_count).product(),
+                Node::Or(edges) => edges.iter().map(edge_count).sum(),
+                Node::True => Integer::from(1),
+                Node::False => Integer::from(0),
+            };
+            counts[usize::from(node)] = n;
+        }
+        Ok(counts.swap_remove(0))
+    }
+
+    pub(crate) fn ddnnf(&self) -> &'a DecisionDNNF {
+        self.ddnnf
+    }
+
+    /// Returns the number of models of the subformula rooted at the given node, restricted to the variables it involves, and the number of these variables.
+    pub(crate) fn involved_vars_count_from(&self, node: NodeIndex) -> (&Integer, usize) {
+        let (n, involved_vars) = self.node_count(node);
+        (n, involved_vars.count_ones())
+    }
+
+    /// Returns the number of models of the subformula rooted at the given node, restricted to the variables it involves, and these variables.
+    pub(crate) fn node_count(&self, node: NodeIndex) -> &(Integer, InvolvedVars) {
+        self.counts[usize::from(node)]
+            .get_or_init(|| compute_node_count(self.ddnnf, node, &|child| self.node_count(child)))
+    }
+
+    pub(crate) fn node_count_under_assumptions(
+        &self,
+        node: NodeIndex,
+        assumed: &VarMap<Option<bool>>,
+        cache: &mut [Option<(Integer, InvolvedVars)>],
+    ) -> (Integer, InvolvedVars) {
+        if let Some(result) = &cache[usize::from(node)] {
+            return result.clone();
+        }
+        let n_vars = self.ddnnf.n_vars();
+        let result = match self.ddnnf.node(node) {
+            Node::And(edges) => {
+                let children = edges
+                    .iter()
+                    .map(|e| self.edge_count_under_assumptions(*e, assumed, cache))
+                    .collect::<Vec<_>>();
+                and_count(n_vars, children)
+            }
+            Node::Or(edges) => {
+                let children = edges
+                    .iter()
+                    .map(|e| self.edge_count_under_assumptions(*e, assumed, cache))
+                    .collect::<Vec<_>>();
+                or_count(n_vars, children, Some(assumed))
+            }
+            Node::True => (Integer::from(1), InvolvedVars::new(n_vars)),
+            Node::False => (Integer::from(0), InvolvedVars::new(n_vars)),
+        };
+        cache[usize::from(node)] = Some(result.clone());
+        result
+    }
+
+    fn edge_count_under_assumptions(
+        &self,
+        edge_index: EdgeIndex,
+        assumed: &VarMap<Option<bool>>,
+        cache: &mut [Option<(Integer, InvolvedVars)>],
+    ) -> (Integer, InvolvedVars) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (mut n, mut involved_vars) =
+            self.node_count_under_assumptions(edge.target(), assumed, cache);
+        if edge
+            .propagated()
+            .iter()
+            .any(|l| assumed[*l].is_some_and(|p| p != l.polarity()))
+        {
+            n = Integer::from(0);
+        }
+        involved_vars.set_literals(edge.propagated());
+        (n, involved_vars)
+    }
+}
+
+/// Computes the count of a node and the variables it involves, given a function returning the same data for its children.
+fn compute_node_count<'b>(
+    ddnnf: &DecisionDNNF,
+    node: NodeIndex,
+    child_count: &dyn Fn(NodeIndex) -> &'b (Integer, InvolvedVars),
+) -> (Integer, InvolvedVars) {
+    let n_vars = ddnnf.n_vars();
+    let edge_count = |edge_index: EdgeIndex| {
+        let edge = ddnnf.edge(edge_index);
+        let (n, target_vars) = child_count(edge.target());
+        let mut involved_vars = target_vars.clone();
+        involved_vars.set_literals(edge.propagated());
+        (n.clone(), involved_vars)
+    };
+    match ddnnf.node(node) {
+        Node::And(edges) => and_count(n_vars, edges.iter().map(|e| edge_count(*e)).collect()),
+        Node::Or(edges) => or_count(n_vars, edges.iter().map(|e| edge_count(*e)).collect(), None),
+        Node::True => (Integer::from(1), InvolvedVars::new(n_vars)),
+        Node::False => (Integer::from(0), InvolvedVars::new(n_vars)),
+    }
+}
+
+/// Merges the counts of the children of a conjunction node, given with the variables they involve (including the literals propagated by their edges).
+///
+/// This function, along with [`or_count`], is shared by all the big integer counts, so that they always agree.
+pub(crate) fn and_count(
+    n_vars: usize,
+    children: Vec<(Integer, InvolvedVars)>,
+) -> (Integer, InvolvedVars) {
+    let mut n = Integer::from(1);
+    let mut involved_vars = InvolvedVars::new(n_vars);
+    for (child_n, child_vars) in children {
+        n *= child_n;
+        involved_vars.or_assign(&child_vars);
+    }
+    (n, involved_vars)
+}
+
+/// Merges the counts of the children of a disjunction node, given with the variables they involve (including the literals propagated by their edges).
+///
+/// The count of each child is multiplied by the number of assignments of the variables the node involves but the child does not, except for the assumed ones.
+pub(crate) fn or_count(
+    n_vars: usize,
+    children: Vec<(Integer, InvolvedVars)>,
+    assumed: Option<&VarMap<Option<bool>>>,
+) -> (Integer, InvolvedVars) {
+    let mut involved_vars = InvolvedVars::new(n_vars);
+    for (_, child_vars) in &children {
+        involved_vars.or_assign(child_vars);
+    }
+    let n = children
+        .into_iter()
+        .map(|(child_n, mut child_vars)| {
+            child_vars.xor_assign(&involved_vars);
+            let n_free = match assumed {
+                Some(assumed) => child_vars
+                    .iter_pos_literals()
+                    .filter(|l| assumed[*l].is_none())
+                    .count(),
+                None => child_vars.count_ones(),
+            };
+            child_n << n_free
+        })
+        .sum();
+    (n, involved_vars)
+}
+
+/// The minimal number of nodes of a same height given to a thread by [`ModelCounter::new_parallel`], under which spawning a thread is not worth it.
+const MIN_NODES_PER_THREAD: usize = 64;
+
+/// Computes the counts of some nodes, given the counts of their children.
+fn count_nodes(
+    ddnnf: &DecisionDNNF,
+    nodes: &[NodeIndex],
+    computed: &[Option<(Integer, InvolvedVars)>],
+) -> Vec<(Integer, InvolvedVars)> {
+    nodes
+        .iter()
+        .map(|node| {
+            compute_node_count(ddnnf, *node, &|child| {
+                computed[usize::from(child)].as_ref().unwrap()
+            })
+        })
+        .collect()
+}
+
+/// Groups the nodes by height, the leaves having height 0 and the other nodes having a height greater than the ones of their children.
+fn nodes_by_height(ddnnf: &DecisionDNNF) -> Vec<Vec<NodeIndex>> {
+    let n_nodes = ddnnf.n_nodes();
+    let children = |i: usize| match ddnnf.node(NodeIndex::from(i)) {
+        Node::And(edges) | Node::Or(edges) => edges.as_slice(),
+        Node::True | Node::False => &[],
+    };
+    let mut heights: Vec<Option<usize>> = vec![None; n_nodes];
+    let mut levels: Vec<Vec<NodeIndex>> = Vec::new();
+    for start in 0..n_nodes {
+        if heights[start].is_some() {
+            continue;
+        }
+        let mut stack = vec![(start, 0)];
+        while let Some((i, next_child)) = stack.pop() {
+            if let Some(e) = children(i).get(next_child) {
+                stack.push((i, next_child + 1));
+                let target = usize::from(ddnnf.edge(*e).target());
+                if heights[target].is_none() {
+                    stack.push((target, 0));
+                }
+                continue;
+            }
+            if heights[i].is_some() {
+                continue;
+            }
+            let height = children(i)
+                .iter()
+                .map(|e| heights[usize::from(ddnnf.edge(*e).target())].unwrap() + 1)
+                .max()
+                .unwrap_or(0);
+            heights[i] = Some(height);
+            if levels.len() <= height {
+                levels.resize_with(height + 1, Vec::new);
+            }
+            levels[height].push(NodeIndex::from(i));
+        }
+    }
+    levels
+}
+
+pub(crate) fn n_unassumed_missing_vars(
+    involved_vars: &InvolvedVars,
+    assumed: &VarMap<Option<bool>>,
+) -> usize {
+    involved_vars
+        .iter_missing_literals()
+        .filter(|l| assumed[*l].is_none())
+        .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{core::BottomUpTraversal, D4Reader};
+    use crate::{core::BottomUpTraversal, D4Reader, ModelEnumerator};
+    use std::fmt::Write;
 
     fn model_count(instance: &str, n_vars: Option<usize>) -> usize {
         let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
@@ -206,4 +820,297 @@ mod tests {
             )
         );
     }
+
+    fn count_from_under_assumptions(
+        instance: &str,
+        n_vars: Option<usize>,
+        assumptions: &[isize],
+    ) -> usize {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let assumptions = assumptions
+            .iter()
+            .map(|l| Literal::from(*l))
+            .collect::<Vec<_>>();
+        ModelCounter::new(&ddnnf)
+            .count_from_under_assumptions(NodeIndex::from(0), &assumptions)
+            .to_usize_wrapping()
+    }
+
+    #[test]
+    fn test_model_counter_same_as_visitor() {
+        let instances = [
+            (
+                "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n",
+                None,
+            ),
+            ("t 1 0\n", Some(2)),
+            ("f 1 0\n", Some(2)),
+            ("o 1 0\no 2 0\nt 3 0\n2 3 -1 -2 0\n2 3 1 0\n1 2 0", Some(3)),
+            (
+                "o 1 0\no 2 0\nt 3 0\nf 4 0\n2 3 -1 0\n2 4 1 0\n1 2 0",
+                Some(2),
+            ),
+        ];
+        for (instance, n_vars) in instances {
+            let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+            if let Some(n) = n_vars {
+                ddnnf.update_n_vars(n);
+            }
+            assert_eq!(
+                model_count(instance, n_vars),
+                ModelCounter::new(&ddnnf).count().to_usize_wrapping()
+            );
+            assert_eq!(
+                model_count(instance, n_vars),
+                count_from_under_assumptions(instance, n_vars, &[])
+            );
+            for n_threads in [1, 2, 8] {
+                let counter = ModelCounter::new_parallel(&ddnnf, n_threads);
+                assert_eq!(
+                    model_count(instance, n_vars),
+                    counter.count().to_usize_wrapping()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_visitor_or_many_free_vars() {
+        // -1 or (1 and 2 and ... and 40)
+        let literals = (1..=40)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let instance = format!("o 1 0\nt 2 0\n1 2 -1 0\n1 2 {literals} 0\n");
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let traversal = BottomUpTraversal::new(Box::<ModelCountingVisitor>::default());
+        let expected = (Integer::from(1) << 39) + 1;
+        assert_eq!(expected, *traversal.traverse(&ddnnf).n_models());
+        assert_eq!(expected, *ModelCounter::new(&ddnnf).count());
+    }
+
+    #[test]
+    fn test_parallel_deep_chain() {
+        let depth = 10_000;
+        let mut instance = String::new();
+        for i in 1..=depth {
+            writeln!(instance, "a {i} 0").unwrap();
+        }
+        writeln!(instance, "t {} 0", depth + 1).unwrap();
+        for i in 1..=depth {
+            writeln!(instance, "{i} {} {i} 0", i + 1).unwrap();
+        }
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let counter = ModelCounter::new_parallel(&ddnnf, 4);
+        assert_eq!(1, counter.count().to_usize_wrapping());
+        let (n, n_involved) = counter.involved_vars_count_from(NodeIndex::from(depth - 1));
+        assert_eq!((1, 1), (n.to_usize_wrapping(), n_involved));
+    }
+
+    #[test]
+    fn test_parallel_wide() {
+        let width = 500;
+        let mut instance = String::from("a 1 0\n");
+        for i in 2..=width + 1 {
+            writeln!(instance, "o {i} 0").unwrap();
+        }
+        writeln!(instance, "t {} 0", width + 2).unwrap();
+        for i in 2..=width + 1 {
+            writeln!(instance, "1 {i} 0").unwrap();
+            writeln!(instance, "{i} {} -{} 0", width + 2, i - 1).unwrap();
+            writeln!(instance, "{i} {} {} {} 0", width + 2, i - 1, i % width + 1).unwrap();
+        }
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let counter = ModelCounter::new(&ddnnf);
+        for n_threads in [1, 3, 16] {
+            let parallel_counter = ModelCounter::new_parallel(&ddnnf, n_threads);
+            assert_eq!(counter.count(), parallel_counter.count());
+            for i in 0..ddnnf.n_nodes() {
+                assert_eq!(
+                    counter.count_from(NodeIndex::from(i)),
+                    parallel_counter.count_from(NodeIndex::from(i))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_by_components() {
+        let instance = r"
+        a 1 0
+        a 2 0
+        o 3 0
+        o 4 0
+        t 5 0
+        1 2 1 0
+        1 3 0
+        2 4 0
+        2 5 2 0
+        3 5 -3 0
+        3 5 3 0
+        4 5 -4 -5 0
+        4 5 4 0
+        ";
+        for (n_vars, false_leaf) in [(5, false), (8, false), (8, true)] {
+            let instance = if false_leaf {
+                format!("{instance}\nf 6 0\n1 6 -6 0\n")
+            } else {
+                instance.to_string()
+            };
+            let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+            ddnnf.update_n_vars(n_vars);
+            let expected = ModelCounter::new(&ddnnf).count().clone();
+            for n_threads in [1, 2, 8] {
+                assert_eq!(
+                    expected,
+                    ModelCounter::count_by_components(&ddnnf, n_threads)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the number of threads must be positive")]
+    fn test_parallel_no_threads() {
+        let ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        let _ = ModelCounter::new_parallel(&ddnnf, 0);
+    }
+
+    #[test]
+    fn test_try_count_under_assumptions_unknown_var() {
+        let ddnnf = D4Reader::read(
+            "t 1 0
+"
+            .as_bytes(),
+        )
+        .unwrap();
+        let counter = ModelCounter::new(&ddnnf);
+        assert_eq!(
+            "no such literal: 1 (the formula has 0 variables)",
+            counter
+                .try_count_from_under_assumptions(NodeIndex::from(0), &[Literal::from(1)])
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_count_under_assumptions() {
+        let clause = "o 1 0\no 2 0\nt 3 0\n2 3 -1 -2 0\n2 3 1 0\n1 2 0";
+        assert_eq!(2, count_from_under_assumptions(clause, None, &[1]));
+        assert_eq!(1, count_from_under_assumptions(clause, None, &[-1]));
+        assert_eq!(2, count_from_under_assumptions(clause, None, &[-2]));
+        assert_eq!(1, count_from_under_assumptions(clause, None, &[-1, -2]));
+        assert_eq!(0, count_from_under_assumptions(clause, None, &[-1, 2]));
+        assert_eq!(0, count_from_under_assumptions(clause, None, &[1, -1]));
+        assert_eq!(4, count_from_under_assumptions(clause, Some(4), &[1, 3]));
+    }
+
+    #[test]
+    fn test_marginals_same_as_assumptions() {
+        let instances = [
+            (
+                "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 0\n",
+                Some(4),
+            ),
+            ("t 1 0\n", Some(2)),
+            ("f 1 0\n", Some(2)),
+            ("o 1 0\no 2 0\nt 3 0\n2 3 -1 -2 0\n2 3 1 0\n1 2 0", Some(3)),
+            (
+                "o 1 0\no 2 0\nt 3 0\nf 4 0\n2 3 -1 0\n2 4 1 0\n1 2 0",
+                Some(2),
+            ),
+            (
+                "o 1 0\na 2 0\no 3 0\nt 4 0\n1 2 1 0\n1 4 -1 0\n2 3 0\n3 4 -2 -3 0\n3 4 2 0\n",
+                None,
+            ),
+        ];
+        for (instance, n_vars) in instances {
+            let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+            if let Some(n) = n_vars {
+                ddnnf.update_n_vars(n);
+            }
+            let counter = ModelCounter::new(&ddnnf);
+            let marginals = counter.marginals();
+            assert_eq!(ddnnf.n_vars(), marginals.len());
+            for (i, (n_pos, n_neg)) in marginals.into_iter().enumerate() {
+                let var = isize::try_from(i + 1).unwrap();
+                for (dimacs, n) in [(var, n_pos), (-var, n_neg)] {
+                    assert_eq!(
+                        counter.count_from_under_assumptions(
+                            NodeIndex::from(0),
+                            &[Literal::from(dimacs)]
+                        ),
+                        n,
+                        "literal {dimacs} in {instance:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_count_from_inner_node() {
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let counter = ModelCounter::new(&ddnnf);
+        assert_eq!(
+            4,
+            counter.count_from(NodeIndex::from(1)).to_usize_wrapping()
+        );
+        assert_eq!(
+            4,
+            counter.count_from(NodeIndex::from(3)).to_usize_wrapping()
+        );
+        assert_eq!(
+            2,
+            counter
+                .count_from_under_assumptions(NodeIndex::from(1), &[Literal::from(-1)])
+                .to_usize_wrapping()
+        );
+    }
+
+    #[test]
+    fn test_count_partial_models() {
+        let ddnnf = D4Reader::read(
+            "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 2 0\n2 4 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let counter = ModelCounter::new(&ddnnf);
+        let partial_models = ModelEnumerator::new(&ddnnf, true);
+        assert_eq!(
+            partial_models.into_iter().count(),
+            counter
+                .count_partial_models_under_assumptions(&[])
+                .to_usize_wrapping()
+        );
+        assert_eq!(
+            2,
+            counter
+                .count_partial_models_under_assumptions(&[Literal::from(1)])
+                .to_usize_wrapping()
+        );
+        assert_eq!(
+            1,
+            counter
+                .count_partial_models_under_assumptions(&[Literal::from(-1), Literal::from(2)])
+                .to_usize_wrapping()
+        );
+        assert_eq!(
+            0,
+            counter
+                .count_partial_models_under_assumptions(&[Literal::from(1), Literal::from(-1)])
+                .to_usize_wrapping()
+        );
+        assert!(counter
+            .try_count_partial_models_under_assumptions(&[Literal::from(4)])
+            .is_err());
+    }
 }
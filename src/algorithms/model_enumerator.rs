@@ -1,7 +1,9 @@
 use crate::{
-    core::{EdgeIndex, InvolvedVars, Node, NodeIndex},
-    DecisionDNNF, Literal,
+    core::{EdgeIndex, LitMap, Node, NodeIndex, VarMap},
+    DecisionDNNF, FreeVariables, Literal,
 };
+use rug::Integer;
+use std::sync::Arc;
 
 /// A structure used to enumerate the models of a [`DecisionDNNF`].
 ///
@@ -80,13 +82,7 @@ use crate::{
 #[derive(Debug)]
 pub struct ModelEnumerator<'a> {
     ddnnf: &'a DecisionDNNF,
-    or_edge_indices: Vec<usize>,
-    or_free_vars: Vec<Vec<Vec<Literal>>>,
-    root_free_vars: Vec<Literal>,
-    first_computed: bool,
-    model: Vec<Option<Literal>>,
-    has_model: bool,
-    elude_free_vars: bool,
+    state: EnumerationState,
 }
 
 impl<'a> ModelEnumerator<'a> {
@@ -95,28 +91,271 @@ impl<'a> ModelEnumerator<'a> {
     /// The second parameter sets whether free variables should be eluded from models.
     /// See top-level [`ModelEnumerator`] documentation for more information about free variables elusion.
     #[must_use]
-    #[allow(clippy::missing_panics_doc)]
     pub fn new(ddnnf: &'a DecisionDNNF, elude_free_vars: bool) -> Self {
-        let n_nodes = ddnnf.nodes().as_slice().len();
         Self {
             ddnnf,
+            state: EnumerationState::new(ddnnf, elude_free_vars),
+        }
+    }
+
+    /// Computes the next model and returns it.
+    /// Returns `None` if all the models have been returned.
+    pub fn compute_next_model(&mut self) -> Option<&[Option<Literal>]> {
+        self.state.compute_next_model(self.ddnnf)
+    }
+
+    /// Sets cubes blocking the models they cover: the models computed from now on contain none of these cubes.
+    ///
+    /// The cubes are checked when the enumerator chooses a child of a disjunction node: a child is skipped as soon as the literals fixed by the path leading to it cover one of the cubes, so that the models it leads to are not enumerated.
+    /// The models that are only covered once all their literals are known are skipped when they are produced.
+    /// When free variables are eluded, a partial model is skipped only if the literals it sets cover one of the cubes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, Literal, ModelEnumerator};
+    ///
+    /// // (-1 and (-2 or 2)) or 1
+    /// let ddnnf = D4Reader::read("o 1 0\no 2 0\nt 3 0\n1 2 -1 0\n1 3 1 0\n2 3 -2 0\n2 3 2 0\n".as_bytes()).unwrap();
+    /// let mut enumerator = ModelEnumerator::new(&ddnnf, true);
+    /// enumerator.set_blocking_cubes(vec![vec![Literal::from(-1)]]);
+    /// assert_eq!(vec![Some(Literal::from(1)), None], enumerator.compute_next_model().unwrap());
+    /// assert!(enumerator.compute_next_model().is_none());
+    /// ```
+    pub fn set_blocking_cubes(&mut self, cubes: Vec<Vec<Literal>>) {
+        self.state.blocking_cubes = cubes;
+    }
+
+    /// Returns the choices made at the disjunction nodes to build the current model.
+    ///
+    /// The disjunction nodes are considered in the order they are reached by a depth-first search starting at the root and following, for each disjunction node, the chosen child.
+    /// For each of these nodes, the returned vector contains the index of the selected child among the children of the node.
+    /// Given the formula, this vector identifies the path of the circuit that produced the current model.
+    ///
+    /// If no model has been computed yet, or if all the models have been returned, an empty vector is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, ModelEnumerator};
+    ///
+    /// // a single disjunction node with two children
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let mut enumerator = ModelEnumerator::new(&ddnnf, false);
+    /// enumerator.compute_next_model();
+    /// assert_eq!(vec![0], enumerator.current_choices());
+    /// enumerator.compute_next_model();
+    /// assert_eq!(vec![1], enumerator.current_choices());
+    /// ```
+    #[must_use]
+    pub fn current_choices(&self) -> Vec<usize> {
+        self.state
+            .current_choices(self.ddnnf)
+            .into_iter()
+            .map(|(choice, _)| choice)
+            .collect()
+    }
+
+    /// Returns the choices made at the disjunction nodes to build the current model, encoded as a single integer.
+    ///
+    /// The choices returned by [`current_choices`](Self::current_choices) are the digits of a mixed-radix integer, the first choice being the least significant digit.
+    /// The radix of each digit is the number of children of the corresponding disjunction node.
+    /// Since the radices are read from the formula while following the choices, the encoding can be decoded given the formula by [`decode_choices`](Self::decode_choices).
+    ///
+    /// If no model has been computed yet, or if all the models have been returned, `0` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, ModelEnumerator};
+    ///
+    /// // a single disjunction node with three children
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 -1 2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let mut enumerator = ModelEnumerator::new(&ddnnf, true);
+    /// let mut codes = Vec::new();
+    /// while enumerator.compute_next_model().is_some() {
+    ///     let code = enumerator.current_encoded_choices();
+    ///     assert_eq!(enumerator.current_choices(), ModelEnumerator::decode_choices(&ddnnf, &code).unwrap());
+    ///     codes.push(code);
+    /// }
+    /// assert_eq!(vec![0, 1, 2], codes);
+    /// ```
+    #[must_use]
+    pub fn current_encoded_choices(&self) -> Integer {
+        self.state
+            .current_choices(self.ddnnf)
+            .into_iter()
+            .rev()
+            .fold(Integer::new(), |code, (choice, arity)| {
+                code * Integer::from(arity) + Integer::from(choice)
+            })
+    }
+
+    /// Decodes choices encoded by [`current_encoded_choices`](Self::current_encoded_choices).
+    ///
+    /// The disjunction nodes are considered in the same order as the one used for the encoding, following the decoded choices.
+    /// Returns [`None`] if the integer is not the encoding of a path of the formula.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn decode_choices(ddnnf: &DecisionDNNF, code: &Integer) -> Option<Vec<usize>> {
+        if *code < 0 {
+            return None;
+        }
+        let mut choices = Vec::new();
+        let mut code = code.clone();
+        let mut to_visit = vec![NodeIndex::from(0)];
+        while let Some(node) = to_visit.pop() {
+            match &ddnnf.nodes()[node] {
+                Node::And(edges) => {
+                    to_visit.extend(edges.iter().rev().map(|e| ddnnf.edges()[*e].target()));
+                }
+                Node::Or(edges) => {
+                    let (quotient, choice) = code.div_rem(Integer::from(edges.len()));
+                    code = quotient;
+                    let choice = choice.to_usize().unwrap();
+                    choices.push(choice);
+                    to_visit.push(ddnnf.edges()[edges[choice]].target());
+                }
+                Node::True | Node::False => {}
+            }
+        }
+        if code == 0 {
+            Some(choices)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> IntoIterator for ModelEnumerator<'a> {
+    type Item = Box<[Option<Literal>]>;
+
+    type IntoIter = ModelIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ModelIter(self)
+    }
+}
+
+/// An iterator over the models of a [`DecisionDNNF`], built by calling [`into_iter`](IntoIterator::into_iter) on a [`ModelEnumerator`].
+///
+/// Each model is returned as an owned copy of the slice returned by [`ModelEnumerator::compute_next_model`], so that models can be consumed with the usual iterator adapters.
+/// Use [`compute_next_model`](ModelEnumerator::compute_next_model) directly to avoid the copies.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, Literal, ModelEnumerator};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let positive_first = ModelEnumerator::new(&ddnnf, false)
+///     .into_iter()
+///     .filter(|m| m[0] == Some(Literal::from(1)))
+///     .count();
+/// assert_eq!(2, positive_first);
+/// ```
+#[derive(Debug)]
+pub struct ModelIter<'a>(ModelEnumerator<'a>);
+
+impl Iterator for ModelIter<'_> {
+    type Item = Box<[Option<Literal>]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.compute_next_model().map(Box::from)
+    }
+}
+
+/// A lazy iterator over the models of a [`DecisionDNNF`] shared through an [`Arc`].
+///
+/// Contrary to a [`ModelEnumerator`], this iterator does not borrow the formula and produces owned models, in the format of [`ModelEnumerator::compute_next_model`].
+/// Since both the iterator and its items can be sent to other threads, it allows to run the enumeration and the processing of the models on different threads.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, OwnedModelIter};
+/// use std::{sync::{mpsc, Arc}, thread};
+///
+/// let ddnnf = Arc::new(D4Reader::read("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n".as_bytes()).unwrap());
+/// let (sender, receiver) = mpsc::channel();
+/// let producer = thread::spawn(move || {
+///     for model in OwnedModelIter::new(ddnnf, false) {
+///         sender.send(model).unwrap();
+///     }
+/// });
+/// assert_eq!(2, receiver.iter().count());
+/// producer.join().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct OwnedModelIter {
+    ddnnf: Arc<DecisionDNNF>,
+    state: EnumerationState,
+}
+
+impl OwnedModelIter {
+    /// Builds a new model iterator for a shared [`DecisionDNNF`].
+    ///
+    /// The second parameter sets whether free variables should be eluded from models, as for [`ModelEnumerator::new`].
+    #[must_use]
+    pub fn new(ddnnf: Arc<DecisionDNNF>, elude_free_vars: bool) -> Self {
+        let state = EnumerationState::new(&ddnnf, elude_free_vars);
+        Self { ddnnf, state }
+    }
+}
+
+impl Iterator for OwnedModelIter {
+    type Item = Box<[Option<Literal>]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.state.compute_next_model(&self.ddnnf).map(Box::from)
+    }
+}
+
+/// The state of an enumeration, which does not hold the formula it is applied on.
+#[derive(Debug)]
+struct EnumerationState {
+    or_edge_indices: Vec<usize>,
+    or_free_vars: Vec<Vec<Vec<Literal>>>,
+    root_free_vars: Vec<Literal>,
+    first_computed: bool,
+    model: VarMap<Option<Literal>>,
+    has_model: bool,
+    elude_free_vars: bool,
+    blocking_cubes: Vec<Vec<Literal>>,
+    fixed_literals: Vec<Literal>,
+    is_fixed: LitMap<bool>,
+    fixed_below: Vec<usize>,
+}
+
+impl EnumerationState {
+    fn new(ddnnf: &DecisionDNNF, elude_free_vars: bool) -> Self {
+        let n_nodes = ddnnf.nodes().as_slice().len();
+        Self {
             or_edge_indices: vec![0; n_nodes],
             or_free_vars: vec![vec![]; n_nodes],
             root_free_vars: vec![],
             first_computed: false,
-            model: vec![None; ddnnf.n_vars()],
+            model: VarMap::new(ddnnf.n_vars(), None),
             has_model: true,
             elude_free_vars,
+            blocking_cubes: vec![],
+            fixed_literals: vec![],
+            is_fixed: LitMap::new(ddnnf.n_vars(), false),
+            fixed_below: vec![0; n_nodes],
         }
     }
 
-    fn compute_free_vars(&mut self) {
-        let mut involved_vars = vec![None; self.or_free_vars.len()];
-        self.compute_free_vars_from(NodeIndex::from(0), &mut involved_vars);
-        let root_free_vars = involved_vars[0]
-            .as_ref()
-            .unwrap()
-            .iter_missing_literals()
+    fn compute_free_vars(&mut self, ddnnf: &DecisionDNNF) {
+        let free_vars = FreeVariables::compute(ddnnf);
+        let to_literal = |i: &usize| Literal::from(isize::try_from(i + 1).unwrap());
+        for (or_node, _, vars) in free_vars.iter() {
+            self.or_free_vars[usize::from(or_node)].push(vars.iter().map(to_literal).collect());
+        }
+        let root_free_vars = free_vars
+            .root_free_vars()
+            .iter()
+            .map(to_literal)
             .collect::<Vec<_>>();
         Self::update_model_with_propagations(
             &mut self.model,
@@ -126,108 +365,133 @@ impl<'a> ModelEnumerator<'a> {
         self.root_free_vars = root_free_vars;
     }
 
-    fn compute_free_vars_from(
-        &mut self,
-        from: NodeIndex,
-        involved_vars: &mut [Option<InvolvedVars>],
-    ) {
-        if involved_vars[usize::from(from)].is_some() {
-            return;
-        }
-        involved_vars[usize::from(from)] = Some(self.compute_involved_vars(from, involved_vars));
-        if let Node::Or(edges) = &self.ddnnf.nodes()[from] {
-            for edge_index in edges {
-                let edge = &self.ddnnf.edges()[*edge_index];
-                let target = edge.target();
-                let mut involved_in_child =
-                    involved_vars[usize::from(target)].as_ref().unwrap().clone();
-                for l in edge.propagated() {
-                    involved_in_child.set_literal(*l);
-                }
-                involved_in_child.xor_assign(involved_vars[usize::from(from)].as_ref().unwrap());
-                self.or_free_vars[usize::from(from)]
-                    .push(involved_in_child.iter_pos_literals().collect());
+    fn compute_next_model(&mut self, ddnnf: &DecisionDNNF) -> Option<&[Option<Literal>]> {
+        loop {
+            if !self.compute_next_candidate(ddnnf) {
+                return None;
             }
-        }
-    }
-
-    fn compute_involved_vars(
-        &mut self,
-        node: NodeIndex,
-        involved_vars: &mut [Option<InvolvedVars>],
-    ) -> InvolvedVars {
-        let mut union = InvolvedVars::new(self.ddnnf.n_vars());
-        match &self.ddnnf.nodes()[node] {
-            Node::And(edges) | Node::Or(edges) => {
-                for edge_index in edges {
-                    let edge = &self.ddnnf.edges()[*edge_index];
-                    let target = edge.target();
-                    self.compute_free_vars_from(target, involved_vars);
-                    union.or_assign(involved_vars[usize::from(target)].as_ref().unwrap());
-                    for l in edge.propagated() {
-                        union.set_literal(*l);
-                    }
-                }
+            if !self.is_model_blocked() {
+                return Some(self.model.as_slice());
             }
-            Node::True | Node::False => {}
         }
-        union
     }
 
-    /// Computes the next model and returns it.
-    /// Returns `None` if all the models have been returned.
-    pub fn compute_next_model(&mut self) -> Option<&[Option<Literal>]> {
+    /// Computes the next model, which may be covered by a blocking cube if its last literals were not fixed by the choices made at disjunction nodes.
+    fn compute_next_candidate(&mut self, ddnnf: &DecisionDNNF) -> bool {
         if !self.first_computed {
-            return self.compute_first_model();
+            return self.compute_first_model(ddnnf);
         }
         if !self.has_model {
-            return None;
+            return false;
         }
         if !Self::next_free_vars_interpretation(
             &mut self.model,
             &mut self.root_free_vars,
             self.elude_free_vars,
-        ) && !self.next_path_from(NodeIndex::from(0))
+        ) && !self.next_path_from(ddnnf, NodeIndex::from(0))
         {
             self.has_model = false;
-            None
+            false
         } else {
-            Some(&self.model)
+            true
         }
     }
 
-    fn compute_first_model(&mut self) -> Option<&[Option<Literal>]> {
-        self.first_computed = true;
-        self.compute_free_vars();
-        if self.first_path_from(NodeIndex::from(0)) {
-            self.has_model = true;
-            Some(&self.model)
-        } else {
-            self.has_model = false;
-            None
+    fn is_model_blocked(&self) -> bool {
+        self.blocking_cubes
+            .iter()
+            .any(|cube| cube.iter().all(|l| self.model[*l] == Some(*l)))
+    }
+
+    /// Returns `true` if the literals fixed by the current path, along with the given ones, cover a blocking cube.
+    fn is_path_blocked(&self, literals: &[Literal]) -> bool {
+        self.blocking_cubes.iter().any(|cube| {
+            cube.iter()
+                .all(|l| self.is_fixed[*l] || literals.contains(l))
+        })
+    }
+
+    fn push_fixed_literals(&mut self, literals: &[Literal]) {
+        for l in literals {
+            self.is_fixed[*l] = true;
+        }
+        self.fixed_literals.extend_from_slice(literals);
+    }
+
+    fn truncate_fixed_literals(&mut self, len: usize) {
+        for l in self.fixed_literals.drain(len..) {
+            self.is_fixed[l] = false;
+        }
+    }
+
+    /// Returns the choices made at the disjunction nodes, each one associated with the number of children of the node.
+    fn current_choices(&self, ddnnf: &DecisionDNNF) -> Vec<(usize, usize)> {
+        let mut choices = Vec::new();
+        if self.first_computed && self.has_model {
+            self.collect_choices_from(ddnnf, NodeIndex::from(0), &mut choices);
         }
+        choices
     }
 
-    fn next_path_from(&mut self, from: NodeIndex) -> bool {
-        match &self.ddnnf.nodes()[from] {
+    fn collect_choices_from(
+        &self,
+        ddnnf: &DecisionDNNF,
+        from: NodeIndex,
+        choices: &mut Vec<(usize, usize)>,
+    ) {
+        match &ddnnf.nodes()[from] {
             Node::And(edges) => {
-                for edge_index in edges.iter().rev() {
-                    let edge = &self.ddnnf.edges()[*edge_index];
-                    if self.next_path_from(edge.target()) {
-                        return true;
-                    }
-                    self.first_path_from(edge.target());
+                for edge_index in edges {
+                    self.collect_choices_from(ddnnf, ddnnf.edges()[*edge_index].target(), choices);
                 }
-                false
             }
+            Node::Or(edges) => {
+                let child_index = self.or_edge_indices[usize::from(from)];
+                choices.push((child_index, edges.len()));
+                self.collect_choices_from(
+                    ddnnf,
+                    ddnnf.edges()[edges[child_index]].target(),
+                    choices,
+                );
+            }
+            Node::True | Node::False => {}
+        }
+    }
+
+    fn compute_first_model(&mut self, ddnnf: &DecisionDNNF) -> bool {
+        self.first_computed = true;
+        self.compute_free_vars(ddnnf);
+        self.has_model = self.first_path_from(ddnnf, NodeIndex::from(0));
+        self.has_model
+    }
+
+    /// Selects the next path of the subformula rooted at the given node.
+    ///
+    /// The literals fixed by the edges of the current path must be the ones of the path leading to the node, followed by the ones of the subformula.
+    /// They are updated according to the new path.
+    fn next_path_from(&mut self, ddnnf: &DecisionDNNF, from: NodeIndex) -> bool {
+        let entry_len = self.fixed_literals.len() - self.fixed_below[usize::from(from)];
+        let result = self.select_next_path(ddnnf, from, entry_len);
+        self.fixed_below[usize::from(from)] = self.fixed_literals.len() - entry_len;
+        result
+    }
+
+    fn select_next_path(
+        &mut self,
+        ddnnf: &DecisionDNNF,
+        from: NodeIndex,
+        entry_len: usize,
+    ) -> bool {
+        match &ddnnf.nodes()[from] {
+            Node::And(edges) => self.next_paths_from(ddnnf, edges, edges.len()),
             Node::Or(edges) => {
                 let mut child_index = self.or_edge_indices[usize::from(from)];
                 if self.next_or_node_free_vars_interpretation(from, child_index) {
                     return true;
                 }
-                let edge = &self.ddnnf.edges()[edges[child_index]];
+                let edge = &ddnnf.edges()[edges[child_index]];
                 Self::update_model_with_propagations(&mut self.model, edge.propagated(), false);
-                if self.next_path_from(edge.target()) {
+                if self.next_path_from(ddnnf, edge.target()) {
                     return true;
                 }
                 loop {
@@ -235,8 +499,8 @@ impl<'a> ModelEnumerator<'a> {
                         return false;
                     }
                     child_index += 1;
-                    self.or_edge_indices[usize::from(from)] += child_index;
-                    if self.update_or_edge(from, edges[child_index]) {
+                    self.or_edge_indices[usize::from(from)] = child_index;
+                    if self.update_or_edge(ddnnf, from, edges[child_index], entry_len) {
                         break;
                     }
                 }
@@ -259,7 +523,7 @@ impl<'a> ModelEnumerator<'a> {
     }
 
     fn next_free_vars_interpretation(
-        model: &mut [Option<Literal>],
+        model: &mut VarMap<Option<Literal>>,
         interpretation: &mut [Literal],
         elude_free_vars: bool,
     ) -> bool {
@@ -280,22 +544,65 @@ impl<'a> ModelEnumerator<'a> {
         has_next
     }
 
-    fn first_path_from(&mut self, from: NodeIndex) -> bool {
-        self.or_edge_indices[usize::from(from)] = 0;
-        match &self.ddnnf.nodes()[from] {
-            Node::And(edges) => {
-                for edge_index in edges {
-                    let edge = &self.ddnnf.edges()[*edge_index];
-                    Self::update_model_with_propagations(&mut self.model, edge.propagated(), false);
-                    if !self.first_path_from(edge.target()) {
-                        return false;
-                    }
+    /// Selects the first path of the subformula rooted at the given node, and fixes the literals of its edges.
+    fn first_path_from(&mut self, ddnnf: &DecisionDNNF, from: NodeIndex) -> bool {
+        let entry_len = self.fixed_literals.len();
+        let result = self.select_first_path(ddnnf, from, entry_len);
+        self.fixed_below[usize::from(from)] = self.fixed_literals.len() - entry_len;
+        result
+    }
+
+    /// Selects the first paths of the subformulas targeted by the children of a conjunction node.
+    ///
+    /// If a blocking cube prevents a child from having a path, the paths of the previous children are changed.
+    fn first_paths_from(&mut self, ddnnf: &DecisionDNNF, edges: &[EdgeIndex]) -> bool {
+        for (i, edge_index) in edges.iter().enumerate() {
+            let edge = &ddnnf.edges()[*edge_index];
+            let child_start = self.fixed_literals.len();
+            Self::update_model_with_propagations(&mut self.model, edge.propagated(), false);
+            self.push_fixed_literals(edge.propagated());
+            if !self.first_path_from(ddnnf, edge.target()) {
+                self.truncate_fixed_literals(child_start);
+                return self.next_paths_from(ddnnf, edges, i);
+            }
+        }
+        true
+    }
+
+    /// Selects the next paths of the subformulas targeted by the `n_set` first children of a conjunction node, and the first paths of the other ones.
+    ///
+    /// The literals fixed by the edges of the current path must end with the ones of the `n_set` first children.
+    fn next_paths_from(&mut self, ddnnf: &DecisionDNNF, edges: &[EdgeIndex], n_set: usize) -> bool {
+        let mut child_end = self.fixed_literals.len();
+        for (i, edge_index) in edges[..n_set].iter().enumerate().rev() {
+            let edge = &ddnnf.edges()[*edge_index];
+            let child_start = child_end - self.fixed_below[usize::from(edge.target())];
+            self.truncate_fixed_literals(child_end);
+            while self.next_path_from(ddnnf, edge.target()) {
+                if self.first_paths_from(ddnnf, &edges[i + 1..]) {
+                    return true;
                 }
-                true
+                self.truncate_fixed_literals(
+                    child_start + self.fixed_below[usize::from(edge.target())],
+                );
             }
+            child_end = child_start - edge.propagated().len();
+        }
+        false
+    }
+
+    fn select_first_path(
+        &mut self,
+        ddnnf: &DecisionDNNF,
+        from: NodeIndex,
+        entry_len: usize,
+    ) -> bool {
+        self.or_edge_indices[usize::from(from)] = 0;
+        match &ddnnf.nodes()[from] {
+            Node::And(edges) => self.first_paths_from(ddnnf, edges),
             Node::Or(edges) => {
                 for edge_index in edges {
-                    if self.update_or_edge(from, *edge_index) {
+                    if self.update_or_edge(ddnnf, from, *edge_index, entry_len) {
                         return true;
                     }
                     self.or_edge_indices[usize::from(from)] += 1;
@@ -307,22 +614,33 @@ impl<'a> ModelEnumerator<'a> {
         }
     }
 
-    fn update_or_edge(&mut self, or_node_index: NodeIndex, edge_index: EdgeIndex) -> bool {
-        let edge = &self.ddnnf.edges()[edge_index];
+    fn update_or_edge(
+        &mut self,
+        ddnnf: &DecisionDNNF,
+        or_node_index: NodeIndex,
+        edge_index: EdgeIndex,
+        entry_len: usize,
+    ) -> bool {
+        let edge = &ddnnf.edges()[edge_index];
+        self.truncate_fixed_literals(entry_len);
+        if self.is_path_blocked(edge.propagated()) {
+            return false;
+        }
         let or_free_vars = &self.or_free_vars[usize::from(or_node_index)]
             [self.or_edge_indices[usize::from(or_node_index)]];
         Self::update_model_with_propagations(&mut self.model, or_free_vars, self.elude_free_vars);
         Self::update_model_with_propagations(&mut self.model, edge.propagated(), false);
-        self.first_path_from(edge.target())
+        self.push_fixed_literals(edge.propagated());
+        self.first_path_from(ddnnf, edge.target())
     }
 
     fn update_model_with_propagations(
-        model: &mut [Option<Literal>],
+        model: &mut VarMap<Option<Literal>>,
         propagations: &[Literal],
         update_with_none: bool,
     ) {
         for p in propagations {
-            model[p.var_index()] = if update_with_none { None } else { Some(*p) };
+            model[*p] = if update_with_none { None } else { Some(*p) };
         }
     }
 }
@@ -356,8 +674,27 @@ mod tests {
                     .collect::<Vec<_>>(),
             );
         }
+        let mut iter_actual = ModelEnumerator::new(&ddnnf, hide_free_vars)
+            .into_iter()
+            .map(|m| {
+                m.iter()
+                    .filter_map(|opt_l| opt_l.map(isize::from))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut owned_actual = OwnedModelIter::new(Arc::new(ddnnf), hide_free_vars)
+            .map(|m| {
+                m.iter()
+                    .filter_map(|opt_l| opt_l.map(isize::from))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
         sort(&mut actual);
+        sort(&mut iter_actual);
+        sort(&mut owned_actual);
         assert_eq!(expected, actual,);
+        assert_eq!(expected, iter_actual);
+        assert_eq!(expected, owned_actual);
     }
 
     #[test]
@@ -448,6 +785,155 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_or_more_than_two_children() {
+        assert_models_eq(
+            "o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 -1 2 0\n1 2 1 -2 0\n1 2 1 2 0\n",
+            vec![vec![-1, -2], vec![-1, 2], vec![1, -2], vec![1, 2]],
+            None,
+            false,
+        );
+    }
+
+    #[test]
+    fn test_current_choices() {
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let mut model_enum = ModelEnumerator::new(&ddnnf, false);
+        assert!(model_enum.current_choices().is_empty());
+        let mut actual = Vec::new();
+        while model_enum.compute_next_model().is_some() {
+            actual.push(model_enum.current_choices());
+        }
+        assert_eq!(vec![vec![0, 0], vec![0, 1], vec![1, 0], vec![1, 1]], actual);
+        assert!(model_enum.current_choices().is_empty());
+    }
+
+    #[test]
+    fn test_encoded_choices() {
+        // -1 or (1 and (-2 or (2 and -3) or (2 and 3)))
+        let ddnnf = D4Reader::read(
+            "o 1 0\nt 2 0\no 3 0\n1 2 -1 0\n1 3 1 0\n3 2 -2 0\n3 2 2 -3 0\n3 2 2 3 0\n".as_bytes(),
+        )
+        .unwrap();
+        let mut model_enum = ModelEnumerator::new(&ddnnf, true);
+        let mut codes = Vec::new();
+        while model_enum.compute_next_model().is_some() {
+            let code = model_enum.current_encoded_choices();
+            assert_eq!(
+                Some(model_enum.current_choices()),
+                ModelEnumerator::decode_choices(&ddnnf, &code)
+            );
+            codes.push(code);
+        }
+        assert_eq!(vec![0, 1, 3, 5], codes);
+        assert_eq!(0, model_enum.current_encoded_choices());
+        assert!(ModelEnumerator::decode_choices(&ddnnf, &Integer::from(7)).is_none());
+        assert!(ModelEnumerator::decode_choices(&ddnnf, &Integer::from(-1)).is_none());
+    }
+
+    fn assert_unblocked_models_eq(
+        str_ddnnf: &str,
+        cubes: &[Vec<isize>],
+        mut expected: Vec<Vec<isize>>,
+    ) {
+        let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        let mut model_enum = ModelEnumerator::new(&ddnnf, false);
+        model_enum.set_blocking_cubes(
+            cubes
+                .iter()
+                .map(|c| c.iter().map(|l| Literal::from(*l)).collect())
+                .collect(),
+        );
+        let mut actual = Vec::new();
+        while let Some(m) = model_enum.compute_next_model() {
+            actual.push(
+                m.iter()
+                    .map(|l| isize::from(l.unwrap()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        expected.sort_unstable();
+        actual.sort_unstable();
+        assert_eq!(expected, actual);
+    }
+
+    // (-1 and (-2 or 2)) or (1 and -2 and (3 or -3))
+    const BLOCKING_DDNNF: &str =
+        "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 -2 0\n2 4 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n";
+
+    #[test]
+    fn test_blocking_no_cubes() {
+        assert_unblocked_models_eq(
+            BLOCKING_DDNNF,
+            &[],
+            vec![
+                vec![-1, -2, -3],
+                vec![-1, -2, 3],
+                vec![-1, 2, -3],
+                vec![-1, 2, 3],
+                vec![1, -2, -3],
+                vec![1, -2, 3],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_blocking_or_subtree() {
+        assert_unblocked_models_eq(
+            BLOCKING_DDNNF,
+            &[vec![-1]],
+            vec![vec![1, -2, -3], vec![1, -2, 3]],
+        );
+    }
+
+    #[test]
+    fn test_blocking_on_complete_models() {
+        assert_unblocked_models_eq(
+            BLOCKING_DDNNF,
+            &[vec![-1, 3]],
+            vec![
+                vec![-1, -2, -3],
+                vec![-1, 2, -3],
+                vec![1, -2, -3],
+                vec![1, -2, 3],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_blocking_all_models() {
+        assert_unblocked_models_eq(BLOCKING_DDNNF, &[vec![-2, 3], vec![-3], vec![2]], vec![]);
+    }
+
+    #[test]
+    fn test_blocking_overlapping_cubes() {
+        assert_unblocked_models_eq(
+            BLOCKING_DDNNF,
+            &[vec![1], vec![1, -2], vec![-1, 2], vec![2, 3]],
+            vec![vec![-1, -2, -3], vec![-1, -2, 3]],
+        );
+    }
+
+    #[test]
+    fn test_blocking_under_and() {
+        let str_ddnnf =
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+        assert_unblocked_models_eq(
+            str_ddnnf,
+            &[vec![1, -2], vec![-1, 2]],
+            vec![vec![-1, -2], vec![1, 2]],
+        );
+        assert_unblocked_models_eq(
+            str_ddnnf,
+            &[vec![-1, -2], vec![-1, 2]],
+            vec![vec![1, -2], vec![1, 2]],
+        );
+    }
+
     #[test]
     fn test_hide_free_var_tautology() {
         assert_models_eq("t 1 0", vec![vec![]], Some(2), true);
@@ -1,7 +1,8 @@
 use crate::{
-    core::{EdgeIndex, InvolvedVars, Node, NodeIndex},
+    core::{check_literals, EdgeIndex, InvolvedVars, LitMap, Node, NodeIndex},
     DecisionDNNF, Literal,
 };
+use anyhow::Result;
 
 /// A structure used to find models in a [`DecisionDNNF`].
 ///
@@ -57,66 +58,78 @@ impl<'a> ModelFinder<'a> {
     /// In case the variable index of a literal is higher than the highest variable index in the formula, this function panics.
     #[must_use]
     pub fn find_model_under_assumptions(&self, assumptions: &[Literal]) -> Option<Vec<Literal>> {
-        if let Some(l) = assumptions
-            .iter()
-            .find(|l| l.var_index() >= self.ddnnf.n_vars())
-        {
-            panic!(
-                "no such literal: {l} (the formula has {} variables)",
-                self.ddnnf.n_vars()
-            );
-        }
-        let mut pos_assumptions = InvolvedVars::new(self.ddnnf.n_vars());
-        let mut neg_assumptions = InvolvedVars::new(self.ddnnf.n_vars());
-        for assumption in assumptions {
-            if is_compatible_with_assumptions(*assumption, &pos_assumptions, &neg_assumptions) {
-                if assumption.polarity() {
-                    pos_assumptions.set_literal(*assumption);
-                } else {
-                    neg_assumptions.set_literal(*assumption);
-                }
-            }
-        }
+        self.try_find_model_under_assumptions(assumptions)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Search for a model compatible with the provided assumptions, as [`find_model_under_assumptions`](Self::find_model_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    pub fn try_find_model_under_assumptions(
+        &self,
+        assumptions: &[Literal],
+    ) -> Result<Option<Vec<Literal>>> {
+        check_literals(assumptions, self.ddnnf.n_vars())?;
+        let assumed = self.assumption_map(assumptions);
         let mut model = Vec::with_capacity(self.ddnnf.n_vars());
-        if self.find_model_under_assumptions_from_node(
-            NodeIndex::from(0),
-            &mut model,
-            &pos_assumptions,
-            &neg_assumptions,
-        ) {
+        if self.find_model_under_assumptions_from_node(NodeIndex::from(0), &mut model, &assumed) {
             if model.len() < self.ddnnf.n_vars() {
                 let mut involved = InvolvedVars::new(self.ddnnf.n_vars());
                 involved.set_literals(&model);
                 for missing in involved.iter_missing_literals() {
-                    if is_compatible_with_assumptions(missing, &pos_assumptions, &neg_assumptions) {
+                    if is_compatible_with_assumptions(missing, &assumed) {
                         model.push(missing);
                     } else {
                         model.push(missing.flip());
                     }
                 }
             }
+            Ok(Some(model))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Search for a model of the subformula rooted at the given node, compatible with the provided assumptions.
+    ///
+    /// The returned model only contains the literals of the variables involved in the subformula.
+    pub(crate) fn find_partial_model_from_node(
+        &self,
+        from: NodeIndex,
+        assumptions: &[Literal],
+    ) -> Option<Vec<Literal>> {
+        let assumed = self.assumption_map(assumptions);
+        let mut model = Vec::new();
+        if self.find_model_under_assumptions_from_node(from, &mut model, &assumed) {
             Some(model)
         } else {
             None
         }
     }
 
+    fn assumption_map(&self, assumptions: &[Literal]) -> LitMap<bool> {
+        check_literals(assumptions, self.ddnnf.n_vars()).unwrap_or_else(|e| panic!("{e}"));
+        let mut assumed = LitMap::new(self.ddnnf.n_vars(), false);
+        for assumption in assumptions {
+            if is_compatible_with_assumptions(*assumption, &assumed) {
+                assumed[*assumption] = true;
+            }
+        }
+        assumed
+    }
+
     fn find_model_under_assumptions_from_node(
         &self,
         from: NodeIndex,
         model: &mut Vec<Literal>,
-        pos_assumptions: &InvolvedVars,
-        neg_assumptions: &InvolvedVars,
+        assumed: &LitMap<bool>,
     ) -> bool {
         match &self.ddnnf.nodes()[from] {
             Node::And(edge_indices) => {
                 for edge_index in edge_indices {
-                    if !self.find_model_under_assumptions_from_edge(
-                        *edge_index,
-                        model,
-                        pos_assumptions,
-                        neg_assumptions,
-                    ) {
+                    if !self.find_model_under_assumptions_from_edge(*edge_index, model, assumed) {
                         return false;
                     }
                 }
@@ -124,12 +137,7 @@ impl<'a> ModelFinder<'a> {
             }
             Node::Or(edge_indices) => {
                 for edge_index in edge_indices {
-                    if self.find_model_under_assumptions_from_edge(
-                        *edge_index,
-                        model,
-                        pos_assumptions,
-                        neg_assumptions,
-                    ) {
+                    if self.find_model_under_assumptions_from_edge(*edge_index, model, assumed) {
                         return true;
                     }
                 }
@@ -144,25 +152,19 @@ impl<'a> ModelFinder<'a> {
         &self,
         from: EdgeIndex,
         model: &mut Vec<Literal>,
-        pos_assumptions: &InvolvedVars,
-        neg_assumptions: &InvolvedVars,
+        assumed: &LitMap<bool>,
     ) -> bool {
         let old_model_len = model.len();
         let edge = &self.ddnnf.edges()[from];
         if edge
             .propagated()
             .iter()
-            .any(|p| !is_compatible_with_assumptions(*p, pos_assumptions, neg_assumptions))
+            .any(|p| !is_compatible_with_assumptions(*p, assumed))
         {
             return false;
         }
         model.append(&mut edge.propagated().to_vec());
-        if self.find_model_under_assumptions_from_node(
-            edge.target(),
-            model,
-            pos_assumptions,
-            neg_assumptions,
-        ) {
+        if self.find_model_under_assumptions_from_node(edge.target(), model, assumed) {
             true
         } else {
             model.truncate(old_model_len);
@@ -171,16 +173,8 @@ impl<'a> ModelFinder<'a> {
     }
 }
 
-fn is_compatible_with_assumptions(
-    l: Literal,
-    pos_assumptions: &InvolvedVars,
-    neg_assumptions: &InvolvedVars,
-) -> bool {
-    if l.polarity() {
-        !neg_assumptions.is_set(l)
-    } else {
-        !pos_assumptions.is_set(l)
-    }
+fn is_compatible_with_assumptions(l: Literal, assumed: &LitMap<bool>) -> bool {
+    !assumed[l.flip()]
 }
 
 #[cfg(test)]
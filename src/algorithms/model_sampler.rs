@@ -0,0 +1,300 @@
+use crate::{
+    core::{InvolvedVars, Node, NodeIndex},
+    DecisionDNNF, Literal, ModelCounter,
+};
+use rug::Integer;
+use rustc_hash::FxHashMap;
+
+/// A structure used to draw models of a [`DecisionDNNF`] uniformly at random.
+///
+/// The models are numbered from 0 to the model count (excluded), following the order given by the structure of the formula;
+/// [`model_at`](Self::model_at) builds the model of a given rank in a time linear in the size of the formula, once the counts of the nodes are computed.
+/// Sampling a model then boils down to drawing a rank uniformly at random.
+/// Samples with replacement draw the ranks independently, while samples without replacement draw distinct ranks with a partial Fisher–Yates shuffle,
+/// which swaps are stored in a hash map so that the memory usage only depends on the number of samples.
+///
+/// The random generator is seeded by [`set_seed`](Self::set_seed), so that the samples are reproducible.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, ModelSampler};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let mut sampler = ModelSampler::new(&ddnnf);
+/// sampler.set_seed(42);
+/// let mut models = sampler.sample_k_distinct(5);
+/// models.sort_by_key(|m| m.iter().map(|l| isize::from(*l)).collect::<Vec<_>>());
+/// assert_eq!(3, models.len());
+/// ```
+pub struct ModelSampler<'a> {
+    counter: ModelCounter<'a>,
+    rng: Xorshift,
+}
+
+impl<'a> ModelSampler<'a> {
+    /// Builds a new sampler given a [`DecisionDNNF`].
+    ///
+    /// The random generator is seeded with 0.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        Self {
+            counter: ModelCounter::new(ddnnf),
+            rng: Xorshift::new(0),
+        }
+    }
+
+    /// Sets the seed of the random generator.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Xorshift::new(seed);
+    }
+
+    /// Returns the model of the given rank, or [`None`] if the rank is not lower than the model count.
+    ///
+    /// The model is given as a full assignment, sorted by variable index.
+    #[must_use]
+    pub fn model_at(&self, rank: &Integer) -> Option<Vec<Literal>> {
+        if *rank < 0 || rank >= self.counter.count() {
+            return None;
+        }
+        let ddnnf = self.counter.ddnnf();
+        let mut model = Vec::with_capacity(ddnnf.n_vars());
+        let root = NodeIndex::from(0);
+        let rank = assign_free_vars(
+            rank.clone(),
+            self.counter.node_count(root).1.iter_missing_literals(),
+            &mut model,
+        );
+        self.build_model(root, rank, &mut model);
+        model.sort_unstable_by_key(Literal::var_index);
+        Some(model)
+    }
+
+    fn build_model(&self, node: NodeIndex, mut rank: Integer, model: &mut Vec<Literal>) {
+        let ddnnf = self.counter.ddnnf();
+        match ddnnf.node(node) {
+            Node::And(edges) => {
+                for edge_index in edges {
+                    let edge = ddnnf.edge(*edge_index);
+                    let (quotient, child_rank) =
+                        rank.div_rem(self.counter.node_count(edge.target()).0.clone());
+                    rank = quotient;
+                    model.extend_from_slice(edge.propagated());
+                    self.build_model(edge.target(), child_rank, model);
+                }
+            }
+            Node::Or(edges) => {
+                let involved_vars = &self.counter.node_count(node).1;
+                for edge_index in edges {
+                    let edge = ddnnf.edge(*edge_index);
+                    let (n, target_vars) = self.counter.node_count(edge.target());
+                    let mut free_vars: InvolvedVars = target_vars.clone();
+                    free_vars.set_literals(edge.propagated());
+                    free_vars.xor_assign(involved_vars);
+                    let n_child_models = Integer::from(n << free_vars.count_ones());
+                    if rank < n_child_models {
+                        let rank = assign_free_vars(rank, free_vars.iter_pos_literals(), model);
+                        model.extend_from_slice(edge.propagated());
+                        self.build_model(edge.target(), rank, model);
+                        return;
+                    }
+                    rank -= n_child_models;
+                }
+                unreachable!()
+            }
+            Node::True | Node::False => {}
+        }
+    }
+
+    /// Draws a model uniformly at random, or returns [`None`] if the formula has no model.
+    ///
+    /// The model is given as a full assignment, sorted by variable index.
+    pub fn sample_one(&mut self) -> Option<Vec<Literal>> {
+        let n_models = self.counter.count().clone();
+        if n_models == 0 {
+            return None;
+        }
+        let rank = self.rng.next_below(&n_models);
+        self.model_at(&rank)
+    }
+
+    /// Draws `k` models uniformly at random, with replacement.
+    ///
+    /// The returned vector is empty if the formula has no model.
+    pub fn sample_k(&mut self, k: usize) -> Vec<Vec<Literal>> {
+        (0..k).map_while(|_| self.sample_one()).collect()
+    }
+
+    /// Draws `k` distinct models uniformly at random, i.e. without replacement.
+    ///
+    /// If the formula has less than `k` models, all of them are returned, in a random order.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn sample_k_distinct(&mut self, k: usize) -> Vec<Vec<Literal>> {
+        let n_models = self.counter.count().clone();
+        let n_samples = n_models.to_usize().map_or(k, |n| n.min(k));
+        let mut swapped: FxHashMap<Integer, Integer> = FxHashMap::default();
+        let mut models = Vec::with_capacity(n_samples);
+        for i in 0..n_samples {
+            let i = Integer::from(i);
+            let j = &i + self.rng.next_below(&Integer::from(&n_models - &i));
+            let at_i = swapped.get(&i).cloned().unwrap_or_else(|| i.clone());
+            let at_j = swapped.insert(j.clone(), at_i).unwrap_or(j);
+            models.push(self.model_at(&at_j).unwrap());
+        }
+        models
+    }
+}
+
+/// Assigns the given variables according to the lowest bits of a rank, and returns the remaining part of the rank.
+fn assign_free_vars<I>(rank: Integer, positive_literals: I, model: &mut Vec<Literal>) -> Integer
+where
+    I: Iterator<Item = Literal>,
+{
+    let mut n_free_vars = 0;
+    for l in positive_literals {
+        model.push(if rank.get_bit(n_free_vars) {
+            l
+        } else {
+            l.flip()
+        });
+        n_free_vars += 1;
+    }
+    rank >> n_free_vars
+}
+
+/// A xorshift64* pseudo-random generator.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn new(seed: u64) -> Self {
+        // the state must not be 0
+        Self(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns an integer drawn uniformly at random between 0 (included) and a positive bound (excluded).
+    fn next_below(&mut self, bound: &Integer) -> Integer {
+        let n_bits = bound.significant_bits();
+        loop {
+            let mut n = Integer::from(0);
+            let mut n_drawn_bits = 0;
+            while n_drawn_bits < n_bits {
+                n <<= 64;
+                n += self.next_u64();
+                n_drawn_bits += 64;
+            }
+            n >>= n_drawn_bits - n_bits;
+            if n < *bound {
+                return n;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{D4Reader, ModelEnumerator};
+
+    // (-1 and (-2 or 2)) or (1 and 2 and (-3 or 3)), with a fourth free variable
+    fn instance() -> DecisionDNNF {
+        let mut ddnnf = D4Reader::read(
+            "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 2 0\n2 4 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        ddnnf.update_n_vars(4);
+        ddnnf
+    }
+
+    fn sorted(mut models: Vec<Vec<Literal>>) -> Vec<Vec<isize>> {
+        let mut result = models
+            .drain(..)
+            .map(|m| m.into_iter().map(isize::from).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result
+    }
+
+    fn all_models(ddnnf: &DecisionDNNF) -> Vec<Vec<isize>> {
+        sorted(
+            ModelEnumerator::new(ddnnf, false)
+                .into_iter()
+                .map(|m| m.iter().map(|l| l.unwrap()).collect())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_model_at() {
+        let ddnnf = instance();
+        let sampler = ModelSampler::new(&ddnnf);
+        let models = (0..12)
+            .map(|i| sampler.model_at(&Integer::from(i)).unwrap())
+            .collect();
+        assert_eq!(all_models(&ddnnf), sorted(models));
+        assert!(sampler.model_at(&Integer::from(12)).is_none());
+        assert!(sampler.model_at(&Integer::from(-1)).is_none());
+    }
+
+    #[test]
+    fn test_sample_k_distinct() {
+        let ddnnf = instance();
+        let mut sampler = ModelSampler::new(&ddnnf);
+        assert_eq!(all_models(&ddnnf), sorted(sampler.sample_k_distinct(100)));
+        let models = sorted(sampler.sample_k_distinct(5));
+        assert_eq!(5, models.len());
+        assert!(models.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn test_sample_k() {
+        let ddnnf = instance();
+        let mut sampler = ModelSampler::new(&ddnnf);
+        sampler.set_seed(7);
+        let models = sampler.sample_k(1200);
+        assert_eq!(1200, models.len());
+        let all = all_models(&ddnnf);
+        let sorted_models = sorted(models);
+        for m in &all {
+            let n = sorted_models.iter().filter(|s| *s == m).count();
+            assert!((50..150).contains(&n), "unexpected frequency {n} for {m:?}");
+        }
+    }
+
+    #[test]
+    fn test_seed() {
+        let ddnnf = instance();
+        let mut sampler = ModelSampler::new(&ddnnf);
+        sampler.set_seed(3);
+        let first = sampler.sample_k(10);
+        sampler.set_seed(3);
+        assert_eq!(first, sampler.sample_k(10));
+    }
+
+    #[test]
+    fn test_no_models() {
+        let ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        let mut sampler = ModelSampler::new(&ddnnf);
+        assert!(sampler.sample_one().is_none());
+        assert!(sampler.sample_k(3).is_empty());
+        assert!(sampler.sample_k_distinct(3).is_empty());
+    }
+
+    #[test]
+    fn test_next_below() {
+        let mut rng = Xorshift::new(0);
+        let bound = (Integer::from(1) << 100) + 3;
+        for _ in 0..100 {
+            assert!(rng.next_below(&bound) < bound);
+        }
+        assert_eq!(0, rng.next_below(&Integer::from(1)));
+    }
+}
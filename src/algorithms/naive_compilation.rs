@@ -0,0 +1,159 @@
+use crate::{
+    core::{simplification, Edge, EdgeIndex, Node, NodeIndex},
+    Cnf, DecisionDNNF, Literal,
+};
+use rustc_hash::FxHashMap;
+
+impl Cnf {
+    /// Compiles this CNF formula into an equivalent [`DecisionDNNF`] by a naive Shannon expansion.
+    ///
+    /// The variables are decided in increasing index order, and the subformulas obtained for the same set of remaining clauses are shared.
+    /// The variables that do not appear in the remaining clauses are not decided, so they are free in the resulting formula.
+    ///
+    /// The size of the result may be exponential in the number of variables, even for formulas that d4 compiles easily;
+    /// this compilation is only intended to build tiny formulas for tests and benchmarks without resorting to an external compiler.
+    /// It is available with the `naive-compiler` feature.
+    #[must_use]
+    pub fn compile_naively(&self) -> DecisionDNNF {
+        let mut compiler = NaiveCompiler::default();
+        let clauses = normalize(
+            self.clauses()
+                .iter()
+                .map(|c| c.iter().map(|l| isize::from(*l)).collect())
+                .collect(),
+        );
+        compiler.compile(clauses);
+        let (nodes, edges) = simplification::simplify(&compiler.nodes, &compiler.edges, true, true);
+        DecisionDNNF::from_raw_data(self.n_vars(), nodes, edges)
+    }
+}
+
+#[derive(Default)]
+struct NaiveCompiler {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    true_node: Option<NodeIndex>,
+    false_node: Option<NodeIndex>,
+    cache: FxHashMap<Vec<Vec<isize>>, NodeIndex>,
+}
+
+impl NaiveCompiler {
+    /// Compiles normalized clauses, and returns the index of the node of the subformula.
+    fn compile(&mut self, clauses: Vec<Vec<isize>>) -> NodeIndex {
+        if clauses.is_empty() {
+            return self.leaf(Node::True);
+        }
+        if clauses[0].is_empty() {
+            return self.leaf(Node::False);
+        }
+        if let Some(index) = self.cache.get(&clauses) {
+            return *index;
+        }
+        let var = clauses.iter().flatten().map(|l| l.abs()).min().unwrap();
+        let index = NodeIndex::from(self.nodes.len());
+        self.nodes.push(Node::Or(vec![]));
+        for literal in [-var, var] {
+            let target = self.compile(condition(&clauses, literal));
+            self.edges
+                .push(Edge::from_raw_data(target, vec![Literal::from(literal)]));
+            self.nodes[usize::from(index)]
+                .add_edge(EdgeIndex::from(self.edges.len() - 1))
+                .unwrap();
+        }
+        self.cache.insert(clauses, index);
+        index
+    }
+
+    fn leaf(&mut self, node: Node) -> NodeIndex {
+        let slot = if matches!(node, Node::True) {
+            &mut self.true_node
+        } else {
+            &mut self.false_node
+        };
+        *slot.get_or_insert_with(|| {
+            self.nodes.push(node);
+            NodeIndex::from(self.nodes.len() - 1)
+        })
+    }
+}
+
+/// Returns the clauses obtained by setting a literal to true, normalized.
+fn condition(clauses: &[Vec<isize>], literal: isize) -> Vec<Vec<isize>> {
+    normalize(
+        clauses
+            .iter()
+            .filter(|c| !c.contains(&literal))
+            .map(|c| c.iter().copied().filter(|l| *l != -literal).collect())
+            .collect(),
+    )
+}
+
+/// Sorts the literals of the clauses and the clauses themselves, removing the duplicates and the tautologies.
+///
+/// If the empty clause is present, it is the only one returned.
+fn normalize(clauses: Vec<Vec<isize>>) -> Vec<Vec<isize>> {
+    let mut normalized = Vec::with_capacity(clauses.len());
+    for mut clause in clauses {
+        clause.sort_unstable_by_key(|l| (l.unsigned_abs(), *l));
+        clause.dedup();
+        if clause.is_empty() {
+            return vec![vec![]];
+        }
+        if clause.windows(2).all(|w| w[0] != -w[1]) {
+            normalized.push(clause);
+        }
+    }
+    normalized.sort_unstable();
+    normalized.dedup();
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CnfValidator, ModelCounter};
+
+    fn compile(cnf: &str) -> DecisionDNNF {
+        Cnf::from_reader(cnf.as_bytes()).unwrap().compile_naively()
+    }
+
+    fn count(cnf: &str) -> usize {
+        ModelCounter::new(&compile(cnf)).count().to_usize_wrapping()
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(3, count("p cnf 2 1\n1 -2 0\n"));
+        assert_eq!(6, count("p cnf 3 1\n1 -2 0\n"));
+        assert_eq!(5, count("p cnf 3 2\n1 2 3 0\n-1 -2 0\n"));
+        assert_eq!(8, count("p cnf 3 0\n"));
+        assert_eq!(0, count("p cnf 2 2\n1 0\n-1 0\n"));
+        assert_eq!(0, count("p cnf 2 1\n0\n"));
+        assert_eq!(4, count("p cnf 2 1\n1 -1 0\n"));
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let content = "p cnf 4 4\n1 2 0\n-2 3 0\n-3 -4 1 0\n2 4 0\n";
+        let cnf = Cnf::from_reader(content.as_bytes()).unwrap();
+        let ddnnf = cnf.compile_naively();
+        let validator = CnfValidator::new(&ddnnf, &cnf).unwrap();
+        assert!(validator.non_entailed_clauses().is_empty());
+        let n_models = (0..16)
+            .filter(|bits: &usize| {
+                cnf.clauses().iter().all(|c| {
+                    c.iter()
+                        .any(|l| (bits >> l.var_index()) & 1 == usize::from(l.polarity()))
+                })
+            })
+            .count();
+        assert!(validator.count_matches(&n_models.into()));
+    }
+
+    #[test]
+    fn test_sharing() {
+        // the two branches on the first variable lead to the same clauses
+        let ddnnf = compile("p cnf 3 2\n1 3 0\n-1 3 0\n");
+        assert_eq!(3, ddnnf.n_nodes());
+    }
+}
@@ -0,0 +1,335 @@
+use crate::{
+    core::{assumed_polarities, check_literals, NodeIndex},
+    DecisionDNNF, Literal, ModelCounter,
+};
+use anyhow::Result;
+use rug::Integer;
+
+/// A view on the negation of a [`DecisionDNNF`], answering queries on the full assignments that are not models of the formula.
+///
+/// The negation is not compiled: each query is answered using model counts on the formula itself,
+/// since the number of non-models consistent with some assumptions is the number of full assignments consistent with them minus the number of models consistent with them.
+/// Counting the non-models thus takes a time linear in the size of the Decision-DNNF,
+/// and finding a non-model takes a time linear in the size of the Decision-DNNF times its number of variables.
+///
+/// A clause is entailed by the negation if every non-model satisfies it, and a term is an implicant of the negation if it has no model in common with the formula.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, Literal, NegatedView};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let negated = NegatedView::new(&ddnnf);
+/// assert_eq!(1, negated.count());
+/// assert!(negated.entails_clause(&[Literal::from(-1)]));
+/// assert_eq!(
+///     Some(vec![Literal::from(-1), Literal::from(2)]),
+///     negated.find_non_model_under_assumptions(&[])
+/// );
+/// ```
+pub struct NegatedView<'a> {
+    model_counter: ModelCounter<'a>,
+    n_vars: usize,
+}
+
+impl<'a> NegatedView<'a> {
+    /// Builds a view on the negation of a [`DecisionDNNF`].
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        Self {
+            model_counter: ModelCounter::new(ddnnf),
+            n_vars: ddnnf.n_vars(),
+        }
+    }
+
+    /// Returns the number of full assignments that are not models of the formula.
+    #[must_use]
+    pub fn count(&self) -> Integer {
+        (Integer::from(1) << self.n_vars) - self.model_counter.count()
+    }
+
+    /// Returns the number of full assignments that are not models of the formula and that are consistent with some assumptions.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an assumption refers to a variable that does not exist.
+    #[must_use]
+    pub fn count_under_assumptions(&self, assumptions: &[Literal]) -> Integer {
+        self.try_count_under_assumptions(assumptions)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the number of full assignments that are not models of the formula and that are consistent with some assumptions,
+    /// as [`count_under_assumptions`](Self::count_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    pub fn try_count_under_assumptions(&self, assumptions: &[Literal]) -> Result<Integer> {
+        let Some(assumed) = assumed_polarities(assumptions, self.n_vars)? else {
+            return Ok(Integer::from(0));
+        };
+        let n_models = self
+            .model_counter
+            .try_count_from_under_assumptions(NodeIndex::from(0), assumptions)?;
+        let n_assumed = assumed.values().filter(|p| p.is_some()).count();
+        Ok((Integer::from(1) << (self.n_vars - n_assumed)) - n_models)
+    }
+
+    /// Returns `true` if and only if the clause made of the given literals is entailed by the negation of the formula.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a literal refers to a variable that does not exist.
+    #[must_use]
+    pub fn entails_clause(&self, clause: &[Literal]) -> bool {
+        self.try_entails_clause(clause)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns `true` if and only if the clause made of the given literals is entailed by the negation of the formula,
+    /// as [`entails_clause`](Self::entails_clause) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a literal refers to a variable that does not exist.
+    pub fn try_entails_clause(&self, clause: &[Literal]) -> Result<bool> {
+        let negated = clause.iter().map(Literal::flip).collect::<Vec<_>>();
+        Ok(self.try_count_under_assumptions(&negated)? == 0)
+    }
+
+    /// Returns `true` if and only if the term made of the given literals is an implicant of the negation of the formula.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a literal refers to a variable that does not exist.
+    #[must_use]
+    pub fn is_implicant(&self, term: &[Literal]) -> bool {
+        self.try_is_implicant(term)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns `true` if and only if the term made of the given literals is an implicant of the negation of the formula,
+    /// as [`is_implicant`](Self::is_implicant) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a literal refers to a variable that does not exist.
+    pub fn try_is_implicant(&self, term: &[Literal]) -> Result<bool> {
+        Ok(self
+            .model_counter
+            .try_count_from_under_assumptions(NodeIndex::from(0), term)?
+            == 0)
+    }
+
+    /// Returns a full assignment that is not a model of the formula and that is consistent with some assumptions, if such an assignment exists.
+    ///
+    /// The literals of the assignment are sorted by variable index.
+    /// Among the candidate assignments, the one returned is the first in the order in which the negative literals come before the positive ones.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an assumption refers to a variable that does not exist.
+    #[must_use]
+    pub fn find_non_model_under_assumptions(
+        &self,
+        assumptions: &[Literal],
+    ) -> Option<Vec<Literal>> {
+        self.try_find_non_model_under_assumptions(assumptions)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns a full assignment that is not a model of the formula and that is consistent with some assumptions, if such an assignment exists,
+    /// as [`find_non_model_under_assumptions`](Self::find_non_model_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    pub fn try_find_non_model_under_assumptions(
+        &self,
+        assumptions: &[Literal],
+    ) -> Result<Option<Vec<Literal>>> {
+        check_literals(assumptions, self.n_vars)?;
+        Ok(NonModelIter::new(self, assumptions).next())
+    }
+
+    /// Returns an iterator over the full assignments that are not models of the formula.
+    ///
+    /// The assignments are given in the order of [`find_non_model_under_assumptions`](Self::find_non_model_under_assumptions),
+    /// and computing each of them takes a time linear in the size of the Decision-DNNF times its number of variables.
+    #[must_use]
+    pub fn non_models(&self) -> NonModelIter<'_, 'a> {
+        NonModelIter::new(self, &[])
+    }
+}
+
+/// An iterator over the full assignments that are not models of a [`DecisionDNNF`].
+///
+/// See [`NegatedView::non_models`] for more information.
+pub struct NonModelIter<'b, 'a> {
+    view: &'b NegatedView<'a>,
+    assumptions: Vec<Literal>,
+    free_vars: Vec<usize>,
+    choices: Vec<Literal>,
+    started: bool,
+}
+
+impl<'b, 'a> NonModelIter<'b, 'a> {
+    fn new(view: &'b NegatedView<'a>, assumptions: &[Literal]) -> Self {
+        let mut assigned = vec![false; view.n_vars];
+        for l in assumptions {
+            assigned[l.var_index()] = true;
+        }
+        Self {
+            view,
+            assumptions: assumptions.to_vec(),
+            free_vars: (0..view.n_vars).filter(|v| !assigned[*v]).collect(),
+            choices: Vec::with_capacity(view.n_vars),
+            started: false,
+        }
+    }
+
+    fn has_non_models(&self) -> bool {
+        let mut literals = self.assumptions.clone();
+        literals.extend_from_slice(&self.choices);
+        self.view.count_under_assumptions(&literals) > 0
+    }
+
+    /// Extends the current choices to a full assignment, given they are consistent with at least one non-model.
+    fn complete(&mut self) {
+        while self.choices.len() < self.free_vars.len() {
+            let var_index = self.free_vars[self.choices.len()];
+            self.choices
+                .push(Literal::from(-isize::try_from(var_index + 1).unwrap()));
+            if !self.has_non_models() {
+                let l = self.choices.pop().unwrap();
+                self.choices.push(l.flip());
+            }
+        }
+    }
+
+    /// Moves the current choices to the next ones consistent with at least one non-model, returning `false` if there is none.
+    fn backtrack(&mut self) -> bool {
+        loop {
+            match self.choices.pop() {
+                None => return false,
+                Some(l) if l.polarity() => {}
+                Some(l) => {
+                    self.choices.push(l.flip());
+                    if self.has_non_models() {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for NonModelIter<'_, '_> {
+    type Item = Vec<Literal>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.started {
+            if !self.backtrack() {
+                return None;
+            }
+        } else {
+            self.started = true;
+            if !self.has_non_models() {
+                return None;
+            }
+        }
+        self.complete();
+        let mut assignment = self.assumptions.clone();
+        assignment.extend_from_slice(&self.choices);
+        assignment.sort_unstable_by_key(Literal::var_index);
+        assignment.dedup();
+        Some(assignment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    // (-1 and -2) or (1 and (-3 or 3)), on 3 variables
+    const INSTANCE: &str = "o 1 0\no 2 0\nt 3 0\n1 3 -1 -2 0\n1 2 1 0\n2 3 -3 0\n2 3 3 0\n";
+
+    fn literals(dimacs: &[isize]) -> Vec<Literal> {
+        dimacs.iter().copied().map(Literal::from).collect()
+    }
+
+    #[test]
+    fn test_count() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let negated = NegatedView::new(&ddnnf);
+        assert_eq!(2, negated.count());
+        assert_eq!(2, negated.count_under_assumptions(&literals(&[-1])));
+        assert_eq!(0, negated.count_under_assumptions(&literals(&[1])));
+        assert_eq!(1, negated.count_under_assumptions(&literals(&[-1, 3, 3])));
+        assert_eq!(0, negated.count_under_assumptions(&literals(&[-1, 1])));
+    }
+
+    #[test]
+    fn test_entailment() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let negated = NegatedView::new(&ddnnf);
+        assert!(negated.entails_clause(&literals(&[-1])));
+        assert!(negated.entails_clause(&literals(&[2])));
+        assert!(!negated.entails_clause(&literals(&[3])));
+        assert!(!negated.entails_clause(&[]));
+        assert!(negated.entails_clause(&literals(&[3, -3])));
+        assert!(negated.is_implicant(&literals(&[-1, 2])));
+        assert!(!negated.is_implicant(&literals(&[-1])));
+        assert!(negated.is_implicant(&literals(&[1, -1])));
+    }
+
+    #[test]
+    fn test_non_models() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let negated = NegatedView::new(&ddnnf);
+        assert_eq!(
+            vec![literals(&[-1, 2, -3]), literals(&[-1, 2, 3])],
+            negated.non_models().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(literals(&[-1, 2, 3])),
+            negated.find_non_model_under_assumptions(&literals(&[3]))
+        );
+        assert_eq!(
+            None,
+            negated.find_non_model_under_assumptions(&literals(&[1]))
+        );
+    }
+
+    #[test]
+    fn test_valid_and_unsat_formulas() {
+        let mut ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let negated = NegatedView::new(&ddnnf);
+        assert_eq!(0, negated.count());
+        assert_eq!(0, negated.non_models().count());
+        let mut ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let negated = NegatedView::new(&ddnnf);
+        assert_eq!(4, negated.count());
+        assert_eq!(4, negated.non_models().count());
+    }
+
+    #[test]
+    fn test_unknown_var() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let negated = NegatedView::new(&ddnnf);
+        assert!(negated
+            .try_count_under_assumptions(&literals(&[4]))
+            .is_err());
+        assert!(negated.try_entails_clause(&literals(&[-4])).is_err());
+        assert!(negated.try_is_implicant(&literals(&[4])).is_err());
+        assert!(negated
+            .try_find_non_model_under_assumptions(&literals(&[4]))
+            .is_err());
+    }
+}
@@ -0,0 +1,421 @@
+use crate::{
+    core::{EdgeIndex, InvolvedVars, LitMap, Node, NodeIndex},
+    DecisionDNNF, Literal,
+};
+use anyhow::{anyhow, Result};
+use std::{cell::OnceCell, cmp::Ordering, ops::Add};
+
+/// A structure used to find the models of minimal cost of a [`DecisionDNNF`].
+///
+/// A cost is attached to each literal; the cost of a model is the sum of the costs of its literals.
+/// The costs can be of any type supporting additions and comparisons, such as [`i64`] or [`f64`].
+///
+/// The minimal costs are computed by a bottom-up dynamic program, in which conjunction nodes sum the costs of their children and disjunction nodes take the minimum of them.
+/// As for the [`WeightedModelCounter`](crate::WeightedModelCounter), the minimal costs of the nodes are stored, so that the optimal model can be built top-down without further computation.
+///
+/// # Panics
+///
+/// The constructor panics if the number of variables of the costs does not match the one of the formula.
+/// The functions of this structure panic if two costs cannot be compared, as [`f64::NAN`] and any other value.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, LitMap, Literal, OptimalModelFinder};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let mut costs = LitMap::new(2, 0);
+/// costs[Literal::from(-1)] = 3;
+/// costs[Literal::from(1)] = 1;
+/// costs[Literal::from(2)] = 2;
+/// let finder = OptimalModelFinder::new(&ddnnf, costs);
+/// let (cost, model) = finder.optimal_model().unwrap();
+/// assert_eq!(1, cost);
+/// assert_eq!(vec![Literal::from(1), Literal::from(-2)], model);
+/// ```
+pub struct OptimalModelFinder<'a, C> {
+    ddnnf: &'a DecisionDNNF,
+    costs: LitMap<C>,
+    min_costs: Vec<OnceCell<(Option<C>, InvolvedVars)>>,
+}
+
+impl<'a, C> OptimalModelFinder<'a, C>
+where
+    C: Clone + From<u8> + Add<Output = C> + PartialOrd,
+{
+    /// Builds a new finder given a [`DecisionDNNF`] and the costs of the literals.
+    ///
+    /// The minimal costs are computed lazily, when they are queried.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of variables of the costs does not match the one of the formula.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF, costs: LitMap<C>) -> Self {
+        Self::try_new(ddnnf, costs).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Builds a new finder, as [`new`](Self::new) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the number of variables of the costs does not match the one of the formula.
+    pub fn try_new(ddnnf: &'a DecisionDNNF, costs: LitMap<C>) -> Result<Self> {
+        if ddnnf.n_vars() != costs.n_vars() {
+            return Err(anyhow!(
+                "the costs must be given for the variables of the formula"
+            ));
+        }
+        Ok(Self {
+            ddnnf,
+            costs,
+            min_costs: (0..ddnnf.n_nodes()).map(|_| OnceCell::new()).collect(),
+        })
+    }
+
+    /// Returns a model of minimal cost together with its cost, or [`None`] if the formula has no model.
+    ///
+    /// The model is given as a full assignment, sorted by variable index.
+    /// When several models have the minimal cost, one of them is returned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if two costs cannot be compared.
+    #[must_use]
+    pub fn optimal_model(&self) -> Option<(C, Vec<Literal>)> {
+        let root = NodeIndex::from(0);
+        let (cost, involved_vars) = self.node_min_cost(root);
+        let mut cost = cost.clone()?;
+        let mut model = Vec::with_capacity(self.ddnnf.n_vars());
+        self.build_optimal_model(root, &mut model);
+        for l in involved_vars.iter_missing_literals() {
+            let best = self.best_literal(l);
+            cost = cost + self.costs[best].clone();
+            model.push(best);
+        }
+        model.sort_unstable_by_key(Literal::var_index);
+        Some((cost, model))
+    }
+
+    /// Returns (at most) the `k` models of minimal costs together with their costs, in increasing order of cost.
+    ///
+    /// The models are given as full assignments, sorted by variable index.
+    /// Contrary to [`optimal_model`](Self::optimal_model), this function keeps up to `k` partial models for each node,
+    /// and thus takes a time and a memory that grow with `k` and the number of variables.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if two costs cannot be compared.
+    #[must_use]
+    pub fn best_models(&self, k: usize) -> Vec<(C, Vec<Literal>)> {
+        if k == 0 {
+            return vec![];
+        }
+        let mut cache = vec![None; self.ddnnf.n_nodes()];
+        let (mut best, involved_vars) = self.node_best_models(NodeIndex::from(0), k, &mut cache);
+        for l in involved_vars.iter_missing_literals() {
+            best = combine(&best, &self.free_var_models(l), k);
+        }
+        for (_, model) in &mut best {
+            model.sort_unstable_by_key(Literal::var_index);
+        }
+        best
+    }
+
+    fn best_literal(&self, positive: Literal) -> Literal {
+        if compare(&self.costs[positive], &self.costs[positive.flip()]) == Ordering::Less {
+            positive
+        } else {
+            positive.flip()
+        }
+    }
+
+    fn node_min_cost(&self, node: NodeIndex) -> &(Option<C>, InvolvedVars) {
+        self.min_costs[usize::from(node)].get_or_init(|| {
+            let n_vars = self.ddnnf.n_vars();
+            match self.ddnnf.node(node) {
+                Node::And(edges) => {
+                    let mut cost = Some(C::from(0));
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for edge_index in edges {
+                        let (child_cost, child_vars) = self.edge_min_cost(*edge_index);
+                        cost = cost.zip(child_cost).map(|(c, child_c)| c + child_c);
+                        involved_vars.or_assign(&child_vars);
+                    }
+                    (cost, involved_vars)
+                }
+                Node::Or(edges) => {
+                    let children = self.or_children_min_costs(edges);
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for (_, child_vars) in &children {
+                        involved_vars.or_assign(child_vars);
+                    }
+                    let cost = children
+                        .into_iter()
+                        .filter_map(|(child_cost, child_vars)| {
+                            child_cost
+                                .map(|c| self.free_vars_min_cost(c, &child_vars, &involved_vars))
+                        })
+                        .min_by(compare);
+                    (cost, involved_vars)
+                }
+                Node::True => (Some(C::from(0)), InvolvedVars::new(n_vars)),
+                Node::False => (None, InvolvedVars::new(n_vars)),
+            }
+        })
+    }
+
+    fn or_children_min_costs(&self, edges: &[EdgeIndex]) -> Vec<(Option<C>, InvolvedVars)> {
+        edges.iter().map(|e| self.edge_min_cost(*e)).collect()
+    }
+
+    fn edge_min_cost(&self, edge_index: EdgeIndex) -> (Option<C>, InvolvedVars) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (cost, target_vars) = self.node_min_cost(edge.target());
+        let mut involved_vars = target_vars.clone();
+        involved_vars.set_literals(edge.propagated());
+        let cost = cost.clone().map(|c| {
+            edge.propagated()
+                .iter()
+                .fold(c, |acc, l| acc + self.costs[*l].clone())
+        });
+        (cost, involved_vars)
+    }
+
+    /// Adds to a cost the minimal costs of the variables involved in `all_vars` but not in `child_vars`.
+    fn free_vars_min_cost(&self, cost: C, child_vars: &InvolvedVars, all_vars: &InvolvedVars) -> C {
+        let mut free_vars = child_vars.clone();
+        free_vars.xor_assign(all_vars);
+        free_vars.iter_pos_literals().fold(cost, |acc, l| {
+            acc + self.costs[self.best_literal(l)].clone()
+        })
+    }
+
+    fn build_optimal_model(&self, node: NodeIndex, model: &mut Vec<Literal>) {
+        match self.ddnnf.node(node) {
+            Node::And(edges) => {
+                for edge_index in edges {
+                    let edge = self.ddnnf.edge(*edge_index);
+                    model.extend_from_slice(edge.propagated());
+                    self.build_optimal_model(edge.target(), model);
+                }
+            }
+            Node::Or(edges) => {
+                let involved_vars = &self.node_min_cost(node).1;
+                let (edge_index, child_vars) = edges
+                    .iter()
+                    .zip(self.or_children_min_costs(edges))
+                    .filter_map(|(e, (child_cost, child_vars))| {
+                        child_cost.map(|c| {
+                            (
+                                self.free_vars_min_cost(c, &child_vars, involved_vars),
+                                e,
+                                child_vars,
+                            )
+                        })
+                    })
+                    .min_by(|(c1, _, _), (c2, _, _)| compare(c1, c2))
+                    .map(|(_, e, child_vars)| (*e, child_vars))
+                    .unwrap();
+                let mut free_vars = child_vars;
+                free_vars.xor_assign(involved_vars);
+                model.extend(free_vars.iter_pos_literals().map(|l| self.best_literal(l)));
+                let edge = self.ddnnf.edge(edge_index);
+                model.extend_from_slice(edge.propagated());
+                self.build_optimal_model(edge.target(), model);
+            }
+            Node::True | Node::False => {}
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn node_best_models(
+        &self,
+        node: NodeIndex,
+        k: usize,
+        cache: &mut [Option<(Vec<(C, Vec<Literal>)>, InvolvedVars)>],
+    ) -> (Vec<(C, Vec<Literal>)>, InvolvedVars) {
+        if let Some(result) = &cache[usize::from(node)] {
+            return result.clone();
+        }
+        let n_vars = self.ddnnf.n_vars();
+        let result = match self.ddnnf.node(node) {
+            Node::And(edges) => {
+                let mut best = vec![(C::from(0), vec![])];
+                let mut involved_vars = InvolvedVars::new(n_vars);
+                for edge_index in edges {
+                    let (child_best, child_vars) = self.edge_best_models(*edge_index, k, cache);
+                    best = combine(&best, &child_best, k);
+                    involved_vars.or_assign(&child_vars);
+                }
+                (best, involved_vars)
+            }
+            Node::Or(edges) => {
+                let children = edges
+                    .iter()
+                    .map(|e| self.edge_best_models(*e, k, cache))
+                    .collect::<Vec<_>>();
+                let mut involved_vars = InvolvedVars::new(n_vars);
+                for (_, child_vars) in &children {
+                    involved_vars.or_assign(child_vars);
+                }
+                let mut best = vec![];
+                for (mut child_best, mut free_vars) in children {
+                    free_vars.xor_assign(&involved_vars);
+                    for l in free_vars.iter_pos_literals() {
+                        child_best = combine(&child_best, &self.free_var_models(l), k);
+                    }
+                    best.append(&mut child_best);
+                }
+                best.sort_by(|(c1, _), (c2, _)| compare(c1, c2));
+                best.truncate(k);
+                (best, involved_vars)
+            }
+            Node::True => (vec![(C::from(0), vec![])], InvolvedVars::new(n_vars)),
+            Node::False => (vec![], InvolvedVars::new(n_vars)),
+        };
+        cache[usize::from(node)] = Some(result.clone());
+        result
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn edge_best_models(
+        &self,
+        edge_index: EdgeIndex,
+        k: usize,
+        cache: &mut [Option<(Vec<(C, Vec<Literal>)>, InvolvedVars)>],
+    ) -> (Vec<(C, Vec<Literal>)>, InvolvedVars) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (mut best, mut involved_vars) = self.node_best_models(edge.target(), k, cache);
+        involved_vars.set_literals(edge.propagated());
+        for (cost, model) in &mut best {
+            for l in edge.propagated() {
+                *cost = cost.clone() + self.costs[*l].clone();
+            }
+            model.extend_from_slice(edge.propagated());
+        }
+        (best, involved_vars)
+    }
+
+    fn free_var_models(&self, positive: Literal) -> Vec<(C, Vec<Literal>)> {
+        let mut models = [positive, positive.flip()]
+            .into_iter()
+            .map(|l| (self.costs[l].clone(), vec![l]))
+            .collect::<Vec<_>>();
+        models.sort_by(|(c1, _), (c2, _)| compare(c1, c2));
+        models
+    }
+}
+
+/// Returns the (at most) `k` best combinations of the partial models of two lists.
+fn combine<C>(
+    first: &[(C, Vec<Literal>)],
+    second: &[(C, Vec<Literal>)],
+    k: usize,
+) -> Vec<(C, Vec<Literal>)>
+where
+    C: Clone + Add<Output = C> + PartialOrd,
+{
+    let mut combined = Vec::with_capacity(first.len() * second.len());
+    for (c1, m1) in first {
+        for (c2, m2) in second {
+            let mut model = m1.clone();
+            model.extend_from_slice(m2);
+            combined.push((c1.clone() + c2.clone(), model));
+        }
+    }
+    combined.sort_by(|(c1, _), (c2, _)| compare(c1, c2));
+    combined.truncate(k);
+    combined
+}
+
+fn compare<C: PartialOrd>(c1: &C, c2: &C) -> Ordering {
+    c1.partial_cmp(c2).expect("costs must be comparable")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{D4Reader, ModelEnumerator};
+
+    // (-1 and (-2 or 2)) or (1 and 2 and (-3 or 3))
+    const INSTANCE: &str =
+        "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 2 0\n2 4 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n";
+
+    fn costs(n_vars: usize, values: &[(isize, i64)]) -> LitMap<i64> {
+        let mut costs = LitMap::new(n_vars, 0);
+        for (l, c) in values {
+            costs[Literal::from(*l)] = *c;
+        }
+        costs
+    }
+
+    fn all_costs(ddnnf: &DecisionDNNF, costs: &LitMap<i64>) -> Vec<i64> {
+        let mut result = ModelEnumerator::new(ddnnf, false)
+            .into_iter()
+            .map(|m| m.iter().map(|l| costs[l.unwrap()]).sum())
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result
+    }
+
+    #[test]
+    fn test_optimal_model() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let costs = costs(3, &[(-1, 5), (2, 1), (3, 2), (-3, 1)]);
+        let finder = OptimalModelFinder::new(&ddnnf, costs.clone());
+        let (cost, model) = finder.optimal_model().unwrap();
+        assert_eq!(all_costs(&ddnnf, &costs)[0], cost);
+        assert_eq!(
+            vec![Literal::from(1), Literal::from(2), Literal::from(-3)],
+            model
+        );
+    }
+
+    #[test]
+    fn test_free_vars() {
+        let mut ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        ddnnf.update_n_vars(4);
+        let costs = costs(4, &[(1, 10), (-2, 3), (4, -1)]);
+        let finder = OptimalModelFinder::new(&ddnnf, costs.clone());
+        let (cost, model) = finder.optimal_model().unwrap();
+        assert_eq!(all_costs(&ddnnf, &costs)[0], cost);
+        assert_eq!(cost, model.iter().map(|l| costs[*l]).sum::<i64>());
+        assert_eq!(Literal::from(4), model[3]);
+    }
+
+    #[test]
+    fn test_best_models() {
+        let mut ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        ddnnf.update_n_vars(4);
+        let costs = costs(4, &[(-1, 5), (2, 1), (3, 2), (-3, 1), (4, 7), (-4, 2)]);
+        let finder = OptimalModelFinder::new(&ddnnf, costs.clone());
+        let expected = all_costs(&ddnnf, &costs);
+        for k in 0..=expected.len() + 1 {
+            let best = finder.best_models(k);
+            assert_eq!(
+                &expected[..k.min(expected.len())],
+                best.iter().map(|(c, _)| *c).collect::<Vec<_>>()
+            );
+            for (c, m) in &best {
+                assert_eq!(*c, m.iter().map(|l| costs[*l]).sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsat() {
+        let ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        let finder = OptimalModelFinder::new(&ddnnf, LitMap::new(0, 0.));
+        assert!(finder.optimal_model().is_none());
+        assert!(finder.best_models(3).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "the costs must be given for the variables of the formula")]
+    fn test_wrong_n_vars() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let _ = OptimalModelFinder::new(&ddnnf, LitMap::new(2, 0));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{
+    core::{EdgeIndex, InvolvedVars, Node, NodeIndex, VarMap},
+    DecisionDNNF, Literal,
+};
+use anyhow::{anyhow, Result};
+use rug::Integer;
+
+/// A structure used to count the models of a [`DecisionDNNF`] projected onto a subset of its variables.
+///
+/// The projected model count is the number of assignments of the projected variables that can be extended into models of the formula.
+/// Since projecting a Decision-DNNF breaks the determinism of the disjunction nodes that decide on variables that are not projected, the count cannot be computed for any formula in polynomial time.
+/// This counter handles the formulas in which the projected variables are decided first, such as the ones produced by the projected compilation of d4 (option `-proj`):
+/// each disjunction node that has several satisfiable children involving projected variables must decide on a projected variable,
+/// i.e. for each pair of children, one of them must propagate a projected literal whose negation is propagated by the other one.
+/// The subformulas that do not involve projected variables only count for their satisfiability.
+/// An error is returned if the formula does not meet this condition.
+///
+/// Variables are given by their indices, as returned by [`Literal::var_index`].
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, ProjectedModelCounter};
+///
+/// // (-1 and (-2 or 2)) or 1
+/// let ddnnf = D4Reader::read("o 1 0\no 2 0\nt 3 0\n1 2 -1 0\n1 3 1 0\n2 3 -2 0\n2 3 2 0\n".as_bytes()).unwrap();
+/// let counter = ProjectedModelCounter::new(&ddnnf, &[0]);
+/// assert_eq!(2, counter.count().unwrap());
+/// ```
+pub struct ProjectedModelCounter<'a> {
+    ddnnf: &'a DecisionDNNF,
+    projected: VarMap<bool>,
+}
+
+impl<'a> ProjectedModelCounter<'a> {
+    /// Builds a new projected model counter given a [`DecisionDNNF`] and the indices of the variables to project onto.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a variable index is out of bounds.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF, projected_vars: &[usize]) -> Self {
+        Self::try_new(ddnnf, projected_vars).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Builds a new projected model counter, as [`new`](Self::new) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a variable index is out of bounds.
+    pub fn try_new(ddnnf: &'a DecisionDNNF, projected_vars: &[usize]) -> Result<Self> {
+        let n_vars = ddnnf.n_vars();
+        let mut projected = VarMap::new(n_vars, false);
+        for v in projected_vars {
+            if *v >= n_vars {
+                return Err(anyhow!(
+                    "no such variable index: {v} (the formula has {n_vars} variables)"
+                ));
+            }
+            projected[*v] = true;
+        }
+        Ok(Self { ddnnf, projected })
+    }
+
+    /// Returns the number of models of the formula projected onto the variables given at construction time.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a disjunction node does not decide on a projected variable while it should (see the documentation of this structure).
+    pub fn count(&self) -> Result<Integer> {
+        let mut cache = vec![None; self.ddnnf.n_nodes()];
+        let (n, involved_vars) = self.node_count(NodeIndex::from(0), &mut cache)?;
+        let n_projected = self.projected.values().filter(|p| **p).count();
+        Ok(n << (n_projected - involved_vars.count_ones()))
+    }
+
+    fn node_count(
+        &self,
+        node: NodeIndex,
+        cache: &mut [Option<(Integer, InvolvedVars)>],
+    ) -> Result<(Integer, InvolvedVars)> {
+        if let Some(result) = &cache[usize::from(node)] {
+            return Ok(result.clone());
+        }
+        let n_vars = self.ddnnf.n_vars();
+        let result = match self.ddnnf.node(node) {
+            Node::And(edges) => {
+                let mut n = Integer::from(1);
+                let mut involved_vars = InvolvedVars::new(n_vars);
+                for edge_index in edges {
+                    let (child_n, child_vars) = self.edge_count(*edge_index, cache)?;
+                    n *= child_n;
+                    involved_vars.or_assign(&child_vars);
+                }
+                (n, involved_vars)
+            }
+            Node::Or(edges) => {
+                let mut children = Vec::with_capacity(edges.len());
+                for edge_index in edges {
+                    let (child_n, child_vars) = self.edge_count(*edge_index, cache)?;
+                    if child_n != 0 {
+                        children.push((*edge_index, child_n, child_vars));
+                    }
+                }
+                let mut involved_vars = InvolvedVars::new(n_vars);
+                for (_, _, child_vars) in &children {
+                    involved_vars.or_assign(child_vars);
+                }
+                if involved_vars.any() {
+                    self.check_projected_decision(node, &children)?;
+                    let n_involved = involved_vars.count_ones();
+                    let n = children
+                        .into_iter()
+                        .map(|(_, child_n, child_vars)| {
+                            child_n << (n_involved - child_vars.count_ones())
+                        })
+                        .sum();
+                    (n, involved_vars)
+                } else {
+                    (Integer::from(u8::from(!children.is_empty())), involved_vars)
+                }
+            }
+            Node::True => (Integer::from(1), InvolvedVars::new(n_vars)),
+            Node::False => (Integer::from(0), InvolvedVars::new(n_vars)),
+        };
+        cache[usize::from(node)] = Some(result.clone());
+        Ok(result)
+    }
+
+    fn edge_count(
+        &self,
+        edge_index: EdgeIndex,
+        cache: &mut [Option<(Integer, InvolvedVars)>],
+    ) -> Result<(Integer, InvolvedVars)> {
+        let edge = self.ddnnf.edge(edge_index);
+        let (n, mut involved_vars) = self.node_count(edge.target(), cache)?;
+        involved_vars.set_literals(&self.projected_literals(edge.propagated()));
+        Ok((n, involved_vars))
+    }
+
+    fn projected_literals(&self, literals: &[Literal]) -> Vec<Literal> {
+        literals
+            .iter()
+            .filter(|l| self.projected[**l])
+            .copied()
+            .collect()
+    }
+
+    fn check_projected_decision(
+        &self,
+        node: NodeIndex,
+        children: &[(EdgeIndex, Integer, InvolvedVars)],
+    ) -> Result<()> {
+        let children_literals = children
+            .iter()
+            .map(|(e, _, _)| self.projected_literals(self.ddnnf.edge(*e).propagated()))
+            .collect::<Vec<_>>();
+        for (i, literals) in children_literals.iter().enumerate() {
+            for other_literals in &children_literals[i + 1..] {
+                if !literals.iter().any(|l| other_literals.contains(&l.flip())) {
+                    return Err(anyhow!(
+                        "the disjunction node at index {} does not decide on a projected variable",
+                        usize::from(node)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    fn projected_count(instance: &str, projected_vars: &[usize]) -> Result<usize> {
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        ProjectedModelCounter::new(&ddnnf, projected_vars)
+            .count()
+            .map(|n| n.to_usize().unwrap())
+    }
+
+    // (-1 and (-2 or 2)) or (1 and 2 and (-3 or 3))
+    const INSTANCE: &str =
+        "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 2 0\n2 4 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n";
+
+    #[test]
+    fn test_all_vars() {
+        assert_eq!(6, projected_count(INSTANCE, &[0, 1, 2]).unwrap());
+    }
+
+    #[test]
+    fn test_no_vars() {
+        assert_eq!(1, projected_count(INSTANCE, &[]).unwrap());
+        assert_eq!(0, projected_count("f 1 0\n", &[]).unwrap());
+    }
+
+    #[test]
+    fn test_first_var() {
+        assert_eq!(2, projected_count(INSTANCE, &[0]).unwrap());
+    }
+
+    #[test]
+    fn test_first_vars() {
+        assert_eq!(3, projected_count(INSTANCE, &[0, 1]).unwrap());
+    }
+
+    #[test]
+    fn test_not_decided_first() {
+        assert!(projected_count(INSTANCE, &[1]).is_err());
+    }
+
+    #[test]
+    fn test_unsat_children_are_ignored() {
+        // (-1 and 2) or (1 and false)
+        let instance = "o 1 0\nt 2 0\nf 3 0\n1 2 -1 2 0\n1 3 1 0\n";
+        assert_eq!(1, projected_count(instance, &[1]).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "no such variable index: 3 (the formula has 3 variables)")]
+    fn test_unknown_var() {
+        let _ = projected_count(INSTANCE, &[3]);
+    }
+}
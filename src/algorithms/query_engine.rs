@@ -0,0 +1,171 @@
+use crate::{
+    core::{assumed_polarities, NodeIndex},
+    DecisionDNNF, Literal, ModelCounter, ModelFinder,
+};
+use anyhow::Result;
+use rug::Integer;
+
+/// A structure used to answer entailment queries on a [`DecisionDNNF`].
+///
+/// A clause is entailed by the formula if every model of the formula satisfies it, that is if the formula has no model in which all the literals of the clause are false.
+/// This is decided by searching a model under the assumption of the negations of the literals of the clause.
+///
+/// A term (a conjunction of literals) is an implicant of the formula if every full assignment that satisfies the term is a model of the formula.
+/// This is decided by counting the models of the formula under the assumption of the literals of the term,
+/// and checking that this count is the number of full assignments that satisfy the term.
+///
+/// Both queries take a time linear in the size of the Decision-DNNF.
+/// Tautological clauses are always entailed, and inconsistent terms are always implicants.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, Literal, QueryEngine};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let engine = QueryEngine::new(&ddnnf);
+/// assert!(engine.entails_clause(&[Literal::from(1), Literal::from(-2)]));
+/// assert!(!engine.entails_clause(&[Literal::from(1)]));
+/// assert!(engine.is_implicant(&[Literal::from(1)]));
+/// assert!(!engine.is_implicant(&[Literal::from(-1)]));
+/// ```
+pub struct QueryEngine<'a> {
+    model_finder: ModelFinder<'a>,
+    model_counter: ModelCounter<'a>,
+    n_vars: usize,
+}
+
+impl<'a> QueryEngine<'a> {
+    /// Builds a new query engine given a [`DecisionDNNF`].
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        Self {
+            model_finder: ModelFinder::new(ddnnf),
+            model_counter: ModelCounter::new(ddnnf),
+            n_vars: ddnnf.n_vars(),
+        }
+    }
+
+    /// Returns `true` if and only if the clause made of the given literals is entailed by the formula.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a literal refers to a variable that does not exist.
+    #[must_use]
+    pub fn entails_clause(&self, clause: &[Literal]) -> bool {
+        self.try_entails_clause(clause)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns `true` if and only if the clause made of the given literals is entailed by the formula, as [`entails_clause`](Self::entails_clause) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a literal refers to a variable that does not exist.
+    pub fn try_entails_clause(&self, clause: &[Literal]) -> Result<bool> {
+        let negated = clause.iter().map(Literal::flip).collect::<Vec<_>>();
+        if assumed_polarities(&negated, self.n_vars)?.is_none() {
+            return Ok(true);
+        }
+        let has_model = self
+            .model_finder
+            .try_find_model_under_assumptions(&negated)?
+            .is_some();
+        Ok(!has_model)
+    }
+
+    /// Returns `true` if and only if the term made of the given literals is an implicant of the formula.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a literal refers to a variable that does not exist.
+    #[must_use]
+    pub fn is_implicant(&self, term: &[Literal]) -> bool {
+        self.try_is_implicant(term)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns `true` if and only if the term made of the given literals is an implicant of the formula, as [`is_implicant`](Self::is_implicant) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a literal refers to a variable that does not exist.
+    pub fn try_is_implicant(&self, term: &[Literal]) -> Result<bool> {
+        let Some(assumed) = assumed_polarities(term, self.n_vars)? else {
+            return Ok(true);
+        };
+        let n_models = self
+            .model_counter
+            .try_count_from_under_assumptions(NodeIndex::from(0), term)?;
+        let n_assumed = assumed.values().filter(|p| p.is_some()).count();
+        Ok(n_models == Integer::from(1) << (self.n_vars - n_assumed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    // (-1 and -2) or (1 and (-3 or 3)), on 3 variables
+    const INSTANCE: &str = "o 1 0\no 2 0\nt 3 0\n1 3 -1 -2 0\n1 2 1 0\n2 3 -3 0\n2 3 3 0\n";
+
+    fn literals(dimacs: &[isize]) -> Vec<Literal> {
+        dimacs.iter().copied().map(Literal::from).collect()
+    }
+
+    #[test]
+    fn test_entails_clause() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let engine = QueryEngine::new(&ddnnf);
+        assert!(engine.entails_clause(&literals(&[1, -2])));
+        assert!(engine.entails_clause(&literals(&[1, -2, 3])));
+        assert!(!engine.entails_clause(&literals(&[1])));
+        assert!(!engine.entails_clause(&literals(&[-2])));
+        assert!(!engine.entails_clause(&literals(&[3])));
+        assert!(!engine.entails_clause(&[]));
+        assert!(engine.entails_clause(&literals(&[3, -3])));
+    }
+
+    #[test]
+    fn test_is_implicant() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let engine = QueryEngine::new(&ddnnf);
+        assert!(engine.is_implicant(&literals(&[1])));
+        assert!(engine.is_implicant(&literals(&[1, 3])));
+        assert!(engine.is_implicant(&literals(&[-1, -2])));
+        assert!(engine.is_implicant(&literals(&[1, 1])));
+        assert!(!engine.is_implicant(&literals(&[-1])));
+        assert!(engine.is_implicant(&literals(&[-2])));
+        assert!(!engine.is_implicant(&literals(&[2])));
+        assert!(!engine.is_implicant(&[]));
+        assert!(engine.is_implicant(&literals(&[2, -2])));
+    }
+
+    #[test]
+    fn test_valid_formula() {
+        let mut ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let engine = QueryEngine::new(&ddnnf);
+        assert!(engine.is_implicant(&[]));
+        assert!(!engine.entails_clause(&literals(&[1])));
+    }
+
+    #[test]
+    fn test_unsat_formula() {
+        let mut ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let engine = QueryEngine::new(&ddnnf);
+        assert!(engine.entails_clause(&[]));
+        assert!(!engine.is_implicant(&literals(&[1, 2])));
+    }
+
+    #[test]
+    fn test_unknown_var() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let engine = QueryEngine::new(&ddnnf);
+        assert!(engine.try_entails_clause(&literals(&[4])).is_err());
+        assert!(engine.try_is_implicant(&literals(&[-4])).is_err());
+    }
+}
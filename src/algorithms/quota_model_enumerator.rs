@@ -0,0 +1,220 @@
+use crate::{
+    core::{Node, NodeIndex},
+    DecisionDNNF, FreeVariables, Literal,
+};
+use std::cell::OnceCell;
+
+/// A structure used to enumerate a structurally diverse subset of the models of a [`DecisionDNNF`].
+///
+/// Instead of returning the first models of the enumeration, which usually differ only by the last choices made at the disjunction nodes, this enumerator caps the number of models taken from each child of each disjunction node.
+/// The quota is applied recursively: the models of a node are built from at most `per_branch_quota` models for each child of each disjunction node below it.
+/// The same quota applies to the interpretations of the variables that do not appear in the formula.
+/// The models built for each node are kept in memory; the total number of models is bounded by the `max_models` parameter.
+///
+/// The returned models are full: they contain exactly one literal by variable.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, QuotaModelEnumerator};
+///
+/// // (-1 and any value for 2 and 3) or (1 2 3)
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 2 3 0\n".as_bytes()).unwrap();
+/// let models = QuotaModelEnumerator::new(&ddnnf, 1, 10).enumerate();
+/// assert_eq!(2, models.len());
+/// ```
+#[derive(Debug)]
+pub struct QuotaModelEnumerator<'a> {
+    ddnnf: &'a DecisionDNNF,
+    per_branch_quota: usize,
+    max_models: usize,
+    free_vars: FreeVariables,
+    node_models: Vec<OnceCell<Vec<Vec<Literal>>>>,
+}
+
+impl<'a> QuotaModelEnumerator<'a> {
+    /// Builds a new enumerator given a [`DecisionDNNF`], the maximal number of models taken from each child of the disjunction nodes, and the maximal number of models to return.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF, per_branch_quota: usize, max_models: usize) -> Self {
+        Self {
+            ddnnf,
+            per_branch_quota,
+            max_models,
+            free_vars: FreeVariables::compute(ddnnf),
+            node_models: (0..ddnnf.n_nodes()).map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    /// Computes the models.
+    ///
+    /// The literals of each model are sorted by variable index.
+    #[must_use]
+    pub fn enumerate(&self) -> Vec<Vec<Literal>> {
+        let root_models = self.node_models(NodeIndex::from(0));
+        let free_var_models = free_vars_interpretations(
+            self.free_vars.root_free_vars(),
+            self.max_models.min(self.per_branch_quota),
+        );
+        let mut models = product(&[root_models, &free_var_models], self.max_models);
+        for m in &mut models {
+            m.sort_unstable_by_key(Literal::var_index);
+        }
+        models
+    }
+
+    fn node_models(&self, node: NodeIndex) -> &Vec<Vec<Literal>> {
+        self.node_models[usize::from(node)].get_or_init(|| match self.ddnnf.node(node) {
+            Node::And(edges) => {
+                let children_models = edges
+                    .iter()
+                    .map(|e| {
+                        let edge = self.ddnnf.edge(*e);
+                        let propagated = vec![edge.propagated().to_vec()];
+                        product(
+                            &[self.node_models(edge.target()), &propagated],
+                            self.max_models,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                product(&children_models.iter().collect::<Vec<_>>(), self.max_models)
+            }
+            Node::Or(edges) => {
+                let quota = self.per_branch_quota.min(self.max_models);
+                let mut models = Vec::new();
+                for (i, e) in edges.iter().enumerate() {
+                    let edge = self.ddnnf.edge(*e);
+                    let propagated = vec![edge.propagated().to_vec()];
+                    let free_var_models = free_vars_interpretations(
+                        self.free_vars.or_child_free_vars(node, i),
+                        quota,
+                    );
+                    let child_models = product(
+                        &[
+                            self.node_models(edge.target()),
+                            &propagated,
+                            &free_var_models,
+                        ],
+                        quota.min(self.max_models - models.len()),
+                    );
+                    models.extend(child_models);
+                    if models.len() == self.max_models {
+                        break;
+                    }
+                }
+                models
+            }
+            Node::True => vec![vec![]],
+            Node::False => vec![],
+        })
+    }
+}
+
+/// Returns at most `max` interpretations of the given variables.
+fn free_vars_interpretations(vars: &[usize], max: usize) -> Vec<Vec<Literal>> {
+    let n = if vars.len() < usize::BITS as usize {
+        max.min(1 << vars.len())
+    } else {
+        max
+    };
+    (0..n)
+        .map(|i| {
+            vars.iter()
+                .enumerate()
+                .map(|(j, v)| {
+                    let l = Literal::from(isize::try_from(v + 1).unwrap());
+                    if j < usize::BITS as usize && i & (1 << j) != 0 {
+                        l
+                    } else {
+                        l.flip()
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns at most `max` combinations of the partial models of the lists, the first list varying the slowest.
+fn product(lists: &[&Vec<Vec<Literal>>], max: usize) -> Vec<Vec<Literal>> {
+    let mut result = vec![vec![]];
+    for list in lists {
+        let mut new_result = Vec::with_capacity(max.min(result.len() * list.len()));
+        'outer: for r in &result {
+            for m in *list {
+                if new_result.len() == max {
+                    break 'outer;
+                }
+                let mut combination = r.clone();
+                combination.extend_from_slice(m);
+                new_result.push(combination);
+            }
+        }
+        result = new_result;
+    }
+    result.truncate(max);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    fn enumerate(
+        str_ddnnf: &str,
+        n_vars: Option<usize>,
+        per_branch_quota: usize,
+        max_models: usize,
+    ) -> Vec<Vec<isize>> {
+        let mut ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        QuotaModelEnumerator::new(&ddnnf, per_branch_quota, max_models)
+            .enumerate()
+            .into_iter()
+            .map(|m| m.into_iter().map(isize::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_unsat() {
+        assert!(enumerate("f 1 0\n", None, 10, 10).is_empty());
+    }
+
+    #[test]
+    fn test_large_quota_gives_all_models() {
+        let instance =
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+        assert_eq!(
+            vec![vec![-1, -2], vec![-1, 2], vec![1, -2], vec![1, 2]],
+            enumerate(instance, None, 10, 10)
+        );
+    }
+
+    #[test]
+    fn test_quota_per_child() {
+        let instance = "o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 2 3 0\n";
+        assert_eq!(
+            vec![vec![-1, -2, -3], vec![-1, 2, -3], vec![1, 2, 3]],
+            enumerate(instance, None, 2, 10)
+        );
+        assert_eq!(
+            vec![vec![-1, -2, -3], vec![1, 2, 3]],
+            enumerate(instance, None, 1, 10)
+        );
+    }
+
+    #[test]
+    fn test_max_models() {
+        let instance = "o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 2 3 0\n";
+        assert_eq!(vec![vec![-1, -2, -3]], enumerate(instance, None, 2, 1));
+    }
+
+    #[test]
+    fn test_root_free_vars() {
+        assert_eq!(
+            vec![vec![-1, -2], vec![1, -2], vec![-1, 2]],
+            enumerate("t 1 0\n", Some(2), 3, 10)
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{core::full_assignment, DecisionDNNF, Literal, ModelFinder};
+use anyhow::{anyhow, Result};
+use rustc_hash::FxHashMap;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// A structure used to find reconfiguration paths between two models of a [`DecisionDNNF`].
+///
+/// A reconfiguration path is a sequence of models in which two consecutive models differ by the value of a single variable.
+/// Given two models, this structure searches for a shortest such path between them.
+///
+/// The search explores the models of the formula from the source one, changing the value of a single variable at a time.
+/// Since a path cannot be shorter than the number of variables on which the models differ, this number is used to guide the search towards the target model.
+/// In addition, the variables that have the same value in all the models of the formula are never changed.
+/// Even with these optimizations, the search may take a time and a memory that are exponential in the number of variables; thus, the length of the paths under consideration may be bounded.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, Literal, ReconfigurationPathFinder};
+///
+/// // (-1 -2) or (1 2), with a third free variable
+/// let mut ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 2 0\n".as_bytes()).unwrap();
+/// ddnnf.update_n_vars(3);
+/// let to_lits = |v: &[isize]| v.iter().map(|i| Literal::from(*i)).collect::<Vec<_>>();
+/// let finder = ReconfigurationPathFinder::new(&ddnnf);
+/// // there is no way to go from (-1 -2) to (1 2) by changing a single variable at a time
+/// assert!(finder.find_path(&to_lits(&[-1, -2, 3]), &to_lits(&[1, 2, 3]), None).is_none());
+/// // but the value of the third variable can be changed
+/// let path = finder.find_path(&to_lits(&[-1, -2, 3]), &to_lits(&[-1, -2, -3]), None).unwrap();
+/// assert_eq!(vec![Literal::from(-3)], path);
+/// ```
+pub struct ReconfigurationPathFinder<'a> {
+    ddnnf: &'a DecisionDNNF,
+    model_finder: ModelFinder<'a>,
+    changeable_vars: Vec<usize>,
+}
+
+impl<'a> ReconfigurationPathFinder<'a> {
+    /// Builds a new path finder given a [`DecisionDNNF`].
+    ///
+    /// Building the path finder involves the computation of the variables that have the same value in all the models.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF) -> Self {
+        let model_finder = ModelFinder::new(ddnnf);
+        let changeable_vars = (0..ddnnf.n_vars())
+            .filter(|i| {
+                let l = Literal::from(isize::try_from(i + 1).unwrap());
+                model_finder.find_model_under_assumptions(&[l]).is_some()
+                    && model_finder
+                        .find_model_under_assumptions(&[l.flip()])
+                        .is_some()
+            })
+            .collect();
+        Self {
+            ddnnf,
+            model_finder,
+            changeable_vars,
+        }
+    }
+
+    /// Searches for a shortest reconfiguration path between two models.
+    ///
+    /// If such a path exists, the literals that are set at each step of the path are returned.
+    /// Applying them in order to the source model produces the successive models of the path, the last one being the target model.
+    /// An empty path is returned if the two models are equal.
+    ///
+    /// If `max_length` is set, then only paths of at most this length are considered.
+    /// If no path is found, `None` is returned.
+    ///
+    /// # Panics
+    ///
+    /// The source and the target must be models of the formula, i.e. full assignments that satisfy it.
+    /// This function panics if this is not the case.
+    #[must_use]
+    pub fn find_path(
+        &self,
+        from: &[Literal],
+        to: &[Literal],
+        max_length: Option<usize>,
+    ) -> Option<Vec<Literal>> {
+        self.try_find_path(from, to, max_length)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Searches for a shortest reconfiguration path between two models, as [`find_path`](Self::find_path) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the source or the target is not a model of the formula.
+    pub fn try_find_path(
+        &self,
+        from: &[Literal],
+        to: &[Literal],
+        max_length: Option<usize>,
+    ) -> Result<Option<Vec<Literal>>> {
+        let from_state = self.to_state(from)?;
+        let to_state = self.to_state(to)?;
+        let max_length = max_length.unwrap_or(usize::MAX);
+        let distance = |s: &[bool]| {
+            s.iter()
+                .zip(to_state.iter())
+                .filter(|(a, b)| a != b)
+                .count()
+        };
+        let mut predecessors: FxHashMap<Vec<bool>, Option<usize>> = FxHashMap::default();
+        let mut queue = BinaryHeap::new();
+        let init_distance = distance(&from_state);
+        if init_distance > max_length {
+            return Ok(None);
+        }
+        predecessors.insert(from_state.clone(), None);
+        queue.push(Reverse((init_distance, 0, from_state)));
+        while let Some(Reverse((_, length, state))) = queue.pop() {
+            if state == to_state {
+                return Ok(Some(Self::build_path(&predecessors, state)));
+            }
+            for var_index in &self.changeable_vars {
+                let mut next_state = state.clone();
+                next_state[*var_index] = !next_state[*var_index];
+                if predecessors.contains_key(&next_state) {
+                    continue;
+                }
+                let estimation = length + 1 + distance(&next_state);
+                if estimation > max_length || !self.is_model(&next_state) {
+                    continue;
+                }
+                predecessors.insert(next_state.clone(), Some(*var_index));
+                queue.push(Reverse((estimation, length + 1, next_state)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn to_state(&self, model: &[Literal]) -> Result<Vec<bool>> {
+        let state = full_assignment(model, self.ddnnf.n_vars())?;
+        if self.is_model(&state) {
+            Ok(state)
+        } else {
+            Err(anyhow!("not a model of the formula"))
+        }
+    }
+
+    fn is_model(&self, state: &[bool]) -> bool {
+        let assumptions = state
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let l = Literal::from(isize::try_from(i + 1).unwrap());
+                if *b {
+                    l
+                } else {
+                    l.flip()
+                }
+            })
+            .collect::<Vec<_>>();
+        self.model_finder
+            .find_model_under_assumptions(&assumptions)
+            .is_some()
+    }
+
+    fn build_path(
+        predecessors: &FxHashMap<Vec<bool>, Option<usize>>,
+        mut state: Vec<bool>,
+    ) -> Vec<Literal> {
+        let mut path = Vec::new();
+        while let Some(var_index) = predecessors.get(&state).unwrap() {
+            let l = Literal::from(isize::try_from(var_index + 1).unwrap());
+            path.push(if state[*var_index] { l } else { l.flip() });
+            state[*var_index] = !state[*var_index];
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    fn find_path(
+        instance: &str,
+        n_vars: Option<usize>,
+        from: &[isize],
+        to: &[isize],
+        max_length: Option<usize>,
+    ) -> Option<Vec<isize>> {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let to_lits = |v: &[isize]| v.iter().map(|i| Literal::from(*i)).collect::<Vec<_>>();
+        ReconfigurationPathFinder::new(&ddnnf)
+            .find_path(&to_lits(from), &to_lits(to), max_length)
+            .map(|p| p.into_iter().map(isize::from).collect())
+    }
+
+    #[test]
+    fn test_same_model() {
+        assert_eq!(
+            Some(vec![]),
+            find_path("t 1 0", Some(2), &[1, -2], &[1, -2], None)
+        );
+    }
+
+    #[test]
+    fn test_direct_path() {
+        assert_eq!(
+            2,
+            find_path("t 1 0", Some(3), &[1, 2, 3], &[-1, 2, -3], None)
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn test_detour() {
+        // models: -1 -2 -3, 1 -2 -3, 1 2 -3, 1 2 3, -1 2 3
+        let instance = r"
+        o 1 0
+        t 2 0
+        1 2 -1 -2 -3 0
+        1 2 1 -2 -3 0
+        1 2 1 2 -3 0
+        1 2 1 2 3 0
+        1 2 -1 2 3 0
+        ";
+        assert_eq!(
+            Some(vec![1, 2, 3, -1]),
+            find_path(instance, None, &[-1, -2, -3], &[-1, 2, 3], None)
+        );
+        assert_eq!(
+            None,
+            find_path(instance, None, &[-1, -2, -3], &[-1, 2, 3], Some(3))
+        );
+    }
+
+    #[test]
+    fn test_no_path() {
+        let instance = "o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 2 0\n";
+        assert_eq!(None, find_path(instance, None, &[-1, -2], &[1, 2], None));
+    }
+
+    #[test]
+    #[should_panic(expected = "not a model of the formula")]
+    fn test_not_a_model() {
+        let instance = "o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 2 0\n";
+        let _ = find_path(instance, None, &[-1, 2], &[1, 2], None);
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{
+    core::NodeIndex, DecisionDNNF, ModelCounter, ModelEnumerator, ModelFinder, ModelSampler,
+};
+use anyhow::{anyhow, Result};
+use rug::Integer;
+
+/// A structure used to compute the similarity of two [`DecisionDNNF`]s defined on the same variables.
+///
+/// The similarity is the Jaccard index of the sets of models of the formulas, i.e. the number of their common models divided by the number of models of their disjunction.
+/// The number of common models is computed exactly by enumerating the compact models of the first formula (see [`ModelEnumerator`]), which are disjoint cubes, and by counting the models of the second formula that are consistent with each of them.
+/// If the first formula has too many compact models, the number of common models is estimated instead:
+/// models of the first formula are drawn uniformly at random (see [`ModelSampler`]), and the proportion of them that are models of the second formula is computed.
+///
+/// The limit on the number of compact models, the number of samples and the seed of the pseudo-random generator can be set before calling [`compute`](Self::compute).
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, SimilarityComputer};
+///
+/// // -1 or -2, and -1
+/// let first = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 -2 0\n".as_bytes()).unwrap();
+/// let mut second = D4Reader::read("a 1 0\nt 2 0\n1 2 -1 0\n".as_bytes()).unwrap();
+/// second.update_n_vars(2);
+/// let similarity = SimilarityComputer::new(&first, &second).compute();
+/// assert!(similarity.is_exact());
+/// assert_eq!(2, *similarity.n_common_models().unwrap());
+/// assert!((similarity.jaccard_index() - 2. / 3.).abs() < f64::EPSILON);
+/// ```
+pub struct SimilarityComputer<'a> {
+    first: &'a DecisionDNNF,
+    second: &'a DecisionDNNF,
+    max_cubes: usize,
+    n_samples: usize,
+    seed: u64,
+}
+
+/// The similarity of two formulas, as computed by a [`SimilarityComputer`].
+#[derive(Debug, Clone)]
+pub struct Similarity {
+    n_first_models: Integer,
+    n_second_models: Integer,
+    n_common_models: Option<Integer>,
+    jaccard_index: f64,
+}
+
+impl Similarity {
+    /// Returns the Jaccard index of the sets of models of the formulas, between 0 and 1.
+    ///
+    /// If both formulas have no models, the index is 1.
+    #[must_use]
+    pub fn jaccard_index(&self) -> f64 {
+        self.jaccard_index
+    }
+
+    /// Returns `true` if the Jaccard index was computed exactly, and `false` if it was estimated by sampling.
+    #[must_use]
+    pub fn is_exact(&self) -> bool {
+        self.n_common_models.is_some()
+    }
+
+    /// Returns the number of models of the first formula.
+    #[must_use]
+    pub fn n_first_models(&self) -> &Integer {
+        &self.n_first_models
+    }
+
+    /// Returns the number of models of the second formula.
+    #[must_use]
+    pub fn n_second_models(&self) -> &Integer {
+        &self.n_second_models
+    }
+
+    /// Returns the number of common models of the formulas, if it was computed exactly.
+    #[must_use]
+    pub fn n_common_models(&self) -> Option<&Integer> {
+        self.n_common_models.as_ref()
+    }
+}
+
+impl<'a> SimilarityComputer<'a> {
+    /// Builds a new similarity computer given two [`DecisionDNNF`]s.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the formulas do not have the same number of variables.
+    #[must_use]
+    pub fn new(first: &'a DecisionDNNF, second: &'a DecisionDNNF) -> Self {
+        Self::try_new(first, second).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Builds a new similarity computer, as [`new`](Self::new) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the formulas do not have the same number of variables.
+    pub fn try_new(first: &'a DecisionDNNF, second: &'a DecisionDNNF) -> Result<Self> {
+        if first.n_vars() != second.n_vars() {
+            return Err(anyhow!(
+                "the formulas must have the same number of variables"
+            ));
+        }
+        Ok(Self {
+            first,
+            second,
+            max_cubes: 1 << 20,
+            n_samples: 10_000,
+            seed: 0,
+        })
+    }
+
+    /// Sets the maximal number of compact models of the first formula to enumerate before resorting to sampling.
+    ///
+    /// The default value is 2^20.
+    pub fn set_max_cubes(&mut self, max_cubes: usize) {
+        self.max_cubes = max_cubes;
+    }
+
+    /// Sets the number of models drawn from the first formula when the similarity is estimated.
+    ///
+    /// The default value is 10,000.
+    pub fn set_n_samples(&mut self, n_samples: usize) {
+        self.n_samples = n_samples;
+    }
+
+    /// Sets the seed of the pseudo-random generator used when the similarity is estimated.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Computes the similarity of the formulas.
+    #[must_use]
+    pub fn compute(&self) -> Similarity {
+        let n_vars = self.first.n_vars();
+        let first_counter = ModelCounter::new(self.first);
+        let second_counter = ModelCounter::new(self.second);
+        let n_first_models = first_counter.count().clone();
+        let n_second_models = second_counter.count().clone();
+        let first_density = density(&n_first_models, n_vars);
+        let second_density = density(&n_second_models, n_vars);
+        let n_common_models = self.count_common_models(&second_counter);
+        let common_density = match &n_common_models {
+            Some(n) => density(n, n_vars),
+            None => first_density * self.estimate_common_ratio(&first_counter),
+        };
+        let union_density = first_density + second_density - common_density;
+        let jaccard_index = if union_density > 0. {
+            (common_density / union_density).clamp(0., 1.)
+        } else {
+            1.
+        };
+        Similarity {
+            n_first_models,
+            n_second_models,
+            n_common_models,
+            jaccard_index,
+        }
+    }
+
+    fn count_common_models(&self, second_counter: &ModelCounter) -> Option<Integer> {
+        let mut n_common_models = Integer::from(0);
+        let mut enumerator = ModelEnumerator::new(self.first, true);
+        let mut n_cubes = 0;
+        while let Some(model) = enumerator.compute_next_model() {
+            n_cubes += 1;
+            if n_cubes > self.max_cubes {
+                return None;
+            }
+            let cube = model.iter().filter_map(|opt_l| *opt_l).collect::<Vec<_>>();
+            n_common_models +=
+                second_counter.count_from_under_assumptions(NodeIndex::from(0), &cube);
+        }
+        Some(n_common_models)
+    }
+
+    fn estimate_common_ratio(&self, first_counter: &ModelCounter) -> f64 {
+        if *first_counter.count() == 0 || self.n_samples == 0 {
+            return 0.;
+        }
+        let model_finder = ModelFinder::new(self.second);
+        let mut sampler = ModelSampler::new(self.first);
+        sampler.set_seed(self.seed);
+        let mut n_common = 0;
+        for _ in 0..self.n_samples {
+            let model = sampler.sample_one().unwrap();
+            if model_finder.find_model_under_assumptions(&model).is_some() {
+                n_common += 1;
+            }
+        }
+        ratio(n_common, self.n_samples)
+    }
+}
+
+/// Returns the number of models divided by the number of assignments of the given number of variables.
+fn density(n: &Integer, n_vars: usize) -> f64 {
+    const MAX_EXPONENT: usize = 1000;
+    if n_vars <= MAX_EXPONENT {
+        n.to_f64() / 2_f64.powi(i32::try_from(n_vars).unwrap())
+    } else {
+        Integer::from(n >> (n_vars - MAX_EXPONENT)).to_f64()
+            / 2_f64.powi(i32::try_from(MAX_EXPONENT).unwrap())
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    numerator as f64 / denominator as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    const CLAUSE: &str = "o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 -2 0\n";
+    const TWO_VARS_AND: &str = "a 1 0\nt 2 0\n1 2 1 2 0\n";
+
+    fn read(instance: &str, n_vars: usize) -> DecisionDNNF {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        ddnnf.update_n_vars(n_vars);
+        ddnnf
+    }
+
+    #[test]
+    fn test_same_formula() {
+        let ddnnf = read(CLAUSE, 2);
+        let similarity = SimilarityComputer::new(&ddnnf, &ddnnf).compute();
+        assert!(similarity.is_exact());
+        assert_eq!(3, *similarity.n_common_models().unwrap());
+        assert!((similarity.jaccard_index() - 1.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_disjoint_formulas() {
+        let first = read(CLAUSE, 2);
+        let second = read(TWO_VARS_AND, 2);
+        let similarity = SimilarityComputer::new(&first, &second).compute();
+        assert_eq!(0, *similarity.n_common_models().unwrap());
+        assert!(similarity.jaccard_index().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_free_vars() {
+        // 1, and the tautology, on 3 variables
+        let first = read("a 1 0\nt 2 0\n1 2 1 0\n", 3);
+        let second = read("t 1 0\n", 3);
+        let similarity = SimilarityComputer::new(&first, &second).compute();
+        assert_eq!(4, *similarity.n_first_models());
+        assert_eq!(8, *similarity.n_second_models());
+        assert_eq!(4, *similarity.n_common_models().unwrap());
+        assert!((similarity.jaccard_index() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_no_models() {
+        let ddnnf = read("f 1 0\n", 2);
+        let similarity = SimilarityComputer::new(&ddnnf, &ddnnf).compute();
+        assert!((similarity.jaccard_index() - 1.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_sampling() {
+        // 1 or (-1 and 2 and 3), and 1, on 3 variables
+        let first = read("o 1 0\nt 2 0\n1 2 1 0\n1 2 -1 2 3 0\n", 3);
+        let second = read("a 1 0\nt 2 0\n1 2 1 0\n", 3);
+        let mut computer = SimilarityComputer::new(&first, &second);
+        computer.set_max_cubes(0);
+        computer.set_seed(42);
+        let similarity = computer.compute();
+        assert!(!similarity.is_exact());
+        assert!((similarity.jaccard_index() - 0.8).abs() < 0.05);
+        computer.set_n_samples(0);
+        assert!(computer.compute().jaccard_index().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    #[should_panic(expected = "the formulas must have the same number of variables")]
+    fn test_different_n_vars() {
+        let first = read(CLAUSE, 2);
+        let second = read(CLAUSE, 3);
+        let _ = SimilarityComputer::new(&first, &second);
+    }
+}
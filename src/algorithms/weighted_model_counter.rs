@@ -0,0 +1,207 @@
+use crate::{
+    core::{EdgeIndex, InvolvedVars, LitMap, Node, NodeIndex},
+    DecisionDNNF, Literal,
+};
+use anyhow::{anyhow, Result};
+use std::{
+    cell::OnceCell,
+    ops::{Add, Mul},
+};
+
+/// A structure used to compute the weighted model count of a [`DecisionDNNF`].
+///
+/// A weight is attached to each literal; the weight of a model is the product of the weights of its literals, and the weighted model count is the sum of the weights of the models.
+/// The weights can be of any type supporting additions and multiplications, such as [`f64`] or [`rug::Rational`].
+///
+/// As for the [`ModelCounter`](crate::ModelCounter), the weighted counts of the nodes are stored, so that the subformula rooted at any node can be queried at no additional cost.
+/// The subformula rooted at a node is considered on the same variables than the whole formula; the variables it does not involve are free.
+///
+/// # Panics
+///
+/// The constructor panics if the number of variables of the weights does not match the one of the formula.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, LitMap, Literal, WeightedModelCounter};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let mut weights = LitMap::new(2, 0.5);
+/// weights[Literal::from(1)] = 0.25;
+/// weights[Literal::from(-1)] = 0.75;
+/// let counter = WeightedModelCounter::new(&ddnnf, weights);
+/// // 0.75 * 0.5 for -1 -2, and 0.25 * (0.5 + 0.5) for the models with 1
+/// assert_eq!(0.625, counter.count());
+/// ```
+pub struct WeightedModelCounter<'a, W> {
+    ddnnf: &'a DecisionDNNF,
+    weights: LitMap<W>,
+    counts: Vec<OnceCell<(W, InvolvedVars)>>,
+}
+
+impl<'a, W> WeightedModelCounter<'a, W>
+where
+    W: Clone + From<u8> + Add<Output = W> + Mul<Output = W>,
+{
+    /// Builds a new weighted model counter given a [`DecisionDNNF`] and the weights of the literals.
+    ///
+    /// The counts are computed lazily, when they are queried.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the number of variables of the weights does not match the one of the formula.
+    #[must_use]
+    pub fn new(ddnnf: &'a DecisionDNNF, weights: LitMap<W>) -> Self {
+        Self::try_new(ddnnf, weights).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Builds a new weighted model counter, as [`new`](Self::new) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the number of variables of the weights does not match the one of the formula.
+    pub fn try_new(ddnnf: &'a DecisionDNNF, weights: LitMap<W>) -> Result<Self> {
+        if ddnnf.n_vars() != weights.n_vars() {
+            return Err(anyhow!(
+                "the weights must be given for the variables of the formula"
+            ));
+        }
+        Ok(Self {
+            ddnnf,
+            weights,
+            counts: (0..ddnnf.n_nodes()).map(|_| OnceCell::new()).collect(),
+        })
+    }
+
+    /// Returns the weighted model count of the whole formula.
+    #[must_use]
+    pub fn count(&self) -> W {
+        self.count_from(NodeIndex::from(0))
+    }
+
+    /// Returns the weighted model count of the subformula rooted at the given node.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn count_from(&self, node: NodeIndex) -> W {
+        let (w, involved_vars) = self.node_count(node);
+        self.free_vars_weight(w.clone(), involved_vars.iter_missing_literals())
+    }
+
+    fn free_vars_weight<I>(&self, init: W, positive_literals: I) -> W
+    where
+        I: Iterator<Item = Literal>,
+    {
+        positive_literals.fold(init, |acc, l| {
+            acc * (self.weights[l].clone() + self.weights[l.flip()].clone())
+        })
+    }
+
+    fn node_count(&self, node: NodeIndex) -> &(W, InvolvedVars) {
+        self.counts[usize::from(node)].get_or_init(|| {
+            let n_vars = self.ddnnf.n_vars();
+            match self.ddnnf.node(node) {
+                Node::And(edges) => {
+                    let mut w = W::from(1);
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for edge_index in edges {
+                        let (child_w, child_vars) = self.edge_count(*edge_index);
+                        w = w * child_w;
+                        involved_vars.or_assign(&child_vars);
+                    }
+                    (w, involved_vars)
+                }
+                Node::Or(edges) => {
+                    let children = edges
+                        .iter()
+                        .map(|e| self.edge_count(*e))
+                        .collect::<Vec<_>>();
+                    let mut involved_vars = InvolvedVars::new(n_vars);
+                    for (_, child_vars) in &children {
+                        involved_vars.or_assign(child_vars);
+                    }
+                    let w = children
+                        .into_iter()
+                        .map(|(child_w, mut child_vars)| {
+                            child_vars.xor_assign(&involved_vars);
+                            self.free_vars_weight(child_w, child_vars.iter_pos_literals())
+                        })
+                        .fold(W::from(0), |acc, w| acc + w);
+                    (w, involved_vars)
+                }
+                Node::True => (W::from(1), InvolvedVars::new(n_vars)),
+                Node::False => (W::from(0), InvolvedVars::new(n_vars)),
+            }
+        })
+    }
+
+    fn edge_count(&self, edge_index: EdgeIndex) -> (W, InvolvedVars) {
+        let edge = self.ddnnf.edge(edge_index);
+        let (w, target_vars) = self.node_count(edge.target());
+        let mut involved_vars = target_vars.clone();
+        involved_vars.set_literals(edge.propagated());
+        let w = edge
+            .propagated()
+            .iter()
+            .fold(w.clone(), |acc, l| acc * self.weights[*l].clone());
+        (w, involved_vars)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use crate::{D4Reader, ModelCounter};
+
+    const INSTANCE: &str = "o 1 0\no 2 0\nt 3 0\n1 2 -1 0\n1 3 1 0\n2 3 -2 0\n2 3 2 0\n";
+
+    #[test]
+    fn test_unit_weights_give_model_count() {
+        let mut ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        ddnnf.update_n_vars(4);
+        let counter = WeightedModelCounter::new(&ddnnf, LitMap::new(4, 1.0));
+        assert_eq!(ModelCounter::new(&ddnnf).count().to_f64(), counter.count());
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let mut weights = LitMap::new(2, 0.5);
+        weights[Literal::from(-1)] = 0.25;
+        weights[Literal::from(1)] = 0.75;
+        let counter = WeightedModelCounter::new(&ddnnf, weights);
+        assert_eq!(1.0, counter.count());
+    }
+
+    #[test]
+    fn test_weights() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let mut weights = LitMap::new(2, 1.0);
+        weights[Literal::from(-1)] = 2.0;
+        weights[Literal::from(1)] = 3.0;
+        weights[Literal::from(-2)] = 5.0;
+        weights[Literal::from(2)] = 7.0;
+        let counter = WeightedModelCounter::new(&ddnnf, weights);
+        // (2 * 5 + 2 * 7) + (3 * 5 + 3 * 7)
+        assert_eq!(60.0, counter.count());
+        assert_eq!(12.0 * 5.0, counter.count_from(NodeIndex::from(1)));
+    }
+
+    #[test]
+    fn test_false() {
+        let ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        let counter = WeightedModelCounter::new(&ddnnf, LitMap::new(0, 1.0));
+        assert_eq!(0.0, counter.count());
+    }
+
+    #[test]
+    #[should_panic(expected = "the weights must be given for the variables of the formula")]
+    fn test_wrong_n_vars() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let _ = WeightedModelCounter::new(&ddnnf, LitMap::new(3, 1.0));
+    }
+}
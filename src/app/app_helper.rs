@@ -66,6 +66,11 @@ impl<'a> AppHelper<'a> {
         self.cli_manager.add_command(command);
     }
 
+    /// Returns the clap app describing the CLI arguments of all the commands, e.g. to generate shell completions.
+    pub fn clap_app(&self) -> clap::App<'a, 'a> {
+        self.cli_manager.clap_app()
+    }
+
     /// Launch the application.
     ///
     /// The command line arguments are read through `std::env::args_os()`.
@@ -0,0 +1,45 @@
+use super::{cli_manager, common};
+use clap::{App, AppSettings, ArgMatches, SubCommand};
+use decdnnf_rs::AtomicSets;
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "atomic-sets";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("prints the groups of variables that take the same value, possibly up to a negation, in all the models")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let atomic_sets = AtomicSets::compute(&ddnnf);
+        let mut writer = common::output(arg_matches)?;
+        for set in atomic_sets.sets() {
+            let literals = set.iter().map(ToString::to_string).collect::<Vec<_>>();
+            writeln!(writer, "{}", literals.join(" "))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use super::{
+    cli_manager, common,
+    model_enumeration::{self, ModelPrinter},
+};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{
+    BottomUpTraversal, CheckingVisitor, DecisionDNNF, HybridModelCounter, ModelCountingVisitor,
+    ModelEnumerator, ModelFormat,
+};
+use log::info;
+use std::{
+    fs,
+    io::{self, Write},
+    time::Instant,
+};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "bench";
+
+const ARG_OPERATIONS: &str = "ARG_OPERATIONS";
+const ARG_N_MODELS: &str = "ARG_N_MODELS";
+
+const OPERATIONS: [&str; 8] = [
+    "parse",
+    "check",
+    "count",
+    "count-hybrid",
+    "enum-default",
+    "enum-compact",
+    "enum-decision-tree",
+    "enum-write",
+];
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("runs some operations on the formula and compares their costs")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_OPERATIONS)
+                    .long("operations")
+                    .empty_values(false)
+                    .multiple(true)
+                    .use_delimiter(true)
+                    .possible_values(&OPERATIONS)
+                    .default_value("parse,check,count,count-hybrid,enum-default,enum-compact,enum-decision-tree,enum-write")
+                    .help("the operations to run, separated by commas"),
+            )
+            .arg(
+                Arg::with_name(ARG_N_MODELS)
+                    .long("n-models")
+                    .empty_values(false)
+                    .multiple(false)
+                    .default_value("1000")
+                    .help("the maximal number of models to enumerate for each strategy"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let operations = arg_matches
+            .values_of(ARG_OPERATIONS)
+            .unwrap()
+            .collect::<Vec<_>>();
+        let n_models = str::parse::<usize>(arg_matches.value_of(ARG_N_MODELS).unwrap())
+            .context("while parsing the number of models provided on the command line")?;
+        let mut rows = Vec::with_capacity(OPERATIONS.len());
+        let (ddnnf, row) = measure("parse", || {
+            let ddnnf = common::read_input_ddnnf(arg_matches)?;
+            let result = format!("{} nodes, {} edges", ddnnf.n_nodes(), ddnnf.n_edges());
+            Ok((ddnnf, result))
+        })?;
+        if operations.contains(&"parse") {
+            rows.push(row);
+        }
+        for operation in OPERATIONS.iter().skip(1) {
+            if !operations.contains(operation) {
+                continue;
+            }
+            let ((), row) = measure(operation, || Ok(((), run(operation, &ddnnf, n_models)?)))?;
+            rows.push(row);
+        }
+        let mut output = common::output(arg_matches)?;
+        print_table(&mut output, &rows)?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+fn run(operation: &str, ddnnf: &DecisionDNNF, n_models: usize) -> anyhow::Result<String> {
+    match operation {
+        "check" => {
+            let checking_data =
+                BottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(ddnnf);
+            if let Some(e) = checking_data.get_error() {
+                return Err(anyhow!("{e}")).context("while checking the formula");
+            }
+            Ok(format!("{} warning(s)", checking_data.get_warnings().len()))
+        }
+        "count" => {
+            let counting_data =
+                BottomUpTraversal::new(Box::<ModelCountingVisitor>::default()).traverse(ddnnf);
+            Ok(format!("{} models", counting_data.n_models()))
+        }
+        "count-hybrid" => {
+            let counter = HybridModelCounter::new(ddnnf);
+            Ok(format!("{} models", counter.count()))
+        }
+        "enum-default" | "enum-compact" => {
+            let mut enumerator = ModelEnumerator::new(ddnnf, operation == "enum-compact");
+            let mut n = 0;
+            while n < n_models && enumerator.compute_next_model().is_some() {
+                n += 1;
+            }
+            Ok(format!("{n} models"))
+        }
+        "enum-decision-tree" => {
+            let mut n = 0;
+            if n_models > 0 {
+                model_enumeration::decision_tree_enumeration(ddnnf, 0..ddnnf.n_vars(), |_| {
+                    n += 1;
+                    n < n_models
+                });
+            }
+            Ok(format!("{n} models"))
+        }
+        "enum-write" => {
+            let mut model_writer = ModelPrinter::new(
+                io::sink(),
+                0..ddnnf.n_vars(),
+                false,
+                false,
+                ModelFormat::Dimacs,
+                b'*',
+            );
+            let mut enumerator = ModelEnumerator::new(ddnnf, false);
+            let mut n = 0;
+            while n < n_models {
+                let Some(model) = enumerator.compute_next_model() else {
+                    break;
+                };
+                model_writer.write_model_ordered(model);
+                n += 1;
+            }
+            model_writer.finalize();
+            Ok(format!("{n} models"))
+        }
+        _ => unreachable!(),
+    }
+}
+
+struct Row {
+    operation: &'static str,
+    result: String,
+    time_ms: f64,
+    peak_rss_kb: Option<u64>,
+}
+
+fn measure<T, F>(operation: &'static str, f: F) -> anyhow::Result<(T, Row)>
+where
+    F: FnOnce() -> anyhow::Result<(T, String)>,
+{
+    reset_peak_rss();
+    let start = Instant::now();
+    let (value, result) = f()?;
+    let time_ms = start.elapsed().as_secs_f64() * 1000.;
+    let row = Row {
+        operation,
+        result,
+        time_ms,
+        peak_rss_kb: peak_rss_kb(),
+    };
+    info!("{operation} done in {time_ms:.3}ms");
+    Ok((value, row))
+}
+
+/// Resets the peak resident set size of the process, if the system allows it.
+fn reset_peak_rss() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Returns the peak resident set size of the process, if the system provides it.
+fn peak_rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn print_table(output: &mut dyn Write, rows: &[Row]) -> io::Result<()> {
+    let operation_width = rows.iter().map(|r| r.operation.len()).max().unwrap_or(0);
+    let result_width = rows.iter().map(|r| r.result.len()).max().unwrap_or(0);
+    writeln!(
+        output,
+        "{:operation_width$}  {:result_width$}  {:>12}  {:>14}",
+        "operation", "result", "time (ms)", "peak RSS (KB)"
+    )?;
+    for row in rows {
+        let peak_rss = row
+            .peak_rss_kb
+            .map_or_else(|| "n/a".to_string(), |kb| kb.to_string());
+        writeln!(
+            output,
+            "{:operation_width$}  {:result_width$}  {:>12.3}  {:>14}",
+            row.operation, row.result, row.time_ms, peak_rss
+        )?;
+    }
+    Ok(())
+}
@@ -57,12 +57,8 @@ impl<'a> CliManager<'a> {
         self.commands.push(command);
     }
 
-    pub fn parse_cli<I, T>(&self, args: I) -> Result<()>
-    where
-        I: IntoIterator<Item = T>,
-        T: Into<OsString> + Clone,
-    {
-        let args: Vec<T> = args.into_iter().collect();
+    /// Builds the clap app, with a subcommand for each command.
+    pub fn clap_app(&self) -> App<'a, 'a> {
         let mut app = App::new(self.app_name)
             .global_setting(AppSettings::DisableVersion)
             .global_setting(AppSettings::VersionlessSubcommands)
@@ -74,6 +70,16 @@ impl<'a> CliManager<'a> {
         for c in &self.commands {
             app = app.subcommand(c.clap_subcommand());
         }
+        app
+    }
+
+    pub fn parse_cli<I, T>(&self, args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let args: Vec<T> = args.into_iter().collect();
+        let mut app = self.clap_app();
         let matches_result = app
             .clone()
             .get_matches_from_safe(&mut args.clone().into_iter());
@@ -0,0 +1,106 @@
+use super::{cli_manager, common};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{Cnf, CnfValidator};
+use log::info;
+use rug::Integer;
+use std::{fs::File, io::Write, str::FromStr};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "cnf-validation";
+
+const ARG_CNF: &str = "ARG_CNF";
+const ARG_EXPECTED_COUNT: &str = "ARG_EXPECTED_COUNT";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("checks that the formula entails each clause of the CNF formula it was compiled from")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_CNF)
+                    .long("cnf")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("the CNF formula, in the DIMACS format"),
+            )
+            .arg(
+                Arg::with_name(ARG_EXPECTED_COUNT)
+                    .long("expected-count")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("also checks that the model count is this one (e.g. the count of the CNF formula given by a model counter)"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let expected_count = arg_matches
+            .value_of(ARG_EXPECTED_COUNT)
+            .map(Integer::from_str)
+            .transpose()
+            .context("while parsing the expected count provided on the command line")?;
+        let cnf_path = arg_matches.value_of(ARG_CNF).unwrap();
+        let file =
+            File::open(cnf_path).with_context(|| format!(r#"while opening file "{cnf_path}""#))?;
+        let cnf = Cnf::from_reader(file)
+            .with_context(|| format!(r#"while reading file "{cnf_path}""#))?;
+        let mut ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        if ddnnf.n_vars() < cnf.n_vars() {
+            ddnnf.update_n_vars(cnf.n_vars());
+        }
+        let validator = CnfValidator::new(&ddnnf, &cnf)?;
+        let non_entailed = validator.non_entailed_clauses();
+        for i in &non_entailed {
+            let literals = cnf.clauses()[*i]
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            writeln!(
+                output,
+                "c clause at index {i} is not entailed: {} 0",
+                literals.join(" ")
+            )?;
+        }
+        let count_matches = match &expected_count {
+            Some(n) if !validator.count_matches(n) => {
+                writeln!(
+                    output,
+                    "c the model count is {}, not {n}",
+                    validator.count()
+                )?;
+                false
+            }
+            _ => true,
+        };
+        if non_entailed.is_empty() && count_matches {
+            info!("all the {} clauses are entailed", cnf.clauses().len());
+            writeln!(output, "s VALIDATED")?;
+            output.flush()?;
+            Ok(())
+        } else {
+            writeln!(output, "s NOT VALIDATED")?;
+            output.flush()?;
+            Err(anyhow!("the formula does not match the CNF formula"))
+        }
+    }
+}
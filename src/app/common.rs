@@ -1,10 +1,15 @@
 use anyhow::{anyhow, Context, Result};
 use clap::{Arg, ArgMatches};
-use decdnnf_rs::{CheckingVisitorData, D4Reader, DecisionDNNF, Literal};
-use log::{info, warn};
+use decdnnf_rs::{
+    Assumptions, CachedBottomUpTraversal, CheckLevel, CheckingVisitor, CheckingVisitorData,
+    ConeOfInfluence, D4Reader, DecisionDNNF, DeterminismVerdict, Format, Literal, Node, NodeIndex,
+};
+use log::{debug, error, info, warn};
 use std::{
+    ffi::OsString,
     fs::{self, File},
-    io::BufReader,
+    io::{BufReader, BufWriter, Write},
+    ops::Range,
     path::PathBuf,
 };
 
@@ -20,6 +25,118 @@ pub(crate) fn arg_input_var<'a>() -> Arg<'a, 'a> {
         .required(true)
 }
 
+const ARG_OUTPUT: &str = "ARG_OUTPUT";
+
+pub(crate) fn arg_output<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_OUTPUT)
+        .short("o")
+        .long("output")
+        .empty_values(false)
+        .multiple(false)
+        .help("writes the results into this file instead of the standard output; {rank} in the path is replaced by the MPI rank of the process")
+}
+
+/// The placeholder replaced by the rank of the process in the output path.
+const RANK_PLACEHOLDER: &str = "{rank}";
+
+/// The environment variables in which the common MPI implementations and job schedulers give the rank of the process.
+const RANK_ENV_VARS: [&str; 4] = [
+    "OMPI_COMM_WORLD_RANK",
+    "PMI_RANK",
+    "PMIX_RANK",
+    "SLURM_PROCID",
+];
+
+/// Returns the buffered writer the results must be written to, that is the file given by `--output` or the standard output.
+///
+/// If the path contains the `{rank}` placeholder, it is replaced by the rank of the process, so that each process of a parallel job writes into its own file.
+pub(crate) fn output(arg_matches: &ArgMatches<'_>) -> Result<Box<dyn Write>> {
+    match arg_matches.value_of(ARG_OUTPUT) {
+        Some(path) => {
+            let path = if path.contains(RANK_PLACEHOLDER) {
+                let rank = RANK_ENV_VARS
+                    .iter()
+                    .find_map(|v| std::env::var(v).ok())
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "the output path contains {RANK_PLACEHOLDER}, but none of the variables {} is set",
+                            RANK_ENV_VARS.join(", ")
+                        )
+                    })?;
+                path.replace(RANK_PLACEHOLDER, &rank)
+            } else {
+                path.to_string()
+            };
+            let file = File::create(&path)
+                .with_context(|| format!(r#"while creating the output file "{path}""#))?;
+            info!("writing the results into {path}");
+            Ok(Box::new(BufWriter::new(file)))
+        }
+        None => Ok(Box::new(BufWriter::new(std::io::stdout().lock()))),
+    }
+}
+
+const ARG_STACK_SIZE: &str = "ARG_STACK_SIZE";
+
+/// The default stack size of the thread running the commands, in MiB.
+const DEFAULT_STACK_SIZE_MIB: usize = 64;
+
+/// A rough upper bound of the stack space required by each level of recursion of the algorithms.
+const STACK_BYTES_PER_LEVEL: usize = 4096;
+
+pub(crate) fn arg_stack_size<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_STACK_SIZE)
+        .long("stack-size")
+        .empty_values(false)
+        .multiple(false)
+        .help("sets the stack size of the thread running the command, in MiB (64 by default); increase it for very deep formulas")
+}
+
+/// Returns the stack size in bytes to use for the thread running the command.
+///
+/// This function is called before the command line arguments are parsed, in order to create the thread.
+/// Thus, it looks for the stack size option by itself, and returns the default size if it is absent or invalid;
+/// in the latter case, the error is reported when the command reads its input formula.
+pub(crate) fn stack_size_from_args<I>(args: I) -> usize
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let args = args
+        .into_iter()
+        .map(|a| a.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let value = args.iter().enumerate().find_map(|(i, a)| {
+        if a == "--stack-size" {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            a.strip_prefix("--stack-size=")
+        }
+    });
+    value
+        .and_then(|v| str::parse::<usize>(v).ok())
+        .unwrap_or(DEFAULT_STACK_SIZE_MIB)
+        .saturating_mul(1 << 20)
+}
+
+/// Warns the user if the formula is so deep that the recursive algorithms may overflow the stack.
+fn check_depth(arg_matches: &ArgMatches<'_>, ddnnf: &DecisionDNNF) -> Result<()> {
+    let stack_size_mib = arg_matches
+        .value_of(ARG_STACK_SIZE)
+        .map(str::parse::<usize>)
+        .transpose()
+        .context("while parsing the stack size provided on the command line")?
+        .unwrap_or(DEFAULT_STACK_SIZE_MIB);
+    let depth = ddnnf.depth();
+    debug!("the formula has depth {depth}");
+    let required_mib = 1 + (depth.saturating_mul(STACK_BYTES_PER_LEVEL) >> 20);
+    if required_mib > stack_size_mib {
+        warn!(
+            "the formula has depth {depth}, which may overflow the stack; consider setting --stack-size to at least {required_mib}"
+        );
+    }
+    Ok(())
+}
+
 const ARG_N_VARS: &str = "ARG_N_VARS";
 
 pub(crate) fn arg_n_vars<'a>() -> Arg<'a, 'a> {
@@ -32,44 +149,419 @@ pub(crate) fn arg_n_vars<'a>() -> Arg<'a, 'a> {
         )
 }
 
+const ARG_CHECK_LEVEL: &str = "ARG_CHECK_LEVEL";
+
+pub(crate) fn arg_check_level<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_CHECK_LEVEL)
+        .long("check-level")
+        .empty_values(false)
+        .multiple(false)
+        .possible_values(&CheckLevel::NAMES)
+        .default_value("decomposability")
+        .help("sets the checks to apply on the input Decision-DNNF")
+}
+
+const ARG_EXACT_DETERMINISM: &str = "ARG_EXACT_DETERMINISM";
+
+pub(crate) fn arg_exact_determinism<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_EXACT_DETERMINISM)
+        .long("exact-determinism")
+        .takes_value(false)
+        .help("decides exactly whether the OR nodes the checker warns about are deterministic (may take an exponential time)")
+}
+
+const ARG_REFUTE_WARNINGS: &str = "ARG_REFUTE_WARNINGS";
+
+pub(crate) fn arg_refute_warnings<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_REFUTE_WARNINGS)
+        .long("refute-warnings")
+        .takes_value(false)
+        .conflicts_with(ARG_EXACT_DETERMINISM)
+        .help("tries to refute the determinism warnings of the checker with a lightweight reasoning step")
+}
+
+const ARG_REPORT_ALL_ERRORS: &str = "ARG_REPORT_ALL_ERRORS";
+
+pub(crate) fn arg_report_all_errors<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_REPORT_ALL_ERRORS)
+        .long("report-all-errors")
+        .takes_value(false)
+        .help("goes on checking the input Decision-DNNF after an error is found, and reports all of them")
+}
+
+const ARG_FIX: &str = "ARG_FIX";
+
+pub(crate) fn arg_fix<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_FIX)
+        .long("fix")
+        .empty_values(false)
+        .multiple(false)
+        .allow_hyphen_values(true)
+        .help("fixes some literals (given in the DIMACS format and separated by blanks) and removes the parts of the formula they falsify")
+}
+
+const ARG_ASSUMPTIONS_FILE: &str = "ARG_ASSUMPTIONS_FILE";
+
+pub(crate) fn arg_assumptions_file<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_ASSUMPTIONS_FILE)
+        .long("assumptions-file")
+        .empty_values(false)
+        .multiple(false)
+        .help("fixes the literals given in this file (DIMACS-like cubes, one per line, comments allowed) like --fix does")
+}
+
+const ARG_SIMPLIFY: &str = "ARG_SIMPLIFY";
+
+pub(crate) fn arg_simplify<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_SIMPLIFY)
+        .long("simplify")
+        .takes_value(false)
+        .help("simplifies the formula after loading it (removes the parts equivalent to false, merges the leaves and collapses the nodes with a single child)")
+}
+
+pub(crate) fn check_level(arg_matches: &ArgMatches<'_>) -> CheckLevel {
+    str::parse(arg_matches.value_of(ARG_CHECK_LEVEL).unwrap()).unwrap()
+}
+
+/// The way the determinism warnings of the checker are processed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WarningRefutation {
+    /// The warnings are reported as is.
+    None,
+    /// The warnings go through the lightweight refutation step of the checker.
+    Lightweight,
+    /// The warnings are decided exactly.
+    Exact,
+}
+
+/// Returns the way the determinism warnings must be processed.
+///
+/// The warnings are only refuted when the user asks for it, or at the strict check level, since they are errors at this level.
+pub(crate) fn warning_refutation(arg_matches: &ArgMatches<'_>) -> WarningRefutation {
+    if arg_matches.is_present(ARG_EXACT_DETERMINISM) {
+        WarningRefutation::Exact
+    } else if arg_matches.is_present(ARG_REFUTE_WARNINGS)
+        || check_level(arg_matches) == CheckLevel::Strict
+    {
+        WarningRefutation::Lightweight
+    } else {
+        WarningRefutation::None
+    }
+}
+
+/// Builds the checker of the input formulas, set as required by the command line arguments.
+pub(crate) fn checking_visitor(arg_matches: &ArgMatches<'_>) -> CheckingVisitor {
+    let mut visitor = CheckingVisitor::default();
+    visitor.set_report_all_errors(arg_matches.is_present(ARG_REPORT_ALL_ERRORS));
+    visitor
+}
+
+/// Reads the input formula, applying the checks of the check level that are processed by the reader.
+///
+/// The checks that involve a [`CheckingVisitor`] are left to the caller.
 pub(crate) fn read_input_ddnnf(arg_matches: &ArgMatches<'_>) -> Result<DecisionDNNF> {
-    let file_reader = create_input_file_reader(arg_matches)?;
-    let mut ddnnf = D4Reader::read(file_reader).context("while parsing the input Decision-DNNF")?;
+    read_ddnnf(arg_matches, ARG_INPUT)
+}
+
+/// Reads the formula which file is given by the argument, in the same way as [`read_input_ddnnf`] reads the input formula.
+pub(crate) fn read_ddnnf(arg_matches: &ArgMatches<'_>, arg: &str) -> Result<DecisionDNNF> {
+    let mut file_reader = create_file_reader(arg_matches, arg)?;
+    let format = Format::detect(&mut file_reader)?;
+    info!("the input formula is in the {format} format");
+    let mut ddnnf = if format == Format::D4 {
+        let mut reader = D4Reader::default();
+        reader.set_check_level(check_level(arg_matches).min(CheckLevel::Connectivity));
+        reader.read_with_options(file_reader)
+    } else {
+        format.read(file_reader)
+    }
+    .context("while parsing the input Decision-DNNF")?;
     if let Some(str_n) = arg_matches.value_of(ARG_N_VARS) {
-        let n = str::parse::<usize>(str_n)
-            .context("while parsing the number of variables provided on the command line")?;
-        ddnnf.update_n_vars(n);
+        let context = "while parsing the number of variables provided on the command line";
+        let n = str::parse::<usize>(str_n).context(context)?;
+        ddnnf.try_update_n_vars(n).context(context)?;
+    }
+    let mut literals = Vec::new();
+    if let Some(str_literals) = arg_matches.value_of(ARG_FIX) {
+        literals = parse_literals(str_literals, ddnnf.n_vars())
+            .context("while parsing the literals to fix provided on the command line")?;
+    }
+    if let Some(path) = arg_matches.value_of(ARG_ASSUMPTIONS_FILE) {
+        let assumptions = read_assumptions_file(path, ddnnf.n_vars())
+            .with_context(|| format!(r#"while reading the assumptions file "{path}""#))?;
+        literals.extend_from_slice(assumptions.literals());
+    }
+    if arg_matches.is_present(ARG_FIX) || arg_matches.is_present(ARG_ASSUMPTIONS_FILE) {
+        ddnnf = ddnnf.condition(&literals);
+        info!(
+            "the conditioned formula has {} nodes and {} edges",
+            ddnnf.n_nodes(),
+            ddnnf.n_edges()
+        );
+    }
+    if arg_matches.is_present(ARG_SIMPLIFY) {
+        ddnnf = ddnnf.simplify();
+        info!(
+            "the simplified formula has {} nodes and {} edges",
+            ddnnf.n_nodes(),
+            ddnnf.n_edges()
+        );
+    }
+    check_depth(arg_matches, &ddnnf)?;
+    Ok(ddnnf)
+}
+
+/// Parses literals given on the command line, in the format of the assumptions files (see [`Assumptions`]).
+pub(crate) fn parse_literals(str_literals: &str, n_vars: usize) -> Result<Vec<Literal>> {
+    Assumptions::from_reader(str_literals.as_bytes(), n_vars).map(|a| a.literals().to_vec())
+}
+
+const ARG_VARS: &str = "ARG_VARS";
+
+pub(crate) fn arg_vars<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_VARS)
+        .long("vars")
+        .empty_values(false)
+        .multiple(false)
+        .help("only considers the models projected onto this range of variables (e.g. 1-40), each of them once")
+}
+
+pub(crate) fn has_var_range(arg_matches: &ArgMatches<'_>) -> bool {
+    arg_matches.is_present(ARG_VARS)
+}
+
+/// Returns the range of variable indices given by `--vars`, if any.
+pub(crate) fn var_range(
+    arg_matches: &ArgMatches<'_>,
+    n_vars: usize,
+) -> Result<Option<Range<usize>>> {
+    arg_matches
+        .value_of(ARG_VARS)
+        .map(|str_range| parse_var_range(str_range, n_vars))
+        .transpose()
+        .context("while parsing the range of variables provided on the command line")
+}
+
+/// Parses a window of variables given as a range of DIMACS variable indices, like `3-10`, or as a single index.
+///
+/// The bounds are inclusive. The returned range contains the corresponding variable indices, beginning at 0.
+fn parse_var_range(str_range: &str, n_vars: usize) -> Result<Range<usize>> {
+    let (str_first, str_last) = str_range.split_once('-').unwrap_or((str_range, str_range));
+    let parse_bound = |w: &str| {
+        let i = str::parse::<usize>(w.trim())
+            .with_context(|| format!(r#"while parsing variable index "{w}""#))?;
+        if i == 0 || i > n_vars {
+            return Err(anyhow!(
+                "no such variable: {i} (the formula has {n_vars} variables)"
+            ));
+        }
+        Ok(i)
+    };
+    let (first, last) = (parse_bound(str_first)?, parse_bound(str_last)?);
+    if first > last {
+        return Err(anyhow!("empty range of variables: {str_range}"));
+    }
+    Ok(first - 1..last)
+}
+
+/// Reads the input formula and applies all the checks required by the check level.
+pub(crate) fn read_and_check_input_ddnnf(arg_matches: &ArgMatches<'_>) -> Result<DecisionDNNF> {
+    read_and_check_ddnnf(arg_matches, ARG_INPUT)
+}
+
+/// Reads the formula which file is given by the argument, in the same way as [`read_and_check_input_ddnnf`] reads the input formula.
+pub(crate) fn read_and_check_ddnnf(
+    arg_matches: &ArgMatches<'_>,
+    arg: &str,
+) -> Result<DecisionDNNF> {
+    let ddnnf = read_ddnnf(arg_matches, arg)?;
+    let check_level = check_level(arg_matches);
+    if check_level >= CheckLevel::Decomposability {
+        let traversal_engine =
+            CachedBottomUpTraversal::new(Box::new(checking_visitor(arg_matches)));
+        let checking_data = traversal_engine.traverse(&ddnnf);
+        print_warnings_and_errors(
+            &ddnnf,
+            &checking_data,
+            check_level,
+            warning_refutation(arg_matches),
+        )?;
     }
     Ok(ddnnf)
 }
 
 pub(crate) fn create_input_file_reader(arg_matches: &ArgMatches<'_>) -> Result<BufReader<File>> {
-    let input_file_canonicalized = realpath_from_arg(arg_matches, ARG_INPUT)?;
+    create_file_reader(arg_matches, ARG_INPUT)
+}
+
+fn create_file_reader(arg_matches: &ArgMatches<'_>, arg: &str) -> Result<BufReader<File>> {
+    let input_file_canonicalized = realpath_from_arg(arg_matches, arg)?;
     info!("reading input file {:?}", input_file_canonicalized);
     Ok(BufReader::new(File::open(input_file_canonicalized)?))
 }
 
+/// Reads a full assignment from a file, in the format of the assumptions files (see [`Assumptions`]).
+///
+/// The literals may be split over multiple lines, so that the outputs of the model computing command can be used.
+pub(crate) fn read_model_file(file_path: &str, n_vars: usize) -> Result<Vec<Literal>> {
+    let context = || format!(r#"while reading the model in file "{file_path}""#);
+    let literals = read_assumptions_file(file_path, n_vars)
+        .with_context(context)?
+        .literals()
+        .to_vec();
+    check_distinct_vars(&literals, n_vars).with_context(context)?;
+    if literals.len() < n_vars {
+        let missing = (0..n_vars)
+            .find(|v| literals.iter().all(|l| l.var_index() != *v))
+            .unwrap();
+        return Err(anyhow!("missing literal for variable {}", missing + 1)).with_context(context);
+    }
+    Ok(literals)
+}
+
+/// Reads a set of cubes from a file, one cube per line, in the format of the assumptions files (see [`Assumptions`]).
+///
+/// This format allows to use the outputs of the model enumeration command.
+pub(crate) fn read_cubes_file(file_path: &str, n_vars: usize) -> Result<Vec<Vec<Literal>>> {
+    let context = || format!(r#"while reading the cubes in file "{file_path}""#);
+    let assumptions = read_assumptions_file(file_path, n_vars).with_context(context)?;
+    assumptions
+        .cubes()
+        .map(|cube| {
+            check_distinct_vars(cube, n_vars).with_context(context)?;
+            Ok(cube.to_vec())
+        })
+        .collect()
+}
+
+fn read_assumptions_file(file_path: &str, n_vars: usize) -> Result<Assumptions> {
+    let file = File::open(file_path)?;
+    Assumptions::from_reader(file, n_vars)
+}
+
+/// Checks that the literals refer to distinct variables.
+fn check_distinct_vars(literals: &[Literal], n_vars: usize) -> Result<()> {
+    let mut seen = vec![false; n_vars];
+    for l in literals {
+        if seen[l.var_index()] {
+            return Err(anyhow!(
+                "multiple literals for variable {}",
+                l.var_index() + 1
+            ));
+        }
+        seen[l.var_index()] = true;
+    }
+    Ok(())
+}
+
 fn realpath_from_arg(arg_matches: &ArgMatches<'_>, arg: &str) -> Result<PathBuf> {
     let file_path = arg_matches.value_of(arg).unwrap();
     fs::canonicalize(PathBuf::from(file_path))
         .with_context(|| format!(r#"while opening file "{file_path}""#))
 }
 
-pub(crate) fn print_dimacs_model(model: &[Literal]) {
-    print!("v");
+pub(crate) fn write_dimacs_model(output: &mut dyn Write, model: &[Literal]) -> Result<()> {
+    write!(output, "v")?;
     for l in model {
-        print!(" {l}");
+        write!(output, " {l}")?;
     }
-    println!(" 0");
+    writeln!(output, " 0")?;
+    Ok(())
 }
 
-pub(crate) fn print_warnings_and_errors(checking_data: &CheckingVisitorData) -> anyhow::Result<()> {
-    for w in checking_data.get_warnings() {
-        warn!("{w}");
-    }
+pub(crate) fn print_warnings_and_errors(
+    ddnnf: &DecisionDNNF,
+    checking_data: &CheckingVisitorData,
+    check_level: CheckLevel,
+    refutation: WarningRefutation,
+) -> anyhow::Result<()> {
     if let Some(e) = checking_data.get_error() {
-        Err(anyhow!("{e}"))
+        for w in checking_data.get_warnings() {
+            warn!("{w}");
+        }
+        let errors = checking_data.get_errors();
+        if errors.len() == 1 {
+            return Err(anyhow!("{e}"));
+        }
+        for e in errors {
+            error!("{e}");
+        }
+        return Err(anyhow!("the checker found {} errors", errors.len()));
+    }
+    let verdicts = match refutation {
+        WarningRefutation::None => None,
+        WarningRefutation::Lightweight => Some(checking_data.refute_warnings(ddnnf)),
+        WarningRefutation::Exact => Some(checking_data.decide_warnings(ddnnf)),
+    };
+    let mut n_warnings = 0;
+    for (i, w) in checking_data.get_warnings().iter().enumerate() {
+        match verdicts.as_ref().map(|v| v[i]) {
+            None => warn!("{w}"),
+            Some(DeterminismVerdict::Refuted) => {
+                info!("{w} (refuted)");
+                continue;
+            }
+            Some(verdict) => warn!("{w} ({verdict})"),
+        }
+        n_warnings += 1;
+    }
+    if check_level == CheckLevel::Strict && n_warnings > 0 {
+        Err(anyhow!(
+            "the checker raised {n_warnings} warning(s), which are errors at the strict check level"
+        ))
     } else {
         Ok(())
     }
 }
+
+pub(crate) const OUTPUT_DOT: &str = "dot";
+
+/// Writes the formula in the dot format.
+///
+/// If a cone of influence is given, its nodes and edges are drawn in red and the other ones in gray.
+pub(crate) fn write_dot<W>(
+    writer: &mut W,
+    ddnnf: &DecisionDNNF,
+    cone: Option<&ConeOfInfluence>,
+) -> Result<()>
+where
+    W: Write,
+{
+    let color = |in_cone| match in_cone {
+        Some(true) => ", color=red",
+        Some(false) => ", color=gray",
+        None => "",
+    };
+    writeln!(writer, "digraph {{")?;
+    for i in 0..ddnnf.n_nodes() {
+        let node = NodeIndex::from(i);
+        let (kind, edges) = match ddnnf.node(node) {
+            Node::And(edges) => ("AND", edges.as_slice()),
+            Node::Or(edges) => ("OR", edges.as_slice()),
+            Node::True => ("TRUE", [].as_slice()),
+            Node::False => ("FALSE", [].as_slice()),
+        };
+        writeln!(
+            writer,
+            "  n{i} [label=\"{kind}\"{}];",
+            color(cone.map(|c| c.contains_node(node)))
+        )?;
+        for edge_index in edges {
+            let edge = ddnnf.edge(*edge_index);
+            let label = edge
+                .propagated()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(
+                writer,
+                "  n{i} -> n{} [label=\"{label}\"{}];",
+                usize::from(edge.target()),
+                color(cone.map(|c| c.contains_edge(*edge_index)))
+            )?;
+        }
+    }
+    writeln!(writer, "}}")?;
+    Ok(())
+}
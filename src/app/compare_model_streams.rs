@@ -0,0 +1,401 @@
+use super::{cli_manager, common};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{
+    models::{self, Side},
+    ModelFormat,
+};
+use log::info;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+    str::FromStr,
+};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "compare-model-streams";
+
+const ARG_LEFT: &str = "ARG_LEFT";
+const ARG_RIGHT: &str = "ARG_RIGHT";
+const ARG_LEFT_STYLE: &str = "ARG_LEFT_STYLE";
+const ARG_RIGHT_STYLE: &str = "ARG_RIGHT_STYLE";
+const ARG_N_VARS: &str = "ARG_N_VARS";
+const ARG_MAX_MODELS_IN_MEMORY: &str = "ARG_MAX_MODELS_IN_MEMORY";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("checks that two files produced by model enumerations contain the same models")
+            .setting(AppSettings::DisableVersion)
+            .arg(
+                Arg::with_name(ARG_LEFT)
+                    .long("left")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("the first file of models"),
+            )
+            .arg(
+                Arg::with_name(ARG_RIGHT)
+                    .long("right")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("the second file of models"),
+            )
+            .arg(
+                Arg::with_name(ARG_LEFT_STYLE)
+                    .long("left-style")
+                    .empty_values(false)
+                    .multiple(false)
+                    .possible_values(&ModelFormat::NAMES)
+                    .default_value(ModelFormat::NAMES[0])
+                    .help("the way models are displayed in the first file"),
+            )
+            .arg(
+                Arg::with_name(ARG_RIGHT_STYLE)
+                    .long("right-style")
+                    .empty_values(false)
+                    .multiple(false)
+                    .possible_values(&ModelFormat::NAMES)
+                    .default_value(ModelFormat::NAMES[0])
+                    .help("the way models are displayed in the second file"),
+            )
+            .arg(
+                Arg::with_name(ARG_N_VARS)
+                    .long("n-vars")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("the number of variables (required by the positive style)"),
+            )
+            .arg(
+                Arg::with_name(ARG_MAX_MODELS_IN_MEMORY)
+                    .long("max-models-in-memory")
+                    .empty_values(false)
+                    .multiple(false)
+                    .default_value("1000000")
+                    .help("the maximal number of models of each file kept in memory; the others are sorted in temporary files"),
+            )
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let n_vars = arg_matches
+            .value_of(ARG_N_VARS)
+            .map(str::parse::<usize>)
+            .transpose()
+            .context("while parsing the number of variables provided on the command line")?;
+        let max_models_in_memory = str::parse::<usize>(
+            arg_matches.value_of(ARG_MAX_MODELS_IN_MEMORY).unwrap(),
+        )
+        .context("while parsing the maximal number of models provided on the command line")?;
+        if max_models_in_memory == 0 {
+            return Err(anyhow!(
+                "the maximal number of models in memory must be positive"
+            ));
+        }
+        let mut left = ModelFileReader::new(arg_matches, ARG_LEFT, ARG_LEFT_STYLE, n_vars)?;
+        let mut right = ModelFileReader::new(arg_matches, ARG_RIGHT, ARG_RIGHT_STYLE, n_vars)?;
+        let mut first_divergence = None;
+        let mut n_divergences = 0_usize;
+        models::symmetric_difference(
+            &mut left,
+            &mut right,
+            max_models_in_memory,
+            |side, model| {
+                n_divergences += 1;
+                if first_divergence.is_none() {
+                    first_divergence = Some((side, model.to_vec()));
+                }
+            },
+        )?;
+        left.check_error()?;
+        right.check_error()?;
+        info!(
+            "read {} models from the first file and {} models from the second one",
+            left.n_models, right.n_models
+        );
+        match first_divergence {
+            None => {
+                writeln!(output, "s SAME")?;
+                output.flush()?;
+                Ok(())
+            }
+            Some((side, model)) => {
+                let file = match side {
+                    Side::Left => &left.path,
+                    Side::Right => &right.path,
+                };
+                write!(output, "c first divergence: only in {file}:")?;
+                for l in model {
+                    write!(output, " {l}")?;
+                }
+                writeln!(output, " 0")?;
+                writeln!(output, "c {n_divergences} model(s) appear in a single file")?;
+                writeln!(output, "s DIFFERENT")?;
+                output.flush()?;
+                Err(anyhow!("the files contain different models"))
+            }
+        }
+    }
+}
+
+/// Reads the models of a file produced by the model enumeration command.
+///
+/// The compact models, in which free variables are eluded, are expanded.
+/// Since the models are consumed by an iterator, the errors are stored and must be checked after the iteration using [`check_error`](Self::check_error).
+struct ModelFileReader {
+    path: String,
+    lines: Lines<BufReader<File>>,
+    style: ModelFormat,
+    csv_vars: Option<Vec<isize>>,
+    n_vars: Option<usize>,
+    line_number: usize,
+    n_models: usize,
+    expansion: Option<CompactModel>,
+    error: Option<anyhow::Error>,
+}
+
+/// A model in which some variables are free, and the index of the next model it represents.
+struct CompactModel {
+    fixed: Vec<isize>,
+    free_vars: Vec<isize>,
+    next: u64,
+}
+
+impl ModelFileReader {
+    fn new(
+        arg_matches: &ArgMatches<'_>,
+        arg_path: &str,
+        arg_style: &str,
+        n_vars: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        let path = arg_matches.value_of(arg_path).unwrap().to_string();
+        let style = ModelFormat::from_str(arg_matches.value_of(arg_style).unwrap())?;
+        if style == ModelFormat::Positive && n_vars.is_none() {
+            return Err(anyhow!(
+                "the number of variables must be provided to read models in the positive style"
+            ));
+        }
+        let file = File::open(&path).with_context(|| format!(r#"while opening file "{path}""#))?;
+        Ok(Self {
+            path,
+            lines: BufReader::new(file).lines(),
+            style,
+            csv_vars: None,
+            n_vars,
+            line_number: 0,
+            n_models: 0,
+            expansion: None,
+            error: None,
+        })
+    }
+
+    fn check_error(&mut self) -> anyhow::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e).with_context(|| format!(r#"while reading file "{}""#, self.path)),
+            None => Ok(()),
+        }
+    }
+
+    fn next_expanded_model(&mut self) -> Option<Vec<isize>> {
+        let compact = self.expansion.as_mut()?;
+        if compact.next >> compact.free_vars.len() != 0 {
+            self.expansion = None;
+            return None;
+        }
+        let mut model = compact.fixed.clone();
+        model.extend(compact.free_vars.iter().enumerate().map(|(i, v)| {
+            if compact.next & (1 << i) == 0 {
+                -v
+            } else {
+                *v
+            }
+        }));
+        compact.next += 1;
+        Some(model)
+    }
+
+    fn parse_line(&mut self, line: &str) -> anyhow::Result<Option<CompactModel>> {
+        let mut words = line.split_whitespace().peekable();
+        match words.peek() {
+            Some(&("c" | "s")) | None => return Ok(None),
+            Some(&"v") => {
+                words.next();
+            }
+            _ => {}
+        }
+        let (mut fixed, free_vars) = match self.style {
+            ModelFormat::Dimacs | ModelFormat::Positive => parse_literals(words)?,
+            ModelFormat::Binary => parse_values(words, |i| isize::try_from(i + 1).unwrap())?,
+            ModelFormat::Csv => {
+                let Some(vars) = &self.csv_vars else {
+                    self.csv_vars = Some(parse_csv_header(line)?);
+                    return Ok(None);
+                };
+                let values = line.split(',').map(str::trim).collect::<Vec<_>>();
+                if values.len() != vars.len() {
+                    return Err(anyhow!(
+                        "expected {} values, got {}",
+                        vars.len(),
+                        values.len()
+                    ));
+                }
+                parse_values(values.into_iter(), |i| vars[i])?
+            }
+            ModelFormat::JsonLines => parse_json_line(line)?,
+        };
+        if self.style == ModelFormat::Positive {
+            let n_vars = isize::try_from(self.n_vars.unwrap()).unwrap();
+            let mut present = vec![false; self.n_vars.unwrap()];
+            for v in fixed.iter().chain(free_vars.iter()) {
+                if *v < 0 || *v > n_vars {
+                    return Err(anyhow!("unexpected literal {v}"));
+                }
+                present[v.unsigned_abs() - 1] = true;
+            }
+            fixed.extend(
+                (1..=n_vars)
+                    .zip(present)
+                    .filter_map(|(v, p)| if p { None } else { Some(-v) }),
+            );
+        }
+        if free_vars.len() >= 64 {
+            return Err(anyhow!(
+                "too many free variables to expand the model ({})",
+                free_vars.len()
+            ));
+        }
+        Ok(Some(CompactModel {
+            fixed,
+            free_vars,
+            next: 0,
+        }))
+    }
+}
+
+impl Iterator for ModelFileReader {
+    type Item = Vec<isize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(model) = self.next_expanded_model() {
+                self.n_models += 1;
+                return Some(model);
+            }
+            if self.error.is_some() {
+                return None;
+            }
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => {
+                    self.error = Some(e.into());
+                    return None;
+                }
+            };
+            self.line_number += 1;
+            match self.parse_line(&line) {
+                Ok(opt_compact) => self.expansion = opt_compact,
+                Err(e) => {
+                    self.error = Some(e.context(format!("at line {}", self.line_number)));
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Parses DIMACS literals, until a `0`; the free variables are given by their index prefixed by the free variable symbol.
+fn parse_literals<'a>(
+    words: impl Iterator<Item = &'a str>,
+) -> anyhow::Result<(Vec<isize>, Vec<isize>)> {
+    let mut fixed = Vec::new();
+    let mut free_vars = Vec::new();
+    for w in words {
+        if w == "0" {
+            break;
+        }
+        if let Ok(l) = str::parse::<isize>(w) {
+            fixed.push(l);
+            continue;
+        }
+        let var = w
+            .char_indices()
+            .nth(1)
+            .and_then(|(j, _)| str::parse::<isize>(&w[j..]).ok())
+            .filter(|v| *v > 0)
+            .ok_or_else(|| anyhow!(r#"unexpected literal "{w}""#))?;
+        free_vars.push(var);
+    }
+    Ok((fixed, free_vars))
+}
+
+/// Parses a value by variable, `1` for true, `0` for false and the free variable symbol for the free variables.
+///
+/// The variable of each value is given by a function of its position.
+fn parse_values<'a>(
+    values: impl Iterator<Item = &'a str>,
+    var: impl Fn(usize) -> isize,
+) -> anyhow::Result<(Vec<isize>, Vec<isize>)> {
+    let mut fixed = Vec::new();
+    let mut free_vars = Vec::new();
+    for (i, w) in values.enumerate() {
+        match w {
+            "1" => fixed.push(var(i)),
+            "0" => fixed.push(-var(i)),
+            _ if w.chars().count() == 1 => free_vars.push(var(i)),
+            _ => return Err(anyhow!(r#"unexpected value "{w}""#)),
+        }
+    }
+    Ok((fixed, free_vars))
+}
+
+/// Parses the header of a CSV file, which gives the variables of the columns (e.g. `x1,x2,x3`).
+fn parse_csv_header(line: &str) -> anyhow::Result<Vec<isize>> {
+    line.split(',')
+        .map(|w| {
+            let w = w.trim();
+            w.strip_prefix('x')
+                .and_then(|v| str::parse::<isize>(v).ok())
+                .filter(|v| *v > 0)
+                .ok_or_else(|| anyhow!(r#"expected a variable in the CSV header, got "{w}""#))
+        })
+        .collect()
+}
+
+/// Parses a JSON array of DIMACS literals, in which the free variables are given by `null`.
+///
+/// Since a `null` does not give the variable, it is deduced from its position, the first one being variable 1.
+fn parse_json_line(line: &str) -> anyhow::Result<(Vec<isize>, Vec<isize>)> {
+    let content = line
+        .trim()
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(|| anyhow!("expected a JSON array"))?;
+    let mut fixed = Vec::new();
+    let mut free_vars = Vec::new();
+    if content.trim().is_empty() {
+        return Ok((fixed, free_vars));
+    }
+    for (i, w) in content.split(',').map(str::trim).enumerate() {
+        if w == "null" {
+            free_vars.push(isize::try_from(i + 1).unwrap());
+        } else {
+            fixed.push(
+                str::parse::<isize>(w)
+                    .ok()
+                    .filter(|l| *l != 0)
+                    .ok_or_else(|| anyhow!(r#"unexpected literal "{w}""#))?,
+            );
+        }
+    }
+    Ok((fixed, free_vars))
+}
@@ -0,0 +1,46 @@
+use super::{cli_manager, common};
+use anyhow::anyhow;
+use clap::{App, AppSettings, Arg, ArgMatches, Shell, SubCommand};
+use std::{io::Write, str::FromStr};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "completions";
+
+const ARG_SHELL: &str = "ARG_SHELL";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("prints a completion script for a shell")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_SHELL)
+                    .long("shell")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .possible_values(&Shell::variants())
+                    .help("the shell the completion script is written for"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let shell =
+            Shell::from_str(arg_matches.value_of(ARG_SHELL).unwrap()).map_err(|e| anyhow!(e))?;
+        let mut output = common::output(arg_matches)?;
+        let app_name = option_env!("CARGO_PKG_NAME").unwrap_or("unknown app name");
+        crate::create_app_helper()
+            .clap_app()
+            .gen_completions_to(app_name, shell, &mut output);
+        output.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use super::{cli_manager, common};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{ConeOfInfluence, Format, Literal};
+use log::info;
+use std::{io::Write, str::FromStr};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "cone-of-influence";
+
+const ARG_VARIABLES: &str = "ARG_VARIABLES";
+const ARG_OUTPUT_FORMAT: &str = "ARG_OUTPUT_FORMAT";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("extracts the part of the formula that mentions some variables")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_VARIABLES)
+                    .long("variables")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("the variables of interest (DIMACS indices, e.g. \"1 2 5\")"),
+            )
+            .arg(
+                Arg::with_name(ARG_OUTPUT_FORMAT)
+                    .long("output-format")
+                    .empty_values(false)
+                    .multiple(false)
+                    .possible_values(&[common::OUTPUT_DOT, Format::NAMES[0], Format::NAMES[1]])
+                    .default_value(common::OUTPUT_DOT)
+                    .help("dot for the whole formula with the cone highlighted, or the format in which the cone is written as a standalone formula"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let context = "while parsing the variables provided on the command line";
+        let literals =
+            common::parse_literals(arg_matches.value_of(ARG_VARIABLES).unwrap(), ddnnf.n_vars())
+                .context(context)?;
+        if let Some(l) = literals.iter().find(|l| !l.polarity()) {
+            return Err(anyhow!("expected a variable index, got {l}")).context(context);
+        }
+        let vars = literals.iter().map(Literal::var_index).collect::<Vec<_>>();
+        let cone = ConeOfInfluence::compute(&ddnnf, &vars);
+        info!(
+            "the cone contains {} node(s) out of {} and {} edge(s) out of {}",
+            cone.n_nodes(),
+            ddnnf.n_nodes(),
+            cone.n_edges(),
+            ddnnf.n_edges()
+        );
+        let mut writer = common::output(arg_matches)?;
+        match arg_matches.value_of(ARG_OUTPUT_FORMAT).unwrap() {
+            common::OUTPUT_DOT => common::write_dot(&mut writer, &ddnnf, Some(&cone))?,
+            format => Format::from_str(format)?.write(&mut writer, &cone.extract())?,
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
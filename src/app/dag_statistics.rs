@@ -0,0 +1,93 @@
+use super::{cli_manager, common};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::DecisionDNNFStats;
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "dag-statistics";
+
+const ARG_JSON: &str = "ARG_JSON";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("loads the formula and prints metrics about its structure")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_JSON)
+                    .long("json")
+                    .takes_value(false)
+                    .help("prints the metrics as a JSON object"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let stats = DecisionDNNFStats::compute(&ddnnf);
+        let metrics = [
+            ("nodes", stats.n_nodes().to_string()),
+            ("and_nodes", stats.n_and_nodes().to_string()),
+            ("or_nodes", stats.n_or_nodes().to_string()),
+            ("true_nodes", stats.n_true_nodes().to_string()),
+            ("false_nodes", stats.n_false_nodes().to_string()),
+            ("edges", stats.n_edges().to_string()),
+            ("depth", stats.depth().to_string()),
+            ("max_fan_out", stats.max_fan_out().to_string()),
+            ("avg_fan_out", format!("{:.2}", stats.average_fan_out())),
+            ("max_fan_in", stats.max_fan_in().to_string()),
+            ("avg_fan_in", format!("{:.2}", stats.average_fan_in())),
+            ("variables", stats.n_vars().to_string()),
+            ("decision_variables", stats.n_decision_vars().to_string()),
+            ("root_free_variables", stats.n_root_free_vars().to_string()),
+            ("or_free_variables", stats.n_or_free_vars().to_string()),
+            (
+                "decomposition_width",
+                stats.decomposition_width().to_string(),
+            ),
+            ("components", stats.component_sizes().len().to_string()),
+        ];
+        let component_sizes = stats
+            .component_sizes()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        if arg_matches.is_present(ARG_JSON) {
+            let fields = metrics
+                .iter()
+                .map(|(key, value)| format!(r#""{key}":{value}"#))
+                .collect::<Vec<_>>();
+            writeln!(
+                output,
+                r#"{{{},"component_sizes":[{}]}}"#,
+                fields.join(","),
+                component_sizes.join(",")
+            )?;
+        } else {
+            for (key, value) in metrics {
+                writeln!(output, "{key}: {value}")?;
+            }
+            writeln!(output, "component_sizes: {}", component_sizes.join(" "))?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
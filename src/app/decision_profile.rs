@@ -0,0 +1,58 @@
+use super::{cli_manager, common};
+use clap::{App, AppSettings, ArgMatches, SubCommand};
+use decdnnf_rs::DecisionProfile;
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "decision-profile";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("prints the depths at which each variable is decided, as CSV")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let profile = DecisionProfile::compute(&ddnnf);
+        let mut writer = common::output(arg_matches)?;
+        writeln!(writer, "variable,n_decisions,min_depth,avg_depth,max_depth")?;
+        for var in 0..ddnnf.n_vars() {
+            let depths = profile.decision_depths(var);
+            if depths.is_empty() {
+                writeln!(writer, "{},0,,,", var + 1)?;
+            } else {
+                writeln!(
+                    writer,
+                    "{},{},{},{:.2},{}",
+                    var + 1,
+                    depths.len(),
+                    profile.min_depth(var).unwrap(),
+                    profile.average_depth(var).unwrap(),
+                    profile.max_depth(var).unwrap()
+                )?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use super::{cli_manager, common, model_counting, translation};
+use clap::{App, AppSettings, ArgMatches, Shell, SubCommand};
+use decdnnf_rs::{CheckLevel, Format, ModelFormat};
+use std::io::{self, Write};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "list-capabilities";
+
+/// The enumeration strategies, named after the options of the model enumeration command that select them.
+const ENUMERATION_STRATEGIES: [&str; 5] = [
+    "default",
+    "compact-free-vars",
+    "decision-tree",
+    "per-branch-quota",
+    "vars",
+];
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about(
+                "prints the formats, arithmetics, strategies and features supported by this binary",
+            )
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let features = [
+            ("cookbook", cfg!(feature = "cookbook")),
+            ("naive-compiler", cfg!(feature = "naive-compiler")),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect::<Vec<_>>();
+        print_capability(&mut output, "input-formats", &Format::NAMES)?;
+        print_capability(&mut output, "output-formats", &translation::OUTPUT_FORMATS)?;
+        print_capability(&mut output, "check-levels", &CheckLevel::NAMES)?;
+        print_capability(&mut output, "arithmetics", &model_counting::ARITH_NAMES)?;
+        print_capability(
+            &mut output,
+            "enumeration-strategies",
+            &ENUMERATION_STRATEGIES,
+        )?;
+        print_capability(&mut output, "model-styles", &ModelFormat::NAMES)?;
+        print_capability(&mut output, "completion-shells", &Shell::variants())?;
+        print_capability(&mut output, "features", &features)?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+fn print_capability(output: &mut dyn Write, name: &str, values: &[&str]) -> io::Result<()> {
+    write!(output, "{name}:")?;
+    for v in values {
+        write!(output, " {v}")?;
+    }
+    writeln!(output)
+}
@@ -1,10 +1,38 @@
 pub(crate) mod app_helper;
 
+mod atomic_sets;
+pub(crate) use atomic_sets::Command as AtomicSetsCommand;
+
+mod bench;
+pub(crate) use bench::Command as BenchCommand;
+
 pub(crate) mod cli_manager;
 
 pub(crate) mod command;
 
+mod cnf_validation;
+pub(crate) use cnf_validation::Command as CnfValidationCommand;
+
 mod common;
+pub(crate) use common::stack_size_from_args;
+
+mod compare_model_streams;
+pub(crate) use compare_model_streams::Command as CompareModelStreamsCommand;
+
+mod completions;
+pub(crate) use completions::Command as CompletionsCommand;
+
+mod cone_of_influence;
+pub(crate) use cone_of_influence::Command as ConeOfInfluenceCommand;
+
+mod dag_statistics;
+pub(crate) use dag_statistics::Command as DagStatisticsCommand;
+
+mod decision_profile;
+pub(crate) use decision_profile::Command as DecisionProfileCommand;
+
+mod list_capabilities;
+pub(crate) use list_capabilities::Command as ListCapabilitiesCommand;
 
 mod model_computer;
 pub(crate) use model_computer::Command as ModelComputerCommand;
@@ -15,7 +43,27 @@ pub(crate) use model_counting::Command as ModelCountingCommand;
 mod model_enumeration;
 pub(crate) use model_enumeration::Command as ModelEnumerationCommand;
 
+mod model_template;
+
+mod node_counts;
+pub(crate) use node_counts::Command as NodeCountsCommand;
+
+mod reconfiguration;
+pub(crate) use reconfiguration::Command as ReconfigurationCommand;
+
+mod self_test;
+pub(crate) use self_test::Command as SelfTestCommand;
+
+mod similarity;
+pub(crate) use similarity::Command as SimilarityCommand;
+
+mod statistics;
+pub(crate) use statistics::Command as StatisticsCommand;
+
 mod translation;
 pub(crate) use translation::Command as TranslationCommand;
 
+mod verify_count_certificate;
+pub(crate) use verify_count_certificate::Command as VerifyCountCertificateCommand;
+
 pub(crate) mod writable_string;
@@ -3,7 +3,8 @@ use super::common;
 use clap::App;
 use clap::ArgMatches;
 use clap::{AppSettings, Arg, SubCommand};
-use decdnnf_rs::{BottomUpTraversal, CheckingVisitor, Literal, ModelFinder};
+use decdnnf_rs::{Literal, ModelFinder};
+use std::io::Write;
 
 #[derive(Default)]
 pub struct Command;
@@ -22,7 +23,16 @@ impl<'a> super::command::Command<'a> for Command {
             .about("returns a model of the formula")
             .setting(AppSettings::DisableVersion)
             .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
             .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
             .arg(
                 Arg::with_name(ARG_ASSUMPTIONS)
                     .short("a")
@@ -36,11 +46,7 @@ impl<'a> super::command::Command<'a> for Command {
     }
 
     fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
-        let ddnnf = common::read_input_ddnnf(arg_matches)?;
-        let traversal_visitor = Box::<CheckingVisitor>::default();
-        let traversal_engine = BottomUpTraversal::new(traversal_visitor);
-        let checking_data = traversal_engine.traverse(&ddnnf);
-        common::print_warnings_and_errors(&checking_data)?;
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
         let assumptions = if let Some(str_assumptions) = arg_matches.value_of(ARG_ASSUMPTIONS) {
             str_assumptions
                 .split_whitespace()
@@ -49,13 +55,15 @@ impl<'a> super::command::Command<'a> for Command {
         } else {
             vec![]
         };
+        let mut output = common::output(arg_matches)?;
         let model_finder = ModelFinder::new(&ddnnf);
         if let Some(model) = model_finder.find_model_under_assumptions(&assumptions) {
-            println!("s SATISFIABLE");
-            common::print_dimacs_model(&model);
+            writeln!(output, "s SATISFIABLE")?;
+            common::write_dimacs_model(&mut output, &model)?;
         } else {
-            println!("s UNSATISFIABLE");
+            writeln!(output, "s UNSATISFIABLE")?;
         }
+        output.flush()?;
         Ok(())
     }
 }
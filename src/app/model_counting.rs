@@ -1,12 +1,44 @@
-use super::{cli_manager, common};
-use clap::{App, AppSettings, ArgMatches, SubCommand};
-use decdnnf_rs::{BiBottomUpVisitor, BottomUpTraversal, CheckingVisitor, ModelCountingVisitor};
+use super::{cli_manager, common, model_enumeration};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{
+    BiBottomUpVisitor, CachedBottomUpTraversal, CheckLevel, DerivedVariables, HammingBallCounter,
+    HybridModelCounter, Literal, ModelCountBounds, ModelCounter, ModelCountingVisitor, NegatedView,
+    NodeIndex, ProjectedModelCounter,
+};
+use log::info;
+use rug::Integer;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    str::FromStr,
+};
 
 #[derive(Default)]
 pub struct Command;
 
 const CMD_NAME: &str = "model-counting";
 
+const ARG_NEAR: &str = "ARG_NEAR";
+const ARG_DISTANCE: &str = "ARG_DISTANCE";
+const ARG_ARITH: &str = "ARG_ARITH";
+const ARG_CERTIFICATE: &str = "ARG_CERTIFICATE";
+const ARG_PROJECTED_VARS: &str = "ARG_PROJECTED_VARS";
+const ARG_DEFINE: &str = "ARG_DEFINE";
+const ARG_COUNT_WHERE: &str = "ARG_COUNT_WHERE";
+const ARG_EXPECTED_COUNT: &str = "ARG_EXPECTED_COUNT";
+const ARG_BOUNDS: &str = "ARG_BOUNDS";
+const ARG_NON_MODELS: &str = "ARG_NON_MODELS";
+const ARG_ASSUMPTIONS: &str = "ARG_ASSUMPTIONS";
+const ARG_PARTIAL_MODELS: &str = "ARG_PARTIAL_MODELS";
+const ARG_THREADS: &str = "ARG_THREADS";
+
+const ARITH_BIGINT: &str = "bigint";
+const ARITH_HYBRID: &str = "hybrid";
+
+/// The names of the arithmetics available to count the models.
+pub(crate) const ARITH_NAMES: [&str; 2] = [ARITH_HYBRID, ARITH_BIGINT];
+
 impl<'a> super::command::Command<'a> for Command {
     fn name(&self) -> &str {
         CMD_NAME
@@ -17,20 +49,388 @@ impl<'a> super::command::Command<'a> for Command {
             .about("counts the models of the formula")
             .setting(AppSettings::DisableVersion)
             .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
             .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
             .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_NEAR)
+                    .long("near")
+                    .empty_values(false)
+                    .multiple(false)
+                    .requires(ARG_DISTANCE)
+                    .help("only count the models close to the full assignment given in this file"),
+            )
+            .arg(
+                Arg::with_name(ARG_DISTANCE)
+                    .long("distance")
+                    .empty_values(false)
+                    .multiple(false)
+                    .requires(ARG_NEAR)
+                    .help("the maximal Hamming distance to the assignment given by --near"),
+            )
+            .arg(
+                Arg::with_name(ARG_ARITH)
+                    .long("arith")
+                    .empty_values(false)
+                    .multiple(false)
+                    .possible_values(&ARITH_NAMES)
+                    .default_value(ARITH_HYBRID)
+                    .help("the arithmetic used to count (hybrid uses big integers only where machine integers overflow)"),
+            )
+            .arg(
+                Arg::with_name(ARG_CERTIFICATE)
+                    .long("certificate")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with(ARG_NEAR)
+                    .help("writes a certificate of the model count into this file (see the verify-count-certificate command)"),
+            )
+            .arg(
+                Arg::with_name(ARG_PROJECTED_VARS)
+                    .long("projected-vars")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with_all(&[ARG_NEAR, ARG_CERTIFICATE])
+                    .help("counts the models projected onto these variables (DIMACS indices, e.g. \"1 2 5\")"),
+            )
+            .arg(common::arg_vars().conflicts_with_all(&[
+                ARG_NEAR,
+                ARG_CERTIFICATE,
+                ARG_PROJECTED_VARS,
+                ARG_COUNT_WHERE,
+                ARG_EXPECTED_COUNT,
+                ARG_BOUNDS,
+                ARG_NON_MODELS,
+                ARG_ASSUMPTIONS,
+                ARG_PARTIAL_MODELS,
+                ARG_THREADS,
+            ]))
+            .arg(
+                Arg::with_name(ARG_DEFINE)
+                    .long("define")
+                    .empty_values(false)
+                    .multiple(true)
+                    .number_of_values(1)
+                    .requires(ARG_COUNT_WHERE)
+                    .help("defines a derived variable as a Boolean function of the variables (e.g. \"premium := x3 AND NOT x9\")"),
+            )
+            .arg(
+                Arg::with_name(ARG_COUNT_WHERE)
+                    .long("count-where")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with_all(&[ARG_NEAR, ARG_CERTIFICATE, ARG_PROJECTED_VARS])
+                    .help("only counts the models satisfying this expression over the variables and the derived ones"),
+            )
+            .arg(
+                Arg::with_name(ARG_EXPECTED_COUNT)
+                    .long("expected-count")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with_all(&[ARG_NEAR, ARG_PROJECTED_VARS, ARG_COUNT_WHERE])
+                    .help("fails if the model count differs from this one (e.g. the count given by the compiler)"),
+            )
+            .arg(arg_bounds())
+            .arg(arg_non_models())
+            .arg(arg_assumptions())
+            .arg(arg_partial_models())
+            .arg(arg_threads())
     }
 
     fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
-        let ddnnf = common::read_input_ddnnf(arg_matches)?;
+        let mut output = common::output(arg_matches)?;
+        let result = count_models(arg_matches, &mut output);
+        output.flush()?;
+        result
+    }
+}
+
+fn count_models(arg_matches: &ArgMatches<'_>, output: &mut dyn Write) -> anyhow::Result<()> {
+    if let Some(near_file) = arg_matches.value_of(ARG_NEAR) {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let center = common::read_model_file(near_file, ddnnf.n_vars())?;
+        let max_distance = str::parse::<usize>(arg_matches.value_of(ARG_DISTANCE).unwrap())
+            .context("while parsing the distance provided on the command line")?;
+        writeln!(
+            output,
+            "{}",
+            HammingBallCounter::new(&ddnnf).count(&center, max_distance)
+        )?;
+        return Ok(());
+    }
+    if arg_matches.is_present(ARG_BOUNDS) {
+        return print_bounds(arg_matches, output);
+    }
+    if arg_matches.is_present(ARG_NON_MODELS) {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        writeln!(output, "{}", NegatedView::new(&ddnnf).count())?;
+        return Ok(());
+    }
+    if arg_matches.is_present(ARG_ASSUMPTIONS) || arg_matches.is_present(ARG_PARTIAL_MODELS) {
+        return count_under_assumptions(arg_matches, output);
+    }
+    if let Some(certificate_path) = arg_matches.value_of(ARG_CERTIFICATE) {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let counter = ModelCounter::new(&ddnnf);
+        let file = File::create(certificate_path).with_context(|| {
+            format!(r#"while creating the certificate file "{certificate_path}""#)
+        })?;
+        counter
+            .write_certificate(&mut BufWriter::new(file))
+            .context("while writing the certificate")?;
+        info!("wrote the certificate into {certificate_path}");
+        writeln!(output, "{}", counter.count())?;
+        return check_expected_count(arg_matches, counter.count());
+    }
+    if let Some(str_vars) = arg_matches.value_of(ARG_PROJECTED_VARS) {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let context = "while parsing the projected variables provided on the command line";
+        let literals = common::parse_literals(str_vars, ddnnf.n_vars()).context(context)?;
+        if let Some(l) = literals.iter().find(|l| !l.polarity()) {
+            return Err(anyhow!("expected a variable index, got {l}")).context(context);
+        }
+        let projected_vars = literals.iter().map(Literal::var_index).collect::<Vec<_>>();
+        writeln!(
+            output,
+            "{}",
+            ProjectedModelCounter::new(&ddnnf, &projected_vars).count()?
+        )?;
+        return Ok(());
+    }
+    if let Some(str_expression) = arg_matches.value_of(ARG_COUNT_WHERE) {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let mut derived = DerivedVariables::new(&ddnnf);
+        for definition in arg_matches.values_of(ARG_DEFINE).into_iter().flatten() {
+            derived
+                .define(definition)
+                .with_context(|| format!(r#"while parsing the definition "{definition}""#))?;
+        }
+        let expression = derived
+            .parse(str_expression)
+            .context("while parsing the expression given to --count-where")?;
+        writeln!(output, "{}", expression.count(&ModelCounter::new(&ddnnf)))?;
+        return Ok(());
+    }
+    if common::has_var_range(arg_matches) {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let vars = common::var_range(arg_matches, ddnnf.n_vars())?.unwrap();
+        let projected_vars = vars.clone().collect::<Vec<_>>();
+        match ProjectedModelCounter::new(&ddnnf, &projected_vars).count() {
+            Ok(n) => writeln!(output, "{n}")?,
+            Err(e) => {
+                info!("{e}; counting the projected models by enumerating them");
+                let mut n = Integer::from(0);
+                model_enumeration::decision_tree_enumeration(&ddnnf, vars, |_| {
+                    n += 1;
+                    true
+                });
+                writeln!(output, "{n}")?;
+            }
+        }
+        return Ok(());
+    }
+    if let Some(str_threads) = arg_matches.value_of(ARG_THREADS) {
+        return count_by_components(arg_matches, str_threads, output);
+    }
+    if arg_matches.value_of(ARG_ARITH).unwrap() == ARITH_HYBRID {
+        // the hybrid counter has no visitor form, so the formula is checked in a traversal of its own
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let counter = HybridModelCounter::new(&ddnnf);
+        let n_models = counter.count();
+        info!(
+            "{} node(s) required big integers",
+            counter.n_overflowing_nodes()
+        );
+        writeln!(output, "{n_models}")?;
+        return check_expected_count(arg_matches, &n_models);
+    }
+    count_with_traversal(arg_matches, output)
+}
+
+/// Counts the models with the bottom-up traversal, which checks the formula at the same time if required.
+fn count_with_traversal(
+    arg_matches: &ArgMatches<'_>,
+    output: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let ddnnf = common::read_input_ddnnf(arg_matches)?;
+    let check_level = common::check_level(arg_matches);
+    let model_counting_data = if check_level >= CheckLevel::Decomposability {
         let traversal_visitor = BiBottomUpVisitor::new(
-            Box::<CheckingVisitor>::default(),
+            Box::new(common::checking_visitor(arg_matches)),
             Box::<ModelCountingVisitor>::default(),
         );
-        let traversal_engine = BottomUpTraversal::new(Box::new(traversal_visitor));
+        let traversal_engine = CachedBottomUpTraversal::new(Box::new(traversal_visitor));
         let (checking_data, model_counting_data) = traversal_engine.traverse(&ddnnf);
-        common::print_warnings_and_errors(&checking_data)?;
-        println!("{}", model_counting_data.n_models());
+        common::print_warnings_and_errors(
+            &ddnnf,
+            &checking_data,
+            check_level,
+            common::warning_refutation(arg_matches),
+        )?;
+        model_counting_data
+    } else {
+        CachedBottomUpTraversal::new(Box::<ModelCountingVisitor>::default()).traverse(&ddnnf)
+    };
+    writeln!(output, "{}", model_counting_data.n_models())?;
+    check_expected_count(arg_matches, model_counting_data.n_models())
+}
+
+/// The argument used to compute bounds of the model count instead of the exact one.
+fn arg_bounds<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_BOUNDS)
+        .long("bounds")
+        .takes_value(false)
+        .conflicts_with_all(&[
+            ARG_NEAR,
+            ARG_CERTIFICATE,
+            ARG_PROJECTED_VARS,
+            ARG_COUNT_WHERE,
+            ARG_EXPECTED_COUNT,
+        ])
+        .help("computes lower and upper bounds of the model count, which remain valid if some disjunction nodes are not deterministic")
+}
+
+/// The argument used to count the non-models instead of the models.
+fn arg_non_models<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_NON_MODELS)
+        .long("non-models")
+        .takes_value(false)
+        .conflicts_with_all(&[
+            ARG_NEAR,
+            ARG_CERTIFICATE,
+            ARG_PROJECTED_VARS,
+            ARG_COUNT_WHERE,
+            ARG_EXPECTED_COUNT,
+            ARG_BOUNDS,
+        ])
+        .help("counts the full assignments that are not models of the formula")
+}
+
+/// The argument used to count the models consistent with some assumptions.
+fn arg_assumptions<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_ASSUMPTIONS)
+        .short("a")
+        .long("assumptions")
+        .empty_values(false)
+        .multiple(false)
+        .allow_hyphen_values(true)
+        .conflicts_with_all(&[
+            ARG_NEAR,
+            ARG_CERTIFICATE,
+            ARG_PROJECTED_VARS,
+            ARG_COUNT_WHERE,
+            ARG_EXPECTED_COUNT,
+            ARG_BOUNDS,
+            ARG_NON_MODELS,
+        ])
+        .help("only counts the models consistent with some assumptions, given as a string of blank separated DIMACS literals")
+}
+
+/// The argument used to count the partial models instead of the models.
+fn arg_partial_models<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_PARTIAL_MODELS)
+        .long("partial-models")
+        .takes_value(false)
+        .conflicts_with_all(&[
+            ARG_NEAR,
+            ARG_CERTIFICATE,
+            ARG_PROJECTED_VARS,
+            ARG_COUNT_WHERE,
+            ARG_EXPECTED_COUNT,
+            ARG_BOUNDS,
+            ARG_NON_MODELS,
+        ])
+        .help("counts the partial models, in which the free variables are eluded, instead of the models (one for each path of the formula)")
+}
+
+/// The argument used to count the models of the independent components of the formula in parallel.
+fn arg_threads<'a>() -> Arg<'a, 'a> {
+    Arg::with_name(ARG_THREADS)
+        .long("threads")
+        .empty_values(false)
+        .multiple(false)
+        .conflicts_with_all(&[
+            ARG_NEAR,
+            ARG_CERTIFICATE,
+            ARG_PROJECTED_VARS,
+            ARG_COUNT_WHERE,
+            ARG_BOUNDS,
+            ARG_NON_MODELS,
+            ARG_ASSUMPTIONS,
+            ARG_PARTIAL_MODELS,
+        ])
+        .help("counts the models of the independent components of the formula using this number of threads")
+}
+
+/// Counts the models or the partial models consistent with the assumptions given on the command line.
+fn count_under_assumptions(
+    arg_matches: &ArgMatches<'_>,
+    output: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+    let assumptions = arg_matches
+        .value_of(ARG_ASSUMPTIONS)
+        .map(|str_literals| common::parse_literals(str_literals, ddnnf.n_vars()))
+        .transpose()
+        .context("while parsing the assumptions provided on the command line")?
+        .unwrap_or_default();
+    let counter = ModelCounter::new(&ddnnf);
+    let n = if arg_matches.is_present(ARG_PARTIAL_MODELS) {
+        counter.count_partial_models_under_assumptions(&assumptions)
+    } else {
+        counter.count_from_under_assumptions(NodeIndex::from(0), &assumptions)
+    };
+    writeln!(output, "{n}")?;
+    Ok(())
+}
+
+/// Counts the models of the independent components of the formula in parallel.
+fn count_by_components(
+    arg_matches: &ArgMatches<'_>,
+    str_threads: &str,
+    output: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let n_threads = str::parse::<usize>(str_threads)
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| anyhow!("expected a positive number of threads, got {str_threads}"))?;
+    let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+    let n_models = ModelCounter::count_by_components(&ddnnf, n_threads);
+    writeln!(output, "{n_models}")?;
+    check_expected_count(arg_matches, &n_models)
+}
+
+fn print_bounds(arg_matches: &ArgMatches<'_>, output: &mut dyn Write) -> anyhow::Result<()> {
+    let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+    let bounds = ModelCountBounds::new(&ddnnf);
+    if bounds.is_exact() {
+        info!("the bounds are equal, so they give the exact model count");
+    }
+    writeln!(output, "lower bound: {}", bounds.lower_bound())?;
+    writeln!(output, "upper bound: {}", bounds.upper_bound())?;
+    Ok(())
+}
+
+fn check_expected_count(arg_matches: &ArgMatches<'_>, n_models: &Integer) -> anyhow::Result<()> {
+    let Some(str_expected) = arg_matches.value_of(ARG_EXPECTED_COUNT) else {
+        return Ok(());
+    };
+    let expected = Integer::from_str(str_expected)
+        .context("while parsing the expected count provided on the command line")?;
+    if *n_models == expected {
+        info!("the model count matches the expected one");
         Ok(())
+    } else {
+        Err(anyhow!(
+            "the model count ({n_models}) differs from the expected one ({expected}); the formula may be truncated or miscompiled"
+        ))
     }
 }
@@ -1,33 +1,66 @@
-use super::{cli_manager, common};
+use super::{
+    cli_manager, common,
+    model_template::{ModelTemplate, VarValue},
+};
+use anyhow::{anyhow, Context};
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use decdnnf_rs::{
-    BottomUpTraversal, CheckingVisitor, DecisionDNNF, Literal, ModelEnumerator, ModelFinder,
+    models::ModelDigest, DecisionDNNF, Literal, ModelEnumerator, ModelFinder, ModelFormat,
+    ModelWriter, QuotaModelEnumerator,
 };
 use log::info;
 use rug::Integer;
-use std::io::{BufWriter, StdoutLock, Write};
+use std::{io::Write, ops::Range, str::FromStr};
 
 #[derive(Default)]
 pub struct Command;
 
 const CMD_NAME: &str = "model-enumeration";
 
+const ARG_BLOCK: &str = "ARG_BLOCK";
 const ARG_COMPACT_FREE_VARS: &str = "ARG_COMPACT_FREE_VARS";
 const ARG_DECISION_TREE: &str = "ARG_DECISION_TREE";
+const ARG_DIGEST: &str = "ARG_DIGEST";
 const ARG_DO_NOT_PRINT: &str = "ARG_DO_NOT_PRINT";
+const ARG_FREE_VAR_SYMBOL: &str = "ARG_FREE_VAR_SYMBOL";
+const ARG_MAX_MODELS: &str = "ARG_MAX_MODELS";
+const ARG_MODEL_STYLE: &str = "ARG_MODEL_STYLE";
+const ARG_MODEL_TEMPLATE: &str = "ARG_MODEL_TEMPLATE";
+const ARG_PER_BRANCH_QUOTA: &str = "ARG_PER_BRANCH_QUOTA";
+const ARG_PRINT_CHOICES: &str = "ARG_PRINT_CHOICES";
 
 impl<'a> super::command::Command<'a> for Command {
     fn name(&self) -> &str {
         CMD_NAME
     }
 
+    #[allow(clippy::too_many_lines)]
     fn clap_subcommand(&self) -> App<'a, 'a> {
         SubCommand::with_name(CMD_NAME)
             .about("enumerates the models of the formula")
             .setting(AppSettings::DisableVersion)
             .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
             .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
             .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_BLOCK)
+                    .long("block")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with_all(&[ARG_COMPACT_FREE_VARS, ARG_DECISION_TREE])
+                    .help(
+                        "a file of cubes (one per line); the models they cover are not enumerated",
+                    ),
+            )
             .arg(
                 Arg::with_name(ARG_COMPACT_FREE_VARS)
                     .short("c")
@@ -48,173 +81,374 @@ impl<'a> super::command::Command<'a> for Command {
                     .takes_value(false)
                     .help("do not print the models (for testing purpose)"),
             )
+            .arg(
+                Arg::with_name(ARG_DIGEST)
+                    .long("digest")
+                    .takes_value(false)
+                    .conflicts_with_all(&[ARG_DO_NOT_PRINT, ARG_PRINT_CHOICES])
+                    .help("print the number of models and an order-independent digest of them instead of the models"),
+            )
+            .arg(
+                Arg::with_name(ARG_MODEL_STYLE)
+                    .long("model-style")
+                    .empty_values(false)
+                    .multiple(false)
+                    .possible_values(&ModelFormat::NAMES)
+                    .default_value(ModelFormat::NAMES[0])
+                    .help("the way models are displayed: DIMACS literals, a 0/1 value per variable, the positive literals only, CSV rows or JSON arrays"),
+            )
+            .arg(
+                Arg::with_name(ARG_MODEL_TEMPLATE)
+                    .long("model-template")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with_all(&[ARG_DIGEST, ARG_DO_NOT_PRINT])
+                    .help("a template used to print each model instead of the model style (e.g. \"cfg({index}): {+lits_csv}\", see the README for the placeholders)"),
+            )
+            .arg(
+                Arg::with_name(ARG_FREE_VAR_SYMBOL)
+                    .long("free-var-symbol")
+                    .empty_values(false)
+                    .multiple(false)
+                    .default_value("*")
+                    .help("the character used to display the eluded free variables"),
+            )
+            .arg(
+                Arg::with_name(ARG_PRINT_CHOICES)
+                    .long("print-choices")
+                    .takes_value(false)
+                    .conflicts_with(ARG_DECISION_TREE)
+                    .help("print after each model the indices of the children chosen at the OR nodes, encoded as a mixed-radix integer"),
+            )
+            .arg(
+                Arg::with_name(ARG_PER_BRANCH_QUOTA)
+                    .long("per-branch-quota")
+                    .empty_values(false)
+                    .multiple(false)
+                    .conflicts_with_all(&[
+                        ARG_BLOCK,
+                        ARG_COMPACT_FREE_VARS,
+                        ARG_DECISION_TREE,
+                        ARG_PRINT_CHOICES,
+                    ])
+                    .help("only take this number of models from each child of each OR node, to get a diverse subset of models"),
+            )
+            .arg(
+                Arg::with_name(ARG_MAX_MODELS)
+                    .long("max-models")
+                    .empty_values(false)
+                    .multiple(false)
+                    .requires(ARG_PER_BRANCH_QUOTA)
+                    .help("the maximal number of models to enumerate with --per-branch-quota"),
+            )
+            .arg(common::arg_vars().conflicts_with_all(&[
+                ARG_BLOCK,
+                ARG_COMPACT_FREE_VARS,
+                ARG_DECISION_TREE,
+                ARG_PER_BRANCH_QUOTA,
+                ARG_PRINT_CHOICES,
+            ]))
     }
 
     fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
-        if arg_matches.is_present(ARG_DECISION_TREE) {
+        if arg_matches.is_present(ARG_DECISION_TREE) || common::has_var_range(arg_matches) {
             enum_decision_tree(arg_matches)
+        } else if arg_matches.is_present(ARG_PER_BRANCH_QUOTA) {
+            enum_with_quota(arg_matches)
         } else {
             enum_default(arg_matches)
         }
     }
 }
 
-fn enum_default(arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
-    let ddnnf = load_ddnnf(arg_matches)?;
-    let mut model_writer = ModelWriter::new(
-        ddnnf.n_vars(),
+fn create_model_writer(
+    vars: Range<usize>,
+    arg_matches: &ArgMatches<'_>,
+) -> anyhow::Result<ModelPrinter<Box<dyn Write>>> {
+    let str_symbol = arg_matches.value_of(ARG_FREE_VAR_SYMBOL).unwrap();
+    let free_var_symbol = match str_symbol.as_bytes() {
+        [c] if c.is_ascii_graphic() => *c,
+        _ => {
+            return Err(anyhow!(
+                r#"the free variable symbol must be a single printable ASCII character, got "{str_symbol}""#
+            ))
+        }
+    };
+    let mut model_writer = ModelPrinter::new(
+        common::output(arg_matches)?,
+        vars.clone(),
         arg_matches.is_present(ARG_COMPACT_FREE_VARS),
         arg_matches.is_present(ARG_DO_NOT_PRINT),
+        ModelFormat::from_str(arg_matches.value_of(ARG_MODEL_STYLE).unwrap())?,
+        free_var_symbol,
     );
+    if arg_matches.is_present(ARG_DIGEST) {
+        model_writer.set_digest();
+    }
+    if let Some(template) = arg_matches.value_of(ARG_MODEL_TEMPLATE) {
+        model_writer.set_template(
+            ModelTemplate::new(template, vars, free_var_symbol)
+                .context("while compiling the model template provided on the command line")?,
+        );
+    }
+    Ok(model_writer)
+}
+
+fn enum_default(arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+    let mut model_writer = create_model_writer(0..ddnnf.n_vars(), arg_matches)?;
+    let print_choices = arg_matches.is_present(ARG_PRINT_CHOICES);
     let mut model_iterator =
         ModelEnumerator::new(&ddnnf, arg_matches.is_present(ARG_COMPACT_FREE_VARS));
+    if let Some(file_path) = arg_matches.value_of(ARG_BLOCK) {
+        let blocking_cubes = common::read_cubes_file(file_path, ddnnf.n_vars())?;
+        info!(
+            "blocking the models covered by {} cube(s)",
+            blocking_cubes.len()
+        );
+        model_iterator.set_blocking_cubes(blocking_cubes);
+    }
     while let Some(model) = model_iterator.compute_next_model() {
         model_writer.write_model_ordered(model);
+        if print_choices {
+            model_writer.write_choices(&model_iterator.current_encoded_choices());
+        }
     }
     model_writer.finalize();
     Ok(())
 }
 
 fn enum_decision_tree(arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
-    let ddnnf = load_ddnnf(arg_matches)?;
-    let mut model_writer = ModelWriter::new(
-        ddnnf.n_vars(),
-        arg_matches.is_present(ARG_COMPACT_FREE_VARS),
-        arg_matches.is_present(ARG_DO_NOT_PRINT),
-    );
-    let model_finder = ModelFinder::new(&ddnnf);
-    let mut assumptions = Vec::with_capacity(ddnnf.n_vars());
-    let mut stack = Vec::with_capacity(ddnnf.n_vars() << 1);
+    let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+    let vars = common::var_range(arg_matches, ddnnf.n_vars())?.unwrap_or(0..ddnnf.n_vars());
+    let mut model_writer = create_model_writer(vars.clone(), arg_matches)?;
+    decision_tree_enumeration(&ddnnf, vars, |model| {
+        model_writer.write_model_no_opt(model);
+        true
+    });
+    model_writer.finalize();
+    Ok(())
+}
+
+fn enum_with_quota(arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+    let per_branch_quota = str::parse::<usize>(arg_matches.value_of(ARG_PER_BRANCH_QUOTA).unwrap())
+        .context("while parsing the per branch quota provided on the command line")?;
+    let max_models = arg_matches
+        .value_of(ARG_MAX_MODELS)
+        .map(str::parse::<usize>)
+        .transpose()
+        .context("while parsing the maximal number of models provided on the command line")?
+        .unwrap_or(usize::MAX);
+    let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+    let mut model_writer = create_model_writer(0..ddnnf.n_vars(), arg_matches)?;
+    for model in QuotaModelEnumerator::new(&ddnnf, per_branch_quota, max_models).enumerate() {
+        model_writer.write_model_no_opt(&model);
+    }
+    model_writer.finalize();
+    Ok(())
+}
+
+/// Enumerates the models by building a decision tree, calling the callback on each of them.
+///
+/// The decision tree only decides on the variables of the given range, so that the models are projected onto them;
+/// each projected model is given once, as the literals of the range in the order of the variables.
+/// The enumeration stops as soon as the callback returns `false`.
+pub(crate) fn decision_tree_enumeration<F>(
+    ddnnf: &DecisionDNNF,
+    vars: Range<usize>,
+    mut callback: F,
+) where
+    F: FnMut(&[Literal]) -> bool,
+{
+    let model_finder = ModelFinder::new(ddnnf);
+    let mut assumptions = Vec::with_capacity(vars.len());
+    let mut stack = Vec::with_capacity(vars.len() << 1);
     let mut last_model = vec![];
     let update_stack = |m: &[Literal], i, stack: &mut Vec<(bool, Literal)>| {
-        let shortcut_lit = *m.iter().find(|l| l.var_index() == i).unwrap();
+        let shortcut_lit = *m.iter().find(|l| l.var_index() == vars.start + i).unwrap();
         stack.push((false, shortcut_lit.flip()));
         stack.push((true, shortcut_lit));
     };
     if let Some(ref mut model) = model_finder.find_model() {
         std::mem::swap(&mut last_model, model);
-        if ddnnf.n_vars() == 0 {
-            model_writer.write_model_no_opt(&[]);
+        if vars.is_empty() {
+            callback(&[]);
         } else {
             update_stack(&last_model, 0, &mut stack);
         }
     }
     while let Some((shortcut, lit)) = stack.pop() {
-        assumptions.truncate(lit.var_index());
+        assumptions.truncate(lit.var_index() - vars.start);
         assumptions.push(lit);
-        if shortcut {
-            if assumptions.len() == ddnnf.n_vars() {
-                model_writer.write_model_no_opt(&last_model);
-            } else {
-                update_stack(&last_model, assumptions.len(), &mut stack);
+        if !shortcut {
+            let Some(mut new_model) = model_finder.find_model_under_assumptions(&assumptions)
+            else {
+                continue;
+            };
+            std::mem::swap(&mut last_model, &mut new_model);
+        }
+        if assumptions.len() == vars.len() {
+            if !callback(&assumptions) {
+                return;
             }
         } else {
-            let opt_model = model_finder.find_model_under_assumptions(&assumptions);
-            if opt_model.is_some() {
-                let mut new_model = opt_model.unwrap();
-                std::mem::swap(&mut last_model, &mut new_model);
-                if assumptions.len() == ddnnf.n_vars() {
-                    model_writer.write_model_no_opt(&last_model);
-                } else {
-                    update_stack(&last_model, assumptions.len(), &mut stack);
-                }
-            }
+            update_stack(&last_model, assumptions.len(), &mut stack);
         }
     }
-    model_writer.finalize();
-    Ok(())
 }
 
-fn load_ddnnf(arg_matches: &ArgMatches<'_>) -> anyhow::Result<DecisionDNNF> {
-    let ddnnf = common::read_input_ddnnf(arg_matches)?;
-    let traversal_visitor = Box::<CheckingVisitor>::default();
-    let traversal_engine = BottomUpTraversal::new(traversal_visitor);
-    let checking_data = traversal_engine.traverse(&ddnnf);
-    common::print_warnings_and_errors(&checking_data)?;
-    Ok(ddnnf)
+/// A counter of models that only resorts to big integers when machine integers overflow.
+#[derive(Default)]
+struct ModelTally {
+    small: u64,
+    big: Integer,
 }
 
-struct ModelWriter {
-    pattern: Vec<u8>,
-    sign_location: Vec<usize>,
-    buf: BufWriter<StdoutLock<'static>>,
-    n_enumerated: Integer,
-    n_models: Integer,
+impl ModelTally {
+    fn add_power_of_two(&mut self, exponent: usize) {
+        if exponent >= 64 {
+            self.big += Integer::from(1) << exponent;
+            return;
+        }
+        let n = 1_u64 << exponent;
+        if let Some(sum) = self.small.checked_add(n) {
+            self.small = sum;
+        } else {
+            self.big += self.small;
+            self.small = n;
+        }
+    }
+
+    fn value(&self) -> Integer {
+        Integer::from(&self.big + self.small)
+    }
+}
+
+/// The writer used by the enumeration commands, which adds the digest, the templates and the statistics to a [`ModelWriter`].
+pub(crate) struct ModelPrinter<W>
+where
+    W: Write,
+{
+    first_var: usize,
+    writer: ModelWriter<W>,
+    n_enumerated: u64,
+    n_models: ModelTally,
     compact_display: bool,
     do_not_print: bool,
+    digest: Option<ModelDigest>,
+    template: Option<ModelTemplate>,
+    var_values: Vec<VarValue>,
 }
 
-impl ModelWriter {
-    fn new(n_vars: usize, compact_display: bool, do_not_print: bool) -> Self {
-        let mut sign_location = Vec::with_capacity(n_vars);
-        let mut pattern = Vec::new();
-        pattern.push(b'v');
-        for i in 1..=n_vars {
-            pattern.push(b' ');
-            sign_location.push(pattern.len());
-            pattern.push(b' ');
-            pattern.extend_from_slice(format!("{i}").as_bytes());
-        }
-        pattern.extend_from_slice(" 0 \n".as_bytes());
+impl<W> ModelPrinter<W>
+where
+    W: Write,
+{
+    /// Builds a writer for the models projected onto the given range of variable indices.
+    pub(crate) fn new(
+        writer: W,
+        vars: Range<usize>,
+        compact_display: bool,
+        do_not_print: bool,
+        format: ModelFormat,
+        free_var_symbol: u8,
+    ) -> Self {
+        let mut model_writer = ModelWriter::new(writer, vars.clone(), format);
+        model_writer.set_free_var_symbol(free_var_symbol);
         Self {
-            pattern,
-            sign_location,
-            buf: BufWriter::with_capacity(128 * 1024, std::io::stdout().lock()),
-            n_enumerated: 0.into(),
-            n_models: 0.into(),
+            first_var: vars.start,
+            writer: model_writer,
+            n_enumerated: 0,
+            n_models: ModelTally::default(),
             compact_display,
             do_not_print,
+            digest: None,
+            template: None,
+            var_values: vec![VarValue::Free; vars.len()],
         }
     }
 
-    fn write_model_ordered(&mut self, model: &[Option<Literal>]) {
+    /// Replaces the printing of the models by the computation of their digest, which is printed when the writer is finalized.
+    pub(crate) fn set_digest(&mut self) {
+        self.do_not_print = true;
+        self.digest = Some(ModelDigest::default());
+    }
+
+    /// Replaces the model style by a template used to print each model.
+    pub(crate) fn set_template(&mut self, template: ModelTemplate) {
+        self.template = Some(template);
+    }
+
+    pub(crate) fn write_model_ordered(&mut self, model: &[Option<Literal>]) {
         self.n_enumerated += 1;
+        if let Some(digest) = self.digest.as_mut() {
+            digest.add_compact_model(model);
+        }
+        let values = &model[self.first_var..self.first_var + self.var_values.len()];
+        self.n_models
+            .add_power_of_two(values.iter().filter(|opt| opt.is_none()).count());
         if self.do_not_print {
-            self.n_models += Integer::from(1) << model.iter().filter(|opt| opt.is_none()).count();
             return;
         }
-        let mut current_n_models = Integer::from(1);
-        model
-            .iter()
-            .zip(self.sign_location.iter())
-            .for_each(|(opt_l, o)| {
-                if let Some(l) = opt_l {
-                    if l.polarity() {
-                        self.pattern[*o] = b' ';
-                    } else {
-                        self.pattern[*o] = b'-';
-                    }
-                } else {
-                    self.pattern[*o] = b'*';
-                    current_n_models <<= 1;
-                }
-            });
-        let _ = self.buf.write_all(&self.pattern);
-        self.n_models += current_n_models;
+        if let Some(template) = &self.template {
+            for (value, opt_l) in self.var_values.iter_mut().zip(values.iter()) {
+                *value = match opt_l {
+                    Some(l) if l.polarity() => VarValue::True,
+                    Some(_) => VarValue::False,
+                    None => VarValue::Free,
+                };
+            }
+            template.render(&mut self.writer, self.n_enumerated - 1, &self.var_values);
+            return;
+        }
+        let _ = self.writer.write_compact_model(model);
     }
 
     fn write_model_no_opt(&mut self, model: &[Literal]) {
         self.n_enumerated += 1;
-        self.n_models += 1;
+        self.n_models.add_power_of_two(0);
+        if let Some(digest) = self.digest.as_mut() {
+            digest.add_model(&model.iter().map(|l| isize::from(*l)).collect::<Vec<_>>());
+        }
         if self.do_not_print {
             return;
         }
-        for l in model {
-            if l.polarity() {
-                self.pattern[self.sign_location[l.var_index()]] = b' ';
-            } else {
-                self.pattern[self.sign_location[l.var_index()]] = b'-';
+        if let Some(template) = &self.template {
+            for l in model {
+                self.var_values[l.var_index() - self.first_var] = if l.polarity() {
+                    VarValue::True
+                } else {
+                    VarValue::False
+                };
             }
+            template.render(&mut self.writer, self.n_enumerated - 1, &self.var_values);
+            return;
         }
-        let _ = self.buf.write_all(&self.pattern);
+        let _ = self.writer.write_model(model);
     }
 
-    fn finalize(mut self) {
-        self.buf.flush().unwrap();
+    fn write_choices(&mut self, encoded_choices: &Integer) {
+        if self.do_not_print {
+            return;
+        }
+        let _ = writeln!(self.writer, "c choices {encoded_choices}");
+    }
+
+    pub(crate) fn finalize(mut self) {
+        if let Some(digest) = &self.digest {
+            let _ = writeln!(self.writer, "models: {}", digest.n_models());
+            let _ = writeln!(self.writer, "digest: {:016x}", digest.value());
+        }
+        if self.do_not_print || self.template.is_some() {
+            self.writer.flush().unwrap();
+        } else {
+            self.writer.finish().and_then(|mut w| w.flush()).unwrap();
+        }
         if self.compact_display {
             info!(
                 "enumerated {} compact models corresponding to {} models",
-                self.n_enumerated, self.n_models
+                self.n_enumerated,
+                self.n_models.value()
             );
         } else {
             info!("enumerated {} models", self.n_enumerated);
@@ -0,0 +1,154 @@
+use anyhow::{anyhow, Result};
+use std::{io::Write, ops::Range};
+
+/// The value of a variable in a model, as given to [`ModelTemplate::render`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VarValue {
+    True,
+    False,
+    Free,
+}
+
+/// The literals a placeholder of a template refers to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LiteralFilter {
+    All,
+    Positive,
+    Negative,
+}
+
+/// A part of a template, precompiled so that rendering a model does not require to parse the template again.
+enum Segment {
+    Text(Vec<u8>),
+    Index,
+    Literals(LiteralFilter, u8),
+    Bits,
+}
+
+/// A template used to print each model on a line.
+///
+/// The template is a text in which the following placeholders are replaced:
+/// - `{index}`: the index of the model in the enumeration, starting at 0;
+/// - `{lits}`: the DIMACS literals of the model, separated by spaces;
+/// - `{+lits}` and `{-lits}`: the positive (resp. negative) literals only;
+/// - `{bits}`: a value by variable, `1` for true and `0` for false.
+///
+/// Appending `_csv` to a literal placeholder (e.g. `{+lits_csv}`) separates the literals by commas.
+/// The eluded free variables are prefixed by the free variable symbol in literal lists, and replaced by it in the bits.
+/// Braces are written by doubling them.
+pub(crate) struct ModelTemplate {
+    segments: Vec<Segment>,
+    labels: Vec<Vec<u8>>,
+    free_var_symbol: u8,
+}
+
+impl ModelTemplate {
+    /// Compiles a template for the models defined on the given range of variable indices.
+    pub(crate) fn new(template: &str, vars: Range<usize>, free_var_symbol: u8) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut text = Vec::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.next_if_eq(&'{').is_some() => text.push(b'{'),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(anyhow!("unclosed placeholder in the template")),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Self::parse_placeholder(&placeholder)?);
+                }
+                '}' if chars.next_if_eq(&'}').is_some() => text.push(b'}'),
+                '}' => {
+                    return Err(anyhow!(
+                        r#"unexpected "}}" in the template; use "}}}}" to write a brace"#
+                    ))
+                }
+                _ => {
+                    let mut buf = [0; 4];
+                    text.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+        text.push(b'\n');
+        segments.push(Segment::Text(text));
+        Ok(Self {
+            segments,
+            labels: (vars.start + 1..=vars.end)
+                .map(|i| format!("{i}").into_bytes())
+                .collect(),
+            free_var_symbol,
+        })
+    }
+
+    fn parse_placeholder(placeholder: &str) -> Result<Segment> {
+        if placeholder == "index" {
+            return Ok(Segment::Index);
+        }
+        if placeholder == "bits" {
+            return Ok(Segment::Bits);
+        }
+        let (filter, name) = match placeholder.as_bytes().first() {
+            Some(b'+') => (LiteralFilter::Positive, &placeholder[1..]),
+            Some(b'-') => (LiteralFilter::Negative, &placeholder[1..]),
+            _ => (LiteralFilter::All, placeholder),
+        };
+        match name {
+            "lits" => Ok(Segment::Literals(filter, b' ')),
+            "lits_csv" => Ok(Segment::Literals(filter, b',')),
+            _ => Err(anyhow!(
+                r#"unknown placeholder "{{{placeholder}}}" in the template (expected index, lits, lits_csv or bits, possibly prefixed by + or - for literals)"#
+            )),
+        }
+    }
+
+    /// Writes a model, given by the values of the variables of the range of the template.
+    pub(crate) fn render<W>(&self, writer: &mut W, index: u64, values: &[VarValue])
+    where
+        W: Write,
+    {
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => {
+                    let _ = writer.write_all(text);
+                }
+                Segment::Index => {
+                    let _ = write!(writer, "{index}");
+                }
+                Segment::Literals(filter, separator) => {
+                    let mut first = true;
+                    for (value, label) in values.iter().zip(self.labels.iter()) {
+                        let prefix: &[u8] = match (value, filter) {
+                            (VarValue::True, LiteralFilter::All | LiteralFilter::Positive) => b"",
+                            (VarValue::False, LiteralFilter::All | LiteralFilter::Negative) => b"-",
+                            (VarValue::Free, _) => &[self.free_var_symbol],
+                            _ => continue,
+                        };
+                        if !first {
+                            let _ = writer.write_all(&[*separator]);
+                        }
+                        first = false;
+                        let _ = writer.write_all(prefix);
+                        let _ = writer.write_all(label);
+                    }
+                }
+                Segment::Bits => {
+                    for value in values {
+                        let _ = writer.write_all(&[match value {
+                            VarValue::True => b'1',
+                            VarValue::False => b'0',
+                            VarValue::Free => self.free_var_symbol,
+                        }]);
+                    }
+                }
+            }
+        }
+    }
+}
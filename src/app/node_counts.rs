@@ -0,0 +1,80 @@
+use super::{cli_manager, common};
+use anyhow::Context;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{ModelCounter, Node, NodeIndex};
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "node-counts";
+
+const ARG_TOP: &str = "ARG_TOP";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("prints the number of models of the subformula rooted at each node, as CSV")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_TOP)
+                    .long("top")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("only prints this number of nodes, with the highest model counts first"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let top = arg_matches
+            .value_of(ARG_TOP)
+            .map(|s| {
+                str::parse::<usize>(s)
+                    .context("while parsing the number of nodes provided on the command line")
+            })
+            .transpose()?;
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let counter = ModelCounter::new(&ddnnf);
+        let mut nodes = (0..ddnnf.n_nodes())
+            .map(NodeIndex::from)
+            .collect::<Vec<_>>();
+        if let Some(n) = top {
+            nodes.sort_by(|n0, n1| counter.count_from(*n1).cmp(counter.count_from(*n0)));
+            nodes.truncate(n);
+        }
+        let mut writer = common::output(arg_matches)?;
+        writeln!(writer, "node,kind,n_children,n_models")?;
+        for node in nodes {
+            let (kind, n_children) = match ddnnf.node(node) {
+                Node::And(edges) => ("and", edges.len()),
+                Node::Or(edges) => ("or", edges.len()),
+                Node::True => ("true", 0),
+                Node::False => ("false", 0),
+            };
+            writeln!(
+                writer,
+                "{},{kind},{n_children},{}",
+                usize::from(node),
+                counter.count_from(node)
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use super::cli_manager;
+use super::common;
+use anyhow::{anyhow, Context};
+use clap::App;
+use clap::ArgMatches;
+use clap::{AppSettings, Arg, SubCommand};
+use decdnnf_rs::{Literal, ModelFinder, ReconfigurationPathFinder};
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "reconfiguration";
+
+const ARG_FROM: &str = "ARG_FROM";
+const ARG_TO: &str = "ARG_TO";
+const ARG_MAX_LENGTH: &str = "ARG_MAX_LENGTH";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("searches for a path between two models, changing the value of a single variable at each step")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(
+                Arg::with_name(ARG_FROM)
+                    .long("from")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("a file containing the source model, given as DIMACS literals"),
+            )
+            .arg(
+                Arg::with_name(ARG_TO)
+                    .long("to")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("a file containing the target model, given as DIMACS literals"),
+            )
+            .arg(
+                Arg::with_name(ARG_MAX_LENGTH)
+                    .long("max-length")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("the maximal length of the paths under consideration"),
+            )
+            .arg(cli_manager::logging_level_cli_arg())
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let max_length = arg_matches
+            .value_of(ARG_MAX_LENGTH)
+            .map(str::parse::<usize>)
+            .transpose()
+            .context("while parsing the maximal length provided on the command line")?;
+        let model_finder = ModelFinder::new(&ddnnf);
+        let mut models = Vec::with_capacity(2);
+        for arg in [ARG_FROM, ARG_TO] {
+            let file_path = arg_matches.value_of(arg).unwrap();
+            let model = common::read_model_file(file_path, ddnnf.n_vars())?;
+            if model_finder.find_model_under_assumptions(&model).is_none() {
+                return Err(anyhow!(
+                    r#"the assignment in file "{file_path}" is not a model of the formula"#
+                ));
+            }
+            models.push(model);
+        }
+        let mut output = common::output(arg_matches)?;
+        let path_finder = ReconfigurationPathFinder::new(&ddnnf);
+        if let Some(path) = path_finder.find_path(&models[0], &models[1], max_length) {
+            writeln!(output, "s PATH FOUND")?;
+            let mut model = models.swap_remove(0);
+            model.sort_unstable_by_key(Literal::var_index);
+            common::write_dimacs_model(&mut output, &model)?;
+            for l in path {
+                model[l.var_index()] = l;
+                common::write_dimacs_model(&mut output, &model)?;
+            }
+        } else {
+            writeln!(output, "s NO PATH FOUND")?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
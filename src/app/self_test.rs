@@ -0,0 +1,221 @@
+use super::{cli_manager, common};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{
+    models, BottomUpTraversal, DecisionDNNF, HammingBallCounter, Literal, ModelCountingVisitor,
+    ModelEnumerator, ModelFinder, ModelSampler,
+};
+use log::info;
+use rug::Integer;
+use rustc_hash::FxHashSet;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "self-test";
+
+const ARG_LIMIT: &str = "ARG_LIMIT";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("cross-checks the results of the algorithms on the formula")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(
+                Arg::with_name(ARG_LIMIT)
+                    .long("limit")
+                    .empty_values(false)
+                    .multiple(false)
+                    .default_value("100000")
+                    .help("the maximal number of models to enumerate; the checks requiring more are skipped"),
+            )
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let limit = str::parse::<usize>(arg_matches.value_of(ARG_LIMIT).unwrap())
+            .context("while parsing the enumeration limit provided on the command line")?;
+        let n_models = BottomUpTraversal::new(Box::<ModelCountingVisitor>::default())
+            .traverse(&ddnnf)
+            .n_models()
+            .clone();
+        info!("the reference model count is {n_models}");
+        let checks: [(&str, CheckFn); 5] = [
+            ("satisfiability", check_satisfiability),
+            ("enumeration", check_enumeration),
+            ("compact enumeration", check_compact_enumeration),
+            ("ranked access", check_ranked_access),
+            ("hamming ball counting", check_hamming_ball_counting),
+        ];
+        let mut n_failures = 0;
+        for (name, check) in checks {
+            let result = check(&ddnnf, &n_models, limit);
+            let (status, details) = match &result {
+                CheckResult::Pass => ("PASS", String::new()),
+                CheckResult::Fail(d) => {
+                    n_failures += 1;
+                    ("FAIL", format!(" ({d})"))
+                }
+                CheckResult::Skipped(d) => ("SKIPPED", format!(" ({d})")),
+            };
+            writeln!(output, "c {name}: {status}{details}")?;
+        }
+        if n_failures == 0 {
+            writeln!(output, "s PASS")?;
+            output.flush()?;
+            Ok(())
+        } else {
+            writeln!(output, "s FAIL")?;
+            output.flush()?;
+            Err(anyhow!("{n_failures} check(s) failed"))
+        }
+    }
+}
+
+type CheckFn = fn(&DecisionDNNF, &Integer, usize) -> CheckResult;
+
+enum CheckResult {
+    Pass,
+    Fail(String),
+    Skipped(String),
+}
+
+fn check_satisfiability(ddnnf: &DecisionDNNF, n_models: &Integer, _limit: usize) -> CheckResult {
+    let opt_model = ModelFinder::new(ddnnf).find_model();
+    match opt_model {
+        Some(_) if *n_models == 0 => {
+            CheckResult::Fail("a model was found but the count is 0".to_string())
+        }
+        None if *n_models != 0 => {
+            CheckResult::Fail(format!("no model was found but the count is {n_models}"))
+        }
+        _ => CheckResult::Pass,
+    }
+}
+
+fn check_enumeration(ddnnf: &DecisionDNNF, n_models: &Integer, limit: usize) -> CheckResult {
+    if *n_models > limit {
+        return CheckResult::Skipped(format!("more than {limit} models"));
+    }
+    let model_finder = ModelFinder::new(ddnnf);
+    let mut enumerator = ModelEnumerator::new(ddnnf, false);
+    let mut seen = FxHashSet::default();
+    let mut n_enumerated = 0_usize;
+    while let Some(model) = enumerator.compute_next_model() {
+        n_enumerated += 1;
+        let normalized = models::normalize(model);
+        if normalized.len() != ddnnf.n_vars() {
+            return CheckResult::Fail(format!("the model {normalized:?} is not complete"));
+        }
+        let assumptions = normalized
+            .iter()
+            .map(|l| Literal::from(*l))
+            .collect::<Vec<_>>();
+        if model_finder
+            .find_model_under_assumptions(&assumptions)
+            .is_none()
+        {
+            return CheckResult::Fail(format!(
+                "the enumerated assignment {normalized:?} is not a model"
+            ));
+        }
+        if !seen.insert(normalized) {
+            return CheckResult::Fail("a model was enumerated twice".to_string());
+        }
+    }
+    if *n_models == n_enumerated {
+        CheckResult::Pass
+    } else {
+        CheckResult::Fail(format!(
+            "enumerated {n_enumerated} models but the count is {n_models}"
+        ))
+    }
+}
+
+fn check_compact_enumeration(
+    ddnnf: &DecisionDNNF,
+    n_models: &Integer,
+    limit: usize,
+) -> CheckResult {
+    let mut enumerator = ModelEnumerator::new(ddnnf, true);
+    let mut n_compact_models = 0_usize;
+    let mut n_represented = Integer::from(0);
+    while let Some(model) = enumerator.compute_next_model() {
+        n_compact_models += 1;
+        if n_compact_models > limit {
+            return CheckResult::Skipped(format!("more than {limit} compact models"));
+        }
+        n_represented += Integer::from(1) << model.iter().filter(|opt| opt.is_none()).count();
+    }
+    if *n_models == n_represented {
+        CheckResult::Pass
+    } else {
+        CheckResult::Fail(format!(
+            "the compact models represent {n_represented} models but the count is {n_models}"
+        ))
+    }
+}
+
+fn check_ranked_access(ddnnf: &DecisionDNNF, n_models: &Integer, limit: usize) -> CheckResult {
+    if *n_models > limit {
+        return CheckResult::Skipped(format!("more than {limit} models"));
+    }
+    let sampler = ModelSampler::new(ddnnf);
+    let mut ranked = Vec::new();
+    let mut rank = Integer::from(0);
+    while let Some(model) = sampler.model_at(&rank) {
+        ranked.push(model.iter().map(|l| isize::from(*l)).collect::<Vec<_>>());
+        rank += 1;
+    }
+    let n_ranked = ranked.len();
+    let distinct = ranked.iter().cloned().collect::<FxHashSet<_>>();
+    if distinct.len() != n_ranked {
+        return CheckResult::Fail(format!(
+            "the {n_ranked} ranks give only {} distinct models",
+            distinct.len()
+        ));
+    }
+    let enumerated = ModelEnumerator::new(ddnnf, false)
+        .into_iter()
+        .map(|model| models::normalize(&model));
+    if models::same_model_sets(ranked, enumerated) {
+        CheckResult::Pass
+    } else {
+        CheckResult::Fail("the ranked models differ from the enumerated ones".to_string())
+    }
+}
+
+fn check_hamming_ball_counting(
+    ddnnf: &DecisionDNNF,
+    n_models: &Integer,
+    _limit: usize,
+) -> CheckResult {
+    let center = (1..=ddnnf.n_vars())
+        .map(|i| Literal::from(isize::try_from(i).unwrap()))
+        .collect::<Vec<_>>();
+    let n_in_ball = HammingBallCounter::new(ddnnf).count(&center, ddnnf.n_vars());
+    if *n_models == n_in_ball {
+        CheckResult::Pass
+    } else {
+        CheckResult::Fail(format!(
+            "the ball covering all the assignments contains {n_in_ball} models but the count is {n_models}"
+        ))
+    }
+}
@@ -0,0 +1,115 @@
+use super::{cli_manager, common};
+use anyhow::Context;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::SimilarityComputer;
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "similarity";
+
+const ARG_OTHER: &str = "ARG_OTHER";
+const ARG_MAX_CUBES: &str = "ARG_MAX_CUBES";
+const ARG_N_SAMPLES: &str = "ARG_N_SAMPLES";
+const ARG_SEED: &str = "ARG_SEED";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("computes the similarity (Jaccard index) of the sets of models of two formulas")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_OTHER)
+                    .long("other")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("the file that contains the formula to compare with the input one"),
+            )
+            .arg(
+                Arg::with_name(ARG_MAX_CUBES)
+                    .long("max-cubes")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("the maximal number of compact models of the input formula to enumerate before estimating the similarity by sampling"),
+            )
+            .arg(
+                Arg::with_name(ARG_N_SAMPLES)
+                    .long("n-samples")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("the number of models drawn from the input formula when the similarity is estimated"),
+            )
+            .arg(
+                Arg::with_name(ARG_SEED)
+                    .long("seed")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("the seed of the pseudo-random generator used when the similarity is estimated"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let mut first = common::read_and_check_input_ddnnf(arg_matches)?;
+        let mut second = common::read_and_check_ddnnf(arg_matches, ARG_OTHER)?;
+        let n_vars = first.n_vars().max(second.n_vars());
+        first.update_n_vars(n_vars);
+        second.update_n_vars(n_vars);
+        let mut computer = SimilarityComputer::new(&first, &second);
+        if let Some(str_n) = arg_matches.value_of(ARG_MAX_CUBES) {
+            computer.set_max_cubes(str::parse(str_n).context(
+                "while parsing the maximal number of cubes provided on the command line",
+            )?);
+        }
+        if let Some(str_n) = arg_matches.value_of(ARG_N_SAMPLES) {
+            computer.set_n_samples(
+                str::parse(str_n)
+                    .context("while parsing the number of samples provided on the command line")?,
+            );
+        }
+        if let Some(str_seed) = arg_matches.value_of(ARG_SEED) {
+            computer.set_seed(
+                str::parse(str_seed)
+                    .context("while parsing the seed provided on the command line")?,
+            );
+        }
+        let similarity = computer.compute();
+        writeln!(
+            output,
+            "c input formula models: {}",
+            similarity.n_first_models()
+        )?;
+        writeln!(
+            output,
+            "c other formula models: {}",
+            similarity.n_second_models()
+        )?;
+        if let Some(n) = similarity.n_common_models() {
+            writeln!(output, "c common models: {n}")?;
+            writeln!(output, "s EXACT")?;
+        } else {
+            writeln!(output, "s ESTIMATED")?;
+        }
+        writeln!(output, "{}", similarity.jaccard_index())?;
+        output.flush()?;
+        Ok(())
+    }
+}
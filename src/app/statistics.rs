@@ -0,0 +1,87 @@
+use super::{cli_manager, common};
+use anyhow::{anyhow, Context};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::{D4Reader, Format};
+use std::io::Write;
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "statistics";
+
+const ARG_MEMORY_BUDGET: &str = "ARG_MEMORY_BUDGET";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("prints statistics about a d4 formatted formula without loading it")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_MEMORY_BUDGET)
+                    .long("memory-budget")
+                    .empty_values(false)
+                    .multiple(false)
+                    .help("checks whether the loaded formula would fit in this amount of memory (in MiB)"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let memory_budget = arg_matches
+            .value_of(ARG_MEMORY_BUDGET)
+            .map(|s| {
+                str::parse::<usize>(s)
+                    .context("while parsing the memory budget provided on the command line")
+            })
+            .transpose()?;
+        let mut file_reader = common::create_input_file_reader(arg_matches)?;
+        let format = Format::detect(&mut file_reader)?;
+        if format != Format::D4 {
+            return Err(anyhow!(
+                "statistics can only be computed for the d4 format, got the {format} format"
+            ));
+        }
+        let statistics = D4Reader::default()
+            .read_statistics(file_reader)
+            .context("while reading the input Decision-DNNF")?;
+        writeln!(
+            output,
+            "nodes: {} ({} and, {} or, {} true, {} false)",
+            statistics.n_nodes(),
+            statistics.n_and_nodes(),
+            statistics.n_or_nodes(),
+            statistics.n_true_nodes(),
+            statistics.n_false_nodes()
+        )?;
+        writeln!(output, "edges: {}", statistics.n_edges())?;
+        writeln!(output, "variables: {}", statistics.n_vars())?;
+        writeln!(
+            output,
+            "propagated literals: {} ({:.2} per edge)",
+            statistics.n_propagated(),
+            statistics.average_propagation_len()
+        )?;
+        writeln!(
+            output,
+            "estimated memory: {} bytes",
+            statistics.estimated_memory()
+        )?;
+        if let Some(budget) = memory_budget {
+            let fits = statistics.fits_in(budget.saturating_mul(1 << 20));
+            writeln!(
+                output,
+                "fits in the memory budget: {}",
+                if fits { "yes" } else { "no" }
+            )?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+}
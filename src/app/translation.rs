@@ -1,12 +1,19 @@
 use super::{cli_manager, common};
-use clap::{App, AppSettings, ArgMatches, SubCommand};
-use decdnnf_rs::{BottomUpTraversal, C2dWriter, CheckingVisitor};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::Format;
+use std::{io::Write, str::FromStr};
 
 #[derive(Default)]
 pub struct Command;
 
 const CMD_NAME: &str = "translation";
 
+const ARG_OUTPUT_FORMAT: &str = "ARG_OUTPUT_FORMAT";
+const ARG_SMOOTH: &str = "ARG_SMOOTH";
+
+pub(crate) const OUTPUT_FORMATS: [&str; 3] =
+    [Format::NAMES[0], Format::NAMES[1], common::OUTPUT_DOT];
+
 impl<'a> super::command::Command<'a> for Command {
     fn name(&self) -> &str {
         CMD_NAME
@@ -17,16 +24,46 @@ impl<'a> super::command::Command<'a> for Command {
             .about("translates a formula from an input format into an output format")
             .setting(AppSettings::DisableVersion)
             .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
             .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(
+                Arg::with_name(ARG_OUTPUT_FORMAT)
+                    .long("output-format")
+                    .visible_alias("to")
+                    .empty_values(false)
+                    .multiple(false)
+                    .possible_values(&OUTPUT_FORMATS)
+                    .default_value(Format::C2d.name())
+                    .help("the format of the output formula"),
+            )
+            .arg(
+                Arg::with_name(ARG_SMOOTH)
+                    .long("smooth")
+                    .takes_value(false)
+                    .help("smooths the formula before writing it"),
+            )
             .arg(cli_manager::logging_level_cli_arg())
     }
 
     fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
-        let ddnnf = common::read_input_ddnnf(arg_matches)?;
-        let traversal_engine = BottomUpTraversal::new(Box::<CheckingVisitor>::default());
-        let checking_data = traversal_engine.traverse(&ddnnf);
-        common::print_warnings_and_errors(&checking_data)?;
-        C2dWriter::write(&mut std::io::stdout(), &ddnnf)?;
+        let mut ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        if arg_matches.is_present(ARG_SMOOTH) {
+            ddnnf = ddnnf.smooth();
+        }
+        let mut output = common::output(arg_matches)?;
+        match arg_matches.value_of(ARG_OUTPUT_FORMAT).unwrap() {
+            common::OUTPUT_DOT => common::write_dot(&mut output, &ddnnf, None)?,
+            format => Format::from_str(format)?.write(&mut output, &ddnnf)?,
+        }
+        output.flush()?;
         Ok(())
     }
 }
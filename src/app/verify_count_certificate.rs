@@ -0,0 +1,57 @@
+use super::{cli_manager, common};
+use anyhow::Context;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use decdnnf_rs::CountCertificateVerifier;
+use std::{fs::File, io::Write};
+
+#[derive(Default)]
+pub struct Command;
+
+const CMD_NAME: &str = "verify-count-certificate";
+
+const ARG_CERTIFICATE: &str = "ARG_CERTIFICATE";
+
+impl<'a> super::command::Command<'a> for Command {
+    fn name(&self) -> &str {
+        CMD_NAME
+    }
+
+    fn clap_subcommand(&self) -> App<'a, 'a> {
+        SubCommand::with_name(CMD_NAME)
+            .about("checks a certificate written by the model counting command")
+            .setting(AppSettings::DisableVersion)
+            .arg(common::arg_input_var())
+            .arg(common::arg_stack_size())
+            .arg(common::arg_n_vars())
+            .arg(common::arg_check_level())
+            .arg(common::arg_exact_determinism())
+            .arg(common::arg_refute_warnings())
+            .arg(common::arg_report_all_errors())
+            .arg(common::arg_fix())
+            .arg(common::arg_assumptions_file())
+            .arg(common::arg_simplify())
+            .arg(common::arg_output())
+            .arg(cli_manager::logging_level_cli_arg())
+            .arg(
+                Arg::with_name(ARG_CERTIFICATE)
+                    .long("certificate")
+                    .empty_values(false)
+                    .multiple(false)
+                    .required(true)
+                    .help("the certificate to check"),
+            )
+    }
+
+    fn execute(&self, arg_matches: &ArgMatches<'_>) -> anyhow::Result<()> {
+        let mut output = common::output(arg_matches)?;
+        let ddnnf = common::read_and_check_input_ddnnf(arg_matches)?;
+        let certificate_path = arg_matches.value_of(ARG_CERTIFICATE).unwrap();
+        let file = File::open(certificate_path)
+            .with_context(|| format!(r#"while opening file "{certificate_path}""#))?;
+        let n_models = CountCertificateVerifier::new(&ddnnf).verify(file)?;
+        writeln!(output, "s VERIFIED")?;
+        writeln!(output, "{n_models}")?;
+        output.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+//! End-to-end recipes combining the features of the library.
+//!
+//! This module contains no code of its own: it is a collection of examples, which are compiled and run as documentation tests.
+//! It is only built when the `cookbook` feature is enabled.
+//!
+//! All the recipes use the following formula, which models are the assignments of the variables 1, 2 and 3 such that 1 implies 2:
+//!
+//! ```text
+//! a 1 0
+//! o 2 0
+//! o 3 0
+//! t 4 0
+//! 1 2 0
+//! 1 3 0
+//! 2 4 -1 0
+//! 2 4 1 2 0
+//! 3 4 -3 0
+//! 3 4 3 0
+//! ```
+//!
+//! Note that the formula does not involve variable 2 when variable 1 is false; thus, the formula has 6 models.
+//!
+//! # Counting under assumptions
+//!
+//! A [`ModelCounter`](crate::ModelCounter) stores the counts of the nodes, so that counting the models under different assumptions does not involve computing all the counts again.
+//! When the same assumptions are used for many queries, conditioning the formula with [`DecisionDNNF::condition`](crate::DecisionDNNF::condition) produces a smaller formula that can be given to any algorithm.
+//!
+//! ```
+//! use decdnnf_rs::{D4Reader, Literal, ModelCounter, NodeIndex};
+//!
+//! # let instance = "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 0\n";
+//! let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+//! let counter = ModelCounter::new(&ddnnf);
+//! let mut n_models_by_literal = Vec::new();
+//! for var in 1..=ddnnf.n_vars() {
+//!     for dimacs in [-isize::try_from(var).unwrap(), isize::try_from(var).unwrap()] {
+//!         let n = counter.count_from_under_assumptions(NodeIndex::from(0), &[Literal::from(dimacs)]);
+//!         n_models_by_literal.push((dimacs, n));
+//!     }
+//! }
+//! let (dimacs, n) = &n_models_by_literal[2];
+//! assert_eq!((-2, 2), (*dimacs, n.to_u32().unwrap()));
+//!
+//! let conditioned = ddnnf.condition(&[Literal::from(3)]);
+//! assert_eq!(3, *ModelCounter::new(&conditioned).count());
+//! ```
+//!
+//! # Building a configurator
+//!
+//! A configurator lets a user choose the values of the variables one after the other, while preventing the choices that cannot lead to a model.
+//! After each choice, the [`ModelFinder`](crate::ModelFinder) gives the literals that are still possible; the variables for which a single literal remains are forced.
+//!
+//! ```
+//! use decdnnf_rs::{D4Reader, Literal, ModelFinder};
+//!
+//! /// Returns the literals that are consistent with the formula and the choices.
+//! fn possible_literals(finder: &ModelFinder, n_vars: usize, choices: &[Literal]) -> Vec<Literal> {
+//!     let mut possible = Vec::new();
+//!     for var in 1..=n_vars {
+//!         let positive = Literal::from(isize::try_from(var).unwrap());
+//!         for l in [positive, positive.flip()] {
+//!             let mut assumptions = choices.to_vec();
+//!             assumptions.push(l);
+//!             if finder.find_model_under_assumptions(&assumptions).is_some() {
+//!                 possible.push(l);
+//!             }
+//!         }
+//!     }
+//!     possible
+//! }
+//!
+//! # let instance = "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 0\n";
+//! let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+//! let finder = ModelFinder::new(&ddnnf);
+//! let mut choices = vec![];
+//! assert_eq!(6, possible_literals(&finder, ddnnf.n_vars(), &choices).len());
+//!
+//! // the user sets variable 1 to true: variable 2 is forced to true
+//! choices.push(Literal::from(1));
+//! let possible = possible_literals(&finder, ddnnf.n_vars(), &choices);
+//! assert!(possible.contains(&Literal::from(2)));
+//! assert!(!possible.contains(&Literal::from(-2)));
+//! ```
+//!
+//! # Exporting an annotated graph
+//!
+//! The nodes and edges of a formula are available through [`DecisionDNNF::node`](crate::DecisionDNNF::node) and [`DecisionDNNF::edge`](crate::DecisionDNNF::edge).
+//! Together with the counts of a [`ModelCounter`](crate::ModelCounter), they allow to export the formula in the [dot](https://graphviz.org/doc/info/lang.html) format, labelling each node with the number of models of its subformula.
+//!
+//! ```
+//! use decdnnf_rs::{D4Reader, ModelCounter, Node, NodeIndex};
+//! use std::fmt::Write;
+//!
+//! # let instance = "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 0\n";
+//! let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+//! let counter = ModelCounter::new(&ddnnf);
+//! let mut dot = String::from("digraph {\n");
+//! for i in 0..ddnnf.n_nodes() {
+//!     let node = ddnnf.node(NodeIndex::from(i));
+//!     let (kind, edges) = match node {
+//!         Node::And(edges) => ("AND", edges.as_slice()),
+//!         Node::Or(edges) => ("OR", edges.as_slice()),
+//!         Node::True => ("TRUE", [].as_slice()),
+//!         Node::False => ("FALSE", [].as_slice()),
+//!     };
+//!     let n_models = counter.count_from(NodeIndex::from(i));
+//!     writeln!(dot, "  n{i} [label=\"{kind}\\n{n_models}\"];").unwrap();
+//!     for edge_index in edges {
+//!         let edge = ddnnf.edge(*edge_index);
+//!         let label = edge.propagated().iter().map(ToString::to_string).collect::<Vec<_>>().join(" ");
+//!         writeln!(dot, "  n{i} -> n{} [label=\"{label}\"];", usize::from(edge.target())).unwrap();
+//!     }
+//! }
+//! dot.push_str("}\n");
+//! assert!(dot.contains("n0 [label=\"AND\\n6\"]"));
+//! assert!(dot.contains("n1 -> n3 [label=\"1 2\"]"));
+//! ```
+//!
+//! # Enumerating models in parallel chunks
+//!
+//! An [`OwnedModelIter`](crate::OwnedModelIter) holds the formula through an [`Arc`](std::sync::Arc) and produces owned models.
+//! It can thus run on its own thread, sending chunks of models to worker threads which write them into files.
+//!
+//! ```
+//! use decdnnf_rs::{models, D4Reader, OwnedModelIter};
+//! use std::{
+//!     fs,
+//!     io::Write,
+//!     sync::{mpsc, Arc, Mutex},
+//!     thread,
+//! };
+//!
+//! const CHUNK_SIZE: usize = 2;
+//! const N_WORKERS: usize = 2;
+//!
+//! # let instance = "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 0\n";
+//! let ddnnf = Arc::new(D4Reader::read(instance.as_bytes()).unwrap());
+//! let (sender, receiver) = mpsc::sync_channel::<(usize, Vec<Vec<isize>>)>(N_WORKERS);
+//! let receiver = Arc::new(Mutex::new(receiver));
+//! let dir = std::env::temp_dir().join(format!("decdnnf_rs_cookbook_{}", std::process::id()));
+//! fs::create_dir_all(&dir).unwrap();
+//! let workers = (0..N_WORKERS)
+//!     .map(|_| {
+//!         let receiver = Arc::clone(&receiver);
+//!         let dir = dir.clone();
+//!         thread::spawn(move || loop {
+//!             let Ok((chunk_index, chunk)) = receiver.lock().unwrap().recv() else {
+//!                 break;
+//!             };
+//!             let mut file = fs::File::create(dir.join(format!("chunk_{chunk_index}.txt"))).unwrap();
+//!             for model in chunk {
+//!                 let literals = model.iter().map(ToString::to_string).collect::<Vec<_>>();
+//!                 writeln!(file, "v {} 0", literals.join(" ")).unwrap();
+//!             }
+//!         })
+//!     })
+//!     .collect::<Vec<_>>();
+//! let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+//! let mut n_chunks = 0;
+//! for model in OwnedModelIter::new(Arc::clone(&ddnnf), false) {
+//!     chunk.push(models::normalize(&model));
+//!     if chunk.len() == CHUNK_SIZE {
+//!         sender.send((n_chunks, std::mem::take(&mut chunk))).unwrap();
+//!         n_chunks += 1;
+//!     }
+//! }
+//! if !chunk.is_empty() {
+//!     sender.send((n_chunks, chunk)).unwrap();
+//!     n_chunks += 1;
+//! }
+//! drop(sender);
+//! for worker in workers {
+//!     worker.join().unwrap();
+//! }
+//! assert_eq!(3, n_chunks);
+//! assert_eq!(3, fs::read_dir(&dir).unwrap().count());
+//! fs::remove_dir_all(&dir).unwrap();
+//! ```
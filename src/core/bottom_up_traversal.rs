@@ -8,6 +8,7 @@ use crate::{DecisionDNNF, Literal};
 /// The bottom-up search follows all the paths from the root to the leaves.
 /// Since Decision-DNNFs are graphs, this means that if a node has multiple ancestors, then it will be reached multiple times.
 /// This makes algorithms using the [`BottomUpVisitor`] take a higher computation time but a lower memory usage than algorithms that would take advantage of caching techniques.
+/// On formulas in which many nodes are shared, the number of paths may be exponential in the size of the formula; in this case, the [`CachedBottomUpTraversal`] should be preferred.
 ///
 /// # Example
 ///
@@ -61,6 +62,15 @@ pub trait BottomUpVisitor<T> {
 
     /// Creates new data from a false node.
     fn new_for_false(&self, ddnnf: &DecisionDNNF, path: &[NodeIndex]) -> T;
+
+    /// Returns `true` if the data computed for the last node of the path may be reused for the other paths reaching this node.
+    ///
+    /// This function is only called by the [`CachedBottomUpTraversal`].
+    /// It must return `false` if the data depends on the path that led to the node, and not only on the node itself.
+    /// The default implementation returns `false`, so that visitors are not cached unless they explicitly allow it.
+    fn is_cacheable(&self, _ddnnf: &DecisionDNNF, _path: &[NodeIndex]) -> bool {
+        false
+    }
 }
 
 impl<T> BottomUpTraversal<T> {
@@ -112,6 +122,88 @@ impl<T> BottomUpTraversal<T> {
     }
 }
 
+/// A structure used to apply algorithms on a Decision-DNNF in a bottom-up fashion, reusing the data computed for the shared nodes.
+///
+/// This structure behaves like the [`BottomUpTraversal`], except that the data computed for a node is stored when the visitor allows it through [`BottomUpVisitor::is_cacheable`].
+/// When the node is reached again through another path, the stored data is cloned instead of traversing the subformula again.
+/// If the visitor allows caching for all the nodes, each node is visited once, at the price of a memory usage linear in the number of nodes.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{CachedBottomUpTraversal, DecisionDNNF, ModelCountingVisitor};
+///
+/// fn count_models(ddnnf: &DecisionDNNF) {
+///     let traversal = CachedBottomUpTraversal::new(Box::<ModelCountingVisitor>::default());
+///     let result = traversal.traverse(&ddnnf);
+///     println!("the formula has {} models", result.n_models());
+/// }
+/// # count_models(&decdnnf_rs::D4Reader::read("t 1 0".as_bytes()).unwrap())
+/// ```
+pub struct CachedBottomUpTraversal<T> {
+    visitor: Box<dyn BottomUpVisitor<T>>,
+}
+
+impl<T> CachedBottomUpTraversal<T>
+where
+    T: Clone,
+{
+    /// Builds a new traversal structure given an algorithms working in a bottom-up fashion.
+    #[must_use]
+    pub fn new(visitor: Box<dyn BottomUpVisitor<T>>) -> Self {
+        Self { visitor }
+    }
+
+    /// Make the traversal, applying the algorithm given at this object creation time.
+    ///
+    /// The data resulting from the traversal of the root node is returned.
+    #[must_use]
+    pub fn traverse(&self, ddnnf: &DecisionDNNF) -> T {
+        let mut path = Vec::with_capacity(ddnnf.n_vars());
+        let mut cache = vec![None; ddnnf.n_nodes()];
+        self.traverse_for(ddnnf, 0.into(), &mut path, &mut cache)
+    }
+
+    fn traverse_for(
+        &self,
+        ddnnf: &DecisionDNNF,
+        node_index: NodeIndex,
+        path: &mut Vec<NodeIndex>,
+        cache: &mut Vec<Option<T>>,
+    ) -> T {
+        if let Some(data) = &cache[usize::from(node_index)] {
+            return data.clone();
+        }
+        path.push(node_index);
+        let mut compute_new_children = |v: &[EdgeIndex]| {
+            v.iter()
+                .map(|e| {
+                    let edge: &Edge = &ddnnf.edges()[*e];
+                    let new_child = self.traverse_for(ddnnf, edge.target(), path, cache);
+                    (edge.propagated(), new_child)
+                })
+                .collect::<Vec<_>>()
+        };
+        let result = match &ddnnf.nodes()[node_index] {
+            Node::And(v) => {
+                let new_children = compute_new_children(v);
+                self.visitor.merge_for_and(ddnnf, path, new_children)
+            }
+            Node::Or(v) => {
+                let new_children = compute_new_children(v);
+                self.visitor.merge_for_or(ddnnf, path, new_children)
+            }
+            Node::True => self.visitor.new_for_true(ddnnf, path),
+            Node::False => self.visitor.new_for_false(ddnnf, path),
+        };
+        if self.visitor.is_cacheable(ddnnf, path) {
+            cache[usize::from(node_index)] = Some(result.clone());
+        }
+        path.pop();
+        result
+    }
+}
+
 /// A Bottom-up visitor made to decorate a pair of underlying visitors.
 ///
 /// # Example
@@ -201,4 +293,111 @@ impl<T, U> BottomUpVisitor<(T, U)> for BiBottomUpVisitor<T, U> {
             self.visitor_u.new_for_false(ddnnf, path),
         )
     }
+
+    fn is_cacheable(&self, ddnnf: &DecisionDNNF, path: &[NodeIndex]) -> bool {
+        self.visitor_t.is_cacheable(ddnnf, path) && self.visitor_u.is_cacheable(ddnnf, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckingVisitor, D4Reader, ModelCountingVisitor};
+    use std::{cell::Cell, rc::Rc};
+
+    struct VisitCounter {
+        n_visits: Rc<Cell<usize>>,
+        cacheable: bool,
+    }
+
+    impl VisitCounter {
+        fn visit(&self) -> usize {
+            self.n_visits.set(self.n_visits.get() + 1);
+            self.n_visits.get()
+        }
+    }
+
+    impl BottomUpVisitor<usize> for VisitCounter {
+        fn merge_for_and(
+            &self,
+            _ddnnf: &DecisionDNNF,
+            _path: &[NodeIndex],
+            _children: Vec<(&[Literal], usize)>,
+        ) -> usize {
+            self.visit()
+        }
+
+        fn merge_for_or(
+            &self,
+            _ddnnf: &DecisionDNNF,
+            _path: &[NodeIndex],
+            _children: Vec<(&[Literal], usize)>,
+        ) -> usize {
+            self.visit()
+        }
+
+        fn new_for_true(&self, _ddnnf: &DecisionDNNF, _path: &[NodeIndex]) -> usize {
+            self.visit()
+        }
+
+        fn new_for_false(&self, _ddnnf: &DecisionDNNF, _path: &[NodeIndex]) -> usize {
+            self.visit()
+        }
+
+        fn is_cacheable(&self, _ddnnf: &DecisionDNNF, _path: &[NodeIndex]) -> bool {
+            self.cacheable
+        }
+    }
+
+    fn count_visits(ddnnf: &DecisionDNNF, cacheable: bool) -> usize {
+        let n_visits = Rc::new(Cell::new(0));
+        let visitor = VisitCounter {
+            n_visits: Rc::clone(&n_visits),
+            cacheable,
+        };
+        let _ = CachedBottomUpTraversal::new(Box::new(visitor)).traverse(ddnnf);
+        n_visits.get()
+    }
+
+    const SHARED: &str = "o 1 0\no 2 0\nt 3 0\n1 2 1 0\n1 2 -1 0\n2 3 2 0\n2 3 -2 0\n";
+
+    #[test]
+    fn test_cached_visits() {
+        let ddnnf = D4Reader::read(SHARED.as_bytes()).unwrap();
+        assert_eq!(3, count_visits(&ddnnf, true));
+        assert_eq!(7, count_visits(&ddnnf, false));
+    }
+
+    #[test]
+    fn test_cached_model_count() {
+        for str_ddnnf in [
+            SHARED,
+            "a 1 0\nt 2 0\n1 2 1 0\n",
+            "o 1 0\na 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 0\n2 3 -2 0\n3 4 3 0\n3 4 -3 0\n",
+            "f 1 0\n",
+        ] {
+            let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+            let expected = BottomUpTraversal::new(Box::<ModelCountingVisitor>::default())
+                .traverse(&ddnnf)
+                .n_models()
+                .clone();
+            let actual = CachedBottomUpTraversal::new(Box::<ModelCountingVisitor>::default())
+                .traverse(&ddnnf)
+                .n_models()
+                .clone();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn test_cached_checker() {
+        let str_ddnnf = "o 1 0\na 2 0\nt 3 0\n1 2 1 0\n1 2 -1 0\n2 3 2 0\n2 3 -2 0\n";
+        let ddnnf = D4Reader::read(str_ddnnf.as_bytes()).unwrap();
+        let result =
+            CachedBottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(&ddnnf);
+        assert_eq!(
+            "AND children share variables (AND node index is 1)",
+            result.get_error().unwrap().to_string()
+        );
+    }
 }
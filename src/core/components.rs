@@ -0,0 +1,204 @@
+use super::{Edge, EdgeIndex, Node, NodeIndex};
+use crate::{DecisionDNNF, Literal};
+use rustc_hash::FxHashMap;
+
+impl DecisionDNNF {
+    /// Splits the formula into independent components.
+    ///
+    /// The components are given by the decomposition of the conjunction at the root of the formula.
+    /// Conjunctions that are directly under the root conjunction are decomposed the same way, and each literal propagated by these conjunctions is a component on its own.
+    /// If the root is not a conjunction, the formula itself is the only component.
+    ///
+    /// Components do not share any variable.
+    /// Each component is defined on the same variables than the formula, and the variables it does not involve are free.
+    /// Thus, the models of the formula are the assignments that are models of all the components.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// // (-1 or 1) and 2 and (-3 or 3)
+    /// let ddnnf = D4Reader::read(r"
+    /// a 1 0
+    /// o 2 0
+    /// o 3 0
+    /// t 4 0
+    /// 1 2 0
+    /// 1 3 2 0
+    /// 2 4 -1 0
+    /// 2 4 1 0
+    /// 3 4 -3 0
+    /// 3 4 3 0
+    /// ".as_bytes()).unwrap();
+    /// assert_eq!(3, ddnnf.components().len());
+    /// ```
+    #[must_use]
+    pub fn components(&self) -> Vec<DecisionDNNF> {
+        let (literals, roots) = self.component_roots();
+        literals
+            .into_iter()
+            .map(|l| self.unit_formula(l))
+            .chain(roots.into_iter().map(|r| self.sub_formula(r)))
+            .collect()
+    }
+
+    /// Returns the literals that are components on their own and the roots of the other components.
+    ///
+    /// If the root of the formula is not a conjunction, it is the only returned root.
+    pub(crate) fn component_roots(&self) -> (Vec<Literal>, Vec<NodeIndex>) {
+        let mut literals = Vec::new();
+        let mut roots = Vec::new();
+        self.collect_components(NodeIndex::from(0), &mut literals, &mut roots);
+        if literals.is_empty() && roots.is_empty() {
+            roots.push(NodeIndex::from(0));
+        }
+        (literals, roots)
+    }
+
+    fn collect_components(
+        &self,
+        from: NodeIndex,
+        literals: &mut Vec<Literal>,
+        roots: &mut Vec<NodeIndex>,
+    ) {
+        match &self.nodes()[from] {
+            Node::And(edges) => {
+                for edge_index in edges {
+                    let edge = &self.edges()[*edge_index];
+                    literals.extend_from_slice(edge.propagated());
+                    self.collect_components(edge.target(), literals, roots);
+                }
+            }
+            Node::Or(_) | Node::False => roots.push(from),
+            Node::True => {}
+        }
+    }
+
+    fn unit_formula(&self, literal: Literal) -> DecisionDNNF {
+        DecisionDNNF::from_raw_data(
+            self.n_vars(),
+            vec![Node::And(vec![EdgeIndex::from(0)]), Node::True],
+            vec![Edge::from_raw_data(NodeIndex::from(1), vec![literal])],
+        )
+    }
+
+    fn sub_formula(&self, root: NodeIndex) -> DecisionDNNF {
+        let mut node_map = FxHashMap::default();
+        let mut nodes = Vec::new();
+        let mut edges = Vec::new();
+        let mut stack = Vec::new();
+        let mut map_node = |old: NodeIndex, nodes: &mut Vec<Node>, stack: &mut Vec<NodeIndex>| {
+            *node_map.entry(usize::from(old)).or_insert_with(|| {
+                nodes.push(match &self.nodes()[old] {
+                    Node::And(_) => Node::And(vec![]),
+                    Node::Or(_) => Node::Or(vec![]),
+                    Node::True => Node::True,
+                    Node::False => Node::False,
+                });
+                stack.push(old);
+                NodeIndex::from(nodes.len() - 1)
+            })
+        };
+        map_node(root, &mut nodes, &mut stack);
+        while let Some(old) = stack.pop() {
+            let new = map_node(old, &mut nodes, &mut stack);
+            if let Node::And(old_edges) | Node::Or(old_edges) = &self.nodes()[old] {
+                for edge_index in old_edges {
+                    let edge = &self.edges()[*edge_index];
+                    let target = map_node(edge.target(), &mut nodes, &mut stack);
+                    edges.push(Edge::from_raw_data(target, edge.propagated().to_vec()));
+                    nodes[usize::from(new)]
+                        .add_edge(EdgeIndex::from(edges.len() - 1))
+                        .unwrap();
+                }
+            }
+        }
+        DecisionDNNF::from_raw_data(self.n_vars(), nodes, edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{models, D4Reader, DecisionDNNF, ModelEnumerator};
+    use std::collections::HashSet;
+
+    fn model_set(ddnnf: &DecisionDNNF) -> HashSet<Vec<isize>> {
+        let mut enumerator = ModelEnumerator::new(ddnnf, false);
+        let mut models = HashSet::new();
+        while let Some(m) = enumerator.compute_next_model() {
+            models.insert(models::normalize(m));
+        }
+        models
+    }
+
+    fn assert_components(instance: &str, n_vars: Option<usize>, n_components: usize) {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let components = ddnnf.components();
+        assert_eq!(n_components, components.len());
+        let intersection = components
+            .iter()
+            .map(model_set)
+            .reduce(|acc, s| acc.intersection(&s).cloned().collect())
+            .unwrap();
+        assert_eq!(model_set(&ddnnf), intersection);
+    }
+
+    #[test]
+    fn test_single_component() {
+        assert_components("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n", None, 1);
+    }
+
+    #[test]
+    fn test_true() {
+        assert_components("t 1 0", Some(2), 1);
+    }
+
+    #[test]
+    fn test_false() {
+        assert_components("a 1 0\nf 2 0\n1 2 1 0\n", None, 2);
+    }
+
+    #[test]
+    fn test_nested_and() {
+        let instance = r"
+        a 1 0
+        a 2 0
+        o 3 0
+        o 4 0
+        t 5 0
+        1 2 1 0
+        1 3 0
+        2 4 0
+        2 5 2 0
+        3 5 -3 0
+        3 5 3 0
+        4 5 -4 -5 0
+        4 5 4 0
+        ";
+        assert_components(instance, Some(6), 4);
+    }
+
+    #[test]
+    fn test_shared_nodes() {
+        let instance = r"
+        a 1 0
+        o 2 0
+        o 3 0
+        o 4 0
+        t 5 0
+        1 2 0
+        1 3 0
+        2 4 -1 0
+        2 4 1 0
+        3 5 -2 0
+        3 5 2 0
+        4 5 -3 0
+        4 5 3 0
+        ";
+        assert_components(instance, None, 2);
+    }
+}
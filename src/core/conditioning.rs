@@ -0,0 +1,158 @@
+use super::{assumed_polarities, simplification, Edge, EdgeIndex, Node, NodeIndex};
+use crate::{DecisionDNNF, Literal};
+use anyhow::Result;
+
+impl DecisionDNNF {
+    /// Builds a formula equivalent to the conjunction of this formula and some literals.
+    ///
+    /// The edges that propagate the negation of one of the literals are removed, and the literals are removed from the other edges.
+    /// The parts of the formula that become equivalent to false are pruned, and the remaining formula is placed under a new conjunction node that propagates the literals.
+    /// Thus, the resulting formula is a Decision-DNNF on the same variables, which models are the models of this formula that satisfy the literals.
+    ///
+    /// If the literals are contradictory, the resulting formula is a single false node.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a literal refers to a variable that does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, Literal, ModelCounter};
+    ///
+    /// // (-1 and -2) or 1
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let conditioned = ddnnf.condition(&[Literal::from(-1)]);
+    /// assert_eq!(1, *ModelCounter::new(&conditioned).count());
+    /// ```
+    #[must_use]
+    pub fn condition(&self, literals: &[Literal]) -> DecisionDNNF {
+        self.try_condition(literals)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Builds a formula equivalent to the conjunction of this formula and some literals, as [`condition`](Self::condition) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a literal refers to a variable that does not exist.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn try_condition(&self, literals: &[Literal]) -> Result<DecisionDNNF> {
+        let n_vars = self.n_vars();
+        let Some(assumed) = assumed_polarities(literals, n_vars)? else {
+            return Ok(DecisionDNNF::from_raw_data(
+                n_vars,
+                vec![Node::False],
+                vec![],
+            ));
+        };
+        let mut fixed = literals.to_vec();
+        fixed.sort_unstable_by_key(Literal::var_index);
+        fixed.dedup();
+        let shift = usize::from(!fixed.is_empty());
+        let false_index = NodeIndex::from(self.n_nodes() + shift);
+        let mut nodes = Vec::with_capacity(self.n_nodes() + shift + 1);
+        let mut edges = Vec::with_capacity(self.n_edges() + shift);
+        if shift == 1 {
+            nodes.push(Node::And(vec![EdgeIndex::from(0)]));
+            edges.push(Edge::from_raw_data(NodeIndex::from(1), fixed));
+        }
+        for node in self.nodes().as_slice() {
+            let (mut new_node, edge_indices) = match node {
+                Node::And(edge_indices) => (Node::And(vec![]), edge_indices.as_slice()),
+                Node::Or(edge_indices) => (Node::Or(vec![]), edge_indices.as_slice()),
+                Node::True => (Node::True, [].as_slice()),
+                Node::False => (Node::False, [].as_slice()),
+            };
+            for edge_index in edge_indices {
+                let edge = self.edge(*edge_index);
+                let contradicted = edge
+                    .propagated()
+                    .iter()
+                    .any(|l| assumed[*l].is_some_and(|p| p != l.polarity()));
+                let new_edge = if contradicted {
+                    Edge::from_raw_data(false_index, vec![])
+                } else {
+                    Edge::from_raw_data(
+                        NodeIndex::from(usize::from(edge.target()) + shift),
+                        edge.propagated()
+                            .iter()
+                            .filter(|l| assumed[**l].is_none())
+                            .copied()
+                            .collect(),
+                    )
+                };
+                edges.push(new_edge);
+                new_node.add_edge(EdgeIndex::from(edges.len() - 1)).unwrap();
+            }
+            nodes.push(new_node);
+        }
+        nodes.push(Node::False);
+        let (nodes, edges) = simplification::simplify(&nodes, &edges, true, false);
+        Ok(DecisionDNNF::from_raw_data(n_vars, nodes, edges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{D4Reader, Literal, ModelCounter, NodeIndex};
+
+    fn check_conditioning(instance: &str, n_vars: Option<usize>, literals: &[isize]) {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let literals = literals
+            .iter()
+            .map(|l| Literal::from(*l))
+            .collect::<Vec<_>>();
+        let conditioned = ddnnf.condition(&literals);
+        assert_eq!(
+            ModelCounter::new(&ddnnf).count_from_under_assumptions(NodeIndex::from(0), &literals),
+            *ModelCounter::new(&conditioned).count()
+        );
+    }
+
+    #[test]
+    fn test_condition() {
+        let instance =
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n";
+        check_conditioning(instance, None, &[]);
+        check_conditioning(instance, None, &[1]);
+        check_conditioning(instance, None, &[1, -2]);
+        check_conditioning(instance, None, &[1, -1]);
+        check_conditioning(instance, Some(3), &[3]);
+    }
+
+    #[test]
+    fn test_condition_prunes() {
+        let clause = "o 1 0\no 2 0\nt 3 0\n2 3 -1 -2 0\n2 3 1 0\n1 2 0";
+        check_conditioning(clause, None, &[-1]);
+        check_conditioning(clause, None, &[-1, 2]);
+        let ddnnf = D4Reader::read(clause.as_bytes()).unwrap();
+        let conditioned = ddnnf.condition(&[Literal::from(1)]);
+        assert_eq!(4, conditioned.n_nodes());
+        assert_eq!(3, conditioned.n_edges());
+        let conditioned = ddnnf.condition(&[Literal::from(-1), Literal::from(2)]);
+        assert_eq!(1, conditioned.n_nodes());
+        assert_eq!(0, conditioned.n_edges());
+    }
+
+    #[test]
+    #[should_panic(expected = "no such literal: 3 (the formula has 2 variables)")]
+    fn test_condition_unknown_var() {
+        let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+        let _ = ddnnf.condition(&[Literal::from(3)]);
+    }
+
+    #[test]
+    fn test_try_condition_unknown_var() {
+        let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+        let e = ddnnf.try_condition(&[Literal::from(3)]).unwrap_err();
+        assert_eq!(
+            "no such literal: 3 (the formula has 2 variables)",
+            e.to_string()
+        );
+        assert!(ddnnf.try_condition(&[Literal::from(2)]).is_ok());
+    }
+}
@@ -0,0 +1,204 @@
+use super::{simplification, Node, NodeIndex};
+use crate::{DecisionDNNF, EdgeIndex};
+use anyhow::{anyhow, Result};
+
+/// The part of a [`DecisionDNNF`] that mentions some variables, also known as their cone of influence.
+///
+/// An edge belongs to the cone if it propagates a literal of one of the variables, or if its target belongs to the cone.
+/// A node belongs to the cone if one of its edges belongs to it.
+/// The cone is thus the smallest subcircuit containing the root and the edges mentioning the variables, together with the paths between them.
+///
+/// The cone can be extracted as a standalone formula, in which the nodes out of the cone are replaced by true nodes.
+/// The extracted formula is entailed by the original one; it keeps the decisions made on the paths to the variables.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{ConeOfInfluence, D4Reader, NodeIndex};
+///
+/// // 1 and ((-2 and 3) or 2)
+/// let ddnnf = D4Reader::read("a 1 0\no 2 0\nt 3 0\n1 3 1 0\n1 2 0\n2 3 -2 3 0\n2 3 2 0\n".as_bytes()).unwrap();
+/// let cone = ConeOfInfluence::compute(&ddnnf, &[2]);
+/// assert!(cone.contains_node(NodeIndex::from(1)));
+/// assert_eq!(2, cone.n_nodes());
+/// assert_eq!(2, cone.n_edges());
+/// ```
+#[derive(Debug)]
+pub struct ConeOfInfluence<'a> {
+    ddnnf: &'a DecisionDNNF,
+    nodes: Vec<bool>,
+    edges: Vec<bool>,
+}
+
+impl<'a> ConeOfInfluence<'a> {
+    /// Computes the cone of influence of some variables, given by their indices.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a variable index is out of bounds.
+    #[must_use]
+    pub fn compute(ddnnf: &'a DecisionDNNF, vars: &[usize]) -> Self {
+        Self::try_compute(ddnnf, vars).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Computes the cone of influence of some variables, as [`compute`](Self::compute) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if a variable index is out of bounds.
+    pub fn try_compute(ddnnf: &'a DecisionDNNF, vars: &[usize]) -> Result<Self> {
+        let n_vars = ddnnf.n_vars();
+        let mut selected = vec![false; n_vars];
+        for v in vars {
+            if *v >= n_vars {
+                return Err(anyhow!(
+                    "no such variable index: {v} (the formula has {n_vars} variables)"
+                ));
+            }
+            selected[*v] = true;
+        }
+        let mut nodes = vec![false; ddnnf.n_nodes()];
+        let mut edges = vec![false; ddnnf.n_edges()];
+        for node in ddnnf.topological_order().into_iter().rev() {
+            if let Node::And(edge_indices) | Node::Or(edge_indices) = ddnnf.node(node) {
+                for edge_index in edge_indices {
+                    let edge = ddnnf.edge(*edge_index);
+                    let in_cone = nodes[usize::from(edge.target())]
+                        || edge.propagated().iter().any(|l| selected[l.var_index()]);
+                    edges[usize::from(*edge_index)] = in_cone;
+                    nodes[usize::from(node)] |= in_cone;
+                }
+            }
+        }
+        Ok(Self {
+            ddnnf,
+            nodes,
+            edges,
+        })
+    }
+
+    /// Returns `true` if and only if the node belongs to the cone.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn contains_node(&self, node: NodeIndex) -> bool {
+        self.nodes[usize::from(node)]
+    }
+
+    /// Returns `true` if and only if the edge belongs to the cone.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the edge index is out of bounds.
+    #[must_use]
+    pub fn contains_edge(&self, edge: EdgeIndex) -> bool {
+        self.edges[usize::from(edge)]
+    }
+
+    /// Returns the number of nodes in the cone.
+    #[must_use]
+    pub fn n_nodes(&self) -> usize {
+        self.nodes.iter().filter(|b| **b).count()
+    }
+
+    /// Returns the number of edges in the cone.
+    #[must_use]
+    pub fn n_edges(&self) -> usize {
+        self.edges.iter().filter(|b| **b).count()
+    }
+
+    /// Extracts the cone as a standalone formula, in which the nodes out of the cone are replaced by a single true node.
+    ///
+    /// The edges from the nodes of the cone to the nodes out of it are kept, together with the literals they propagate.
+    #[must_use]
+    pub fn extract(&self) -> DecisionDNNF {
+        let nodes = self
+            .ddnnf
+            .nodes()
+            .as_slice()
+            .iter()
+            .zip(self.nodes.iter())
+            .map(|(node, in_cone)| match node {
+                Node::And(edges) if *in_cone => Node::And(edges.clone()),
+                Node::Or(edges) if *in_cone => Node::Or(edges.clone()),
+                _ => Node::True,
+            })
+            .collect::<Vec<_>>();
+        let (nodes, edges) =
+            simplification::simplify(&nodes, self.ddnnf.edges().as_slice(), false, true);
+        DecisionDNNF::from_raw_data(self.ddnnf.n_vars(), nodes, edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{D4Reader, D4Writer};
+
+    // 1 and ((-2 and 3) or 2) and (-4 or 4)
+    const INSTANCE: &str =
+        "a 1 0\no 2 0\no 3 0\nt 4 0\n1 4 1 0\n1 2 0\n1 3 0\n2 4 -2 3 0\n2 4 2 0\n3 4 -4 0\n3 4 4 0\n";
+
+    fn extracted(instance: &str, vars: &[usize]) -> String {
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        D4Writer::write(
+            &mut output,
+            &ConeOfInfluence::compute(&ddnnf, vars).extract(),
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_cone() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let cone = ConeOfInfluence::compute(&ddnnf, &[2]);
+        assert_eq!(
+            vec![true, true, false, false],
+            (0..4)
+                .map(|i| cone.contains_node(NodeIndex::from(i)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![false, true, false, true, false, false, false],
+            (0..7)
+                .map(|i| cone.contains_edge(EdgeIndex::from(i)))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_cone_propagated_literal() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        let cone = ConeOfInfluence::compute(&ddnnf, &[0]);
+        assert_eq!(1, cone.n_nodes());
+        assert_eq!(1, cone.n_edges());
+    }
+
+    #[test]
+    fn test_cone_unmentioned_var() {
+        let mut ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        ddnnf.update_n_vars(5);
+        let cone = ConeOfInfluence::compute(&ddnnf, &[4]);
+        assert_eq!(0, cone.n_nodes());
+        assert_eq!(0, cone.n_edges());
+        assert_eq!("t 1 0\n", extracted(INSTANCE, &[]));
+    }
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(
+            "a 1 0\nt 2 0\no 3 0\n1 2 1 0\n1 2 0\n1 3 0\n3 2 -4 0\n3 2 4 0\n",
+            extracted(INSTANCE, &[3])
+        );
+    }
+
+    #[test]
+    fn test_unknown_var() {
+        let ddnnf = D4Reader::read(INSTANCE.as_bytes()).unwrap();
+        assert!(ConeOfInfluence::try_compute(&ddnnf, &[4]).is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use super::{InvolvedVars, Node};
+use crate::{DecisionDNNF, DecisionProfile, FreeVariables};
+
+/// Structural metrics of a [`DecisionDNNF`], computed on the loaded formula.
+///
+/// The fan-out of a node is its number of children, and its fan-in is its number of parents.
+/// The averages of the fan-outs are computed on the internal nodes, and the averages of the fan-ins on the nodes that are not the root.
+///
+/// The decision variables are the ones some disjunction node decides on (see [`DecisionProfile`]).
+/// The free variables of the root are the ones the formula does not involve, while the free variables of the disjunction children are the ones a child does not involve while one of its siblings does (see [`FreeVariables`]).
+///
+/// The decomposition width is the maximal number of variables involved in a child of a conjunction node, that is the size of the largest subproblem left after a decomposition;
+/// it is 0 if the formula has no conjunction node.
+///
+/// The components are the independent sub-formulas given by [`DecisionDNNF::components`];
+/// the size of a component is the number of variables it involves.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, DecisionDNNFStats};
+///
+/// // 1 and (-2 or 2)
+/// let ddnnf = D4Reader::read("a 1 0\no 2 0\nt 3 0\n1 3 1 0\n1 2 0\n2 3 -2 0\n2 3 2 0\n".as_bytes()).unwrap();
+/// let stats = DecisionDNNFStats::compute(&ddnnf);
+/// assert_eq!(3, stats.n_nodes());
+/// assert_eq!(3, stats.max_fan_in());
+/// assert_eq!(1, stats.n_decision_vars());
+/// assert_eq!(1, stats.decomposition_width());
+/// assert_eq!(&[1, 1], stats.component_sizes());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionDNNFStats {
+    n_and_nodes: usize,
+    n_or_nodes: usize,
+    n_true_nodes: usize,
+    n_false_nodes: usize,
+    n_edges: usize,
+    depth: usize,
+    max_fan_out: usize,
+    max_fan_in: usize,
+    n_vars: usize,
+    n_decision_vars: usize,
+    n_root_free_vars: usize,
+    n_or_free_vars: usize,
+    decomposition_width: usize,
+    component_sizes: Vec<usize>,
+}
+
+impl DecisionDNNFStats {
+    /// Computes the metrics of a [`DecisionDNNF`].
+    ///
+    /// The computation takes a time linear in the size of the formula times its number of variables.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn compute(ddnnf: &DecisionDNNF) -> Self {
+        let mut stats = Self {
+            n_and_nodes: 0,
+            n_or_nodes: 0,
+            n_true_nodes: 0,
+            n_false_nodes: 0,
+            n_edges: ddnnf.n_edges(),
+            depth: ddnnf.depth(),
+            max_fan_out: 0,
+            max_fan_in: 0,
+            n_vars: ddnnf.n_vars(),
+            n_decision_vars: 0,
+            n_root_free_vars: 0,
+            n_or_free_vars: 0,
+            decomposition_width: 0,
+            component_sizes: vec![],
+        };
+        let mut fan_ins = vec![0; ddnnf.n_nodes()];
+        let mut involved_vars: Vec<Option<InvolvedVars>> = vec![None; ddnnf.n_nodes()];
+        for node in ddnnf.topological_order().into_iter().rev() {
+            let edges = match ddnnf.node(node) {
+                Node::And(edges) => {
+                    stats.n_and_nodes += 1;
+                    edges.as_slice()
+                }
+                Node::Or(edges) => {
+                    stats.n_or_nodes += 1;
+                    edges.as_slice()
+                }
+                Node::True => {
+                    stats.n_true_nodes += 1;
+                    &[]
+                }
+                Node::False => {
+                    stats.n_false_nodes += 1;
+                    &[]
+                }
+            };
+            stats.max_fan_out = stats.max_fan_out.max(edges.len());
+            let mut node_vars = InvolvedVars::new(ddnnf.n_vars());
+            for edge_index in edges {
+                let edge = ddnnf.edge(*edge_index);
+                fan_ins[usize::from(edge.target())] += 1;
+                let mut edge_vars = involved_vars[usize::from(edge.target())].clone().unwrap();
+                edge_vars.set_literals(edge.propagated());
+                if matches!(ddnnf.node(node), Node::And(_)) {
+                    stats.decomposition_width =
+                        stats.decomposition_width.max(edge_vars.count_ones());
+                }
+                node_vars.or_assign(&edge_vars);
+            }
+            involved_vars[usize::from(node)] = Some(node_vars);
+        }
+        stats.max_fan_in = fan_ins.into_iter().max().unwrap_or_default();
+        let (component_literals, component_roots) = ddnnf.component_roots();
+        stats.component_sizes = component_literals
+            .iter()
+            .map(|_| 1)
+            .chain(component_roots.iter().map(|r| {
+                involved_vars[usize::from(*r)]
+                    .as_ref()
+                    .unwrap()
+                    .count_ones()
+            }))
+            .collect();
+        let profile = DecisionProfile::compute(ddnnf);
+        stats.n_decision_vars = (0..ddnnf.n_vars())
+            .filter(|v| !profile.decision_depths(*v).is_empty())
+            .count();
+        let free_vars = FreeVariables::compute(ddnnf);
+        stats.n_root_free_vars = free_vars.root_free_vars().len();
+        stats.n_or_free_vars = free_vars.iter().map(|(_, _, vars)| vars.len()).sum();
+        stats
+    }
+
+    /// Returns the number of nodes.
+    #[must_use]
+    pub fn n_nodes(&self) -> usize {
+        self.n_and_nodes + self.n_or_nodes + self.n_true_nodes + self.n_false_nodes
+    }
+
+    /// Returns the number of conjunction nodes.
+    #[must_use]
+    pub fn n_and_nodes(&self) -> usize {
+        self.n_and_nodes
+    }
+
+    /// Returns the number of disjunction nodes.
+    #[must_use]
+    pub fn n_or_nodes(&self) -> usize {
+        self.n_or_nodes
+    }
+
+    /// Returns the number of true nodes.
+    #[must_use]
+    pub fn n_true_nodes(&self) -> usize {
+        self.n_true_nodes
+    }
+
+    /// Returns the number of false nodes.
+    #[must_use]
+    pub fn n_false_nodes(&self) -> usize {
+        self.n_false_nodes
+    }
+
+    /// Returns the number of edges.
+    #[must_use]
+    pub fn n_edges(&self) -> usize {
+        self.n_edges
+    }
+
+    /// Returns the depth of the formula (see [`DecisionDNNF::depth`]).
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the maximal fan-out of a node.
+    #[must_use]
+    pub fn max_fan_out(&self) -> usize {
+        self.max_fan_out
+    }
+
+    /// Returns the average fan-out of the internal nodes, or 0 if there is no internal node.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_fan_out(&self) -> f64 {
+        let n_internal = self.n_and_nodes + self.n_or_nodes;
+        if n_internal == 0 {
+            0.
+        } else {
+            self.n_edges as f64 / n_internal as f64
+        }
+    }
+
+    /// Returns the maximal fan-in of a node.
+    #[must_use]
+    pub fn max_fan_in(&self) -> usize {
+        self.max_fan_in
+    }
+
+    /// Returns the average fan-in of the nodes that are not the root, or 0 if the root is the only node.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_fan_in(&self) -> f64 {
+        let n_non_root = self.n_nodes() - 1;
+        if n_non_root == 0 {
+            0.
+        } else {
+            self.n_edges as f64 / n_non_root as f64
+        }
+    }
+
+    /// Returns the number of variables.
+    #[must_use]
+    pub fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    /// Returns the number of variables some disjunction node decides on.
+    #[must_use]
+    pub fn n_decision_vars(&self) -> usize {
+        self.n_decision_vars
+    }
+
+    /// Returns the number of variables the formula does not involve.
+    #[must_use]
+    pub fn n_root_free_vars(&self) -> usize {
+        self.n_root_free_vars
+    }
+
+    /// Returns the total number of free variables of the children of the disjunction nodes.
+    #[must_use]
+    pub fn n_or_free_vars(&self) -> usize {
+        self.n_or_free_vars
+    }
+
+    /// Returns the decomposition width of the formula.
+    #[must_use]
+    pub fn decomposition_width(&self) -> usize {
+        self.decomposition_width
+    }
+
+    /// Returns the sizes of the independent components of the formula, in the order given by [`DecisionDNNF::components`].
+    #[must_use]
+    pub fn component_sizes(&self) -> &[usize] {
+        &self.component_sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    #[test]
+    fn test_stats() {
+        // (1 and ((-2 and -3) or 2)) or -1
+        let ddnnf = D4Reader::read(
+            "o 1 0\na 2 0\no 3 0\nt 4 0\n1 2 1 0\n1 4 -1 0\n2 3 0\n3 4 -2 -3 0\n3 4 2 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let stats = DecisionDNNFStats::compute(&ddnnf);
+        assert_eq!(
+            (1, 2, 1, 0),
+            (
+                stats.n_and_nodes(),
+                stats.n_or_nodes(),
+                stats.n_true_nodes(),
+                stats.n_false_nodes()
+            )
+        );
+        assert_eq!(5, stats.n_edges());
+        assert_eq!(3, stats.depth());
+        assert_eq!(2, stats.max_fan_out());
+        assert_eq!(3, stats.max_fan_in());
+        assert!((stats.average_fan_out() - 5. / 3.).abs() < 1e-9);
+        assert!((stats.average_fan_in() - 5. / 3.).abs() < 1e-9);
+        assert_eq!(3, stats.n_vars());
+        assert_eq!(2, stats.n_decision_vars());
+        assert_eq!(0, stats.n_root_free_vars());
+        assert_eq!(3, stats.n_or_free_vars());
+        assert_eq!(2, stats.decomposition_width());
+        assert_eq!(&[3], stats.component_sizes());
+    }
+
+    #[test]
+    fn test_single_node() {
+        let mut ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let stats = DecisionDNNFStats::compute(&ddnnf);
+        assert_eq!(1, stats.n_nodes());
+        assert_eq!(0, stats.depth());
+        assert!(stats.average_fan_out().abs() < f64::EPSILON);
+        assert!(stats.average_fan_in().abs() < f64::EPSILON);
+        assert_eq!(2, stats.n_root_free_vars());
+        assert_eq!(0, stats.decomposition_width());
+        assert_eq!(&[0], stats.component_sizes());
+    }
+}
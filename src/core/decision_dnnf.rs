@@ -186,11 +186,24 @@ impl DecisionDNNF {
     ///
     /// This function panics if the new number of variables is lower than the current.
     pub fn update_n_vars(&mut self, n_vars: usize) {
-        assert!(
-            n_vars >= self.n_vars,
-            "cannot reduce the number of variables"
-        );
+        self.try_update_n_vars(n_vars)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Updates the number of variables, as [`update_n_vars`](Self::update_n_vars) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the new number of variables is lower than the current; the formula is left unchanged in this case.
+    pub fn try_update_n_vars(&mut self, n_vars: usize) -> Result<()> {
+        if n_vars < self.n_vars {
+            return Err(anyhow!(
+                "cannot reduce the number of variables (from {} to {n_vars})",
+                self.n_vars
+            ));
+        }
         self.n_vars = n_vars;
+        Ok(())
     }
 
     /// Returns the number of variables involved in this Decision-DNNF.
@@ -201,6 +214,40 @@ impl DecisionDNNF {
         self.n_vars
     }
 
+    /// Returns the number of nodes of this Decision-DNNF.
+    #[must_use]
+    pub fn n_nodes(&self) -> usize {
+        self.nodes.0.len()
+    }
+
+    /// Returns the number of edges of this Decision-DNNF.
+    #[must_use]
+    pub fn n_edges(&self) -> usize {
+        self.edges.0.len()
+    }
+
+    /// Returns the node with the given index.
+    ///
+    /// The root of the formula has index 0.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the index is out of bounds.
+    #[must_use]
+    pub fn node(&self, index: NodeIndex) -> &Node {
+        &self.nodes[index]
+    }
+
+    /// Returns the edge with the given index.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the index is out of bounds.
+    #[must_use]
+    pub fn edge(&self, index: EdgeIndex) -> &Edge {
+        &self.edges[index]
+    }
+
     pub(crate) fn nodes(&self) -> &NodeVec {
         &self.nodes
     }
@@ -0,0 +1,201 @@
+use super::{Node, NodeIndex};
+use crate::{DecisionDNNF, Literal};
+
+/// The depths at which the variables of a [`DecisionDNNF`] are decided.
+///
+/// A disjunction node decides on a variable if it has two children and the edges to them propagate opposite literals on this variable, as the decision nodes produced by d4.
+/// The decision depth of a node is the number of disjunction nodes on the shortest path from the root to this node, excluding the node itself; the decision depth of the root is thus 0.
+/// For each variable, this structure gathers the decision depths of the nodes that decide on it.
+/// Comparing these depths between formulas helps to compare the variable ordering heuristics of the compilers that produced them.
+///
+/// Variables are given by their indices, beginning at 0.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, DecisionProfile};
+///
+/// // (-1 and (-2 or 2)) or (1 and (-2 or 2))
+/// let ddnnf = D4Reader::read("o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 0\n2 4 -2 0\n2 4 2 0\n3 4 2 0\n3 4 -2 0\n".as_bytes()).unwrap();
+/// let profile = DecisionProfile::compute(&ddnnf);
+/// assert_eq!(&[0], profile.decision_depths(0));
+/// assert_eq!(&[1, 1], profile.decision_depths(1));
+/// ```
+#[derive(Debug)]
+pub struct DecisionProfile {
+    decision_vars: Vec<Option<usize>>,
+    decision_depths: Vec<Vec<usize>>,
+}
+
+impl DecisionProfile {
+    /// Computes the decision profile of a [`DecisionDNNF`].
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn compute(ddnnf: &DecisionDNNF) -> Self {
+        let n_nodes = ddnnf.n_nodes();
+        let decision_vars = (0..n_nodes)
+            .map(|i| decision_var(ddnnf, NodeIndex::from(i)))
+            .collect::<Vec<_>>();
+        let mut depths: Vec<Option<usize>> = vec![None; n_nodes];
+        depths[0] = Some(0);
+        for node in ddnnf.topological_order() {
+            let Some(depth) = depths[usize::from(node)] else {
+                continue;
+            };
+            let (Node::And(edges) | Node::Or(edges)) = ddnnf.node(node) else {
+                continue;
+            };
+            let child_depth = depth + usize::from(matches!(ddnnf.node(node), Node::Or(_)));
+            for edge_index in edges {
+                let target = &mut depths[usize::from(ddnnf.edge(*edge_index).target())];
+                *target = Some(target.map_or(child_depth, |d| d.min(child_depth)));
+            }
+        }
+        let mut decision_depths = vec![vec![]; ddnnf.n_vars()];
+        for (opt_var, opt_depth) in decision_vars.iter().zip(depths.iter()) {
+            if let (Some(var), Some(depth)) = (opt_var, opt_depth) {
+                decision_depths[*var].push(*depth);
+            }
+        }
+        for depths in &mut decision_depths {
+            depths.sort_unstable();
+        }
+        Self {
+            decision_vars,
+            decision_depths,
+        }
+    }
+
+    /// Returns the variable the given node decides on, if any.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn decision_var(&self, node: NodeIndex) -> Option<usize> {
+        self.decision_vars[usize::from(node)]
+    }
+
+    /// Returns the decision depths of the nodes deciding on the given variable, in increasing order.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the variable index is out of bounds.
+    #[must_use]
+    pub fn decision_depths(&self, var: usize) -> &[usize] {
+        &self.decision_depths[var]
+    }
+
+    /// Returns the minimal decision depth of the nodes deciding on the given variable, or [`None`] if no node decides on it.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the variable index is out of bounds.
+    #[must_use]
+    pub fn min_depth(&self, var: usize) -> Option<usize> {
+        self.decision_depths[var].first().copied()
+    }
+
+    /// Returns the maximal decision depth of the nodes deciding on the given variable, or [`None`] if no node decides on it.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the variable index is out of bounds.
+    #[must_use]
+    pub fn max_depth(&self, var: usize) -> Option<usize> {
+        self.decision_depths[var].last().copied()
+    }
+
+    /// Returns the average decision depth of the nodes deciding on the given variable, or [`None`] if no node decides on it.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the variable index is out of bounds.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_depth(&self, var: usize) -> Option<f64> {
+        let depths = &self.decision_depths[var];
+        if depths.is_empty() {
+            None
+        } else {
+            Some(depths.iter().sum::<usize>() as f64 / depths.len() as f64)
+        }
+    }
+}
+
+fn decision_var(ddnnf: &DecisionDNNF, node: NodeIndex) -> Option<usize> {
+    let Node::Or(edges) = ddnnf.node(node) else {
+        return None;
+    };
+    let [first, second] = edges.as_slice() else {
+        return None;
+    };
+    let second_propagated = ddnnf.edge(*second).propagated();
+    ddnnf
+        .edge(*first)
+        .propagated()
+        .iter()
+        .find(|l| second_propagated.contains(&l.flip()))
+        .map(Literal::var_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    #[test]
+    fn test_shared_node() {
+        // the node deciding on 3 is reached at decision depths 1 and 2
+        let ddnnf = D4Reader::read(
+            "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 0\n2 3 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let profile = DecisionProfile::compute(&ddnnf);
+        assert_eq!(Some(0), profile.decision_var(NodeIndex::from(0)));
+        assert_eq!(Some(2), profile.decision_var(NodeIndex::from(2)));
+        assert_eq!(None, profile.decision_var(NodeIndex::from(3)));
+        assert_eq!(&[1], profile.decision_depths(2));
+        assert_eq!(Some(1), profile.min_depth(1));
+        assert_eq!(Some(1), profile.max_depth(1));
+    }
+
+    #[test]
+    fn test_and_nodes_do_not_count() {
+        // (-1 or 1) and (-2 or 2)
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let profile = DecisionProfile::compute(&ddnnf);
+        assert_eq!(&[0], profile.decision_depths(0));
+        assert_eq!(&[0], profile.decision_depths(1));
+    }
+
+    #[test]
+    fn test_average_depth() {
+        // (-1 and (-2 or 2)) or (1 and (2 or (-2 and (-3 or 3))))
+        let ddnnf = D4Reader::read(
+            "o 1 0\no 2 0\no 3 0\no 4 0\nt 5 0\n1 2 -1 0\n1 3 1 0\n2 5 -2 0\n2 5 2 0\n3 5 2 0\n3 4 -2 0\n4 5 -3 0\n4 5 3 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let profile = DecisionProfile::compute(&ddnnf);
+        assert_eq!(&[1, 1], profile.decision_depths(1));
+        assert_eq!(&[2], profile.decision_depths(2));
+        assert!((profile.average_depth(1).unwrap() - 1.).abs() < f64::EPSILON);
+        assert!((profile.average_depth(2).unwrap() - 2.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_no_decision() {
+        let mut ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        ddnnf.update_n_vars(1);
+        let profile = DecisionProfile::compute(&ddnnf);
+        assert!(profile.decision_depths(0).is_empty());
+        assert_eq!(None, profile.min_depth(0));
+        assert_eq!(None, profile.average_depth(0));
+    }
+}
@@ -0,0 +1,110 @@
+use super::{Node, NodeIndex};
+use crate::DecisionDNNF;
+
+impl DecisionDNNF {
+    /// Returns the depth of the formula, that is the number of edges of the longest path from the root to a leaf.
+    ///
+    /// Many algorithms of this crate are recursive, and the depth of their recursion is bounded by the depth of the formula.
+    /// This function does not recurse itself, allowing to check whether a formula is too deep for the available stack before processing it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// let ddnnf = D4Reader::read("a 1 0\no 2 0\nt 3 0\n1 2 0\n2 3 -1 0\n2 3 1 0\n1 3 2 0\n".as_bytes()).unwrap();
+    /// assert_eq!(2, ddnnf.depth());
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn depth(&self) -> usize {
+        let mut depths: Vec<Option<usize>> = vec![None; self.n_nodes()];
+        let mut stack = vec![(NodeIndex::from(0), 0)];
+        while let Some((node, next_child)) = stack.pop() {
+            let edges = match self.node(node) {
+                Node::And(edges) | Node::Or(edges) => edges.as_slice(),
+                Node::True | Node::False => &[],
+            };
+            if let Some(edge_index) = edges.get(next_child) {
+                stack.push((node, next_child + 1));
+                let target = self.edge(*edge_index).target();
+                if depths[usize::from(target)].is_none() {
+                    stack.push((target, 0));
+                }
+                continue;
+            }
+            depths[usize::from(node)] = Some(
+                edges
+                    .iter()
+                    .map(|e| 1 + depths[usize::from(self.edge(*e).target())].unwrap())
+                    .max()
+                    .unwrap_or_default(),
+            );
+        }
+        depths[0].unwrap()
+    }
+
+    /// Returns the nodes reachable from the root, each of them coming before its children.
+    pub(crate) fn topological_order(&self) -> Vec<NodeIndex> {
+        let mut visited = vec![false; self.n_nodes()];
+        let mut post_order = Vec::with_capacity(self.n_nodes());
+        visited[0] = true;
+        let mut stack = vec![(NodeIndex::from(0), 0)];
+        while let Some((node, next_child)) = stack.pop() {
+            let edges = match self.node(node) {
+                Node::And(edges) | Node::Or(edges) => edges.as_slice(),
+                Node::True | Node::False => &[],
+            };
+            if let Some(edge_index) = edges.get(next_child) {
+                stack.push((node, next_child + 1));
+                let target = self.edge(*edge_index).target();
+                if !visited[usize::from(target)] {
+                    visited[usize::from(target)] = true;
+                    stack.push((target, 0));
+                }
+                continue;
+            }
+            post_order.push(node);
+        }
+        post_order.reverse();
+        post_order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CheckLevel, D4Reader};
+    use std::fmt::Write;
+
+    #[test]
+    fn test_leaf() {
+        assert_eq!(0, D4Reader::read("t 1 0\n".as_bytes()).unwrap().depth());
+    }
+
+    #[test]
+    fn test_shared_node() {
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 3 -1 0\n2 4 1 0\n3 4 -2 0\n3 4 2 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(3, ddnnf.depth());
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let n = 100_000;
+        let mut instance = String::new();
+        for i in 1..=n {
+            writeln!(instance, "a {i} 0").unwrap();
+        }
+        writeln!(instance, "t {} 0", n + 1).unwrap();
+        for i in 1..=n {
+            writeln!(instance, "{i} {} 0", i + 1).unwrap();
+        }
+        let mut reader = D4Reader::default();
+        reader.set_check_level(CheckLevel::None);
+        let ddnnf = reader.read_with_options(instance.as_bytes()).unwrap();
+        assert_eq!(n, ddnnf.depth());
+    }
+}
@@ -0,0 +1,469 @@
+use super::{assumed_polarities, InvolvedVars, Node, NodeIndex};
+use crate::{DecisionDNNF, Literal};
+use anyhow::Result;
+
+/// The free variables of a [`DecisionDNNF`].
+///
+/// A variable is free at the root if it does not appear in the formula.
+/// A variable is free for a child of a disjunction node if it appears under the disjunction node but neither under the child nor in the literals propagated by the edge to this child.
+/// Such variables can take any value in the models built from this child.
+///
+/// Free variables are given as variable indices, regardless of any polarity; variable indices begin at 0.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, FreeVariables, NodeIndex};
+///
+/// // (-1 -2) or (1), with a third variable that does not appear in the formula
+/// let mut ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// ddnnf.update_n_vars(3);
+/// let free_vars = FreeVariables::compute(&ddnnf);
+/// assert_eq!(&[2], free_vars.root_free_vars());
+/// assert_eq!(&[] as &[usize], free_vars.or_child_free_vars(NodeIndex::from(0), 0));
+/// assert_eq!(&[1], free_vars.or_child_free_vars(NodeIndex::from(0), 1));
+/// ```
+#[derive(Debug)]
+pub struct FreeVariables {
+    root_free_vars: Vec<usize>,
+    or_free_vars: Vec<Vec<Vec<usize>>>,
+    n_variables: usize,
+}
+
+impl FreeVariables {
+    /// Computes the free variables of a [`DecisionDNNF`].
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn compute(ddnnf: &DecisionDNNF) -> Self {
+        let n_nodes = ddnnf.nodes().as_slice().len();
+        let mut free_vars = Self {
+            root_free_vars: vec![],
+            or_free_vars: vec![vec![]; n_nodes],
+            n_variables: ddnnf.n_vars(),
+        };
+        let mut involved_vars = vec![None; n_nodes];
+        free_vars.compute_from(ddnnf, NodeIndex::from(0), &mut involved_vars);
+        free_vars.root_free_vars = involved_vars[0]
+            .as_ref()
+            .unwrap()
+            .iter_missing_literals()
+            .map(|l| l.var_index())
+            .collect();
+        free_vars
+    }
+
+    /// Computes the free variables of the formula obtained by conditioning a [`DecisionDNNF`] by some assumptions.
+    ///
+    /// The parts of the formula that are equivalent to false under the assumptions are ignored, as [`DecisionDNNF::condition`] does;
+    /// thus, a variable that only appears in such parts is free higher in the formula, or at the root.
+    /// The assumed variables are never free.
+    /// Contrary to the conditioned formula, the nodes keep their indices in the original formula, so that the partial models computed on this formula under the assumptions can be expanded.
+    /// The children of the disjunction nodes that are equivalent to false under the assumptions have no free variables.
+    /// If the formula has no model under the assumptions, no variable is free.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an assumption refers to a variable that does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, FreeVariables, Literal, NodeIndex};
+    ///
+    /// // (-1 -2) or (1)
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let free_vars = FreeVariables::compute_under_assumptions(&ddnnf, &[Literal::from(-2)]);
+    /// assert_eq!(&[] as &[usize], free_vars.or_child_free_vars(NodeIndex::from(0), 1));
+    /// let free_vars = FreeVariables::compute_under_assumptions(&ddnnf, &[Literal::from(1)]);
+    /// assert_eq!(&[1], free_vars.root_free_vars());
+    /// ```
+    #[must_use]
+    pub fn compute_under_assumptions(ddnnf: &DecisionDNNF, assumptions: &[Literal]) -> Self {
+        Self::try_compute_under_assumptions(ddnnf, assumptions).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Computes the free variables of the formula obtained by conditioning a [`DecisionDNNF`] by some assumptions,
+    /// as [`compute_under_assumptions`](Self::compute_under_assumptions) does.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if an assumption refers to a variable that does not exist.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn try_compute_under_assumptions(
+        ddnnf: &DecisionDNNF,
+        assumptions: &[Literal],
+    ) -> Result<Self> {
+        let n_vars = ddnnf.n_vars();
+        let mut free_vars = Self {
+            root_free_vars: vec![],
+            or_free_vars: (0..ddnnf.n_nodes())
+                .map(|i| match ddnnf.node(NodeIndex::from(i)) {
+                    Node::Or(edges) => vec![vec![]; edges.len()],
+                    _ => vec![],
+                })
+                .collect(),
+            n_variables: n_vars,
+        };
+        let Some(assumed) = assumed_polarities(assumptions, n_vars)? else {
+            return Ok(free_vars);
+        };
+        // the variables involved in each node, or None if the node is equivalent to false under the assumptions
+        let mut involved_vars: Vec<Option<InvolvedVars>> = vec![None; ddnnf.n_nodes()];
+        let edge_vars = |edge_index, involved_vars: &[Option<InvolvedVars>]| {
+            let edge = ddnnf.edge(edge_index);
+            if edge
+                .propagated()
+                .iter()
+                .any(|l| assumed[*l].is_some_and(|p| p != l.polarity()))
+            {
+                return None;
+            }
+            let mut vars = involved_vars[usize::from(edge.target())].clone()?;
+            vars.set_literals(edge.propagated());
+            Some(vars)
+        };
+        let order = ddnnf.topological_order();
+        for node in order.iter().rev() {
+            let mut union = InvolvedVars::new(n_vars);
+            involved_vars[usize::from(*node)] = match ddnnf.node(*node) {
+                Node::And(edges) => edges
+                    .iter()
+                    .try_for_each(|e| {
+                        union.or_assign(&edge_vars(*e, &involved_vars)?);
+                        Some(())
+                    })
+                    .map(|()| union),
+                Node::Or(edges) => {
+                    let mut consistent = false;
+                    for vars in edges.iter().filter_map(|e| edge_vars(*e, &involved_vars)) {
+                        union.or_assign(&vars);
+                        consistent = true;
+                    }
+                    consistent.then_some(union)
+                }
+                Node::True => Some(union),
+                Node::False => None,
+            };
+        }
+        let Some(root_vars) = &involved_vars[0] else {
+            return Ok(free_vars);
+        };
+        let not_assumed = |l: &Literal| assumed[*l].is_none();
+        free_vars.root_free_vars = root_vars
+            .iter_missing_literals()
+            .filter(not_assumed)
+            .map(|l| l.var_index())
+            .collect();
+        for node in order {
+            let (Node::Or(edges), Some(node_vars)) =
+                (ddnnf.node(node), &involved_vars[usize::from(node)])
+            else {
+                continue;
+            };
+            for (child_index, edge_index) in edges.iter().enumerate() {
+                if let Some(mut vars) = edge_vars(*edge_index, &involved_vars) {
+                    vars.xor_assign(node_vars);
+                    free_vars.or_free_vars[usize::from(node)][child_index] = vars
+                        .iter_pos_literals()
+                        .filter(not_assumed)
+                        .map(|l| l.var_index())
+                        .collect();
+                }
+            }
+        }
+        Ok(free_vars)
+    }
+
+    fn compute_from(
+        &mut self,
+        ddnnf: &DecisionDNNF,
+        from: NodeIndex,
+        involved_vars: &mut [Option<InvolvedVars>],
+    ) {
+        if involved_vars[usize::from(from)].is_some() {
+            return;
+        }
+        involved_vars[usize::from(from)] =
+            Some(self.compute_involved_vars(ddnnf, from, involved_vars));
+        if let Node::Or(edges) = &ddnnf.nodes()[from] {
+            for edge_index in edges {
+                let edge = &ddnnf.edges()[*edge_index];
+                let mut involved_in_child = involved_vars[usize::from(edge.target())]
+                    .as_ref()
+                    .unwrap()
+                    .clone();
+                involved_in_child.set_literals(edge.propagated());
+                involved_in_child.xor_assign(involved_vars[usize::from(from)].as_ref().unwrap());
+                self.or_free_vars[usize::from(from)].push(
+                    involved_in_child
+                        .iter_pos_literals()
+                        .map(|l| l.var_index())
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    fn compute_involved_vars(
+        &mut self,
+        ddnnf: &DecisionDNNF,
+        node: NodeIndex,
+        involved_vars: &mut [Option<InvolvedVars>],
+    ) -> InvolvedVars {
+        let mut union = InvolvedVars::new(ddnnf.n_vars());
+        match &ddnnf.nodes()[node] {
+            Node::And(edges) | Node::Or(edges) => {
+                for edge_index in edges {
+                    let edge = &ddnnf.edges()[*edge_index];
+                    let target = edge.target();
+                    self.compute_from(ddnnf, target, involved_vars);
+                    union.or_assign(involved_vars[usize::from(target)].as_ref().unwrap());
+                    union.set_literals(edge.propagated());
+                }
+            }
+            Node::True | Node::False => {}
+        }
+        union
+    }
+
+    /// Returns the indices of the variables that do not appear in the formula.
+    #[must_use]
+    pub fn root_free_vars(&self) -> &[usize] {
+        &self.root_free_vars
+    }
+
+    /// Returns the indices of the variables that are free for a child of a disjunction node.
+    ///
+    /// The child is given by its index among the children of the disjunction node.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node is not a disjunction node or if the child index is out of bounds.
+    #[must_use]
+    pub fn or_child_free_vars(&self, or_node: NodeIndex, child_index: usize) -> &[usize] {
+        &self.or_free_vars[usize::from(or_node)][child_index]
+    }
+
+    /// Iterates over the free variables of the children of a node, in the order of the children.
+    ///
+    /// If the node is not a disjunction node, the iterator is empty.
+    pub fn iter_child_free_vars(&self, node: NodeIndex) -> impl Iterator<Item = &[usize]> + '_ {
+        self.or_free_vars[usize::from(node)]
+            .iter()
+            .map(Vec::as_slice)
+    }
+
+    /// Iterates over the free variables of all the children of all the disjunction nodes.
+    ///
+    /// Each item is made of the disjunction node, the index of the child among the children of the node, and the free variables of this child.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeIndex, usize, &[usize])> + '_ {
+        self.or_free_vars
+            .iter()
+            .enumerate()
+            .flat_map(|(node_index, children)| {
+                children.iter().enumerate().map(move |(child_index, vars)| {
+                    (NodeIndex::from(node_index), child_index, vars.as_slice())
+                })
+            })
+    }
+
+    /// Iterates over the free variables of all the children of all the disjunction nodes, one variable at a time.
+    ///
+    /// Each item is made of the disjunction node, the index of the child among the children of the node, and a free variable of this child.
+    /// The items are given in the same order as the ones of [`iter`](Self::iter).
+    pub fn iter_vars(&self) -> impl Iterator<Item = (NodeIndex, usize, usize)> + '_ {
+        self.iter()
+            .flat_map(|(node, child_index, vars)| vars.iter().map(move |v| (node, child_index, *v)))
+    }
+
+    /// Returns, for each variable, the children of the disjunction nodes for which it is free.
+    ///
+    /// The vector is indexed by the variable indices; each child is given by the disjunction node and its index among the children of this node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, FreeVariables};
+    ///
+    /// // (-1 -2) or (1)
+    /// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// let by_var = FreeVariables::compute(&ddnnf).or_children_by_free_var();
+    /// assert!(by_var[0].is_empty());
+    /// assert_eq!(1, by_var[1].len());
+    /// assert_eq!((0, 1), (usize::from(by_var[1][0].0), by_var[1][0].1));
+    /// ```
+    #[must_use]
+    pub fn or_children_by_free_var(&self) -> Vec<Vec<(NodeIndex, usize)>> {
+        let mut by_var = vec![vec![]; self.n_variables];
+        for (node, child_index, var) in self.iter_vars() {
+            by_var[var].push((node, child_index));
+        }
+        by_var
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::D4Reader;
+
+    #[test]
+    fn test_and_or() {
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 -3 0\n3 4 2 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let free_vars = FreeVariables::compute(&ddnnf);
+        assert!(free_vars.root_free_vars().is_empty());
+        assert_eq!(
+            vec![
+                (1, 0, vec![]),
+                (1, 1, vec![]),
+                (2, 0, vec![]),
+                (2, 1, vec![2])
+            ],
+            free_vars
+                .iter()
+                .map(|(n, i, v)| (usize::from(n), i, v.to_vec()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            0,
+            free_vars.iter_child_free_vars(NodeIndex::from(0)).count()
+        );
+        assert_eq!(
+            2,
+            free_vars.iter_child_free_vars(NodeIndex::from(2)).count()
+        );
+    }
+
+    /// The root free variables and the free variables of the children of the disjunction nodes.
+    type FreeVarsData = (Vec<usize>, Vec<(usize, usize, Vec<usize>)>);
+
+    fn under_assumptions(
+        instance: &str,
+        n_vars: Option<usize>,
+        assumptions: &[isize],
+    ) -> FreeVarsData {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let assumptions = assumptions
+            .iter()
+            .map(|l| Literal::from(*l))
+            .collect::<Vec<_>>();
+        let free_vars = FreeVariables::compute_under_assumptions(&ddnnf, &assumptions);
+        (
+            free_vars.root_free_vars().to_vec(),
+            free_vars
+                .iter()
+                .map(|(n, i, v)| (usize::from(n), i, v.to_vec()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_under_assumptions() {
+        let instance =
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 0\n3 4 -2 -3 0\n3 4 2 0\n";
+        assert_eq!(
+            (
+                vec![],
+                vec![
+                    (1, 0, vec![]),
+                    (1, 1, vec![]),
+                    (2, 0, vec![]),
+                    (2, 1, vec![2])
+                ]
+            ),
+            under_assumptions(instance, None, &[])
+        );
+        assert_eq!(
+            (
+                vec![],
+                vec![
+                    (1, 0, vec![]),
+                    (1, 1, vec![]),
+                    (2, 0, vec![]),
+                    (2, 1, vec![])
+                ]
+            ),
+            under_assumptions(instance, None, &[3])
+        );
+        // the only child involving variable 3 is pruned
+        assert_eq!(
+            (
+                vec![2],
+                vec![
+                    (1, 0, vec![]),
+                    (1, 1, vec![]),
+                    (2, 0, vec![]),
+                    (2, 1, vec![])
+                ]
+            ),
+            under_assumptions(instance, Some(4), &[2, 4])
+        );
+    }
+
+    #[test]
+    fn test_under_assumptions_no_model() {
+        let instance = "o 1 0\nt 2 0\nf 3 0\n1 2 -1 0\n1 3 1 0\n";
+        assert_eq!(
+            (vec![], vec![(0, 0, vec![]), (0, 1, vec![])]),
+            under_assumptions(instance, Some(2), &[1])
+        );
+        assert_eq!(
+            (vec![], vec![(0, 0, vec![]), (0, 1, vec![])]),
+            under_assumptions(instance, Some(2), &[2, -2])
+        );
+        assert_eq!(
+            (vec![1], vec![(0, 0, vec![]), (0, 1, vec![])]),
+            under_assumptions(instance, Some(2), &[])
+        );
+    }
+
+    #[test]
+    fn test_try_under_assumptions_unknown_var() {
+        let ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        assert!(FreeVariables::try_compute_under_assumptions(&ddnnf, &[Literal::from(1)]).is_err());
+    }
+
+    #[test]
+    fn test_iter_vars_and_by_var() {
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 -4 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let free_vars = FreeVariables::compute(&ddnnf);
+        assert_eq!(
+            vec![(1, 0, 1), (2, 0, 3)],
+            free_vars
+                .iter_vars()
+                .map(|(n, i, v)| (usize::from(n), i, v))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![vec![], vec![(1, 0)], vec![], vec![(2, 0)]],
+            free_vars
+                .or_children_by_free_var()
+                .into_iter()
+                .map(|children| children
+                    .into_iter()
+                    .map(|(n, i)| (usize::from(n), i))
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_root_free_vars() {
+        let mut ddnnf = D4Reader::read("t 1 0".as_bytes()).unwrap();
+        ddnnf.update_n_vars(2);
+        let free_vars = FreeVariables::compute(&ddnnf);
+        assert_eq!(&[0, 1], free_vars.root_free_vars());
+        assert_eq!(0, free_vars.iter().count());
+    }
+}
@@ -0,0 +1,52 @@
+use super::VarMap;
+use crate::Literal;
+use anyhow::{anyhow, Result};
+
+/// Returns an error if a literal refers to a variable that does not exist.
+pub(crate) fn check_literals(literals: &[Literal], n_vars: usize) -> Result<()> {
+    match literals.iter().find(|l| l.var_index() >= n_vars) {
+        Some(l) => Err(anyhow!(
+            "no such literal: {l} (the formula has {n_vars} variables)"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Returns the polarities the literals give to the variables, or `None` if some literals are contradictory.
+///
+/// The same literal may appear several times.
+/// An error is returned if a literal refers to a variable that does not exist.
+pub(crate) fn assumed_polarities(
+    literals: &[Literal],
+    n_vars: usize,
+) -> Result<Option<VarMap<Option<bool>>>> {
+    check_literals(literals, n_vars)?;
+    let mut assumed = VarMap::new(n_vars, None);
+    for l in literals {
+        match assumed[*l] {
+            Some(p) if p != l.polarity() => return Ok(None),
+            _ => assumed[*l] = Some(l.polarity()),
+        }
+    }
+    Ok(Some(assumed))
+}
+
+/// Returns the polarities of the variables set by a full assignment, or an error if the literals do not form a full assignment.
+pub(crate) fn full_assignment(literals: &[Literal], n_vars: usize) -> Result<Vec<bool>> {
+    check_literals(literals, n_vars)?;
+    let mut polarities = vec![None; n_vars];
+    for l in literals {
+        if polarities[l.var_index()].is_some() {
+            return Err(anyhow!(
+                "multiple literals for variable {}",
+                l.var_index() + 1
+            ));
+        }
+        polarities[l.var_index()] = Some(l.polarity());
+    }
+    polarities
+        .iter()
+        .enumerate()
+        .map(|(i, p)| p.ok_or_else(|| anyhow!("missing literal for variable {}", i + 1)))
+        .collect()
+}
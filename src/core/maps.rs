@@ -0,0 +1,233 @@
+use crate::Literal;
+use std::ops::{Index, IndexMut};
+
+/// A map associating a value to each variable of a formula.
+///
+/// Values can be accessed using variable indices (beginning at 0) or literals; in the latter case, the value associated with the variable of the literal is returned.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{Literal, VarMap};
+///
+/// let mut polarities = VarMap::<Option<bool>>::with_default(3);
+/// let l = Literal::from(-2);
+/// polarities[l] = Some(l.polarity());
+/// assert_eq!(Some(false), polarities[1]);
+/// assert_eq!(Some(false), polarities[l.flip()]);
+/// assert_eq!(vec![None, Some(false), None], polarities.values().copied().collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VarMap<T>(Vec<T>);
+
+impl<T> VarMap<T>
+where
+    T: Clone,
+{
+    /// Builds a map in which each of the `n_vars` variables is associated with the given value.
+    #[must_use]
+    pub fn new(n_vars: usize, value: T) -> Self {
+        Self(vec![value; n_vars])
+    }
+}
+
+impl<T> VarMap<T>
+where
+    T: Default,
+{
+    /// Builds a map in which each of the `n_vars` variables is associated with the default value of the type.
+    #[must_use]
+    pub fn with_default(n_vars: usize) -> Self {
+        Self((0..n_vars).map(|_| T::default()).collect())
+    }
+}
+
+impl<T> VarMap<T> {
+    /// Returns the number of variables of the map.
+    #[must_use]
+    pub fn n_vars(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns the values of the map, ordered by variable index.
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Iterates over the values of the map, ordered by variable index.
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.0.iter()
+    }
+
+    /// Iterates over the variable indices and their associated values.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+        self.0.iter().enumerate()
+    }
+
+    /// Iterates over the variable indices and mutable references to their associated values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> + '_ {
+        self.0.iter_mut().enumerate()
+    }
+}
+
+impl<T> Index<usize> for VarMap<T> {
+    type Output = T;
+
+    fn index(&self, var_index: usize) -> &Self::Output {
+        &self.0[var_index]
+    }
+}
+
+impl<T> IndexMut<usize> for VarMap<T> {
+    fn index_mut(&mut self, var_index: usize) -> &mut Self::Output {
+        &mut self.0[var_index]
+    }
+}
+
+impl<T> Index<Literal> for VarMap<T> {
+    type Output = T;
+
+    fn index(&self, literal: Literal) -> &Self::Output {
+        &self.0[literal.var_index()]
+    }
+}
+
+impl<T> IndexMut<Literal> for VarMap<T> {
+    fn index_mut(&mut self, literal: Literal) -> &mut Self::Output {
+        &mut self.0[literal.var_index()]
+    }
+}
+
+/// A map associating a value to each literal of a formula.
+///
+/// Contrary to a [`VarMap`], the two literals of a variable are associated with distinct values.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{LitMap, Literal};
+///
+/// let mut seen = LitMap::<bool>::with_default(2);
+/// seen[Literal::from(-2)] = true;
+/// assert!(!seen[Literal::from(2)]);
+/// let seen_literals = seen.iter().filter(|(_, b)| **b).map(|(l, _)| isize::from(l));
+/// assert_eq!(vec![-2], seen_literals.collect::<Vec<_>>());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LitMap<T>(Vec<T>);
+
+impl<T> LitMap<T>
+where
+    T: Clone,
+{
+    /// Builds a map in which each literal of the `n_vars` variables is associated with the given value.
+    #[must_use]
+    pub fn new(n_vars: usize, value: T) -> Self {
+        Self(vec![value; n_vars << 1])
+    }
+}
+
+impl<T> LitMap<T>
+where
+    T: Default,
+{
+    /// Builds a map in which each literal of the `n_vars` variables is associated with the default value of the type.
+    #[must_use]
+    pub fn with_default(n_vars: usize) -> Self {
+        Self((0..n_vars << 1).map(|_| T::default()).collect())
+    }
+}
+
+impl<T> LitMap<T> {
+    /// Returns the number of variables of the map.
+    #[must_use]
+    pub fn n_vars(&self) -> usize {
+        self.0.len() >> 1
+    }
+
+    /// Iterates over the literals and their associated values.
+    ///
+    /// Literals are ordered by variable index, the positive literal of a variable coming first.
+    pub fn iter(&self) -> impl Iterator<Item = (Literal, &T)> + '_ {
+        self.0.iter().enumerate().map(|(i, v)| (literal_at(i), v))
+    }
+
+    /// Iterates over the literals and mutable references to their associated values.
+    ///
+    /// Literals are ordered by variable index, the positive literal of a variable coming first.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Literal, &mut T)> + '_ {
+        self.0
+            .iter_mut()
+            .enumerate()
+            .map(|(i, v)| (literal_at(i), v))
+    }
+}
+
+fn literal_at(map_index: usize) -> Literal {
+    let positive = Literal::from(isize::try_from((map_index >> 1) + 1).unwrap());
+    if map_index & 1 == 0 {
+        positive
+    } else {
+        positive.flip()
+    }
+}
+
+fn map_index(literal: Literal) -> usize {
+    (literal.var_index() << 1) | usize::from(!literal.polarity())
+}
+
+impl<T> Index<Literal> for LitMap<T> {
+    type Output = T;
+
+    fn index(&self, literal: Literal) -> &Self::Output {
+        &self.0[map_index(literal)]
+    }
+}
+
+impl<T> IndexMut<Literal> for LitMap<T> {
+    fn index_mut(&mut self, literal: Literal) -> &mut Self::Output {
+        &mut self.0[map_index(literal)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_var_map() {
+        let mut map = VarMap::new(2, 0);
+        map[Literal::from(-1)] += 1;
+        map[Literal::from(1)] += 1;
+        map[1] = 5;
+        assert_eq!(2, map.n_vars());
+        assert_eq!(&[2, 5], map.as_slice());
+        assert_eq!(vec![(0, &2), (1, &5)], map.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_lit_map() {
+        let mut map = LitMap::new(2, 0);
+        for (l, v) in map.iter_mut() {
+            *v = isize::from(l);
+        }
+        assert_eq!(2, map.n_vars());
+        assert_eq!(-2, map[Literal::from(-2)]);
+        assert_eq!(
+            vec![1, -1, 2, -2],
+            map.iter()
+                .map(|(l, v)| {
+                    assert_eq!(isize::from(l), *v);
+                    *v
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(0, VarMap::<bool>::with_default(0).n_vars());
+        assert_eq!(0, LitMap::<bool>::with_default(0).iter().count());
+    }
+}
@@ -2,6 +2,20 @@ mod bottom_up_traversal;
 pub use bottom_up_traversal::BiBottomUpVisitor;
 pub use bottom_up_traversal::BottomUpTraversal;
 pub use bottom_up_traversal::BottomUpVisitor;
+pub use bottom_up_traversal::CachedBottomUpTraversal;
+
+mod components;
+
+mod cone_of_influence;
+pub use cone_of_influence::ConeOfInfluence;
+
+mod conditioning;
+
+mod dag_statistics;
+pub use dag_statistics::DecisionDNNFStats;
+
+mod decision_profile;
+pub use decision_profile::DecisionProfile;
 
 mod decision_dnnf;
 pub use decision_dnnf::DecisionDNNF;
@@ -11,5 +25,26 @@ pub use decision_dnnf::Literal;
 pub use decision_dnnf::Node;
 pub use decision_dnnf::NodeIndex;
 
+mod depth;
+
+mod free_variables;
+pub use free_variables::FreeVariables;
+
 mod involved_vars;
 pub(crate) use involved_vars::InvolvedVars;
+
+mod literal_checks;
+pub(crate) use literal_checks::assumed_polarities;
+pub(crate) use literal_checks::check_literals;
+pub(crate) use literal_checks::full_assignment;
+
+mod maps;
+pub use maps::LitMap;
+pub use maps::VarMap;
+
+mod node_implications;
+pub use node_implications::NodeImplications;
+
+pub(crate) mod simplification;
+
+mod smoothing;
@@ -0,0 +1,206 @@
+use super::{Node, NodeIndex};
+use crate::{DecisionDNNF, Literal};
+
+/// The literals implied by the nodes of a [`DecisionDNNF`].
+///
+/// A literal is implied by a node if it is true in every model of the subformula rooted at this node.
+/// The literals implied by an edge are the ones it propagates together with the ones implied by its target.
+/// The literals implied by a conjunction node are the union of the ones implied by its edges,
+/// while the ones implied by a disjunction node are the intersection of the ones implied by its edges leading to satisfiable nodes.
+///
+/// Unsatisfiable nodes imply every literal; they are reported as such instead of being given a list of literals.
+/// Nodes that are not reachable from the root are also reported as unsatisfiable.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, Literal, NodeImplications, NodeIndex};
+///
+/// // (-1 and 2) or (1 and 2)
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 2 0\n1 2 1 2 0\n".as_bytes()).unwrap();
+/// let implications = NodeImplications::compute(&ddnnf);
+/// assert_eq!(Some(&[Literal::from(2)] as &[Literal]), implications.implied_literals(NodeIndex::from(0)));
+/// ```
+#[derive(Debug)]
+pub struct NodeImplications {
+    implied: Vec<Option<Vec<Literal>>>,
+}
+
+impl NodeImplications {
+    /// Computes the literals implied by the nodes of a [`DecisionDNNF`].
+    ///
+    /// The nodes are processed in a bottom-up fashion, each of them being visited once, without recursion.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn compute(ddnnf: &DecisionDNNF) -> Self {
+        let mut implied: Vec<Option<Vec<Literal>>> = vec![None; ddnnf.n_nodes()];
+        for node in ddnnf.topological_order().into_iter().rev() {
+            let edge_implied = |edge_index| {
+                let edge = ddnnf.edge(edge_index);
+                implied[usize::from(edge.target())].as_ref().and_then(|l| {
+                    let mut literals = l.clone();
+                    literals.extend_from_slice(edge.propagated());
+                    normalize(literals)
+                })
+            };
+            let node_implied = match ddnnf.node(node) {
+                Node::And(edges) => edges
+                    .iter()
+                    .map(|e| edge_implied(*e))
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|v| normalize(v.concat())),
+                Node::Or(edges) => edges
+                    .iter()
+                    .filter_map(|e| edge_implied(*e))
+                    .reduce(|acc, l| acc.into_iter().filter(|x| l.contains(x)).collect()),
+                Node::True => Some(vec![]),
+                Node::False => None,
+            };
+            implied[usize::from(node)] = node_implied;
+        }
+        Self { implied }
+    }
+
+    /// Returns the literals implied by a node, or [`None`] if the node is unsatisfiable.
+    ///
+    /// The literals are sorted by variable index.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn implied_literals(&self, node: NodeIndex) -> Option<&[Literal]> {
+        self.implied[usize::from(node)].as_deref()
+    }
+
+    /// Returns `true` if and only if a node is satisfiable.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn is_satisfiable(&self, node: NodeIndex) -> bool {
+        self.implied[usize::from(node)].is_some()
+    }
+
+    /// Returns `true` if and only if a literal is true in every model of the subformula rooted at a node.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the node index is out of bounds.
+    #[must_use]
+    pub fn implies(&self, node: NodeIndex, literal: Literal) -> bool {
+        self.implied[usize::from(node)]
+            .as_ref()
+            .map_or(true, |l| l.contains(&literal))
+    }
+}
+
+/// Sorts and deduplicates a set of literals, returning [`None`] if it contains complementary literals.
+fn normalize(mut literals: Vec<Literal>) -> Option<Vec<Literal>> {
+    literals.sort_unstable_by_key(|l| (l.var_index(), l.polarity()));
+    literals.dedup();
+    if literals
+        .windows(2)
+        .any(|w| w[0].var_index() == w[1].var_index())
+    {
+        None
+    } else {
+        Some(literals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CheckLevel, D4Reader};
+    use std::fmt::Write;
+
+    fn literals(dimacs: &[isize]) -> Vec<Literal> {
+        dimacs.iter().map(|l| Literal::from(*l)).collect()
+    }
+
+    #[test]
+    fn test_true() {
+        let ddnnf = D4Reader::read("t 1 0\n".as_bytes()).unwrap();
+        let implications = NodeImplications::compute(&ddnnf);
+        assert_eq!(
+            Some(&[] as &[Literal]),
+            implications.implied_literals(NodeIndex::from(0))
+        );
+    }
+
+    #[test]
+    fn test_false() {
+        let ddnnf = D4Reader::read("f 1 0\n".as_bytes()).unwrap();
+        let implications = NodeImplications::compute(&ddnnf);
+        assert!(!implications.is_satisfiable(NodeIndex::from(0)));
+        assert!(implications.implies(NodeIndex::from(0), Literal::from(1)));
+    }
+
+    #[test]
+    fn test_and() {
+        // (-1 or 1) and 2 and -3
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\nt 3 0\n1 2 0\n1 3 2 0\n1 3 -3 0\n2 3 -1 0\n2 3 1 0\n".as_bytes(),
+        )
+        .unwrap();
+        let implications = NodeImplications::compute(&ddnnf);
+        assert_eq!(
+            Some(literals(&[2, -3]).as_slice()),
+            implications.implied_literals(NodeIndex::from(0))
+        );
+        assert_eq!(
+            Some(&[] as &[Literal]),
+            implications.implied_literals(NodeIndex::from(1))
+        );
+    }
+
+    #[test]
+    fn test_or_ignores_unsat_children() {
+        // (-1 and 2) or (1 and false)
+        let ddnnf =
+            D4Reader::read("o 1 0\nt 2 0\nf 3 0\n1 2 -1 2 0\n1 3 1 0\n".as_bytes()).unwrap();
+        let implications = NodeImplications::compute(&ddnnf);
+        assert_eq!(
+            Some(literals(&[-1, 2]).as_slice()),
+            implications.implied_literals(NodeIndex::from(0))
+        );
+        assert!(implications.implies(NodeIndex::from(0), Literal::from(-1)));
+        assert!(!implications.implies(NodeIndex::from(0), Literal::from(1)));
+    }
+
+    #[test]
+    fn test_unsat_and() {
+        // (1 and 2) and (-1 and 3)
+        let ddnnf = D4Reader::read(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 1 0\n1 3 -1 0\n2 4 2 0\n3 4 3 0\n".as_bytes(),
+        )
+        .unwrap();
+        let implications = NodeImplications::compute(&ddnnf);
+        assert!(implications.is_satisfiable(NodeIndex::from(1)));
+        assert!(!implications.is_satisfiable(NodeIndex::from(0)));
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let n = 100_000;
+        let mut instance = String::new();
+        for i in 1..=n {
+            writeln!(instance, "a {i} 0").unwrap();
+        }
+        writeln!(instance, "t {} 0", n + 1).unwrap();
+        for i in 1..=n {
+            writeln!(instance, "{i} {} 0", i + 1).unwrap();
+        }
+        writeln!(instance, "{n} {} 1 0", n + 1).unwrap();
+        let mut reader = D4Reader::default();
+        reader.set_check_level(CheckLevel::None);
+        let ddnnf = reader.read_with_options(instance.as_bytes()).unwrap();
+        let implications = NodeImplications::compute(&ddnnf);
+        assert_eq!(
+            Some(literals(&[1]).as_slice()),
+            implications.implied_literals(NodeIndex::from(0))
+        );
+    }
+}
@@ -0,0 +1,418 @@
+//! Simplifications of the raw data of Decision-DNNFs.
+
+use super::{Edge, EdgeIndex, Node};
+use crate::DecisionDNNF;
+use rustc_hash::FxHashMap;
+
+impl DecisionDNNF {
+    /// Builds a smaller formula equivalent to this one.
+    ///
+    /// The following simplifications are applied:
+    /// - the edges from disjunction nodes to nodes equivalent to false are removed, and the nodes that become equivalent to false are replaced by false nodes;
+    /// - the true nodes (resp. the false nodes) are merged into a single node;
+    /// - the conjunction and disjunction nodes with a single child are collapsed: the edges pointing to them are redirected to their child, and propagate the literals of both edges;
+    /// - the structurally identical nodes are merged (see [`deduplicate`](Self::deduplicate));
+    /// - the nodes that are no longer reachable from the root are removed.
+    ///
+    /// The root is never collapsed, so that the literals it propagates are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, ModelCounter};
+    ///
+    /// // -1 or (1 and false), with a chain of single-child nodes
+    /// let ddnnf = D4Reader::read("o 1 0\na 2 0\na 3 0\nt 4 0\nf 5 0\n1 2 -1 0\n1 5 1 0\n2 3 0\n3 4 0\n".as_bytes()).unwrap();
+    /// let simplified = ddnnf.simplify();
+    /// assert_eq!(2, simplified.n_nodes());
+    /// assert_eq!(ModelCounter::new(&ddnnf).count(), ModelCounter::new(&simplified).count());
+    /// ```
+    #[must_use]
+    pub fn simplify(&self) -> DecisionDNNF {
+        let (nodes, edges) = simplify(self.nodes().as_slice(), self.edges().as_slice(), true, true);
+        let (nodes, edges) = collapse_single_child_nodes(&nodes, &edges);
+        let (nodes, edges) = merge_identical_nodes(&nodes, &edges);
+        DecisionDNNF::from_raw_data(self.n_vars(), nodes, edges)
+    }
+
+    /// Builds an equivalent formula in which the structurally identical subgraphs are shared.
+    ///
+    /// Two nodes are structurally identical if they have the same kind and if their edges, taken in any order, propagate the same literals to identical nodes.
+    /// The nodes are compared bottom-up, so that whole identical subgraphs are merged, as in a hash-consing construction.
+    /// The nodes that are not reachable from the root are removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// // -1 or 1, each side having its own (-2 or 2) subformula
+    /// let ddnnf = D4Reader::read("o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 0\n2 4 -2 0\n2 4 2 0\n3 4 2 0\n3 4 -2 0\n".as_bytes()).unwrap();
+    /// assert_eq!(3, ddnnf.deduplicate().n_nodes());
+    /// ```
+    #[must_use]
+    pub fn deduplicate(&self) -> DecisionDNNF {
+        let (nodes, edges) =
+            merge_identical_nodes(self.nodes().as_slice(), self.edges().as_slice());
+        DecisionDNNF::from_raw_data(self.n_vars(), nodes, edges)
+    }
+}
+
+/// Simplifies a formula given by its nodes and edges, the root being the first node.
+///
+/// If `prune_false` is set, the edges from disjunction nodes to nodes equivalent to false are removed,
+/// and the conjunction nodes with a child equivalent to false and the disjunction nodes with no child are replaced by false nodes.
+/// If `share_leaves` is set, the true nodes (resp. the false nodes) are merged into a single node.
+/// The nodes that are not reachable from the root are removed, and the remaining ones keep their relative order.
+///
+/// This function does not require the formula to be acyclic.
+pub(crate) fn simplify(
+    nodes: &[Node],
+    edges: &[Edge],
+    prune_false: bool,
+    share_leaves: bool,
+) -> (Vec<Node>, Vec<Edge>) {
+    let n_nodes = nodes.len();
+    let mut redirections = (0..n_nodes).collect::<Vec<_>>();
+    if share_leaves {
+        let mut first_true = None;
+        let mut first_false = None;
+        for (i, node) in nodes.iter().enumerate() {
+            match node {
+                Node::True => redirections[i] = *first_true.get_or_insert(i),
+                Node::False => redirections[i] = *first_false.get_or_insert(i),
+                Node::And(_) | Node::Or(_) => {}
+            }
+        }
+    }
+    let is_false = if prune_false {
+        compute_false_nodes(nodes, edges, &redirections)
+    } else {
+        vec![false; n_nodes]
+    };
+    if share_leaves {
+        if let Some(first_false) = is_false.iter().position(|b| *b) {
+            for i in 0..n_nodes {
+                if is_false[i] {
+                    redirections[i] = first_false;
+                }
+            }
+        }
+    }
+    let kept_edges = |node: &Node| -> Vec<EdgeIndex> {
+        match node {
+            Node::And(edge_indices) | Node::Or(edge_indices) if !prune_false => {
+                edge_indices.clone()
+            }
+            Node::And(edge_indices) => edge_indices.clone(),
+            Node::Or(edge_indices) => edge_indices
+                .iter()
+                .filter(|e| !is_false[redirections[usize::from(edges[usize::from(**e)].target())]])
+                .copied()
+                .collect(),
+            Node::True | Node::False => vec![],
+        }
+    };
+    let mut new_indices = vec![None; n_nodes];
+    new_indices[0] = Some(0);
+    let mut stack = vec![0];
+    while let Some(i) = stack.pop() {
+        if is_false[i] {
+            continue;
+        }
+        for e in kept_edges(&nodes[i]) {
+            let target = redirections[usize::from(edges[usize::from(e)].target())];
+            if new_indices[target].is_none() {
+                new_indices[target] = Some(0);
+                stack.push(target);
+            }
+        }
+    }
+    let mut n_new_nodes = 0;
+    for new_index in new_indices.iter_mut().flatten() {
+        *new_index = n_new_nodes;
+        n_new_nodes += 1;
+    }
+    let mut new_nodes = Vec::with_capacity(n_new_nodes);
+    let mut new_edges = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if new_indices[i].is_none() {
+            continue;
+        }
+        if is_false[i] {
+            new_nodes.push(Node::False);
+            continue;
+        }
+        let mut new_node = match node {
+            Node::And(_) => Node::And(vec![]),
+            Node::Or(_) => Node::Or(vec![]),
+            Node::True => Node::True,
+            Node::False => Node::False,
+        };
+        for e in kept_edges(node) {
+            let edge = &edges[usize::from(e)];
+            let target = new_indices[redirections[usize::from(edge.target())]].unwrap();
+            new_edges.push(Edge::from_raw_data(
+                target.into(),
+                edge.propagated().to_vec(),
+            ));
+            new_node.add_edge((new_edges.len() - 1).into()).unwrap();
+        }
+        new_nodes.push(new_node);
+    }
+    (new_nodes, new_edges)
+}
+
+/// Redirects the edges pointing to conjunction and disjunction nodes with a single child to this child, merging the propagated literals.
+///
+/// The root is kept, and the nodes that are no longer reachable from it are removed.
+/// The formula must be acyclic.
+fn collapse_single_child_nodes(nodes: &[Node], edges: &[Edge]) -> (Vec<Node>, Vec<Edge>) {
+    let single_edge = |i: usize| match &nodes[i] {
+        Node::And(edge_indices) | Node::Or(edge_indices) if edge_indices.len() == 1 => {
+            Some(&edges[usize::from(edge_indices[0])])
+        }
+        _ => None,
+    };
+    let mut new_edges = edges
+        .iter()
+        .map(|e| Edge::from_raw_data(e.target(), e.propagated().to_vec()))
+        .collect::<Vec<_>>();
+    for (i, node) in nodes.iter().enumerate() {
+        let (Node::And(edge_indices) | Node::Or(edge_indices)) = node else {
+            continue;
+        };
+        if i != 0 && edge_indices.len() == 1 {
+            continue;
+        }
+        for e in edge_indices {
+            let new_edge = &mut new_edges[usize::from(*e)];
+            let mut target = usize::from(new_edge.target());
+            let mut propagated = new_edge.propagated().to_vec();
+            while let Some(edge) = single_edge(target) {
+                propagated.extend_from_slice(edge.propagated());
+                target = usize::from(edge.target());
+            }
+            *new_edge = Edge::from_raw_data(target.into(), propagated);
+        }
+    }
+    simplify(nodes, &new_edges, false, false)
+}
+
+/// Merges the structurally identical nodes reachable from the root.
+///
+/// The nodes are given a class in post-order, so that the class of a node depends on the classes of its children.
+/// The classes are then written in reverse order, which puts the root first; each class is built from the edges of its first node.
+/// The formula must be acyclic.
+fn merge_identical_nodes(nodes: &[Node], edges: &[Edge]) -> (Vec<Node>, Vec<Edge>) {
+    let edge_indices = |i: usize| match &nodes[i] {
+        Node::And(edge_indices) | Node::Or(edge_indices) => edge_indices.as_slice(),
+        Node::True | Node::False => &[],
+    };
+    let target = |e: EdgeIndex| usize::from(edges[usize::from(e)].target());
+    let mut classes: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut representatives = Vec::new();
+    let mut class_by_key = FxHashMap::default();
+    let mut visited = vec![false; nodes.len()];
+    visited[0] = true;
+    let mut stack = vec![(0, 0)];
+    while let Some((i, next_edge)) = stack.pop() {
+        if let Some(e) = edge_indices(i).get(next_edge) {
+            stack.push((i, next_edge + 1));
+            let child = target(*e);
+            if !visited[child] {
+                visited[child] = true;
+                stack.push((child, 0));
+            }
+            continue;
+        }
+        let kind: u8 = match &nodes[i] {
+            Node::And(_) => 0,
+            Node::Or(_) => 1,
+            Node::True => 2,
+            Node::False => 3,
+        };
+        let mut edge_keys = edge_indices(i)
+            .iter()
+            .map(|e| {
+                let mut literals = edges[usize::from(*e)]
+                    .propagated()
+                    .iter()
+                    .map(|l| isize::from(*l))
+                    .collect::<Vec<_>>();
+                literals.sort_unstable();
+                (classes[target(*e)].unwrap(), literals)
+            })
+            .collect::<Vec<_>>();
+        edge_keys.sort_unstable();
+        let class = *class_by_key.entry((kind, edge_keys)).or_insert_with(|| {
+            representatives.push(i);
+            representatives.len() - 1
+        });
+        classes[i] = Some(class);
+    }
+    let n_classes = representatives.len();
+    let mut new_nodes = Vec::with_capacity(n_classes);
+    let mut new_edges = Vec::new();
+    for i in representatives.into_iter().rev() {
+        let mut new_node = match &nodes[i] {
+            Node::And(_) => Node::And(vec![]),
+            Node::Or(_) => Node::Or(vec![]),
+            Node::True => Node::True,
+            Node::False => Node::False,
+        };
+        for e in edge_indices(i) {
+            let new_target = n_classes - 1 - classes[target(*e)].unwrap();
+            new_edges.push(Edge::from_raw_data(
+                new_target.into(),
+                edges[usize::from(*e)].propagated().to_vec(),
+            ));
+            new_node.add_edge((new_edges.len() - 1).into()).unwrap();
+        }
+        new_nodes.push(new_node);
+    }
+    (new_nodes, new_edges)
+}
+
+fn compute_false_nodes(nodes: &[Node], edges: &[Edge], redirections: &[usize]) -> Vec<bool> {
+    let n_nodes = nodes.len();
+    let mut is_false = vec![false; n_nodes];
+    let mut n_remaining_children = vec![0; n_nodes];
+    let mut parents = vec![vec![]; n_nodes];
+    let mut queue = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        match node {
+            Node::And(edge_indices) | Node::Or(edge_indices) => {
+                for e in edge_indices {
+                    parents[redirections[usize::from(edges[usize::from(*e)].target())]].push(i);
+                }
+                if let Node::Or(_) = node {
+                    n_remaining_children[i] = edge_indices.len();
+                    if edge_indices.is_empty() {
+                        queue.push(i);
+                    }
+                }
+            }
+            Node::False => queue.push(i),
+            Node::True => {}
+        }
+    }
+    for i in &queue {
+        is_false[*i] = true;
+    }
+    while let Some(i) = queue.pop() {
+        for p in std::mem::take(&mut parents[i]) {
+            if is_false[p] {
+                continue;
+            }
+            let becomes_false = match &nodes[p] {
+                Node::Or(_) => {
+                    n_remaining_children[p] -= 1;
+                    n_remaining_children[p] == 0
+                }
+                _ => true,
+            };
+            if becomes_false {
+                is_false[p] = true;
+                queue.push(p);
+            }
+        }
+    }
+    is_false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{D4Reader, DecisionDNNF, ModelCounter};
+    use std::fmt::Write;
+
+    fn read_simplified(instance: &str) -> (DecisionDNNF, DecisionDNNF) {
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let simplified = ddnnf.simplify();
+        assert_eq!(
+            ModelCounter::new(&ddnnf).count(),
+            ModelCounter::new(&simplified).count()
+        );
+        (ddnnf, simplified)
+    }
+
+    #[test]
+    fn test_collapse_chain() {
+        let (_, simplified) =
+            read_simplified("a 1 0\no 2 0\na 3 0\nt 4 0\n1 2 1 0\n2 3 2 0\n3 4 3 0\n");
+        assert_eq!(2, simplified.n_nodes());
+        assert_eq!(1, simplified.n_edges());
+        let edge = simplified.edge(0.into());
+        assert_eq!(
+            vec![1, 2, 3],
+            edge.propagated()
+                .iter()
+                .map(|l| isize::from(*l))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_collapse_shared_node() {
+        let (_, simplified) = read_simplified(
+            "o 1 0\na 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 2 1 0\n2 3 0\n3 4 -2 0\n3 4 2 0\n",
+        );
+        assert_eq!(3, simplified.n_nodes());
+        assert_eq!(4, simplified.n_edges());
+    }
+
+    #[test]
+    fn test_prune_and_share() {
+        let (_, simplified) = read_simplified(
+            "o 1 0\na 2 0\nt 3 0\nf 4 0\nt 5 0\n1 2 -1 0\n1 5 1 0\n2 3 -2 0\n2 4 2 0\n",
+        );
+        assert_eq!(2, simplified.n_nodes());
+    }
+
+    #[test]
+    fn test_leaves() {
+        assert_eq!(1, read_simplified("t 1 0\n").1.n_nodes());
+        assert_eq!(1, read_simplified("f 1 0\n").1.n_nodes());
+    }
+
+    #[test]
+    fn test_merge_identical_subgraphs() {
+        // (-1 and ((-2 and -3) or 2)) or (1 and (2 or (-3 and -2))), with no shared node
+        let (ddnnf, simplified) = read_simplified(
+            "o 1 0\no 2 0\no 3 0\na 4 0\na 5 0\nt 6 0\nt 7 0\n1 2 -1 0\n1 3 1 0\n2 4 -2 0\n2 6 2 0\n3 6 2 0\n3 5 -2 0\n4 7 -3 0\n5 6 -3 0\n",
+        );
+        let deduplicated = ddnnf.deduplicate();
+        assert_eq!(4, deduplicated.n_nodes());
+        assert_eq!(5, deduplicated.n_edges());
+        assert_eq!(
+            ModelCounter::new(&ddnnf).count(),
+            ModelCounter::new(&deduplicated).count()
+        );
+        assert_eq!(3, simplified.n_nodes());
+    }
+
+    #[test]
+    fn test_merge_keeps_distinct_literals() {
+        let ddnnf = D4Reader::read(
+            "o 1 0\no 2 0\no 3 0\nt 4 0\n1 2 -1 0\n1 3 1 0\n2 4 -2 0\n2 4 2 0\n3 4 -3 0\n3 4 3 0\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(4, ddnnf.deduplicate().n_nodes());
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        let depth = 10_000;
+        let mut instance = String::new();
+        for i in 1..=depth {
+            writeln!(instance, "a {i} 0").unwrap();
+        }
+        writeln!(instance, "t {} 0", depth + 1).unwrap();
+        for i in 1..=depth {
+            writeln!(instance, "{i} {} 0", i + 1).unwrap();
+        }
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        assert_eq!(2, ddnnf.simplify().n_nodes());
+    }
+}
@@ -0,0 +1,184 @@
+use super::{simplification, Edge, EdgeIndex, Node, NodeIndex};
+use crate::{DecisionDNNF, FreeVariables, Literal};
+
+impl DecisionDNNF {
+    /// Builds an equivalent smooth formula.
+    ///
+    /// A formula is smooth if the children of each disjunction node involve the same variables, and if the formula involves all its variables.
+    /// Each child of a disjunction node which has free variables (see [`FreeVariables`]) is replaced by a conjunction node joining the child and a disjunction `-v or v` for each of its free variables `v`.
+    /// In the same way, if the formula does not involve some variables, a new root joins the former root and a disjunction for each of these variables.
+    /// The disjunctions are shared, so that the formula grows by at most a node and an edge for each free variable, plus three nodes and two edges for each variable.
+    ///
+    /// The resulting formula is still a Decision-DNNF, with the same number of models.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, FreeVariables, ModelCounter};
+    ///
+    /// // (-1 and -2) or 1, on 3 variables
+    /// let mut ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+    /// ddnnf.update_n_vars(3);
+    /// let smooth = ddnnf.smooth();
+    /// let free_vars = FreeVariables::compute(&smooth);
+    /// assert!(free_vars.root_free_vars().is_empty());
+    /// assert!(free_vars.iter().all(|(_, _, vars)| vars.is_empty()));
+    /// assert_eq!(6, *ModelCounter::new(&smooth).count());
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn smooth(&self) -> DecisionDNNF {
+        let free_vars = FreeVariables::compute(self);
+        let shift = usize::from(!free_vars.root_free_vars().is_empty());
+        let shifted = |node: NodeIndex| NodeIndex::from(usize::from(node) + shift);
+        let mut smoother = Smoother {
+            nodes: Vec::with_capacity(self.n_nodes() + shift),
+            edges: Vec::with_capacity(self.n_edges()),
+            true_node: None,
+            tautologies: vec![None; self.n_vars()],
+        };
+        if shift == 1 {
+            smoother.nodes.push(Node::And(vec![]));
+        }
+        for node in self.nodes().as_slice() {
+            smoother.nodes.push(match node {
+                Node::And(edges) => Node::And(edges.clone()),
+                Node::Or(edges) => Node::Or(edges.clone()),
+                Node::True => Node::True,
+                Node::False => Node::False,
+            });
+        }
+        for edge in self.edges().as_slice() {
+            smoother.edges.push(Edge::from_raw_data(
+                shifted(edge.target()),
+                edge.propagated().to_vec(),
+            ));
+        }
+        for (or_node, child_index, vars) in free_vars.iter() {
+            if vars.is_empty() {
+                continue;
+            }
+            let Node::Or(edges) = self.node(or_node) else {
+                unreachable!()
+            };
+            let edge_index = usize::from(edges[child_index]);
+            let target = smoother.edges[edge_index].target();
+            let conjunction = smoother.add_node(Node::And(vec![]));
+            smoother.join_tautologies(conjunction, target, vars);
+            smoother.edges[edge_index] = Edge::from_raw_data(
+                conjunction,
+                self.edge(edges[child_index]).propagated().to_vec(),
+            );
+        }
+        if shift == 1 {
+            smoother.join_tautologies(
+                NodeIndex::from(0),
+                NodeIndex::from(1),
+                free_vars.root_free_vars(),
+            );
+        }
+        let (nodes, edges) =
+            simplification::simplify(&smoother.nodes, &smoother.edges, false, true);
+        DecisionDNNF::from_raw_data(self.n_vars(), nodes, edges)
+    }
+}
+
+struct Smoother {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    true_node: Option<NodeIndex>,
+    tautologies: Vec<Option<NodeIndex>>,
+}
+
+impl Smoother {
+    /// Adds edges from a conjunction node to another node and to the tautologies of some variables.
+    fn join_tautologies(&mut self, conjunction: NodeIndex, node: NodeIndex, vars: &[usize]) {
+        self.add_edge(conjunction, node, vec![]);
+        for v in vars {
+            let tautology = self.tautology(*v);
+            self.add_edge(conjunction, tautology, vec![]);
+        }
+    }
+
+    /// Returns the index of the node `-v or v` for a variable, adding it if needed.
+    fn tautology(&mut self, var_index: usize) -> NodeIndex {
+        if let Some(index) = self.tautologies[var_index] {
+            return index;
+        }
+        let true_node = if let Some(index) = self.true_node {
+            index
+        } else {
+            let index = self.add_node(Node::True);
+            self.true_node = Some(index);
+            index
+        };
+        let positive = Literal::from(isize::try_from(var_index + 1).unwrap());
+        let disjunction = self.add_node(Node::Or(vec![]));
+        for l in [positive.flip(), positive] {
+            self.add_edge(disjunction, true_node, vec![l]);
+        }
+        self.tautologies[var_index] = Some(disjunction);
+        disjunction
+    }
+
+    fn add_node(&mut self, node: Node) -> NodeIndex {
+        self.nodes.push(node);
+        NodeIndex::from(self.nodes.len() - 1)
+    }
+
+    fn add_edge(&mut self, from: NodeIndex, to: NodeIndex, propagated: Vec<Literal>) {
+        self.edges.push(Edge::from_raw_data(to, propagated));
+        self.nodes[usize::from(from)]
+            .add_edge(EdgeIndex::from(self.edges.len() - 1))
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{D4Reader, FreeVariables, ModelCounter};
+
+    fn check_smoothing(instance: &str, n_vars: Option<usize>) {
+        let mut ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        if let Some(n) = n_vars {
+            ddnnf.update_n_vars(n);
+        }
+        let smooth = ddnnf.smooth();
+        assert_eq!(ddnnf.n_vars(), smooth.n_vars());
+        assert_eq!(
+            ModelCounter::new(&ddnnf).count(),
+            ModelCounter::new(&smooth).count()
+        );
+        let free_vars = FreeVariables::compute(&smooth);
+        assert!(free_vars.root_free_vars().is_empty());
+        assert!(free_vars.iter().all(|(_, _, vars)| vars.is_empty()));
+        let checking_data = crate::BottomUpTraversal::new(Box::<crate::CheckingVisitor>::default())
+            .traverse(&smooth);
+        assert!(checking_data.get_error().is_none());
+        assert!(checking_data.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_smooth() {
+        check_smoothing(
+            "a 1 0\no 2 0\no 3 0\nt 4 0\n1 2 0\n1 3 0\n2 4 -1 0\n2 4 1 2 0\n3 4 -3 0\n3 4 3 -4 0\n",
+            Some(6),
+        );
+        check_smoothing("o 1 0\no 2 0\nt 3 0\n2 3 -1 -2 0\n2 3 1 0\n1 2 0", None);
+    }
+
+    #[test]
+    fn test_already_smooth() {
+        let instance = "o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n";
+        check_smoothing(instance, None);
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        assert_eq!(ddnnf.n_nodes(), ddnnf.smooth().n_nodes());
+    }
+
+    #[test]
+    fn test_leaves() {
+        check_smoothing("t 1 0\n", Some(2));
+        check_smoothing("f 1 0\n", Some(2));
+        check_smoothing("t 1 0\n", None);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::Literal;
+use anyhow::{anyhow, Context, Result};
+use std::{
+    io::{BufRead, BufReader, Read},
+    str::FromStr,
+};
+
+/// A set of assumptions, i.e. a conjunction of literals, read from a file.
+///
+/// The format is DIMACS-like: each line is a cube given as blank separated literals, optionally ended by a 0.
+/// Lines beginning by `c` are comments, and blank lines are ignored.
+/// A leading `v` is allowed and lines beginning by `s` are ignored, so that the models written by SAT solvers and by the commands of this crate can be read as well.
+/// The assumptions are the conjunction of all the cubes, which can also be considered one by one with [`cubes`](Self::cubes).
+///
+/// The same literal may appear several times, and contradictory literals are allowed.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{Assumptions, Literal};
+///
+/// let content = "c the assumptions\n1 -2 0\n3 0\n";
+/// let assumptions = Assumptions::from_reader(content.as_bytes(), 3).unwrap();
+/// assert_eq!(&[1, -2, 3].map(Literal::from), assumptions.literals());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Assumptions {
+    literals: Vec<Literal>,
+    cube_ends: Vec<usize>,
+}
+
+impl Assumptions {
+    /// Reads assumptions given in the format described in the documentation of this structure.
+    ///
+    /// The number of variables of the formula the assumptions refer to is used to check the literals.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the content cannot be read, does not follow the format, or refers to a variable that does not exist.
+    pub fn from_reader<R>(reader: R, n_vars: usize) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut assumptions = Self::default();
+        for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.with_context(|| format!("while reading line at index {line_index}"))?;
+            assumptions
+                .parse_line(&line, n_vars)
+                .with_context(|| format!("while parsing line at index {line_index}"))?;
+        }
+        Ok(assumptions)
+    }
+
+    fn parse_line(&mut self, line: &str, n_vars: usize) -> Result<()> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') || line.starts_with('s') {
+            return Ok(());
+        }
+        let line = line.strip_prefix('v').unwrap_or(line);
+        let literals = &mut self.literals;
+        let mut words = line.split_whitespace();
+        while let Some(w) = words.next() {
+            let i =
+                isize::from_str(w).with_context(|| format!(r#"while parsing literal "{w}""#))?;
+            if i == 0 {
+                if words.next().is_some() {
+                    return Err(anyhow!("unexpected content after 0"));
+                }
+                break;
+            }
+            let l = Literal::from(i);
+            if l.var_index() >= n_vars {
+                return Err(anyhow!(
+                    "no such literal: {l} (the formula has {n_vars} variables)"
+                ));
+            }
+            literals.push(l);
+        }
+        self.cube_ends.push(literals.len());
+        Ok(())
+    }
+
+    /// Returns the literals of the assumptions, in the order they were read.
+    #[must_use]
+    pub fn literals(&self) -> &[Literal] {
+        &self.literals
+    }
+
+    /// Returns the cubes of the assumptions, one for each line that is not ignored, in the order they were read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{Assumptions, Literal};
+    ///
+    /// let content = "v 1 -2 0\nv -1 0\n";
+    /// let assumptions = Assumptions::from_reader(content.as_bytes(), 2).unwrap();
+    /// let cubes = assumptions.cubes().collect::<Vec<_>>();
+    /// assert_eq!(vec![&[1, -2].map(Literal::from)[..], &[Literal::from(-1)]], cubes);
+    /// ```
+    pub fn cubes(&self) -> impl Iterator<Item = &[Literal]> + '_ {
+        let starts = std::iter::once(0).chain(self.cube_ends.iter().copied());
+        starts
+            .zip(self.cube_ends.iter())
+            .map(|(start, end)| &self.literals[start..*end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(content: &str, n_vars: usize) -> Result<Vec<isize>> {
+        Assumptions::from_reader(content.as_bytes(), n_vars)
+            .map(|a| a.literals().iter().map(|l| isize::from(*l)).collect())
+    }
+
+    #[test]
+    fn test_ok() {
+        assert_eq!(
+            vec![1, -2, 3, -1],
+            read("c comment\n\n 1 -2 0\n3\nc 4\n-1 0\n", 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_solver_output() {
+        let assumptions =
+            Assumptions::from_reader("s SATISFIABLE\nv 1 -2\nv 3 0\n\n0\n".as_bytes(), 3).unwrap();
+        assert_eq!(
+            vec![vec![1, -2], vec![3], vec![]],
+            assumptions
+                .cubes()
+                .map(|c| c.iter().map(|l| isize::from(*l)).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(read("", 0).unwrap().is_empty());
+        assert!(read("c nothing\n0\n", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        for (content, expected_error) in [
+            ("1 x 0\n", "invalid digit found in string"),
+            ("1 0 2\n", "unexpected content after 0"),
+            ("4 0\n", "no such literal: 4 (the formula has 3 variables)"),
+        ] {
+            let error = read(content, 3).unwrap_err();
+            assert_eq!(expected_error, error.root_cause().to_string());
+        }
+    }
+}
@@ -1,11 +1,228 @@
 use crate::{
-    core::{EdgeIndex, Literal, Node, NodeIndex},
+    core::{Edge, EdgeIndex, Literal, Node, NodeIndex},
     DecisionDNNF,
 };
 use anyhow::{anyhow, Context, Result};
 use rustc_hash::FxHashMap;
-use std::io::BufWriter;
 pub use std::io::Write;
+use std::io::{BufRead, BufReader, BufWriter, Read};
+use std::str::FromStr;
+
+/// A structure used to read a Decision-DNNF written in the [c2d](http://reasoning.cs.ucla.edu/c2d/) output format.
+///
+/// The input begins with a header `nnf n_nodes n_edges n_vars`, followed by one line per node: `L l` for a literal, `A k c1 .. ck` for a conjunction and `O j k c1 .. ck` for a disjunction deciding on variable `j` (0 if unknown).
+/// Nodes are referenced by their position in the input, starting at 0; children must be declared before their parents, and the root is the last node.
+///
+/// The literal nodes are translated into edges propagating the literals, and the nodes that are not reachable from the root are discarded.
+/// The reader only performs syntactic checks; see [`CheckingVisitor`](crate::CheckingVisitor) to check the decomposability and the determinism of the formula.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{C2dReader, ModelCounter};
+///
+/// // -1 or 1
+/// let ddnnf = C2dReader::read("nnf 3 2 1\nL 1\nL -1\nO 1 2 0 1\n".as_bytes()).unwrap();
+/// assert_eq!(2, *ModelCounter::new(&ddnnf).count());
+/// ```
+pub struct Reader;
+
+impl Reader {
+    /// Reads an instance in the c2d format and returns it.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the content of the instance does not follow the c2d format.
+    pub fn read<R>(reader: R) -> Result<DecisionDNNF>
+    where
+        R: Read,
+    {
+        let context = "while parsing a c2d formatted Decision-DNNF";
+        let mut lines = BufReader::new(reader).lines().enumerate();
+        let line_index_context = |i| format!("while parsing line at index {i}");
+        let (n_nodes, n_edges, n_vars) = loop {
+            let Some((i, line)) = lines.next() else {
+                return Err(anyhow!("missing header")).context(context);
+            };
+            let line = line
+                .with_context(|| line_index_context(i))
+                .context(context)?;
+            if !line.trim().is_empty() {
+                break Self::parse_header(&line)
+                    .with_context(|| line_index_context(i))
+                    .context(context)?;
+            }
+        };
+        let mut c2d_nodes = Vec::with_capacity(n_nodes);
+        let mut n_read_edges = 0;
+        for (i, line) in lines {
+            let line = line
+                .with_context(|| line_index_context(i))
+                .context(context)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let node = Self::parse_node(&line, c2d_nodes.len(), n_vars)
+                .with_context(|| line_index_context(i))
+                .context("while parsing a node")
+                .context(context)?;
+            if let C2dNode::And(children) | C2dNode::Or(children) = &node {
+                n_read_edges += children.len();
+            }
+            c2d_nodes.push(node);
+        }
+        if c2d_nodes.is_empty() {
+            return Err(anyhow!("the formula has no nodes")).context(context);
+        }
+        if (n_nodes, n_edges) != (c2d_nodes.len(), n_read_edges) {
+            return Err(anyhow!(
+                "the header declares {n_nodes} nodes and {n_edges} edges, but {} nodes and {n_read_edges} edges were found",
+                c2d_nodes.len()
+            ))
+            .context(context);
+        }
+        Ok(Self::build(n_vars, &c2d_nodes))
+    }
+
+    fn parse_header(line: &str) -> Result<(usize, usize, usize)> {
+        let context = "while parsing the header";
+        let mut words = line.split_whitespace();
+        if words.next() != Some("nnf") {
+            return Err(anyhow!(r#"expected "nnf" as first word"#)).context(context);
+        }
+        let mut values = [0; 3];
+        for v in &mut values {
+            let w = words
+                .next()
+                .ok_or(anyhow!("missing value"))
+                .context(context)?;
+            *v = usize::from_str(w).context(context)?;
+        }
+        if words.next().is_some() {
+            return Err(anyhow!("unexpected content after the number of variables"))
+                .context(context);
+        }
+        Ok((values[0], values[1], values[2]))
+    }
+
+    fn parse_node(line: &str, index: usize, n_vars: usize) -> Result<C2dNode> {
+        let mut words = line.split_whitespace();
+        let first_word = words.next().unwrap();
+        if first_word == "L" {
+            let w = words.next().ok_or(anyhow!("missing literal"))?;
+            let i = isize::from_str(w).context("while parsing the literal")?;
+            if i == 0 || i.unsigned_abs() > n_vars {
+                return Err(anyhow!(
+                    "no such literal: {i} (the formula has {n_vars} variables)"
+                ));
+            }
+            if words.next().is_some() {
+                return Err(anyhow!("unexpected content after the literal"));
+            }
+            return Ok(C2dNode::Literal(Literal::from(i)));
+        }
+        let mut next_usize = |what: &str| -> Result<usize> {
+            let w = words.next().ok_or(anyhow!("missing {what}"))?;
+            usize::from_str(w).with_context(|| format!("while parsing the {what}"))
+        };
+        let (is_and, n_children) = match first_word {
+            "A" => (true, next_usize("number of children")?),
+            "O" => {
+                let var = next_usize("conflict variable")?;
+                if var > n_vars {
+                    return Err(anyhow!(
+                        "no such variable: {var} (the formula has {n_vars} variables)"
+                    ));
+                }
+                (false, next_usize("number of children")?)
+            }
+            _ => return Err(anyhow!(r#"unexpected first word "{first_word}""#)),
+        };
+        let children = (0..n_children)
+            .map(|_| {
+                let child = next_usize("child index")?;
+                if child >= index {
+                    return Err(anyhow!("child {child} is not declared before node {index}"));
+                }
+                Ok(child)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if words.next().is_some() {
+            return Err(anyhow!("unexpected content after the children"));
+        }
+        Ok(if is_and {
+            C2dNode::And(children)
+        } else {
+            C2dNode::Or(children)
+        })
+    }
+
+    fn build(n_vars: usize, c2d_nodes: &[C2dNode]) -> DecisionDNNF {
+        let root = c2d_nodes.len() - 1;
+        if let C2dNode::Literal(l) = c2d_nodes[root] {
+            return DecisionDNNF::from_raw_data(
+                n_vars,
+                vec![Node::And(vec![EdgeIndex::from(0)]), Node::True],
+                vec![Edge::from_raw_data(NodeIndex::from(1), vec![l])],
+            );
+        }
+        let mut reachable = vec![false; c2d_nodes.len()];
+        reachable[root] = true;
+        let mut new_indices = vec![None; c2d_nodes.len()];
+        let mut n_new_nodes = 0;
+        let mut needs_true_node = false;
+        for i in (0..c2d_nodes.len()).rev() {
+            if !reachable[i] {
+                continue;
+            }
+            if let C2dNode::And(children) | C2dNode::Or(children) = &c2d_nodes[i] {
+                new_indices[i] = Some(n_new_nodes);
+                n_new_nodes += 1;
+                for c in children {
+                    reachable[*c] = true;
+                    needs_true_node |= matches!(c2d_nodes[*c], C2dNode::Literal(_));
+                }
+            }
+        }
+        let true_index = NodeIndex::from(n_new_nodes);
+        let mut nodes = Vec::with_capacity(n_new_nodes + usize::from(needs_true_node));
+        let mut edges = Vec::new();
+        for i in (0..c2d_nodes.len()).rev() {
+            if new_indices[i].is_none() {
+                continue;
+            }
+            let (C2dNode::And(children) | C2dNode::Or(children)) = &c2d_nodes[i] else {
+                unreachable!()
+            };
+            let edge_indices = children
+                .iter()
+                .map(|c| {
+                    edges.push(match c2d_nodes[*c] {
+                        C2dNode::Literal(l) => Edge::from_raw_data(true_index, vec![l]),
+                        _ => Edge::from_raw_data(NodeIndex::from(new_indices[*c].unwrap()), vec![]),
+                    });
+                    EdgeIndex::from(edges.len() - 1)
+                })
+                .collect::<Vec<_>>();
+            nodes.push(match &c2d_nodes[i] {
+                C2dNode::And(_) if edge_indices.is_empty() => Node::True,
+                C2dNode::Or(_) if edge_indices.is_empty() => Node::False,
+                C2dNode::And(_) => Node::And(edge_indices),
+                _ => Node::Or(edge_indices),
+            });
+        }
+        if needs_true_node {
+            nodes.push(Node::True);
+        }
+        DecisionDNNF::from_raw_data(n_vars, nodes, edges)
+    }
+}
+
+enum C2dNode {
+    Literal(Literal),
+    And(Vec<usize>),
+    Or(Vec<usize>),
+}
 
 /// A structure used to write a Decision-DNNF using the [c2d](http://reasoning.cs.ucla.edu/c2d/) output format.
 pub struct Writer;
@@ -294,7 +511,7 @@ fn write_opt_bool(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::D4Reader;
+    use crate::{D4Reader, ModelCounter};
 
     fn assert_translation(init: &str, expected: &str) {
         let ddnnf = D4Reader::read(&mut init.as_bytes()).unwrap();
@@ -347,4 +564,68 @@ mod tests {
             "nnf 14 14 5\nL 4\nL -5\nA 2 0 1\nL -4\nL 5\nA 2 3 4\nO 4 2 2 5\nL 1\nL -3\nA 3 6 7 8\nL -1\nL 2\nA 3 6 10 11\nO 1 2 9 12\n",
         );
     }
+
+    fn assert_read_error(instance: &str, expected_error: &str) {
+        match Reader::read(instance.as_bytes()) {
+            Ok(_) => panic!(),
+            Err(e) => assert_eq!(expected_error, format!("{}", e.root_cause())),
+        }
+    }
+
+    fn read_count(instance: &str) -> usize {
+        let ddnnf = Reader::read(instance.as_bytes()).unwrap();
+        ModelCounter::new(&ddnnf).count().to_usize().unwrap()
+    }
+
+    #[test]
+    fn test_read_leaves() {
+        assert_eq!(4, read_count("nnf 1 0 2\nA 0\n"));
+        assert_eq!(0, read_count("nnf 1 0 2\nO 0 0\n"));
+        assert_eq!(2, read_count("nnf 1 0 2\nL -2\n"));
+    }
+
+    #[test]
+    fn test_read_round_trip() {
+        let instance = "o 1 0\no 2 0\nt 3 0\n1 2 -1 2 0\n1 2 1 -3 0\n2 3 -4 5 0\n2 3 4 -5 0";
+        let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+        let mut buffer = Vec::new();
+        Writer::write(&mut buffer, &ddnnf).unwrap();
+        let read = Reader::read(buffer.as_slice()).unwrap();
+        assert_eq!(ddnnf.n_vars(), read.n_vars());
+        assert_eq!(
+            ModelCounter::new(&ddnnf).count(),
+            ModelCounter::new(&read).count()
+        );
+    }
+
+    #[test]
+    fn test_read_unreachable() {
+        let ddnnf = Reader::read("nnf 4 2 2\nL 1\nL 2\nA 0\nA 2 0 1\n".as_bytes()).unwrap();
+        assert_eq!(2, ddnnf.n_nodes());
+        assert_eq!(2, ddnnf.n_edges());
+    }
+
+    #[test]
+    fn test_read_errors() {
+        assert_read_error("", "missing header");
+        assert_read_error("nnf 1 0\nA 0\n", "missing value");
+        assert_read_error("nnf 0 0 0\n", "the formula has no nodes");
+        assert_read_error(
+            "nnf 2 0 1\nA 0\n",
+            "the header declares 2 nodes and 0 edges, but 1 nodes and 0 edges were found",
+        );
+        assert_read_error(
+            "nnf 1 0 1\nL 2\n",
+            "no such literal: 2 (the formula has 1 variables)",
+        );
+        assert_read_error(
+            "nnf 2 1 1\nL 1\nA 1 1\n",
+            "child 1 is not declared before node 1",
+        );
+        assert_read_error("nnf 1 0 1\nX 0\n", r#"unexpected first word "X""#);
+        assert_read_error(
+            "nnf 2 1 1\nL 1\nA 1 0 0\n",
+            "unexpected content after the children",
+        );
+    }
 }
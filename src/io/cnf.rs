@@ -0,0 +1,174 @@
+use crate::Literal;
+use anyhow::{anyhow, Context, Result};
+use std::{
+    io::{BufRead, BufReader, Read},
+    str::FromStr,
+};
+
+/// A formula in conjunctive normal form, read from a DIMACS file.
+///
+/// The file begins with a `p cnf <n_vars> <n_clauses>` header, possibly preceded by comment lines beginning by `c`.
+/// Each clause is then given as blank separated literals ended by a 0; a clause may span several lines.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{Cnf, Literal};
+///
+/// let content = "c a CNF\np cnf 3 2\n1 -2 0\n2\n3 0\n";
+/// let cnf = Cnf::from_reader(content.as_bytes()).unwrap();
+/// assert_eq!(3, cnf.n_vars());
+/// assert_eq!(&[Literal::from(2), Literal::from(3)], cnf.clauses()[1].as_slice());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cnf {
+    n_vars: usize,
+    clauses: Vec<Vec<Literal>>,
+}
+
+impl Cnf {
+    /// Reads a CNF formula given in the DIMACS format.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the content cannot be read or does not follow the format,
+    /// if a literal refers to a variable that is not declared in the header, or if the number of clauses does not match the header.
+    pub fn from_reader<R>(reader: R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let context = "while reading a CNF formula";
+        let mut header = None;
+        let mut clauses = Vec::new();
+        let mut current_clause = Vec::new();
+        for (line_index, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line
+                .with_context(|| format!("while reading line at index {line_index}"))
+                .context(context)?;
+            Self::parse_line(&line, &mut header, &mut clauses, &mut current_clause)
+                .with_context(|| format!("while parsing line at index {line_index}"))
+                .context(context)?;
+        }
+        let (n_vars, n_clauses) = header
+            .ok_or_else(|| anyhow!("missing header"))
+            .context(context)?;
+        if !current_clause.is_empty() {
+            return Err(anyhow!("the last clause is not ended by 0")).context(context);
+        }
+        if clauses.len() != n_clauses {
+            return Err(anyhow!(
+                "the header announces {n_clauses} clauses, but {} were found",
+                clauses.len()
+            ))
+            .context(context);
+        }
+        Ok(Self { n_vars, clauses })
+    }
+
+    fn parse_line(
+        line: &str,
+        header: &mut Option<(usize, usize)>,
+        clauses: &mut Vec<Vec<Literal>>,
+        current_clause: &mut Vec<Literal>,
+    ) -> Result<()> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            return Ok(());
+        }
+        let Some((n_vars, _)) = header else {
+            *header = Some(Self::parse_header(line)?);
+            return Ok(());
+        };
+        if line.starts_with('p') {
+            return Err(anyhow!("unexpected second header"));
+        }
+        for w in line.split_whitespace() {
+            let i =
+                isize::from_str(w).with_context(|| format!(r#"while parsing literal "{w}""#))?;
+            if i == 0 {
+                clauses.push(std::mem::take(current_clause));
+                continue;
+            }
+            let l = Literal::from(i);
+            if l.var_index() >= *n_vars {
+                return Err(anyhow!(
+                    "no such literal: {l} (the header declares {n_vars} variables)"
+                ));
+            }
+            current_clause.push(l);
+        }
+        Ok(())
+    }
+
+    fn parse_header(line: &str) -> Result<(usize, usize)> {
+        match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["p", "cnf", n_vars, n_clauses] => Ok((
+                usize::from_str(n_vars).context("while parsing the number of variables")?,
+                usize::from_str(n_clauses).context("while parsing the number of clauses")?,
+            )),
+            _ => Err(anyhow!(r#"expected a "p cnf" header, got "{line}""#)),
+        }
+    }
+
+    /// Returns the number of variables declared in the header.
+    #[must_use]
+    pub fn n_vars(&self) -> usize {
+        self.n_vars
+    }
+
+    /// Returns the clauses, in the order they were read.
+    #[must_use]
+    pub fn clauses(&self) -> &[Vec<Literal>] {
+        &self.clauses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(content: &str) -> Result<Vec<Vec<isize>>> {
+        Cnf::from_reader(content.as_bytes()).map(|cnf| {
+            cnf.clauses()
+                .iter()
+                .map(|c| c.iter().map(|l| isize::from(*l)).collect())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_ok() {
+        assert_eq!(
+            vec![vec![1, -2], vec![2, 3], vec![]],
+            read("c comment\np cnf 3 3\n1 -2 0\n\n2\n3 0 0\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_no_clauses() {
+        let cnf = Cnf::from_reader("p cnf 2 0\n".as_bytes()).unwrap();
+        assert_eq!(2, cnf.n_vars());
+        assert!(cnf.clauses().is_empty());
+    }
+
+    #[test]
+    fn test_missing_header() {
+        assert!(read("1 -2 0\n").is_err());
+        assert!(read("c only a comment\n").is_err());
+    }
+
+    #[test]
+    fn test_undeclared_var() {
+        assert!(read("p cnf 2 1\n1 -3 0\n").is_err());
+    }
+
+    #[test]
+    fn test_wrong_number_of_clauses() {
+        assert!(read("p cnf 2 2\n1 -2 0\n").is_err());
+    }
+
+    #[test]
+    fn test_unterminated_clause() {
+        assert!(read("p cnf 2 1\n1 -2\n").is_err());
+    }
+}
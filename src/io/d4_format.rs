@@ -1,10 +1,11 @@
-use crate::core::{Edge, Node, NodeIndex};
-use crate::{DecisionDNNF, Literal};
+use crate::core::{simplification, BottomUpTraversal, Edge, EdgeIndex, Node, NodeIndex};
+use crate::{CheckLevel, CheckingVisitor, DecisionDNNF, DeterminismVerdict, Literal};
 use anyhow::{anyhow, Context, Result};
 use std::str::FromStr;
 use std::{
     cell::RefCell,
-    io::{BufRead, BufReader, Read},
+    fmt::Display,
+    io::{BufRead, BufReader, BufWriter, Read, Write},
     rc::Rc,
     str::SplitWhitespace,
 };
@@ -19,10 +20,115 @@ use std::{
 /// The index of the root must be 1. The root must be the first node that is described.
 /// The decomposability of the conjunction nodes and the determinism of the disjunction nodes are not check by this reader.
 /// See [`CheckingVisitor`](crate::CheckingVisitor) if you need to assert these properties.
-pub struct Reader;
+///
+/// The checks to apply can be tuned by setting a [`CheckLevel`] before calling [`read_with_options`](Self::read_with_options).
+/// The default level is [`CheckLevel::Connectivity`], which corresponds to the checks described above.
+/// Since the syntactic checks are required to build the formula, the levels [`CheckLevel::None`] and [`CheckLevel::Syntactic`] are equivalent for this reader.
+/// The levels [`CheckLevel::Decomposability`] and [`CheckLevel::Strict`] make the reader run a [`CheckingVisitor`](crate::CheckingVisitor) after the formula is built;
+/// in this case, the warnings produced by the checker are not returned (at the strict level, the ones that are not refuted trigger an error, see [`CheckLevel`]).
+///
+/// The reader can also simplify the formula while reading it, see [`set_prune_false`](Self::set_prune_false) and [`set_share_leaves`](Self::set_share_leaves).
+/// These simplifications are applied after the connectivity checks, and before the checks made by the [`CheckingVisitor`](crate::CheckingVisitor).
+pub struct Reader {
+    check_level: CheckLevel,
+    prune_false: bool,
+    share_leaves: bool,
+    tolerant: bool,
+}
+
+impl Default for Reader {
+    fn default() -> Self {
+        Self {
+            check_level: CheckLevel::Connectivity,
+            prune_false: false,
+            share_leaves: false,
+            tolerant: true,
+        }
+    }
+}
+
+const BYTE_ORDER_MARK: char = '\u{feff}';
 
 impl Reader {
-    /// Reads an instance and returns it.
+    /// Sets the level of checks to apply while reading.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{CheckLevel, D4Reader};
+    ///
+    /// let mut reader = D4Reader::default();
+    /// reader.set_check_level(CheckLevel::Strict);
+    /// assert!(reader.read_with_options("o 1 0\nt 2 0\n1 2 1 0\n1 2 1 0\n".as_bytes()).is_err());
+    /// reader.set_check_level(CheckLevel::Decomposability);
+    /// assert!(reader.read_with_options("o 1 0\nt 2 0\n1 2 1 0\n1 2 1 0\n".as_bytes()).is_ok());
+    /// ```
+    pub fn set_check_level(&mut self, check_level: CheckLevel) {
+        self.check_level = check_level;
+    }
+
+    /// Sets whether the parts of the formula that are equivalent to false must be pruned.
+    ///
+    /// When this option is set, the edges from disjunction nodes to nodes that are equivalent to false are removed.
+    /// A conjunction node with a child equivalent to false and a disjunction node with no child are replaced by false nodes.
+    /// The nodes that are no longer reachable from the root are removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// // the second child of the root is a conjunction with a false child
+    /// let instance = "o 1 0\nt 2 0\na 3 0\nf 4 0\n1 2 -1 0\n1 3 1 0\n3 4 0\n";
+    /// let mut reader = D4Reader::default();
+    /// reader.set_prune_false(true);
+    /// let ddnnf = reader.read_with_options(instance.as_bytes()).unwrap();
+    /// assert_eq!(2, ddnnf.n_nodes());
+    /// ```
+    pub fn set_prune_false(&mut self, prune_false: bool) {
+        self.prune_false = prune_false;
+    }
+
+    /// Sets whether the true nodes (resp. the false nodes) must be merged into a single node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// let instance = "o 1 0\nt 2 0\nt 3 0\n1 2 -1 0\n1 3 1 0\n";
+    /// let mut reader = D4Reader::default();
+    /// reader.set_share_leaves(true);
+    /// let ddnnf = reader.read_with_options(instance.as_bytes()).unwrap();
+    /// assert_eq!(2, ddnnf.n_nodes());
+    /// ```
+    pub fn set_share_leaves(&mut self, share_leaves: bool) {
+        self.share_leaves = share_leaves;
+    }
+
+    /// Sets whether the reader must tolerate the layout defects of files produced on other platforms or concatenated by scripts.
+    ///
+    /// When this option is set, which is the default, blank lines, Windows line endings (CRLF), spaces at the beginning or the end of lines and byte order marks at the beginning of lines are ignored.
+    /// When it is not set, these defects are rejected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// let instance = "\u{feff}o 1 0\r\nt 2 0\r\n\r\n1 2 -1 0\r\n1 2 1 0\r\n";
+    /// let mut reader = D4Reader::default();
+    /// assert!(reader.read_with_options(instance.as_bytes()).is_ok());
+    /// reader.set_tolerant(false);
+    /// assert!(reader.read_with_options(instance.as_bytes()).is_err());
+    /// ```
+    pub fn set_tolerant(&mut self, tolerant: bool) {
+        self.tolerant = tolerant;
+    }
+
+    /// Reads an instance using the default options and returns it.
+    ///
+    /// This is a shortcut for calling [`read_with_options`](Self::read_with_options) on a default reader.
     ///
     /// # Errors
     ///
@@ -46,6 +152,18 @@ impl Reader {
     /// # load_decision_dnnf("t 1 0").unwrap();
     /// ```
     pub fn read<R>(reader: R) -> Result<DecisionDNNF>
+    where
+        R: Read,
+    {
+        Self::default().read_with_options(reader)
+    }
+
+    /// Reads an instance using the options set for this reader and returns it.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the content of the instance does not follow the d4 format or one of the checks required by the check level fails.
+    pub fn read_with_options<R>(&self, reader: R) -> Result<DecisionDNNF>
     where
         R: Read,
     {
@@ -63,7 +181,11 @@ impl Reader {
             if line_len == 0 {
                 break;
             }
-            let mut words = buffer.split_whitespace();
+            let line = self
+                .line_content(&buffer)
+                .with_context(line_index_context)
+                .context(context)?;
+            let mut words = line.split_whitespace();
             if let Some(first_word) = words.next() {
                 match first_word {
                     "o" | "a" | "t" | "f" => {
@@ -88,19 +210,70 @@ impl Reader {
             buffer.clear();
             *line_index.borrow_mut() += 1;
         }
-        reader_data.check_connectivity().context(context)?;
-        Ok(DecisionDNNF::from_raw_data(
-            reader_data.n_vars,
-            reader_data.nodes,
-            reader_data.edges,
-        ))
+        if self.check_level >= CheckLevel::Connectivity {
+            reader_data.check_connectivity().context(context)?;
+        }
+        if !reader_data.nodes.is_empty() && (self.prune_false || self.share_leaves) {
+            let (nodes, edges) = simplification::simplify(
+                &reader_data.nodes,
+                &reader_data.edges,
+                self.prune_false,
+                self.share_leaves,
+            );
+            reader_data.nodes = nodes;
+            reader_data.edges = edges;
+        }
+        let ddnnf =
+            DecisionDNNF::from_raw_data(reader_data.n_vars, reader_data.nodes, reader_data.edges);
+        if self.check_level >= CheckLevel::Decomposability {
+            let checking_data =
+                BottomUpTraversal::new(Box::<CheckingVisitor>::default()).traverse(&ddnnf);
+            if let Some(e) = checking_data.get_error() {
+                return Err(anyhow!("{e}")).context(context);
+            }
+            if self.check_level == CheckLevel::Strict {
+                let verdicts = checking_data.refute_warnings(&ddnnf);
+                if let Some((w, _)) = checking_data
+                    .get_warnings()
+                    .iter()
+                    .zip(verdicts)
+                    .find(|(_, v)| *v != DeterminismVerdict::Refuted)
+                {
+                    return Err(anyhow!("{w}")).context(context);
+                }
+            }
+        }
+        Ok(ddnnf)
+    }
+
+    fn line_content<'b>(&self, line: &'b str) -> Result<&'b str> {
+        if self.tolerant {
+            return Ok(line.trim_start_matches(BYTE_ORDER_MARK));
+        }
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        if content.starts_with(BYTE_ORDER_MARK) {
+            Err(anyhow!("unexpected byte order mark"))
+        } else if content.ends_with('\r') {
+            Err(anyhow!("unexpected carriage return"))
+        } else if content.trim().is_empty() {
+            Err(anyhow!("unexpected blank line"))
+        } else if content.trim() != content {
+            Err(anyhow!("unexpected leading or trailing whitespace"))
+        } else {
+            Ok(content)
+        }
     }
 
     fn add_new_node(
         reader_data: &mut D4FormatReaderData,
         first_word: &str,
-        mut words: SplitWhitespace,
+        words: SplitWhitespace,
     ) -> Result<()> {
+        let index = Self::parse_node(words)?;
+        reader_data.add_new_node(first_word, index)
+    }
+
+    fn parse_node(mut words: SplitWhitespace) -> Result<usize> {
         let str_index = words.next().ok_or(anyhow!("missing node index"))?;
         let index = usize::from_str(str_index).context("while parsing the node index")?;
         if words.next() != Some("0") {
@@ -109,14 +282,22 @@ impl Reader {
         if words.next().is_some() {
             return Err(anyhow!("unexpected content after 0"));
         }
-        reader_data.add_new_node(first_word, index)
+        Ok(index)
     }
 
     fn add_new_edge(
         reader_data: &mut D4FormatReaderData,
         first_word: &str,
-        mut words: SplitWhitespace,
+        words: SplitWhitespace,
     ) -> Result<()> {
+        let (source_index, target_index, propagated) = Self::parse_edge(first_word, words)?;
+        reader_data.add_new_edge(source_index, target_index, propagated)
+    }
+
+    fn parse_edge(
+        first_word: &str,
+        mut words: SplitWhitespace,
+    ) -> Result<(usize, usize, Vec<Literal>)> {
         let source_index = usize::from_str(first_word).context("while parsing the source index")?;
         let str_target_index = words.next().ok_or(anyhow!("missing target index"))?;
         let target_index =
@@ -135,7 +316,490 @@ impl Reader {
         if words.next().is_some() {
             return Err(anyhow!("unexpected content after 0"));
         }
-        reader_data.add_new_edge(source_index, target_index, propagated)
+        Ok((source_index, target_index, propagated))
+    }
+
+    /// Reads an instance, recovering from the errors when it is possible, and returns the formula together with the problems that were found.
+    ///
+    /// Contrary to [`read_with_options`](Self::read_with_options), which stops at the first error, this function reports all the problems of the instance.
+    /// The following recoveries are applied:
+    /// - the lines that cannot be parsed, the nodes declared twice and the edges that cannot be added (e.g. loops and edges from leaves) are skipped;
+    /// - the nodes that are missing from the sequence of declared nodes or that are only referenced by edges are replaced by false nodes;
+    /// - the edges closing cycles are removed;
+    /// - the nodes that are not reachable from the root are removed.
+    ///
+    /// Since the edges are added once all the lines are read, they may refer to nodes declared after them.
+    /// If the instance declares no node at all, the returned formula is a single false node.
+    /// The simplifications set for this reader are applied, but the checks of the decomposability and the determinism are not;
+    /// see [`CheckingVisitor`](crate::CheckingVisitor) to run them on the returned formula.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the instance cannot be read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::{D4Reader, D4Recovery};
+    ///
+    /// let instance = "o 1 0\nt 2 0\n1 2 -1 0\n1 3 1 0\n1 2 x 0\n";
+    /// let (ddnnf, diagnostics) = D4Reader::default().read_with_diagnostics(instance.as_bytes()).unwrap();
+    /// assert_eq!(3, ddnnf.n_nodes());
+    /// assert_eq!(2, diagnostics.len());
+    /// assert_eq!(Some(3), diagnostics[0].line_index());
+    /// assert_eq!(D4Recovery::NodeStubbed, diagnostics[0].recovery());
+    /// assert_eq!(Some(4), diagnostics[1].line_index());
+    /// assert_eq!(D4Recovery::LineSkipped, diagnostics[1].recovery());
+    /// ```
+    pub fn read_with_diagnostics<R>(&self, reader: R) -> Result<(DecisionDNNF, Vec<Diagnostic>)>
+    where
+        R: Read,
+    {
+        let mut reader = BufReader::new(reader);
+        let mut buffer = String::new();
+        let mut data = DiagnosticsData::default();
+        let mut pending_edges = Vec::new();
+        let mut line_index = 0;
+        while reader
+            .read_line(&mut buffer)
+            .with_context(|| format!("while parsing line at index {line_index}"))
+            .context("while parsing a d4 formatted Decision-DNNF")?
+            > 0
+        {
+            let parsed = self.line_content(&buffer).and_then(|line| {
+                let mut words = line.split_whitespace();
+                match words.next() {
+                    None => Ok(()),
+                    Some(first_word @ ("o" | "a" | "t" | "f")) => Self::parse_node(words)
+                        .map(|index| data.add_new_node(line_index, first_word, index)),
+                    Some(first_word) if usize::from_str(first_word).is_ok() => {
+                        Self::parse_edge(first_word, words)
+                            .map(|edge| pending_edges.push((line_index, edge)))
+                    }
+                    Some(first_word) => Err(anyhow!(r#"unexpected first word "{first_word}""#)),
+                }
+            });
+            if let Err(e) = parsed {
+                data.report(
+                    Some(line_index),
+                    e.root_cause().to_string(),
+                    Recovery::LineSkipped,
+                );
+            }
+            buffer.clear();
+            line_index += 1;
+        }
+        if data.reader_data.nodes.is_empty() {
+            data.report(None, "missing root node".to_string(), Recovery::NodeStubbed);
+            data.push_node(None, Node::False);
+        }
+        for (line_index, (source_index, target_index, propagated)) in pending_edges {
+            data.add_new_edge(line_index, source_index, target_index, propagated);
+        }
+        data.remove_cycles_and_unreachable_nodes();
+        let (nodes, edges) = simplification::simplify(
+            &data.reader_data.nodes,
+            &data.reader_data.edges,
+            self.prune_false,
+            self.share_leaves,
+        );
+        let mut diagnostics = data.diagnostics;
+        diagnostics.sort_by_key(|d| d.line_index.unwrap_or(usize::MAX));
+        Ok((
+            DecisionDNNF::from_raw_data(data.reader_data.n_vars, nodes, edges),
+            diagnostics,
+        ))
+    }
+
+    /// Reads an instance once without building the formula, and returns statistics about it.
+    ///
+    /// Only the syntax of the lines is checked, and the memory used by this function does not depend on the size of the instance.
+    /// This allows to triage large inputs before committing to a full load, e.g. by checking the formula would fit in a memory budget with [`Statistics::fits_in`].
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the instance cannot be read or a line does not follow the d4 format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use decdnnf_rs::D4Reader;
+    ///
+    /// let instance = "o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n";
+    /// let statistics = D4Reader::default().read_statistics(instance.as_bytes()).unwrap();
+    /// assert_eq!(2, statistics.n_nodes());
+    /// assert_eq!(2, statistics.n_edges());
+    /// assert_eq!(1, statistics.n_vars());
+    /// ```
+    pub fn read_statistics<R>(&self, reader: R) -> Result<Statistics>
+    where
+        R: Read,
+    {
+        let mut reader = BufReader::new(reader);
+        let mut buffer = String::new();
+        let mut statistics = Statistics::default();
+        let mut line_index = 0;
+        let context = "while reading the statistics of a d4 formatted Decision-DNNF";
+        while reader
+            .read_line(&mut buffer)
+            .with_context(|| format!("while parsing line at index {line_index}"))
+            .context(context)?
+            > 0
+        {
+            self.line_content(&buffer)
+                .and_then(|line| {
+                    let mut words = line.split_whitespace();
+                    match words.next() {
+                        None => {}
+                        Some(first_word @ ("o" | "a" | "t" | "f")) => {
+                            Self::parse_node(words)?;
+                            statistics.add_node(first_word);
+                        }
+                        Some(first_word) if usize::from_str(first_word).is_ok() => {
+                            let (_, _, propagated) = Self::parse_edge(first_word, words)?;
+                            statistics.add_edge(&propagated);
+                        }
+                        Some(first_word) => {
+                            return Err(anyhow!(r#"unexpected first word "{first_word}""#))
+                        }
+                    }
+                    Ok(())
+                })
+                .with_context(|| format!("while parsing line at index {line_index}"))
+                .context(context)?;
+            buffer.clear();
+            line_index += 1;
+        }
+        Ok(statistics)
+    }
+}
+
+/// The data built while reading an instance with [`Reader::read_with_diagnostics`].
+#[derive(Default)]
+struct DiagnosticsData {
+    reader_data: D4FormatReaderData,
+    node_lines: Vec<Option<usize>>,
+    edge_lines: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticsData {
+    fn report(&mut self, line_index: Option<usize>, message: String, recovery: Recovery) {
+        self.diagnostics.push(Diagnostic {
+            line_index,
+            message,
+            recovery,
+        });
+    }
+
+    fn push_node(&mut self, line_index: Option<usize>, node: Node) {
+        self.reader_data.nodes.push(node);
+        self.node_lines.push(line_index);
+    }
+
+    fn stub_nodes_until(&mut self, line_index: usize, index: usize) {
+        for missing_index in 1 + self.reader_data.nodes.len()..=index {
+            self.report(
+                Some(line_index),
+                format!("missing node with index {missing_index}"),
+                Recovery::NodeStubbed,
+            );
+            self.push_node(Some(line_index), Node::False);
+        }
+    }
+
+    fn add_new_node(&mut self, line_index: usize, label: &str, index: usize) {
+        let expected_index = 1 + self.reader_data.nodes.len();
+        if index < expected_index {
+            self.report(
+                Some(line_index),
+                format!("wrong node index; expected {expected_index}, got {index}"),
+                Recovery::LineSkipped,
+            );
+            return;
+        }
+        self.stub_nodes_until(line_index, index - 1);
+        self.push_node(Some(line_index), Node::from_str(label).unwrap());
+    }
+
+    fn add_new_edge(
+        &mut self,
+        line_index: usize,
+        source_index: usize,
+        target_index: usize,
+        propagated: Vec<Literal>,
+    ) {
+        self.stub_nodes_until(line_index, usize::max(source_index, target_index));
+        let added = if source_index == 0 || target_index == 0 {
+            Err(anyhow!("node indices begin at 1"))
+        } else {
+            self.reader_data
+                .add_new_edge(source_index, target_index, propagated)
+        };
+        match added {
+            Ok(()) => self.edge_lines.push(line_index),
+            Err(e) => self.report(
+                Some(line_index),
+                e.root_cause().to_string(),
+                Recovery::LineSkipped,
+            ),
+        }
+    }
+
+    fn remove_cycles_and_unreachable_nodes(&mut self) {
+        let (seen_once, cycle_edges) = self.reader_data.explore_from_root();
+        for (source, edge_index) in cycle_edges {
+            self.report(
+                Some(self.edge_lines[usize::from(edge_index)]),
+                "cycle detected".to_string(),
+                Recovery::EdgeRemoved,
+            );
+            if let Node::And(v) | Node::Or(v) = &mut self.reader_data.nodes[usize::from(source)] {
+                v.retain(|e| usize::from(*e) != usize::from(edge_index));
+            }
+        }
+        for (i, seen) in seen_once.into_iter().enumerate() {
+            if !seen {
+                self.report(
+                    self.node_lines[i],
+                    format!("no path to the node with index {}", i + 1),
+                    Recovery::NodeRemoved,
+                );
+            }
+        }
+    }
+}
+
+/// A problem found by [`Reader::read_with_diagnostics`], and the way the reader recovered from it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    line_index: Option<usize>,
+    message: String,
+    recovery: Recovery,
+}
+
+impl Diagnostic {
+    /// Returns the index of the line the problem was found at, beginning at 0.
+    ///
+    /// Returns [`None`] if the problem concerns the whole instance.
+    #[must_use]
+    pub fn line_index(&self) -> Option<usize> {
+        self.line_index
+    }
+
+    /// Returns a description of the problem.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the way the reader recovered from the problem.
+    #[must_use]
+    pub fn recovery(&self) -> Recovery {
+        self.recovery
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(i) = self.line_index {
+            write!(f, "line at index {i}: ")?;
+        }
+        write!(f, "{} ({})", self.message, self.recovery)
+    }
+}
+
+/// The ways [`Reader::read_with_diagnostics`] recovers from the problems it finds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// The line was ignored.
+    LineSkipped,
+    /// A missing node was replaced by a false node.
+    NodeStubbed,
+    /// An edge was removed.
+    EdgeRemoved,
+    /// A node was removed.
+    NodeRemoved,
+}
+
+impl Display for Recovery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recovery::LineSkipped => write!(f, "line skipped"),
+            Recovery::NodeStubbed => write!(f, "replaced by a false node"),
+            Recovery::EdgeRemoved => write!(f, "edge removed"),
+            Recovery::NodeRemoved => write!(f, "node removed"),
+        }
+    }
+}
+
+/// Statistics about an instance, computed by [`Reader::read_statistics`] without building the formula.
+///
+/// The counts are the ones of the instance as it is written: nodes that are not reachable from the root are included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    and_nodes: usize,
+    or_nodes: usize,
+    true_nodes: usize,
+    false_nodes: usize,
+    edges: usize,
+    propagated: usize,
+    vars: usize,
+}
+
+impl Statistics {
+    fn add_node(&mut self, label: &str) {
+        match label {
+            "a" => self.and_nodes += 1,
+            "o" => self.or_nodes += 1,
+            "t" => self.true_nodes += 1,
+            "f" => self.false_nodes += 1,
+            _ => unreachable!(),
+        }
+    }
+
+    fn add_edge(&mut self, propagated: &[Literal]) {
+        self.edges += 1;
+        self.propagated += propagated.len();
+        if let Some(max_var) = propagated.iter().map(Literal::var_index).max() {
+            self.vars = self.vars.max(max_var + 1);
+        }
+    }
+
+    /// Returns the number of nodes.
+    #[must_use]
+    pub fn n_nodes(&self) -> usize {
+        self.and_nodes + self.or_nodes + self.true_nodes + self.false_nodes
+    }
+
+    /// Returns the number of conjunction nodes.
+    #[must_use]
+    pub fn n_and_nodes(&self) -> usize {
+        self.and_nodes
+    }
+
+    /// Returns the number of disjunction nodes.
+    #[must_use]
+    pub fn n_or_nodes(&self) -> usize {
+        self.or_nodes
+    }
+
+    /// Returns the number of true nodes.
+    #[must_use]
+    pub fn n_true_nodes(&self) -> usize {
+        self.true_nodes
+    }
+
+    /// Returns the number of false nodes.
+    #[must_use]
+    pub fn n_false_nodes(&self) -> usize {
+        self.false_nodes
+    }
+
+    /// Returns the number of edges.
+    #[must_use]
+    pub fn n_edges(&self) -> usize {
+        self.edges
+    }
+
+    /// Returns the total number of literals propagated by the edges.
+    #[must_use]
+    pub fn n_propagated(&self) -> usize {
+        self.propagated
+    }
+
+    /// Returns the number of variables, i.e. the highest variable index that appears in the instance.
+    #[must_use]
+    pub fn n_vars(&self) -> usize {
+        self.vars
+    }
+
+    /// Returns the average number of literals propagated by an edge.
+    ///
+    /// Returns 0 if the instance has no edges.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn average_propagation_len(&self) -> f64 {
+        if self.edges == 0 {
+            0.
+        } else {
+            self.propagated as f64 / self.edges as f64
+        }
+    }
+
+    /// Returns an estimation of the memory, in bytes, used by the [`DecisionDNNF`] built from this instance.
+    ///
+    /// The estimation takes into account the nodes, the edges and the propagated literals, but neither the spare capacity of the underlying vectors nor the memory used while reading.
+    #[must_use]
+    pub fn estimated_memory(&self) -> usize {
+        self.n_nodes() * std::mem::size_of::<Node>()
+            + self.edges * (std::mem::size_of::<Edge>() + std::mem::size_of::<EdgeIndex>())
+            + self.propagated * std::mem::size_of::<Literal>()
+    }
+
+    /// Returns `true` if the [estimated memory](Self::estimated_memory) does not exceed the given budget, in bytes.
+    #[must_use]
+    pub fn fits_in(&self, memory_budget: usize) -> bool {
+        self.estimated_memory() <= memory_budget
+    }
+}
+
+/// A structure used to write a Decision-DNNF using the output format of the d4 compiler.
+///
+/// The nodes are written in the order of their indices, so the root comes first as required by the [`Reader`].
+/// The edges are written after the nodes, in the order given by their source nodes.
+/// Since the d4 format does not store the number of variables, reading the output back gives a formula whose number of variables is the highest variable index that appears in it;
+/// use [`DecisionDNNF::update_n_vars`] to restore the original value if needed.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, D4Writer};
+///
+/// let instance = "o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n";
+/// let ddnnf = D4Reader::read(instance.as_bytes()).unwrap();
+/// let mut output = Vec::new();
+/// D4Writer::write(&mut output, &ddnnf).unwrap();
+/// assert_eq!(instance, String::from_utf8(output).unwrap());
+/// ```
+pub struct Writer;
+
+impl Writer {
+    /// Writes a Decision-DNNF using the d4 format.
+    ///
+    /// # Errors
+    ///
+    /// An error is raised if an I/O exception occurs.
+    pub fn write<W>(writer: W, ddnnf: &DecisionDNNF) -> Result<()>
+    where
+        W: Write,
+    {
+        let context = "while writing a formula in the d4 format";
+        let mut writer = BufWriter::new(writer);
+        for i in 0..ddnnf.n_nodes() {
+            let label = match ddnnf.node(NodeIndex::from(i)) {
+                Node::And(_) => "a",
+                Node::Or(_) => "o",
+                Node::True => "t",
+                Node::False => "f",
+            };
+            writeln!(writer, "{label} {} 0", i + 1).context(context)?;
+        }
+        for i in 0..ddnnf.n_nodes() {
+            let edges = match ddnnf.node(NodeIndex::from(i)) {
+                Node::And(v) | Node::Or(v) => v.as_slice(),
+                Node::True | Node::False => &[],
+            };
+            for edge_index in edges {
+                let edge = ddnnf.edge(*edge_index);
+                write!(writer, "{} {}", i + 1, usize::from(edge.target()) + 1).context(context)?;
+                for l in edge.propagated() {
+                    write!(writer, " {l}").context(context)?;
+                }
+                writeln!(writer, " 0").context(context)?;
+            }
+        }
+        writer.flush().context(context)
     }
 }
 
@@ -197,44 +861,46 @@ impl D4FormatReaderData {
     }
 
     fn check_connectivity(&self) -> Result<()> {
-        let mut seen_once = vec![false; self.nodes.len()];
-        let mut seen_on_path = vec![false; self.nodes.len()];
-        self.check_connectivity_from(&mut seen_once, &mut seen_on_path, 0.into())?;
-        match seen_once.iter().position(|b| !b) {
+        let (seen, cycle_edges) = self.explore_from_root();
+        if !cycle_edges.is_empty() {
+            return Err(anyhow!("cycle detected"));
+        }
+        match seen.iter().position(|b| !b) {
             Some(i) => Err(anyhow!("no path to the node with index {}", i + 1)),
             None => Ok(()),
         }
     }
 
-    fn check_connectivity_from(
-        &self,
-        seen_once: &mut [bool],
-        seen_on_path: &mut [bool],
-        node_index: NodeIndex,
-    ) -> Result<()> {
-        let add_to_seen_on_path = |i: NodeIndex, sop: &mut [bool]| {
-            if sop[usize::from(i)] {
-                return Err(anyhow!("cycle detected"));
-            }
-            sop[usize::from(i)] = true;
-            Ok(())
-        };
-        add_to_seen_on_path(node_index, seen_on_path)?;
-        seen_once[usize::from(node_index)] = true;
-        match &self.nodes[usize::from(node_index)] {
-            Node::And(v) | Node::Or(v) => {
-                v.iter().try_for_each(|e| {
-                    self.check_connectivity_from(
-                        seen_once,
-                        seen_on_path,
-                        self.edges[usize::from(*e)].target(),
-                    )
-                })?;
+    /// Explores the nodes from the root, without recursion.
+    ///
+    /// Returns the nodes that are reachable from the root, and the edges that close a cycle (given with their source).
+    fn explore_from_root(&self) -> (Vec<bool>, Vec<(NodeIndex, EdgeIndex)>) {
+        let mut seen_once = vec![false; self.nodes.len()];
+        let mut seen_on_path = vec![false; self.nodes.len()];
+        let mut cycle_edges = Vec::new();
+        let mut stack = vec![(NodeIndex::from(0), 0)];
+        seen_once[0] = true;
+        seen_on_path[0] = true;
+        while let Some((node_index, next_child)) = stack.pop() {
+            let edges = match &self.nodes[usize::from(node_index)] {
+                Node::And(v) | Node::Or(v) => v.as_slice(),
+                Node::True | Node::False => &[],
+            };
+            let Some(edge_index) = edges.get(next_child) else {
+                seen_on_path[usize::from(node_index)] = false;
+                continue;
+            };
+            stack.push((node_index, next_child + 1));
+            let target = self.edges[usize::from(*edge_index)].target();
+            if seen_on_path[usize::from(target)] {
+                cycle_edges.push((node_index, *edge_index));
+            } else if !seen_once[usize::from(target)] {
+                seen_once[usize::from(target)] = true;
+                seen_on_path[usize::from(target)] = true;
+                stack.push((target, 0));
             }
-            Node::True | Node::False => {}
         }
-        seen_on_path[usize::from(node_index)] = false;
-        Ok(())
+        (seen_once, cycle_edges)
     }
 }
 
@@ -334,6 +1000,49 @@ mod tests {
         assert_error("a 1 0\na 2 0\n1 2 0\n2 1 0\n", "cycle detected");
     }
 
+    fn read_with_check_level(instance: &str, check_level: CheckLevel) -> Result<DecisionDNNF> {
+        let mut reader = Reader::default();
+        reader.set_check_level(check_level);
+        reader.read_with_options(instance.as_bytes())
+    }
+
+    #[test]
+    fn test_node_unreachable_no_connectivity_check() {
+        assert!(read_with_check_level("f 1 0\nt 2 0\n", CheckLevel::Syntactic).is_ok());
+    }
+
+    #[test]
+    fn test_not_decomposable_check_level() {
+        let instance = "a 1 0\nt 2 0\n1 2 1 0\n1 2 -1 0";
+        assert!(read_with_check_level(instance, CheckLevel::Connectivity).is_ok());
+        assert_eq!(
+            "AND children share variables (AND node index is 0)",
+            read_with_check_level(instance, CheckLevel::Decomposability)
+                .unwrap_err()
+                .root_cause()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_not_determinist_check_level() {
+        let instance = "o 1 0\nt 2 0\n1 2 1 0\n1 2 1 0";
+        assert!(read_with_check_level(instance, CheckLevel::Decomposability).is_ok());
+        assert_eq!(
+            "OR children at indices 0 and 1 may not be contradictory (OR node index is 0)",
+            read_with_check_level(instance, CheckLevel::Strict)
+                .unwrap_err()
+                .root_cause()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_refuted_warning_check_level() {
+        let instance = "o 1 0\no 2 0\nt 3 0\n1 3 -2 0\n1 2 1 0\n2 3 -1 0\n";
+        assert!(read_with_check_level(instance, CheckLevel::Strict).is_ok());
+    }
+
     #[test]
     fn test_edge_from_true() {
         assert_error(
@@ -383,4 +1092,288 @@ mod tests {
         assert_eq!(1, ddnnf.nodes().as_slice().len());
         assert_eq!(0, ddnnf.edges().as_slice().len());
     }
+
+    fn read_simplified(instance: &str, prune_false: bool, share_leaves: bool) -> DecisionDNNF {
+        let mut reader = Reader::default();
+        reader.set_prune_false(prune_false);
+        reader.set_share_leaves(share_leaves);
+        reader.read_with_options(instance.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_prune_false_propagation() {
+        let instance = r"
+        o 1 0
+        a 2 0
+        o 3 0
+        f 4 0
+        t 5 0
+        1 2 -1 0
+        1 5 1 0
+        2 3 0
+        2 5 2 0
+        3 4 -3 0
+        3 4 3 0
+        ";
+        let ddnnf = read_simplified(instance, true, false);
+        assert_eq!(2, ddnnf.n_nodes());
+        assert_eq!(1, ddnnf.n_edges());
+        assert_eq!(&[Literal::from(1)], ddnnf.edges()[0].propagated());
+    }
+
+    #[test]
+    fn test_prune_false_root() {
+        let ddnnf = read_simplified("a 1 0\nf 2 0\nt 3 0\n1 2 0\n1 3 1 0\n", true, false);
+        assert_eq!(1, ddnnf.n_nodes());
+        assert_eq!(0, ddnnf.n_edges());
+        assert!(matches!(ddnnf.nodes()[0], Node::False));
+    }
+
+    #[test]
+    fn test_prune_false_empty_or() {
+        let ddnnf = read_simplified("o 1 0\no 2 0\nt 3 0\n1 2 -1 0\n1 3 1 0\n", true, false);
+        assert_eq!(2, ddnnf.n_nodes());
+        assert_eq!(1, ddnnf.n_edges());
+    }
+
+    #[test]
+    fn test_share_leaves() {
+        let instance = r"
+        a 1 0
+        o 2 0
+        o 3 0
+        t 4 0
+        t 5 0
+        f 6 0
+        f 7 0
+        1 2 0
+        1 3 0
+        2 4 -1 0
+        2 6 1 0
+        3 5 -2 0
+        3 7 2 0
+        ";
+        let ddnnf = read_simplified(instance, false, true);
+        assert_eq!(5, ddnnf.n_nodes());
+        assert_eq!(6, ddnnf.n_edges());
+        let ddnnf = read_simplified(instance, true, true);
+        assert_eq!(4, ddnnf.n_nodes());
+        assert_eq!(4, ddnnf.n_edges());
+    }
+
+    fn read_with_diagnostics(instance: &str) -> (DecisionDNNF, Vec<(Option<usize>, Recovery)>) {
+        let (ddnnf, diagnostics) = Reader::default()
+            .read_with_diagnostics(instance.as_bytes())
+            .unwrap();
+        let diagnostics = diagnostics
+            .iter()
+            .map(|d| (d.line_index(), d.recovery()))
+            .collect();
+        (ddnnf, diagnostics)
+    }
+
+    #[test]
+    fn test_diagnostics_ok() {
+        let (ddnnf, diagnostics) = read_with_diagnostics("a 1 0\nt 2 0\n1 2 1 0\n");
+        assert!(diagnostics.is_empty());
+        assert_eq!(2, ddnnf.n_nodes());
+    }
+
+    #[test]
+    fn test_diagnostics_empty_instance() {
+        let (ddnnf, diagnostics) = read_with_diagnostics("\n");
+        assert_eq!(vec![(None, Recovery::NodeStubbed)], diagnostics);
+        assert!(matches!(ddnnf.node(NodeIndex::from(0)), Node::False));
+    }
+
+    #[test]
+    fn test_diagnostics_all_syntax_errors() {
+        let (ddnnf, diagnostics) =
+            read_with_diagnostics("n 1 0\no 1 0\nt 2 1\nt 2 0\n1 2 -1\n1 2 1 0 0\n1 2 1 0\n");
+        assert_eq!(
+            vec![
+                (Some(0), Recovery::LineSkipped),
+                (Some(2), Recovery::LineSkipped),
+                (Some(4), Recovery::LineSkipped),
+                (Some(5), Recovery::LineSkipped),
+            ],
+            diagnostics
+        );
+        assert_eq!(2, ddnnf.n_nodes());
+        assert_eq!(1, ddnnf.n_edges());
+    }
+
+    #[test]
+    fn test_diagnostics_node_indices() {
+        let (ddnnf, diagnostics) =
+            read_with_diagnostics("o 1 0\nt 3 0\na 2 0\n1 3 -1 0\n1 2 1 0\n1 4 2 0\n");
+        assert_eq!(
+            vec![
+                (Some(1), Recovery::NodeStubbed),
+                (Some(2), Recovery::LineSkipped),
+                (Some(5), Recovery::NodeStubbed),
+            ],
+            diagnostics
+        );
+        assert_eq!(4, ddnnf.n_nodes());
+    }
+
+    #[test]
+    fn test_diagnostics_bad_edges() {
+        let (ddnnf, diagnostics) =
+            read_with_diagnostics("a 1 0\nt 2 0\n1 1 0\n2 1 0\n0 2 0\n1 2 0\n");
+        assert_eq!(
+            vec![
+                (Some(2), Recovery::LineSkipped),
+                (Some(3), Recovery::LineSkipped),
+                (Some(4), Recovery::LineSkipped),
+            ],
+            diagnostics
+        );
+        assert_eq!(1, ddnnf.n_edges());
+    }
+
+    #[test]
+    fn test_diagnostics_cycle_and_unreachable() {
+        let (ddnnf, diagnostics) =
+            read_with_diagnostics("a 1 0\na 2 0\nt 3 0\n1 2 0\n2 1 0\n2 3 1 0\n");
+        assert_eq!(vec![(Some(4), Recovery::EdgeRemoved)], diagnostics);
+        assert_eq!(3, ddnnf.n_nodes());
+        let (ddnnf, diagnostics) = read_with_diagnostics("f 1 0\nt 2 0\n");
+        assert_eq!(vec![(Some(1), Recovery::NodeRemoved)], diagnostics);
+        assert_eq!(1, ddnnf.n_nodes());
+    }
+
+    fn assert_write(instance: &str, expected: &str) {
+        let ddnnf = Reader::read(&mut instance.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        Writer::write(&mut output, &ddnnf).unwrap();
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_write_leaves() {
+        assert_write("t 1 0\n", "t 1 0\n");
+        assert_write("f 1 0\n", "f 1 0\n");
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let instance = "o 1 0\na 2 0\nt 3 0\nf 4 0\n1 2 -1 0\n1 3 1 2 0\n2 3 2 0\n2 4 0\n";
+        assert_write(instance, instance);
+    }
+
+    #[test]
+    fn test_write_simplified() {
+        let ddnnf = read_simplified(
+            "o 1 0\na 2 0\nf 3 0\nt 4 0\n1 2 -1 0\n1 4 1 0\n2 3 0\n",
+            true,
+            true,
+        );
+        let mut output = Vec::new();
+        Writer::write(&mut output, &ddnnf).unwrap();
+        assert_eq!(
+            "o 1 0\nt 2 0\n1 2 1 0\n",
+            String::from_utf8(output).unwrap()
+        );
+    }
+
+    fn read_strict(instance: &str) -> Result<DecisionDNNF> {
+        let mut reader = Reader::default();
+        reader.set_tolerant(false);
+        reader.read_with_options(instance.as_bytes())
+    }
+
+    #[test]
+    fn test_tolerant_layout() {
+        for instance in [
+            "\u{feff}o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n",
+            "o 1 0\r\nt 2 0\r\n1 2 -1 0\r\n1 2 1 0\r\n",
+            "\no 1 0\n\nt 2 0\n \n1 2 -1 0\n1 2 1 0\n\n",
+            "o 1 0 \n  t 2 0\n1 2 -1 0\t\n1 2 1 0",
+            "o 1 0\nt 2 0\n\u{feff}1 2 -1 0\n1 2 1 0\n",
+        ] {
+            let ddnnf = Reader::read(instance.as_bytes()).unwrap();
+            assert_eq!(2, ddnnf.n_nodes());
+            assert_eq!(2, ddnnf.n_edges());
+        }
+    }
+
+    #[test]
+    fn test_strict_layout() {
+        assert!(read_strict("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0").is_ok());
+        for (instance, expected_error) in [
+            ("\u{feff}t 1 0\n", "unexpected byte order mark"),
+            ("t 1 0\r\n", "unexpected carriage return"),
+            ("t 1 0\n\n", "unexpected blank line"),
+            ("t 1 0 \n", "unexpected leading or trailing whitespace"),
+            (" t 1 0\n", "unexpected leading or trailing whitespace"),
+        ] {
+            let error = read_strict(instance).unwrap_err();
+            assert_eq!(expected_error, error.root_cause().to_string());
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_strict_layout() {
+        let mut reader = Reader::default();
+        reader.set_tolerant(false);
+        let (ddnnf, diagnostics) = reader
+            .read_with_diagnostics("t 1 0\n\n1 2 0\r\n".as_bytes())
+            .unwrap();
+        assert_eq!(1, ddnnf.n_nodes());
+        assert_eq!(
+            vec![
+                (Some(1), "unexpected blank line"),
+                (Some(2), "unexpected carriage return")
+            ],
+            diagnostics
+                .iter()
+                .map(|d| (d.line_index(), d.message()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_statistics() {
+        let instance = "a 1 0\no 2 0\nt 3 0\nf 4 0\nt 5 0\n1 2 0\n1 3 3 0\n2 3 -1 -2 0\n2 4 1 0\n";
+        let statistics = Reader::default()
+            .read_statistics(instance.as_bytes())
+            .unwrap();
+        assert_eq!(5, statistics.n_nodes());
+        assert_eq!(
+            (1, 1, 2, 1),
+            (
+                statistics.n_and_nodes(),
+                statistics.n_or_nodes(),
+                statistics.n_true_nodes(),
+                statistics.n_false_nodes()
+            )
+        );
+        assert_eq!(4, statistics.n_edges());
+        assert_eq!(4, statistics.n_propagated());
+        assert_eq!(3, statistics.n_vars());
+        assert!((statistics.average_propagation_len() - 1.).abs() < f64::EPSILON);
+        let memory = statistics.estimated_memory();
+        assert!(statistics.fits_in(memory));
+        assert!(!statistics.fits_in(memory - 1));
+    }
+
+    #[test]
+    fn test_statistics_syntax_error() {
+        let error = Reader::default()
+            .read_statistics("t 1 0\n1 2 x 0\n".as_bytes())
+            .unwrap_err();
+        assert_eq!(
+            r#"expected a literal, got "x""#,
+            error.root_cause().to_string()
+        );
+    }
+
+    #[test]
+    fn test_statistics_empty() {
+        let statistics = Reader::default().read_statistics("".as_bytes()).unwrap();
+        assert_eq!(0, statistics.n_nodes());
+        assert!(statistics.average_propagation_len().abs() < f64::EPSILON);
+    }
 }
@@ -0,0 +1,164 @@
+use crate::{C2dReader, C2dWriter, D4Reader, D4Writer, DecisionDNNF};
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fmt::Display,
+    io::{BufRead, Read, Write},
+    str::FromStr,
+};
+
+/// The formats used to read and write Decision-DNNFs.
+///
+/// This enum is the single place where the supported formats are listed; each of them can be both read and written.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::Format;
+/// use std::io::BufReader;
+///
+/// let mut input = BufReader::new("o 1 0\nt 2 0\n1 2 -1 0\n1 2 1 0\n".as_bytes());
+/// let format = Format::detect(&mut input).unwrap();
+/// assert_eq!(Format::D4, format);
+/// let ddnnf = format.read(input).unwrap();
+/// let mut output = Vec::new();
+/// Format::C2d.write(&mut output, &ddnnf).unwrap();
+/// assert!(String::from_utf8(output).unwrap().starts_with("nnf "));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// The format of the [d4](https://github.com/crillab/d4) compiler.
+    D4,
+    /// The format of the [c2d](http://reasoning.cs.ucla.edu/c2d/) compiler.
+    C2d,
+}
+
+impl Format {
+    /// The names of the formats, as accepted by the [`FromStr`] implementation.
+    pub const NAMES: [&'static str; 2] = ["d4", "c2d"];
+
+    /// Returns the name of the format.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::D4 => Self::NAMES[0],
+            Format::C2d => Self::NAMES[1],
+        }
+    }
+
+    /// Detects the format of an input by looking at its first word.
+    ///
+    /// The content of the input is not consumed, so the reader can then be given to [`read`](Self::read).
+    /// The detection only relies on the data that is already buffered by the reader, which is sufficient for the usual buffer sizes.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the input cannot be read, or if its first word does not match any known format.
+    pub fn detect<R>(reader: &mut R) -> Result<Self>
+    where
+        R: BufRead,
+    {
+        let context = "while detecting the format of the input";
+        let buffer = reader.fill_buf().context(context)?;
+        let first_word = buffer
+            .strip_prefix("\u{feff}".as_bytes())
+            .unwrap_or(buffer)
+            .split(u8::is_ascii_whitespace)
+            .find(|w| !w.is_empty())
+            .ok_or_else(|| anyhow!("the input is empty"))
+            .context(context)?;
+        match first_word {
+            b"nnf" => Ok(Format::C2d),
+            b"o" | b"a" | b"t" | b"f" => Ok(Format::D4),
+            _ => Err(anyhow!(
+                r#"unknown format; the input begins with "{}""#,
+                String::from_utf8_lossy(first_word)
+            ))
+            .context(context),
+        }
+    }
+
+    /// Reads a formula in this format, using the default options of the corresponding reader.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the reader returns an error.
+    pub fn read<R>(&self, reader: R) -> Result<DecisionDNNF>
+    where
+        R: Read,
+    {
+        match self {
+            Format::D4 => D4Reader::read(reader),
+            Format::C2d => C2dReader::read(reader),
+        }
+    }
+
+    /// Writes a formula in this format.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the writer returns an error.
+    pub fn write<W>(&self, writer: W, ddnnf: &DecisionDNNF) -> Result<()>
+    where
+        W: Write,
+    {
+        match self {
+            Format::D4 => D4Writer::write(writer, ddnnf),
+            Format::C2d => C2dWriter::write(writer, ddnnf),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "d4" => Ok(Format::D4),
+            "c2d" => Ok(Format::C2d),
+            _ => Err(anyhow!("unknown format {s}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(content: &str) -> Result<Format> {
+        Format::detect(&mut content.as_bytes())
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::D4, detect("t 1 0\n").unwrap());
+        assert_eq!(Format::D4, detect("\n  a 1 0\n").unwrap());
+        assert_eq!(Format::D4, detect("\u{feff}o 1 0\r\n").unwrap());
+        assert_eq!(Format::C2d, detect("nnf 1 0 0\nA 0\n").unwrap());
+    }
+
+    #[test]
+    fn test_detect_error() {
+        assert_eq!(
+            "the input is empty",
+            detect(" \n").unwrap_err().root_cause().to_string()
+        );
+        assert_eq!(
+            r#"unknown format; the input begins with "p""#,
+            detect("p cnf 1 1\n").unwrap_err().root_cause().to_string()
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        for name in Format::NAMES {
+            assert_eq!(name, Format::from_str(name).unwrap().name());
+        }
+        assert!(Format::from_str("foo").is_err());
+    }
+}
@@ -1,5 +1,23 @@
+mod assumptions;
+pub use assumptions::Assumptions;
+
 mod c2d_format;
+pub use c2d_format::Reader as C2dReader;
 pub use c2d_format::Writer as C2dWriter;
 
+mod cnf;
+pub use cnf::Cnf;
+
 mod d4_format;
+pub use d4_format::Diagnostic as D4Diagnostic;
 pub use d4_format::Reader as D4Reader;
+pub use d4_format::Recovery as D4Recovery;
+pub use d4_format::Statistics as D4Statistics;
+pub use d4_format::Writer as D4Writer;
+
+mod format;
+pub use format::Format;
+
+mod model_writer;
+pub use model_writer::ModelFormat;
+pub use model_writer::ModelWriter;
@@ -0,0 +1,401 @@
+use crate::Literal;
+use anyhow::{anyhow, Result};
+use std::{
+    fmt::Display,
+    io::{BufWriter, IntoInnerError, Write},
+    ops::Range,
+    str::FromStr,
+};
+
+/// The formats used to write models.
+///
+/// In each format, a model is written on a line.
+/// The variables that are free in a compact model (see [`ModelWriter::write_compact_model`]) are denoted by the free variable symbol, `*` by default.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFormat {
+    /// DIMACS literals, after a `v` and followed by a `0`, e.g. `v -1  2 *3 0`; the sign of the free variables is replaced by the free variable symbol.
+    Dimacs,
+    /// A value by variable after a `v`, `1` for true and `0` for false, e.g. `v 0 1 *`; the free variables are replaced by the free variable symbol.
+    Binary,
+    /// The positive literals only, after a `v` and followed by a `0`, e.g. `v 2 *3 0`; the free variables are prefixed by the free variable symbol.
+    Positive,
+    /// A value by variable separated by commas, e.g. `0,1,*`, after a header line giving the variables (`x1,x2,x3`); the free variables are replaced by the free variable symbol.
+    Csv,
+    /// A JSON array of DIMACS literals, e.g. `[-1, 2, 3]`; the free variables are given by `null`.
+    JsonLines,
+}
+
+impl ModelFormat {
+    /// The names of the formats, as accepted by the [`FromStr`] implementation.
+    pub const NAMES: [&'static str; 5] = ["dimacs", "binary", "positive", "csv", "json-lines"];
+
+    /// Returns the name of the format.
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModelFormat::Dimacs => Self::NAMES[0],
+            ModelFormat::Binary => Self::NAMES[1],
+            ModelFormat::Positive => Self::NAMES[2],
+            ModelFormat::Csv => Self::NAMES[3],
+            ModelFormat::JsonLines => Self::NAMES[4],
+        }
+    }
+}
+
+impl FromStr for ModelFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "dimacs" => Ok(ModelFormat::Dimacs),
+            "binary" => Ok(ModelFormat::Binary),
+            "positive" => Ok(ModelFormat::Positive),
+            "csv" => Ok(ModelFormat::Csv),
+            "json-lines" => Ok(ModelFormat::JsonLines),
+            _ => Err(anyhow!(
+                r#"unknown model format "{s}" (expected one of {})"#,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl Display for ModelFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A structure used to write models, e.g. the ones given by a [`ModelEnumerator`](crate::ModelEnumerator), in a [`ModelFormat`].
+///
+/// The writer is built for a range of variable indices, and only writes the values of these variables.
+/// Since the models to write are usually numerous, the output is buffered,
+/// and the line of the formats which values have a fixed width is precomputed so that only the values change from a model to the next.
+/// The buffer is flushed when the writer is [finished](Self::finish); it is also flushed when the writer is dropped, but the errors are then ignored.
+///
+/// The writer also implements [`Write`], so that other lines (e.g. comments) can be written between the models.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{D4Reader, ModelEnumerator, ModelFormat, ModelWriter};
+///
+/// // (-1 and -2) or 1
+/// let ddnnf = D4Reader::read("o 1 0\nt 2 0\n1 2 -1 -2 0\n1 2 1 0\n".as_bytes()).unwrap();
+/// let mut writer = ModelWriter::new(Vec::new(), 0..ddnnf.n_vars(), ModelFormat::Csv);
+/// let mut enumerator = ModelEnumerator::new(&ddnnf, true);
+/// while let Some(model) = enumerator.compute_next_model() {
+///     writer.write_compact_model(model).unwrap();
+/// }
+/// let output = writer.finish().unwrap();
+/// assert_eq!("x1,x2\n0,0\n1,*\n", String::from_utf8(output).unwrap());
+/// ```
+pub struct ModelWriter<W>
+where
+    W: Write,
+{
+    format: ModelFormat,
+    first_var: usize,
+    pattern: Vec<u8>,
+    value_locations: Vec<usize>,
+    var_labels: Vec<Vec<u8>>,
+    symbols: [u8; 3],
+    buf: BufWriter<W>,
+    header: Option<Vec<u8>>,
+    n_written: u64,
+}
+
+impl<W> ModelWriter<W>
+where
+    W: Write,
+{
+    /// Builds a writer for the models projected onto the given range of variable indices.
+    #[must_use]
+    pub fn new(writer: W, vars: Range<usize>, format: ModelFormat) -> Self {
+        let var_labels = (vars.start + 1..=vars.end)
+            .map(|i| format!("{i}").into_bytes())
+            .collect::<Vec<_>>();
+        let (prefix, separator, with_labels, suffix): (&[u8], &[u8], bool, &[u8]) = match format {
+            ModelFormat::Dimacs | ModelFormat::Positive => (b"v", b" ", true, b" 0 \n"),
+            ModelFormat::Binary => (b"v", b" ", false, b" \n"),
+            ModelFormat::Csv => (b"", b",", false, b"\n"),
+            ModelFormat::JsonLines => (b"[", b",", true, b"]\n"),
+        };
+        let mut pattern = prefix.to_vec();
+        let mut value_locations = Vec::with_capacity(vars.len());
+        for (i, label) in var_labels.iter().enumerate() {
+            if i > 0 || prefix == b"v" {
+                pattern.extend_from_slice(separator);
+            }
+            value_locations.push(pattern.len());
+            pattern.push(b' ');
+            if with_labels {
+                pattern.extend_from_slice(label);
+            }
+        }
+        pattern.extend_from_slice(suffix);
+        let symbols = match format {
+            ModelFormat::Binary | ModelFormat::Csv => [b'1', b'0', b'*'],
+            _ => [b' ', b'-', b'*'],
+        };
+        let header = if format == ModelFormat::Csv {
+            let mut header = Vec::new();
+            for (i, label) in var_labels.iter().enumerate() {
+                if i > 0 {
+                    header.push(b',');
+                }
+                header.push(b'x');
+                header.extend_from_slice(label);
+            }
+            header.push(b'\n');
+            Some(header)
+        } else {
+            None
+        };
+        Self {
+            format,
+            first_var: vars.start,
+            pattern,
+            value_locations,
+            var_labels,
+            symbols,
+            buf: BufWriter::with_capacity(128 * 1024, writer),
+            header,
+            n_written: 0,
+        }
+    }
+
+    /// Sets the symbol used to denote the free variables of the compact models.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the symbol is not a printable ASCII character.
+    pub fn set_free_var_symbol(&mut self, symbol: u8) {
+        assert!(
+            symbol.is_ascii_graphic(),
+            "the free variable symbol must be a printable ASCII character"
+        );
+        self.symbols[2] = symbol;
+    }
+
+    /// Writes a full model, given by the literals of the variables of the range of the writer.
+    ///
+    /// The literals may be given in any order, and the ones that refer to variables out of the range are ignored.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the model cannot be written.
+    pub fn write_model(&mut self, model: &[Literal]) -> std::io::Result<()> {
+        let n_vars = self.value_locations.len();
+        for l in model {
+            let Some(i) = l
+                .var_index()
+                .checked_sub(self.first_var)
+                .filter(|i| *i < n_vars)
+            else {
+                continue;
+            };
+            self.pattern[self.value_locations[i]] = self.symbols[usize::from(!l.polarity())];
+        }
+        self.write_pattern()
+    }
+
+    /// Writes a compact model, as given by a [`ModelEnumerator`](crate::ModelEnumerator): the model is indexed by the variable indices, and `None` denotes a free variable.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the model cannot be written.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the model does not cover the range of variables of the writer.
+    pub fn write_compact_model(&mut self, model: &[Option<Literal>]) -> std::io::Result<()> {
+        let values = &model[self.first_var..self.first_var + self.value_locations.len()];
+        for (opt_l, o) in values.iter().zip(self.value_locations.iter()) {
+            self.pattern[*o] = match opt_l {
+                Some(l) if l.polarity() => self.symbols[0],
+                Some(_) => self.symbols[1],
+                None => self.symbols[2],
+            };
+        }
+        self.write_pattern()
+    }
+
+    /// Returns the number of models written so far.
+    #[must_use]
+    pub fn n_written(&self) -> u64 {
+        self.n_written
+    }
+
+    /// Flushes the output and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the output cannot be written.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.write_header()?;
+        self.buf.into_inner().map_err(IntoInnerError::into_error)
+    }
+
+    fn write_header(&mut self) -> std::io::Result<()> {
+        if let Some(header) = self.header.take() {
+            self.buf.write_all(&header)?;
+        }
+        Ok(())
+    }
+
+    fn write_pattern(&mut self) -> std::io::Result<()> {
+        self.write_header()?;
+        self.n_written += 1;
+        let has_free_vars = || {
+            self.value_locations
+                .iter()
+                .any(|o| self.pattern[*o] == self.symbols[2])
+        };
+        match self.format {
+            ModelFormat::Positive => self.write_positive_literals(),
+            ModelFormat::JsonLines if has_free_vars() => self.write_json_with_nulls(),
+            _ => self.buf.write_all(&self.pattern),
+        }
+    }
+
+    fn write_positive_literals(&mut self) -> std::io::Result<()> {
+        self.buf.write_all(b"v")?;
+        for (o, label) in self.value_locations.iter().zip(self.var_labels.iter()) {
+            if self.pattern[*o] == self.symbols[0] {
+                self.buf.write_all(b" ")?;
+                self.buf.write_all(label)?;
+            } else if self.pattern[*o] == self.symbols[2] {
+                self.buf.write_all(&[b' ', self.symbols[2]])?;
+                self.buf.write_all(label)?;
+            }
+        }
+        self.buf.write_all(b" 0\n")
+    }
+
+    fn write_json_with_nulls(&mut self) -> std::io::Result<()> {
+        self.buf.write_all(b"[")?;
+        for (i, (o, label)) in self
+            .value_locations
+            .iter()
+            .zip(self.var_labels.iter())
+            .enumerate()
+        {
+            if i > 0 {
+                self.buf.write_all(b",")?;
+            }
+            if self.pattern[*o] == self.symbols[2] {
+                self.buf.write_all(b"null")?;
+            } else {
+                self.buf.write_all(&[self.pattern[*o]])?;
+                self.buf.write_all(label)?;
+            }
+        }
+        self.buf.write_all(b"]\n")
+    }
+}
+
+impl<W> Write for ModelWriter<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buf.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.buf.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literals(dimacs: &[isize]) -> Vec<Literal> {
+        dimacs.iter().copied().map(Literal::from).collect()
+    }
+
+    fn compact(dimacs: &[isize]) -> Vec<Option<Literal>> {
+        dimacs
+            .iter()
+            .map(|l| {
+                if *l == 0 {
+                    None
+                } else {
+                    Some(Literal::from(*l))
+                }
+            })
+            .collect()
+    }
+
+    fn write_models(format: ModelFormat, vars: Range<usize>) -> String {
+        let mut writer = ModelWriter::new(Vec::new(), vars, format);
+        writer.write_model(&literals(&[3, -1, 2])).unwrap();
+        writer.write_compact_model(&compact(&[1, 0, -3])).unwrap();
+        assert_eq!(2, writer.n_written());
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(
+            "v -1  2  3 0 \nv  1 *2 -3 0 \n",
+            write_models(ModelFormat::Dimacs, 0..3)
+        );
+        assert_eq!(
+            "v 0 1 1 \nv 1 * 0 \n",
+            write_models(ModelFormat::Binary, 0..3)
+        );
+        assert_eq!(
+            "v 2 3 0\nv 1 *2 0\n",
+            write_models(ModelFormat::Positive, 0..3)
+        );
+        assert_eq!(
+            "x1,x2,x3\n0,1,1\n1,*,0\n",
+            write_models(ModelFormat::Csv, 0..3)
+        );
+        assert_eq!(
+            "[-1, 2, 3]\n[ 1,null,-3]\n",
+            write_models(ModelFormat::JsonLines, 0..3)
+        );
+    }
+
+    #[test]
+    fn test_projection() {
+        assert_eq!(
+            "v  2  3 0 \nv *2 -3 0 \n",
+            write_models(ModelFormat::Dimacs, 1..3)
+        );
+        assert_eq!("x2\n1\n*\n", write_models(ModelFormat::Csv, 1..2));
+    }
+
+    #[test]
+    fn test_free_var_symbol() {
+        let mut writer = ModelWriter::new(Vec::new(), 0..2, ModelFormat::Binary);
+        writer.set_free_var_symbol(b'-');
+        writer.write_compact_model(&compact(&[0, 2])).unwrap();
+        writeln!(writer, "c end").unwrap();
+        assert_eq!(
+            "v - 1 \nc end\n",
+            String::from_utf8(writer.finish().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_empty_csv() {
+        let writer = ModelWriter::new(Vec::new(), 0..2, ModelFormat::Csv);
+        assert_eq!(
+            "x1,x2\n",
+            String::from_utf8(writer.finish().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_format_names() {
+        for name in ModelFormat::NAMES {
+            assert_eq!(name, ModelFormat::from_str(name).unwrap().name());
+        }
+        assert!(ModelFormat::from_str("foo").is_err());
+    }
+}
@@ -1,20 +1,75 @@
 #![doc = include_str!("../README.md")]
 
 mod algorithms;
+pub use algorithms::AtomicSets;
+pub use algorithms::BooleanExpression;
+pub use algorithms::CheckLevel;
+pub use algorithms::CheckerIssue;
 pub use algorithms::CheckingVisitor;
 pub use algorithms::CheckingVisitorData;
+pub use algorithms::CnfValidator;
+pub use algorithms::CostDistribution;
+pub use algorithms::CostDistributionComputer;
+pub use algorithms::CountCertificateVerifier;
+pub use algorithms::DerivedVariables;
+pub use algorithms::DeterminismVerdict;
+pub use algorithms::HammingBallCounter;
+pub use algorithms::HybridModelCounter;
+pub use algorithms::IncrementalModelCounter;
+pub use algorithms::ModelCountBounds;
+pub use algorithms::ModelCounter;
 pub use algorithms::ModelCountingVisitor;
 pub use algorithms::ModelCountingVisitorData;
 pub use algorithms::ModelEnumerator;
 pub use algorithms::ModelFinder;
+pub use algorithms::ModelIter;
+pub use algorithms::ModelSampler;
+pub use algorithms::NegatedView;
+pub use algorithms::NonModelIter;
+pub use algorithms::OptimalModelFinder;
+pub use algorithms::OwnedModelIter;
+pub use algorithms::ProjectedModelCounter;
+pub use algorithms::QueryEngine;
+pub use algorithms::QuotaModelEnumerator;
+pub use algorithms::ReconfigurationPathFinder;
+pub use algorithms::Similarity;
+pub use algorithms::SimilarityComputer;
+pub use algorithms::WeightedModelCounter;
+
+#[cfg(feature = "cookbook")]
+pub mod cookbook;
 
 mod core;
 pub use core::BiBottomUpVisitor;
 pub use core::BottomUpTraversal;
 pub use core::BottomUpVisitor;
+pub use core::CachedBottomUpTraversal;
+pub use core::ConeOfInfluence;
 pub use core::DecisionDNNF;
+pub use core::DecisionDNNFStats;
+pub use core::DecisionProfile;
+pub use core::Edge;
+pub use core::EdgeIndex;
+pub use core::FreeVariables;
+pub use core::LitMap;
 pub use core::Literal;
+pub use core::Node;
+pub use core::NodeImplications;
+pub use core::NodeIndex;
+pub use core::VarMap;
 
 mod io;
+pub use io::Assumptions;
+pub use io::C2dReader;
 pub use io::C2dWriter;
+pub use io::Cnf;
+pub use io::D4Diagnostic;
 pub use io::D4Reader;
+pub use io::D4Recovery;
+pub use io::D4Statistics;
+pub use io::D4Writer;
+pub use io::Format;
+pub use io::ModelFormat;
+pub use io::ModelWriter;
+
+pub mod models;
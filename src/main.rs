@@ -3,8 +3,12 @@
 mod app;
 
 use app::{
-    app_helper::AppHelper, command::Command, ModelComputerCommand, ModelCountingCommand,
-    ModelEnumerationCommand, TranslationCommand,
+    app_helper::AppHelper, command::Command, stack_size_from_args, AtomicSetsCommand, BenchCommand,
+    CnfValidationCommand, CompareModelStreamsCommand, CompletionsCommand, ConeOfInfluenceCommand,
+    DagStatisticsCommand, DecisionProfileCommand, ListCapabilitiesCommand, ModelComputerCommand,
+    ModelCountingCommand, ModelEnumerationCommand, NodeCountsCommand, ReconfigurationCommand,
+    SelfTestCommand, SimilarityCommand, StatisticsCommand, TranslationCommand,
+    VerifyCountCertificateCommand,
 };
 
 pub(crate) fn create_app_helper() -> AppHelper<'static> {
@@ -18,10 +22,25 @@ pub(crate) fn create_app_helper() -> AppHelper<'static> {
         "decdnnf-rs, a library for Decision-DNNFs.",
     );
     let commands: Vec<Box<dyn Command>> = vec![
+        Box::<AtomicSetsCommand>::default(),
+        Box::<BenchCommand>::default(),
+        Box::<CnfValidationCommand>::default(),
+        Box::<CompareModelStreamsCommand>::default(),
+        Box::<CompletionsCommand>::default(),
+        Box::<ConeOfInfluenceCommand>::default(),
+        Box::<DagStatisticsCommand>::default(),
+        Box::<DecisionProfileCommand>::default(),
+        Box::<ListCapabilitiesCommand>::default(),
         Box::<ModelComputerCommand>::default(),
         Box::<ModelCountingCommand>::default(),
         Box::<ModelEnumerationCommand>::default(),
+        Box::<NodeCountsCommand>::default(),
+        Box::<ReconfigurationCommand>::default(),
+        Box::<SelfTestCommand>::default(),
+        Box::<SimilarityCommand>::default(),
+        Box::<StatisticsCommand>::default(),
         Box::<TranslationCommand>::default(),
+        Box::<VerifyCountCertificateCommand>::default(),
     ];
     for c in commands {
         app.add_command(c);
@@ -30,6 +49,15 @@ pub(crate) fn create_app_helper() -> AppHelper<'static> {
 }
 
 fn main() {
-    let app = create_app_helper();
-    app.launch_app();
+    // the algorithms are recursive, so they run on a thread which stack size can be set by the user
+    let worker = std::thread::Builder::new()
+        .stack_size(stack_size_from_args(std::env::args_os()))
+        .spawn(|| {
+            let app = create_app_helper();
+            app.launch_app();
+        })
+        .expect("cannot create the thread running the command");
+    if worker.join().is_err() {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,594 @@
+//! Utilities to normalize and compare models.
+//!
+//! Models are normalized into vectors of DIMACS literals sorted by variable index.
+//! The normalized models can then be compared, either in memory or with a bounded memory using [`symmetric_difference`].
+//! Large collections of models can also be summarized by a [`ModelDigest`], which does not require to store them.
+
+use crate::Literal;
+use anyhow::{anyhow, Context, Result};
+use rug::Integer;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, ErrorKind, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
+
+/// Normalizes a model, as returned by a [`ModelEnumerator`](crate::ModelEnumerator).
+///
+/// The result contains the DIMACS representations of the literals, sorted by variable index.
+/// Absent literals (i.e. free variables eluded by the enumerator) are ignored.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{models, Literal};
+///
+/// let model = vec![Some(Literal::from(-2)), None, Some(Literal::from(1))];
+/// assert_eq!(vec![1, -2], models::normalize(&model));
+/// ```
+#[must_use]
+pub fn normalize(model: &[Option<Literal>]) -> Vec<isize> {
+    normalize_dimacs(
+        model
+            .iter()
+            .filter_map(|opt_l| opt_l.map(isize::from))
+            .collect(),
+    )
+}
+
+/// Normalizes a model given by DIMACS literals, sorting them by variable index.
+#[must_use]
+pub fn normalize_dimacs(mut model: Vec<isize>) -> Vec<isize> {
+    model.sort_unstable_by_key(|l| l.unsigned_abs());
+    model
+}
+
+/// Returns `true` if and only if two collections of DIMACS models contain the same models.
+///
+/// The models are normalized before the comparison, and duplicates are ignored.
+/// This function keeps all the models in memory; see [`symmetric_difference`] for a bounded memory alternative.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::models;
+///
+/// assert!(models::same_model_sets(
+///     vec![vec![1, -2], vec![-1, 2]],
+///     vec![vec![2, -1], vec![-2, 1], vec![1, -2]],
+/// ));
+/// ```
+#[must_use]
+pub fn same_model_sets<L, R>(left: L, right: R) -> bool
+where
+    L: IntoIterator<Item = Vec<isize>>,
+    R: IntoIterator<Item = Vec<isize>>,
+{
+    let sorted_set = |models: Vec<Vec<isize>>| {
+        let mut set = models.into_iter().map(normalize_dimacs).collect::<Vec<_>>();
+        set.sort_unstable();
+        set.dedup();
+        set
+    };
+    sorted_set(left.into_iter().collect()) == sorted_set(right.into_iter().collect())
+}
+
+/// An order-independent digest of a collection of models.
+///
+/// Each model is hashed as the product of pseudo-random weights associated with its literals, modulo the prime 2^61-1, and the digest is the sum of these hashes.
+/// The digest thus does not depend on the order in which the models are given, and two distinct sets of models get the same digest with a negligible probability.
+/// Since the weights are fixed, digests computed on different machines can be compared.
+///
+/// A compact model, in which some variables are free, contributes the same value as the models it represents;
+/// this value is computed without expanding it, by replacing the weight of each free variable by the sum of the weights of its literals.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::{models::ModelDigest, Literal};
+///
+/// let mut compact = ModelDigest::default();
+/// compact.add_compact_model(&[Some(Literal::from(1)), None]);
+/// let mut expanded = ModelDigest::default();
+/// expanded.add_model(&[-2, 1]);
+/// expanded.add_model(&[1, 2]);
+/// assert_eq!(expanded.value(), compact.value());
+/// assert_eq!(2, *compact.n_models());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModelDigest {
+    value: u64,
+    n_models: Integer,
+}
+
+const DIGEST_MODULUS: u64 = (1 << 61) - 1;
+
+impl ModelDigest {
+    /// Adds a model given by DIMACS literals.
+    pub fn add_model(&mut self, model: &[isize]) {
+        let hash = model
+            .iter()
+            .fold(1, |acc, l| digest_mul(acc, digest_weight(*l)));
+        self.value = digest_add(self.value, hash);
+        self.n_models += 1;
+    }
+
+    /// Adds a model, as returned by a [`ModelEnumerator`](crate::ModelEnumerator), which `i`-th element is the literal of the variable of index `i`.
+    ///
+    /// Absent literals (i.e. free variables eluded by the enumerator) stand for both the literals of their variable.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn add_compact_model(&mut self, model: &[Option<Literal>]) {
+        let mut n_free_vars = 0;
+        let mut hash = 1;
+        for (i, opt_l) in model.iter().enumerate() {
+            let factor = if let Some(l) = opt_l {
+                digest_weight(isize::from(*l))
+            } else {
+                n_free_vars += 1;
+                let positive = isize::try_from(i + 1).unwrap();
+                digest_add(digest_weight(positive), digest_weight(-positive))
+            };
+            hash = digest_mul(hash, factor);
+        }
+        self.value = digest_add(self.value, hash);
+        self.n_models += Integer::from(1) << n_free_vars;
+    }
+
+    /// Returns the value of the digest.
+    #[must_use]
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Returns the number of models that were added, counting the ones represented by compact models.
+    #[must_use]
+    pub fn n_models(&self) -> &Integer {
+        &self.n_models
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn digest_weight(literal: isize) -> u64 {
+    // splitmix64 finalizer
+    let mut z = (literal as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) % DIGEST_MODULUS
+}
+
+fn digest_add(a: u64, b: u64) -> u64 {
+    (a + b) % DIGEST_MODULUS
+}
+
+fn digest_mul(a: u64, b: u64) -> u64 {
+    let product = u128::from(a) * u128::from(b) % u128::from(DIGEST_MODULUS);
+    u64::try_from(product).unwrap()
+}
+
+/// The collection in which a model of a symmetric difference appears.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    /// The model only appears in the left collection.
+    Left,
+    /// The model only appears in the right collection.
+    Right,
+}
+
+/// Computes the symmetric difference of two streams of DIMACS models.
+///
+/// The models are normalized and duplicates are ignored.
+/// Each model that appears in a single stream is given to the callback function, along with the side of the stream it comes from.
+/// The models are given in lexicographic order.
+///
+/// At most `max_models_in_memory` models of each stream are kept in memory at a time.
+/// When a stream contains more models, they are sorted by chunks which are written to temporary files and then merged.
+/// The files are merged by groups of bounded size, in several passes if needed, so that the number of open files remains bounded.
+///
+/// # Errors
+///
+/// An error is returned if the temporary files cannot be written or read.
+///
+/// # Panics
+///
+/// This function panics if `max_models_in_memory` is zero.
+///
+/// # Example
+///
+/// ```
+/// use decdnnf_rs::models::{self, Side};
+///
+/// let mut diff = Vec::new();
+/// models::symmetric_difference(
+///     vec![vec![1, 2], vec![-1, 2]],
+///     vec![vec![2, 1], vec![1, -2]],
+///     1000,
+///     |side, model| diff.push((side, model.to_vec())),
+/// )
+/// .unwrap();
+/// assert_eq!(vec![(Side::Left, vec![-1, 2]), (Side::Right, vec![1, -2])], diff);
+/// ```
+pub fn symmetric_difference<L, R, F>(
+    left: L,
+    right: R,
+    max_models_in_memory: usize,
+    mut callback: F,
+) -> Result<()>
+where
+    L: IntoIterator<Item = Vec<isize>>,
+    R: IntoIterator<Item = Vec<isize>>,
+    F: FnMut(Side, &[isize]),
+{
+    assert!(
+        max_models_in_memory > 0,
+        "the number of models in memory must be positive"
+    );
+    let mut left = SortedModels::new(left, max_models_in_memory)?;
+    let mut right = SortedModels::new(right, max_models_in_memory)?;
+    let mut left_model = left.next_model()?;
+    let mut right_model = right.next_model()?;
+    loop {
+        match (&left_model, &right_model) {
+            (None, None) => return Ok(()),
+            (Some(l), None) => {
+                callback(Side::Left, l);
+                left_model = left.next_model()?;
+            }
+            (None, Some(r)) => {
+                callback(Side::Right, r);
+                right_model = right.next_model()?;
+            }
+            (Some(l), Some(r)) => match l.cmp(r) {
+                Ordering::Less => {
+                    callback(Side::Left, l);
+                    left_model = left.next_model()?;
+                }
+                Ordering::Greater => {
+                    callback(Side::Right, r);
+                    right_model = right.next_model()?;
+                }
+                Ordering::Equal => {
+                    left_model = left.next_model()?;
+                    right_model = right.next_model()?;
+                }
+            },
+        }
+    }
+}
+
+/// The maximal number of sorted runs merged at once, which bounds the number of temporary files open at the same time.
+const MERGE_FAN_IN: usize = 64;
+
+/// A stream of normalized models, sorted and without duplicates, built from sorted runs.
+struct SortedModels {
+    runs: Vec<Run>,
+    heap: BinaryHeap<Reverse<(Vec<isize>, usize)>>,
+    last: Option<Vec<isize>>,
+}
+
+impl SortedModels {
+    fn new<I>(models: I, max_models_in_memory: usize) -> Result<Self>
+    where
+        I: IntoIterator<Item = Vec<isize>>,
+    {
+        let mut levels = Vec::new();
+        let mut chunk = Vec::with_capacity(max_models_in_memory);
+        for model in models {
+            chunk.push(normalize_dimacs(model));
+            if chunk.len() == max_models_in_memory {
+                chunk.sort_unstable();
+                let run_file = RunFile::write(std::mem::take(&mut chunk).into_iter().map(Ok))?;
+                add_run_file(&mut levels, run_file)?;
+            }
+        }
+        let mut run_files = levels.into_iter().flatten().collect::<Vec<_>>();
+        while run_files.len() >= MERGE_FAN_IN {
+            let merged = RunFile::merge(run_files.drain(..MERGE_FAN_IN).collect())?;
+            run_files.push(merged);
+        }
+        let mut runs = run_files
+            .into_iter()
+            .map(RunFile::open)
+            .collect::<Result<Vec<_>>>()?;
+        if !chunk.is_empty() {
+            chunk.sort_unstable();
+            runs.push(Run::Memory(chunk.into_iter()));
+        }
+        Self::from_runs(runs)
+    }
+
+    fn from_runs(mut runs: Vec<Run>) -> Result<Self> {
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (i, run) in runs.iter_mut().enumerate() {
+            if let Some(model) = run.next_model()? {
+                heap.push(Reverse((model, i)));
+            }
+        }
+        Ok(Self {
+            runs,
+            heap,
+            last: None,
+        })
+    }
+
+    fn next_model(&mut self) -> Result<Option<Vec<isize>>> {
+        while let Some(Reverse((model, i))) = self.heap.pop() {
+            if let Some(next) = self.runs[i].next_model()? {
+                self.heap.push(Reverse((next, i)));
+            }
+            if self.last.as_ref() != Some(&model) {
+                self.last = Some(model.clone());
+                return Ok(Some(model));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Adds a run file to the ones written so far, grouped by the number of merges they result from.
+///
+/// When a group reaches [`MERGE_FAN_IN`] runs, they are merged into a run of the next group, so that each model is merged a logarithmic number of times.
+fn add_run_file(levels: &mut Vec<Vec<RunFile>>, run_file: RunFile) -> Result<()> {
+    let mut run_file = run_file;
+    let mut level = 0;
+    loop {
+        if levels.len() == level {
+            levels.push(Vec::new());
+        }
+        levels[level].push(run_file);
+        if levels[level].len() < MERGE_FAN_IN {
+            return Ok(());
+        }
+        run_file = RunFile::merge(std::mem::take(&mut levels[level]))?;
+        level += 1;
+    }
+}
+
+static RUN_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary file containing a sorted run of models, which is removed when dropped.
+///
+/// The file is only open while it is read, so that the number of open files does not grow with the number of runs.
+struct RunFile(PathBuf);
+
+impl RunFile {
+    /// Creates a new temporary file; existing files are never reused, so that they cannot be overwritten.
+    fn create() -> Result<(Self, File)> {
+        loop {
+            let path = std::env::temp_dir().join(format!(
+                "decdnnf_rs_models_{}_{}",
+                std::process::id(),
+                RUN_FILE_COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+            ));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => return Ok((RunFile(path), file)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(r#"while creating temporary file "{}""#, path.display())
+                    })
+                }
+            }
+        }
+    }
+
+    fn write<I>(models: I) -> Result<Self>
+    where
+        I: Iterator<Item = Result<Vec<isize>>>,
+    {
+        let (run_file, file) = Self::create()?;
+        let context = || format!(r#"while writing temporary file "{}""#, run_file.0.display());
+        let mut writer = BufWriter::new(file);
+        for model in models {
+            for l in model? {
+                write!(writer, "{l} ").with_context(context)?;
+            }
+            writeln!(writer, "0").with_context(context)?;
+        }
+        writer.flush().with_context(context)?;
+        Ok(run_file)
+    }
+
+    /// Merges sorted runs into a new one, removing the duplicates.
+    fn merge(run_files: Vec<RunFile>) -> Result<Self> {
+        let runs = run_files
+            .into_iter()
+            .map(RunFile::open)
+            .collect::<Result<Vec<_>>>()?;
+        let mut merged = SortedModels::from_runs(runs)?;
+        Self::write(std::iter::from_fn(|| merged.next_model().transpose()))
+    }
+
+    fn open(self) -> Result<Run> {
+        let file = File::open(&self.0)
+            .with_context(|| format!(r#"while opening temporary file "{}""#, self.0.display()))?;
+        Ok(Run::File(BufReader::new(file), self))
+    }
+}
+
+impl Drop for RunFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// A sorted run of models, either kept in memory or read from a temporary file.
+enum Run {
+    Memory(std::vec::IntoIter<Vec<isize>>),
+    File(BufReader<File>, RunFile),
+}
+
+impl Run {
+    fn next_model(&mut self) -> Result<Option<Vec<isize>>> {
+        match self {
+            Run::Memory(models) => Ok(models.next()),
+            Run::File(reader, run_file) => {
+                let context =
+                    || format!(r#"while reading temporary file "{}""#, run_file.0.display());
+                let mut line = String::new();
+                if reader.read_line(&mut line).with_context(context)? == 0 {
+                    return Ok(None);
+                }
+                line.split_whitespace()
+                    .map(str::parse::<isize>)
+                    .filter(|r| !matches!(r, Ok(0)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Some)
+                    .map_err(|e| anyhow!(e))
+                    .with_context(context)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(models: &[Vec<isize>]) -> ModelDigest {
+        let mut digest = ModelDigest::default();
+        for model in models {
+            digest.add_model(model);
+        }
+        digest
+    }
+
+    fn symmetric_difference_vec(
+        left: Vec<Vec<isize>>,
+        right: Vec<Vec<isize>>,
+        max_models_in_memory: usize,
+    ) -> Vec<(Side, Vec<isize>)> {
+        let mut diff = Vec::new();
+        symmetric_difference(left, right, max_models_in_memory, |side, model| {
+            diff.push((side, model.to_vec()));
+        })
+        .unwrap();
+        diff
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            vec![-1, 2, -3],
+            normalize(&[
+                Some(Literal::from(2)),
+                Some(Literal::from(-3)),
+                None,
+                Some(Literal::from(-1))
+            ])
+        );
+    }
+
+    #[test]
+    fn test_same_model_sets() {
+        assert!(same_model_sets(vec![], vec![]));
+        assert!(same_model_sets(
+            vec![vec![1, 2]],
+            vec![vec![2, 1], vec![1, 2]]
+        ));
+        assert!(!same_model_sets(vec![vec![1, 2]], vec![vec![1, -2]]));
+    }
+
+    #[test]
+    fn test_symmetric_difference_empty() {
+        assert!(symmetric_difference_vec(vec![], vec![], 2).is_empty());
+    }
+
+    #[test]
+    fn test_symmetric_difference_equal() {
+        let left = vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]];
+        let right = vec![
+            vec![-2, -1],
+            vec![-2, 1],
+            vec![2, -1],
+            vec![2, 1],
+            vec![1, 2],
+        ];
+        assert!(symmetric_difference_vec(left, right, 2).is_empty());
+    }
+
+    #[test]
+    fn test_symmetric_difference_with_runs() {
+        let left = vec![
+            vec![1, 2, 3],
+            vec![-1, 2, 3],
+            vec![1, -2, 3],
+            vec![1, 2, -3],
+            vec![1, 2, 3],
+        ];
+        let right = vec![vec![3, 2, 1], vec![-3, -2, -1], vec![-1, 2, 3]];
+        let expected = vec![
+            (Side::Right, vec![-1, -2, -3]),
+            (Side::Left, vec![1, -2, 3]),
+            (Side::Left, vec![1, 2, -3]),
+        ];
+        for max_models_in_memory in 1..=6 {
+            assert_eq!(
+                expected,
+                symmetric_difference_vec(left.clone(), right.clone(), max_models_in_memory)
+            );
+        }
+    }
+
+    #[test]
+    fn test_symmetric_difference_merge_passes() {
+        // two levels of merges, then a last merge since the remaining runs reach the fan-in
+        let n_models = MERGE_FAN_IN * MERGE_FAN_IN + MERGE_FAN_IN - 1;
+        let models = (0..n_models)
+            .map(|i| {
+                (0..13)
+                    .map(|v| if i >> v & 1 == 0 { -v - 1 } else { v + 1 })
+                    .collect::<Vec<isize>>()
+            })
+            .collect::<Vec<_>>();
+        let mut right = models.clone();
+        right.reverse();
+        let missing = right.remove(n_models / 2);
+        assert_eq!(
+            vec![(Side::Left, missing)],
+            symmetric_difference_vec(models, right, 1)
+        );
+    }
+
+    #[test]
+    fn test_digest_order_independent() {
+        let models = vec![vec![1, 2, 3], vec![-1, 2, -3], vec![1, -2, -3]];
+        let reversed = models
+            .iter()
+            .rev()
+            .map(|m| m.iter().rev().copied().collect())
+            .collect::<Vec<_>>();
+        assert_eq!(digest(&models), digest(&reversed));
+        assert_eq!(3, *digest(&models).n_models());
+    }
+
+    #[test]
+    fn test_digest_distinct_sets() {
+        let left = digest(&[vec![1, 2], vec![-1, -2]]);
+        let right = digest(&[vec![1, -2], vec![-1, 2]]);
+        assert_ne!(left.value(), right.value());
+        assert_eq!(left.n_models(), right.n_models());
+    }
+
+    #[test]
+    fn test_digest_compact_model() {
+        let mut compact = ModelDigest::default();
+        compact.add_compact_model(&[None, Some(Literal::from(-2)), None]);
+        let expanded = digest(&[
+            vec![1, -2, 3],
+            vec![1, -2, -3],
+            vec![-1, -2, 3],
+            vec![-1, -2, -3],
+        ]);
+        assert_eq!(expanded, compact);
+    }
+
+    #[test]
+    fn test_digest_empty() {
+        let digest = ModelDigest::default();
+        assert_eq!(0, digest.value());
+        assert_eq!(0, *digest.n_models());
+    }
+}